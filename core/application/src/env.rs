@@ -268,6 +268,12 @@ pub fn apply_genesis_block(&mut self, config: &Config) -> Result<bool> {
                         genesis.min_num_measurements as u128
                     );
                 }
+                if param_table.get(ProtocolParams::ReputationOutlierThreshold).is_none() {
+                    param_table.insert(
+                        ProtocolParams::ReputationOutlierThreshold,
+                        genesis.reputation_outlier_threshold as u128
+                    );
+                }
 
                 return Ok(false);
             }
@@ -351,6 +357,10 @@ pub fn apply_genesis_block(&mut self, config: &Config) -> Result<bool> {
                 ProtocolParams::MinNumMeasurements,
                 genesis.min_num_measurements as u128
             );
+            param_table.insert(
+                ProtocolParams::ReputationOutlierThreshold,
+                genesis.reputation_outlier_threshold as u128
+            );
 
             let epoch_end: u64 = genesis.epoch_time + genesis.epoch_start;
             let mut committee_members = Vec::with_capacity(4);
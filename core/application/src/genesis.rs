@@ -1,8 +1,10 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::fs;
 use std::net::IpAddr;
+use std::time::Duration;
 
-use anyhow::{Context, Result};
+use anyhow::{bail, Context, Result};
+use fast_sri::IntegrityMetadata;
 use fleek_crypto::{ClientPublicKey, ConsensusPublicKey, EthAddress, NodePublicKey};
 use hp_fixed::unsigned::HpUfixed;
 use lightning_interfaces::types::{
@@ -20,6 +22,11 @@
 use serde::{self, Deserialize, Serialize};
 use serde_with::{serde_as, DisplayFromStr};
 
+/// How long downloading the genesis from a URL may take.
+const GENESIS_DOWNLOAD_TIMEOUT: Duration = Duration::from_secs(60);
+/// The largest genesis file we are willing to download.
+const MAX_GENESIS_SIZE: usize = 32 << 20;
+
 #[serde_as]
 #[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
 pub struct Genesis {
@@ -39,6 +46,9 @@ pub struct Genesis {
     pub max_boost: u16,
     pub max_lock_time: u64,
     pub min_num_measurements: u64,
+    /// See `ProtocolParams::ReputationOutlierThreshold`, the outlier filter is off if not set.
+    #[serde(default)]
+    pub reputation_outlier_threshold: u64,
     pub node_info: Vec<GenesisNode>,
     pub service: Vec<GenesisService>,
     pub account: Vec<GenesisAccount>,
@@ -62,17 +72,126 @@ pub fn load_from_file(path: ResolvedPathBuf) -> Result<Self> {
         Ok(genesis)
     }
 
+    /// Fetch the genesis from an HTTP(S) URL and parse it, after verifying the downloaded file
+    /// against an SRI-style integrity string such as `sha256-<base64 digest>`.
+    pub async fn load_from_url(url: &str, expected_hash: &str) -> Result<Self> {
+        Self::load_from_url_with_limits(
+            url,
+            expected_hash,
+            GENESIS_DOWNLOAD_TIMEOUT,
+            MAX_GENESIS_SIZE,
+        )
+        .await
+    }
+
+    async fn load_from_url_with_limits(
+        url: &str,
+        expected_hash: &str,
+        timeout: Duration,
+        max_size: usize,
+    ) -> Result<Self> {
+        let integrity: IntegrityMetadata = expected_hash
+            .parse()
+            .context("Invalid genesis integrity metadata")?;
+        let client = reqwest::ClientBuilder::new()
+            .use_rustls_tls()
+            .timeout(timeout)
+            .build()?;
+        let mut response = client.get(url).send().await?.error_for_status()?;
+        if response
+            .content_length()
+            .is_some_and(|len| len > max_size as u64)
+        {
+            bail!("Genesis file is larger than {max_size} bytes");
+        }
+        let mut data = Vec::new();
+        while let Some(chunk) = response.chunk().await? {
+            if data.len() + chunk.len() > max_size {
+                bail!("Genesis file is larger than {max_size} bytes");
+            }
+            data.extend_from_slice(&chunk);
+        }
+
+        let (is_valid, data) = integrity.verify(data);
+        if !is_valid {
+            bail!("Genesis integrity check failed: invalid digest");
+        }
+
+        let raw = String::from_utf8(data).context("Genesis file is not valid UTF-8")?;
+        let genesis = toml::from_str(&raw).context("Failed to parse genesis file")?;
+        Ok(genesis)
+    }
+
     pub fn write_to_file(&self, path: ResolvedPathBuf) -> Result<()> {
         let raw = toml::to_string_pretty(self)?;
         fs::write(path, raw)?;
         Ok(())
     }
 
+    /// Validate the genesis and write it to `genesis.toml` in the given directory.
     pub fn write_to_dir(&self, dir: ResolvedPathBuf) -> Result<ResolvedPathBuf> {
+        self.validate()?;
         let path: ResolvedPathBuf = dir.join("genesis.toml").try_into()?;
         self.write_to_file(path.clone())?;
         Ok(path)
     }
+
+    /// Check the genesis nodes for misconfigurations that would make the network misbehave.
+    ///
+    /// This rejects duplicate node public keys, duplicate consensus public keys, ports that are
+    /// used more than once on the same domain, and nodes that stake less than `min_stake`. Nodes
+    /// without a stake are accepted, the application stakes `min_stake` for them at genesis.
+    pub fn validate(&self) -> Result<()> {
+        let min_stake = HpUfixed::<18>::from(self.min_stake);
+        let mut node_keys = HashSet::new();
+        let mut consensus_keys = HashSet::new();
+        let mut addresses = HashMap::new();
+
+        for node in &self.node_info {
+            if !node_keys.insert(node.primary_public_key) {
+                bail!(
+                    "Duplicate node public key in genesis: {}",
+                    node.primary_public_key
+                );
+            }
+            if !consensus_keys.insert(node.consensus_public_key) {
+                bail!(
+                    "Duplicate consensus public key in genesis: {}",
+                    node.consensus_public_key
+                );
+            }
+            if node.stake.staked != HpUfixed::<18>::zero() && node.stake.staked < min_stake {
+                bail!(
+                    "Genesis node {} stakes less than the minimum stake of {}",
+                    node.primary_public_key,
+                    self.min_stake
+                );
+            }
+
+            let ports = &node.ports;
+            let node_addresses = [
+                (node.primary_domain, ports.primary),
+                (node.worker_domain, ports.worker),
+                (node.primary_domain, ports.mempool),
+                (node.primary_domain, ports.rpc),
+                (node.primary_domain, ports.pool),
+                (node.primary_domain, ports.pinger),
+                (node.primary_domain, ports.handshake.http),
+                (node.primary_domain, ports.handshake.webrtc),
+                (node.primary_domain, ports.handshake.webtransport),
+            ];
+            for (domain, port) in node_addresses {
+                if let Some(other) = addresses.insert((domain, port), node.primary_public_key) {
+                    bail!(
+                        "Port {port} on {domain} is used by both genesis node {other} and {}",
+                        node.primary_public_key
+                    );
+                }
+            }
+        }
+
+        Ok(())
+    }
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
@@ -232,11 +351,212 @@ fn from(genesis_node_served: GenesisNodeServed) -> Self {
 
 #[cfg(test)]
 mod genesis_tests {
+    use fleek_crypto::{ConsensusSecretKey, NodeSecretKey, SecretKey};
+    use lightning_interfaces::types::HandshakePorts;
     use resolved_pathbuf::ResolvedPathBuf;
     use tempfile::tempdir;
 
     use super::*;
 
+    fn test_node(index: u16) -> GenesisNode {
+        let node_public_key = NodeSecretKey::generate().to_pk();
+        GenesisNode::new(
+            EthAddress([0; 20]),
+            node_public_key,
+            "127.0.0.1".parse().unwrap(),
+            ConsensusSecretKey::generate().to_pk(),
+            "127.0.0.1".parse().unwrap(),
+            node_public_key,
+            NodePorts {
+                primary: 8000 + index,
+                worker: 9000 + index,
+                mempool: 7000 + index,
+                rpc: 6000 + index,
+                pool: 5000 + index,
+                pinger: 2000 + index,
+                handshake: HandshakePorts {
+                    http: 3000 + index,
+                    webrtc: 4000 + index,
+                    webtransport: 10000 + index,
+                },
+            },
+            Some(Staking {
+                staked: 1000_u64.into(),
+                ..Default::default()
+            }),
+            true,
+        )
+    }
+
+    fn test_genesis(node_info: Vec<GenesisNode>) -> Genesis {
+        Genesis {
+            chain_id: 1337,
+            min_stake: 1000,
+            node_info,
+            ..Genesis::default()
+        }
+    }
+
+    async fn spawn_genesis_server(raw: String) -> String {
+        let router = axum::Router::new().route(
+            "/genesis.toml",
+            axum::routing::get(|| async move { raw.clone() }),
+        );
+        let server =
+            axum::Server::bind(&"127.0.0.1:0".parse().unwrap()).serve(router.into_make_service());
+        let addr = server.local_addr();
+        tokio::spawn(server);
+        format!("http://{addr}/genesis.toml")
+    }
+
+    #[tokio::test]
+    async fn load_from_url_verifies_integrity() {
+        let genesis = Genesis {
+            chain_id: 1337,
+            ..Genesis::default()
+        };
+        let raw = toml::to_string_pretty(&genesis).unwrap();
+        let mut builder = fast_sri::Integrity::<fast_sri::Sha256>::builder();
+        builder.update(raw.as_bytes());
+        let integrity = builder.finalize().to_string();
+        let url = spawn_genesis_server(raw).await;
+
+        let loaded_genesis = Genesis::load_from_url(&url, &integrity).await.unwrap();
+        assert_eq!(genesis, loaded_genesis);
+
+        let mut builder = fast_sri::Integrity::<fast_sri::Sha256>::builder();
+        builder.update(b"not the genesis");
+        let wrong_integrity = builder.finalize().to_string();
+        assert!(
+            Genesis::load_from_url(&url, &wrong_integrity)
+                .await
+                .is_err()
+        );
+    }
+
+    #[tokio::test]
+    async fn load_from_url_rejects_oversize_genesis() {
+        let raw = toml::to_string_pretty(&Genesis::default()).unwrap();
+        let mut builder = fast_sri::Integrity::<fast_sri::Sha256>::builder();
+        builder.update(raw.as_bytes());
+        let integrity = builder.finalize().to_string();
+        let size = raw.len();
+        let url = spawn_genesis_server(raw).await;
+
+        assert!(
+            Genesis::load_from_url_with_limits(&url, &integrity, Duration::from_secs(5), size)
+                .await
+                .is_ok()
+        );
+        assert!(
+            Genesis::load_from_url_with_limits(&url, &integrity, Duration::from_secs(5), size - 1)
+                .await
+                .is_err()
+        );
+    }
+
+    #[tokio::test]
+    async fn load_from_url_times_out() {
+        let router = axum::Router::new().route(
+            "/genesis.toml",
+            axum::routing::get(|| async move {
+                tokio::time::sleep(Duration::from_secs(60)).await;
+                String::new()
+            }),
+        );
+        let server =
+            axum::Server::bind(&"127.0.0.1:0".parse().unwrap()).serve(router.into_make_service());
+        let url = format!("http://{}/genesis.toml", server.local_addr());
+        tokio::spawn(server);
+
+        let result = Genesis::load_from_url_with_limits(
+            &url,
+            "sha256-47DEQpj8HBSa+/TImW+5JCeuQeRkm5NMpJWZG3hSuFU=",
+            Duration::from_millis(100),
+            MAX_GENESIS_SIZE,
+        )
+        .await;
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn validate_accepts_valid_genesis() {
+        let genesis = test_genesis(vec![test_node(0), test_node(1), test_node(2)]);
+        assert!(genesis.validate().is_ok());
+    }
+
+    #[test]
+    fn validate_rejects_duplicate_node_key() {
+        let node = test_node(0);
+        let mut other = test_node(1);
+        other.primary_public_key = node.primary_public_key;
+        let genesis = test_genesis(vec![node, other]);
+        assert!(genesis.validate().is_err());
+    }
+
+    #[test]
+    fn validate_rejects_duplicate_consensus_key() {
+        let node = test_node(0);
+        let mut other = test_node(1);
+        other.consensus_public_key = node.consensus_public_key;
+        let genesis = test_genesis(vec![node, other]);
+        assert!(genesis.validate().is_err());
+    }
+
+    #[test]
+    fn validate_rejects_overlapping_ports() {
+        let node = test_node(0);
+        let mut other = test_node(1);
+        other.ports.rpc = node.ports.rpc;
+        let genesis = test_genesis(vec![node, other]);
+        assert!(genesis.validate().is_err());
+
+        // The same port on a different domain is fine.
+        let node = test_node(0);
+        let mut other = test_node(1);
+        other.ports.rpc = node.ports.rpc;
+        other.primary_domain = "127.0.0.2".parse().unwrap();
+        let genesis = test_genesis(vec![node, other]);
+        assert!(genesis.validate().is_ok());
+
+        // Ports also must not overlap within a single node.
+        let mut node = test_node(0);
+        node.ports.pinger = node.ports.pool;
+        let genesis = test_genesis(vec![node]);
+        assert!(genesis.validate().is_err());
+    }
+
+    #[test]
+    fn validate_rejects_stake_below_min_stake() {
+        let mut node = test_node(0);
+        node.stake.staked = 999_u64.into();
+        let genesis = test_genesis(vec![node, test_node(1)]);
+        assert!(genesis.validate().is_err());
+    }
+
+    #[test]
+    fn validate_accepts_node_without_stake() {
+        let mut node = test_node(0);
+        node.stake = Default::default();
+        let genesis = test_genesis(vec![node, test_node(1)]);
+        assert!(genesis.validate().is_ok());
+    }
+
+    #[test]
+    fn write_to_dir_rejects_invalid_genesis() {
+        let temp_dir = tempdir().unwrap();
+        let node = test_node(0);
+        let mut other = test_node(1);
+        other.primary_public_key = node.primary_public_key;
+        let genesis = test_genesis(vec![node, other]);
+        assert!(
+            genesis
+                .write_to_dir(temp_dir.path().to_path_buf().try_into().unwrap())
+                .is_err()
+        );
+        assert!(!temp_dir.path().join("genesis.toml").exists());
+    }
+
     #[test]
     fn write_to_file_load_from_file() {
         let temp_dir = tempdir().unwrap();
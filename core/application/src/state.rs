@@ -900,8 +900,15 @@ fn calculate_reputation_scores(&self) {
         let nodes = self.uptime.keys();
         nodes.for_each(|node| self.uptime.remove(&node));
 
+        let mad_outlier_threshold = self
+            .parameters
+            .get(&ProtocolParams::ReputationOutlierThreshold)
+            .filter(|threshold| *threshold > 0)
+            .map(|threshold| threshold.min(i128::MAX as u128) as i128);
+
         // Store new scores in application state.
-        let new_rep_scores = lightning_reputation::calculate_reputation_scores(map);
+        let new_rep_scores =
+            lightning_reputation::calculate_reputation_scores(map, mad_outlier_threshold);
         let nodes = self.node_info.keys();
         nodes.for_each(|node| {
             let (new_score, uptime) = match new_rep_scores.get(&node) {
@@ -53,6 +53,7 @@
 };
 use lightning_interfaces::PagingParams;
 use lightning_test_utils::json_config::JsonConfigProvider;
+use lightning_test_utils::transaction::BlockBuilder;
 use lightning_test_utils::{random, reputation};
 use lightning_utils::application::QueryRunnerExt;
 use rand::seq::SliceRandom;
@@ -722,9 +723,9 @@ fn create_committee_member(
             pool: 5000 + index,
             pinger: 2000 + index,
             handshake: HandshakePorts {
-                http: 5000 + index,
-                webrtc: 6000 + index,
-                webtransport: 7000 + index,
+                http: 3000 + index,
+                webrtc: 4000 + index,
+                webtransport: 10000 + index,
             },
         },
         None,
@@ -1059,6 +1060,20 @@ fn paging_params(ignore_stake: bool, start: u32, limit: usize) -> PagingParams {
         ignore_stake,
         start,
         limit,
+        participation: None,
+    }
+}
+
+/// Helper function that prepare `PagingParams` filtered by `Participation`
+fn paging_params_with_participation(
+    ignore_stake: bool,
+    start: u32,
+    limit: usize,
+    participation: Participation,
+) -> PagingParams {
+    PagingParams {
+        participation: Some(participation),
+        ..paging_params(ignore_stake, start, limit)
     }
 }
 
@@ -1089,9 +1104,9 @@ fn add_to_committee(
             pool: 5000 + index,
             pinger: 2000 + index,
             handshake: HandshakePorts {
-                http: 5000 + index,
-                webrtc: 6000 + index,
-                webtransport: 7000 + index,
+                http: 3000 + index,
+                webrtc: 4000 + index,
+                webtransport: 10000 + index,
             },
         },
         None,
@@ -1282,6 +1297,37 @@ async fn test_epoch_change() {
     assert_eq!(query_runner.get_epoch_info().epoch, 1);
 }
 
+#[tokio::test]
+async fn test_get_committee_members_at_epoch() {
+    let temp_dir = tempdir().unwrap();
+
+    let committee_size = 4;
+    let (committee, keystore) = create_genesis_committee(committee_size);
+    let (update_socket, query_runner) = test_init_app(&temp_dir, committee);
+
+    let epoch_0_members = query_runner.get_committee_members_by_index();
+    simple_epoch_change!(&update_socket, &keystore, &query_runner, 0);
+    let epoch_1_members = query_runner.get_committee_members_by_index();
+    simple_epoch_change!(&update_socket, &keystore, &query_runner, 1);
+    assert_eq!(query_runner.get_current_epoch(), 2);
+
+    // The committees of the previous epochs are still available.
+    assert_eq!(
+        query_runner.get_committee_members_at_epoch(0),
+        Some(epoch_0_members)
+    );
+    assert_eq!(
+        query_runner.get_committee_members_at_epoch(1),
+        Some(epoch_1_members)
+    );
+    assert_eq!(
+        query_runner.get_committee_members_at_epoch(2),
+        Some(query_runner.get_committee_members_by_index())
+    );
+    // There is no committee for a future epoch yet.
+    assert_eq!(query_runner.get_committee_members_at_epoch(3), None);
+}
+
 #[tokio::test]
 async fn test_change_epoch_reverts_account_key() {
     let temp_dir = tempdir().unwrap();
@@ -1739,6 +1785,46 @@ async fn test_stake() {
     expect_tx_revert!(update, &update_socket, ExecutionError::TokensLocked);
 }
 
+#[tokio::test]
+async fn test_block_builder() {
+    let temp_dir = tempdir().unwrap();
+
+    let committee_size = 4;
+    let (committee, _keystore) = create_genesis_committee(committee_size);
+    let (update_socket, query_runner) = test_init_app(&temp_dir, committee);
+
+    let owner_secret_key = AccountOwnerSecretKey::generate();
+    let deposit = 1000_u64.into();
+    let update1 = prepare_deposit_update(&deposit, &owner_secret_key, 1);
+    let update2 = prepare_deposit_update(&deposit, &owner_secret_key, 2);
+
+    let parent_digest = query_runner.get_last_block();
+    let block = BlockBuilder::new()
+        .with_parent_digest(parent_digest)
+        .with_sub_dag_index(1)
+        .with_transactions(vec![update1, update2])
+        .build();
+    assert_eq!(block.transactions.len(), 2);
+    assert_ne!(block.digest, parent_digest);
+    let digest = block.digest;
+
+    let response = update_socket.run(block).await.unwrap();
+    assert_eq!(response.txn_receipts.len(), 2);
+    assert!(
+        response
+            .txn_receipts
+            .iter()
+            .all(|receipt| receipt.response.is_success())
+    );
+
+    // Both transactions were executed as part of the same block.
+    assert_eq!(query_runner.get_last_block(), digest);
+    assert_eq!(
+        get_flk_balance(&query_runner, &owner_secret_key.to_pk().into()),
+        (HpUfixed::<18>::from(2u16) * deposit)
+    );
+}
+
 #[tokio::test]
 async fn test_stake_lock() {
     let temp_dir = tempdir().unwrap();
@@ -2319,6 +2405,40 @@ async fn test_get_node_registry() {
         paging_params(false, keystore.len() as u32, 1),
         1
     );
+
+    // The committee nodes are participating, the newly staked nodes are not yet.
+    assert_paging_node_registry!(
+        &query_runner,
+        paging_params_with_participation(true, 0, keystore.len() + 3, Participation::True),
+        keystore.len()
+    );
+    assert_paging_node_registry!(
+        &query_runner,
+        paging_params_with_participation(true, 0, keystore.len() + 3, Participation::False),
+        3
+    );
+    assert_paging_node_registry!(
+        &query_runner,
+        paging_params_with_participation(false, 0, keystore.len() + 3, Participation::False),
+        2
+    );
+
+    // Paging is applied after filtering by participation.
+    assert_paging_node_registry!(
+        &query_runner,
+        paging_params_with_participation(true, 0, 2, Participation::True),
+        2
+    );
+    assert_paging_node_registry!(
+        &query_runner,
+        paging_params_with_participation(true, 2, keystore.len() + 3, Participation::True),
+        keystore.len() - 2
+    );
+    assert_paging_node_registry!(
+        &query_runner,
+        paging_params_with_participation(true, keystore.len() as u32, 2, Participation::False),
+        2
+    );
 }
 
 #[tokio::test]
@@ -11,15 +11,16 @@
     TransactionRequest,
 };
 use resolved_pathbuf::ResolvedPathBuf;
-use rocksdb::{Options, DB};
+use rocksdb::{IteratorMode, Options, WriteBatch, DB};
 use tokio::pin;
 
-use crate::config::Config;
+use crate::config::{Config, RetentionPolicy};
 
 // Column families
 const BLKHASH_TO_BLKNUM: &str = "blkhash_to_blknum";
 const BLKNUM_TO_BLK: &str = "blknum_to_blk";
 const TXHASH_TO_TXRCT: &str = "txhash_to_txrct";
+const EPOCH_BOUNDARIES: &str = "epoch_boundaries";
 const MISC: &str = "misc";
 
 // Special keys
@@ -27,14 +28,18 @@
 const EARLIEST: &str = "earliest";
 
 pub struct Archive<C: Collection> {
-    inner: Option<Arc<ArchiveInner<C>>>,
+    pub(crate) inner: Option<Arc<ArchiveInner<C>>>,
 }
 
-struct ArchiveInner<C: Collection> {
+pub(crate) struct ArchiveInner<C: Collection> {
     db: DB,
     blockstore: c!(C::BlockstoreInterface),
     /// Handles the rocks db storage for each epoch
     historical_state_dir: ResolvedPathBuf,
+    /// Decides which blocks and historical states are pruned on epoch change
+    retention: RetentionPolicy,
+    /// Epochs whose historical state is never pruned
+    preserved_checkpoints: Vec<u64>,
 }
 
 impl<C: Collection> BuildGraph for Archive<C> {
@@ -61,7 +66,13 @@ pub fn new(
         db_options.create_if_missing(true);
         db_options.create_missing_column_families(true);
 
-        let cf = vec![BLKHASH_TO_BLKNUM, BLKNUM_TO_BLK, TXHASH_TO_TXRCT, MISC];
+        let cf = vec![
+            BLKHASH_TO_BLKNUM,
+            BLKNUM_TO_BLK,
+            TXHASH_TO_TXRCT,
+            EPOCH_BOUNDARIES,
+            MISC,
+        ];
         let db =
             DB::open_cf(&db_options, &config.store_path, cf).expect("Failed to create archive db");
 
@@ -76,7 +87,13 @@ pub fn new(
                 .expect("Failed to create historical dir");
         }
 
-        let inner = ArchiveInner::<C>::new(db, historical_state_dir, blockstore.clone());
+        let inner = ArchiveInner::<C>::new(
+            db,
+            historical_state_dir,
+            blockstore.clone(),
+            config.retention,
+            config.preserved_checkpoints,
+        );
 
         Self {
             inner: Some(Arc::new(inner)),
@@ -160,11 +177,15 @@ fn new(
         db: DB,
         historical_state_dir: ResolvedPathBuf,
         blockstore: c!(C::BlockstoreInterface),
+        retention: RetentionPolicy,
+        preserved_checkpoints: Vec<u64>,
     ) -> Self {
         Self {
             db,
             historical_state_dir,
             blockstore,
+            retention,
+            preserved_checkpoints,
         }
     }
 
@@ -271,7 +292,11 @@ fn get_block_by_num(&self, blk_num: &[u8]) -> Result<Option<BlockInfo>> {
         Ok(())
     }
 
-    fn handle_block(&self, block: Block, response: BlockExecutionResponse) -> Result<()> {
+    pub(crate) fn handle_block(
+        &self,
+        block: Block,
+        response: BlockExecutionResponse,
+    ) -> Result<()> {
         let (blk_receipt, txn_receipts) = response.to_receipts();
         let blk_info = BlockInfo {
             block,
@@ -292,10 +317,8 @@ fn handle_block(&self, block: Block, response: BlockExecutionResponse) -> Result
             .cf_handle(MISC)
             .context("Column family `misc` not found in db")?;
 
-        // Store the first block, if we haven't already
+        // Store the first block, if we haven't already. Pruning moves this forward.
         if self.db.get_cf(&misc_cf, EARLIEST)?.is_none() {
-            // Once we prune old blocks from the archiver, we have to store the actual block info
-            // here.
             self.db.put_cf(&misc_cf, EARLIEST, blk_num)?;
         }
 
@@ -320,6 +343,150 @@ fn handle_block(&self, block: Block, response: BlockExecutionResponse) -> Result
             self.db
                 .put_cf(&txhash_cf, txn_receipt.transaction_hash, txn_receipt_bytes)?;
         }
+
+        // Remember the blocks that ended an epoch, these are used to prune by epoch depth. The
+        // keys are big endian so that they are iterated in order.
+        if blk_info.receipt.change_epoch {
+            let boundaries_cf = self
+                .db
+                .cf_handle(EPOCH_BOUNDARIES)
+                .context("Column family `epoch_boundaries` not found in db")?;
+            self.db.put_cf(
+                &boundaries_cf,
+                blk_info.receipt.block_number.to_be_bytes(),
+                b"",
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Prunes the blocks and historical states that fall outside of the retention policy.
+    pub(crate) fn prune(&self, current_epoch: u64) -> Result<()> {
+        let cutoff = match self.retention {
+            RetentionPolicy::KeepAll => return Ok(()),
+            RetentionPolicy::Epochs(epochs) => {
+                let epochs = epochs.max(1);
+                self.prune_historical_states(current_epoch, epochs)?;
+                self.get_first_retained_block(epochs)?
+            },
+            RetentionPolicy::Blocks(blocks) => {
+                let blocks = blocks.max(1);
+                self.get_latest_block_num()?
+                    .map(|latest| (latest + 1).saturating_sub(blocks))
+            },
+        };
+
+        match cutoff {
+            Some(cutoff) => self.prune_blocks_before(cutoff),
+            None => Ok(()),
+        }
+    }
+
+    /// Returns the number of the first block of the oldest epoch we want to keep, or `None` if we
+    /// have not archived more than `epochs` epochs yet.
+    fn get_first_retained_block(&self, epochs: u64) -> Result<Option<u64>> {
+        let boundaries_cf = self
+            .db
+            .cf_handle(EPOCH_BOUNDARIES)
+            .context("Column family `epoch_boundaries` not found in db")?;
+
+        // The current epoch has not ended yet, so the `epochs - 1`th last boundary is the end of
+        // the newest epoch we don't keep.
+        let Some(boundary) = self
+            .db
+            .iterator_cf(&boundaries_cf, IteratorMode::End)
+            .nth(epochs as usize - 1)
+            .transpose()?
+        else {
+            return Ok(None);
+        };
+        let boundary = u64::from_be_bytes(boundary.0[..].try_into()?);
+        Ok(Some(boundary + 1))
+    }
+
+    fn get_latest_block_num(&self) -> Result<Option<u64>> {
+        self.get_misc_block_num(LATEST)
+    }
+
+    fn get_misc_block_num(&self, key: &str) -> Result<Option<u64>> {
+        let misc_cf = self
+            .db
+            .cf_handle(MISC)
+            .context("Column family `misc` not found in db")?;
+        match self.db.get_cf(&misc_cf, key)? {
+            Some(bytes) => Ok(Some(u64::from_le_bytes(bytes.as_slice().try_into()?))),
+            None => Ok(None),
+        }
+    }
+
+    /// Removes all the blocks with a number lower than `cutoff`, along with their hash and
+    /// transaction receipt entries.
+    fn prune_blocks_before(&self, cutoff: u64) -> Result<()> {
+        let Some(earliest) = self.get_misc_block_num(EARLIEST)? else {
+            return Ok(());
+        };
+        if earliest >= cutoff {
+            return Ok(());
+        }
+
+        let blknum_cf = self
+            .db
+            .cf_handle(BLKNUM_TO_BLK)
+            .context("Column family `blknum_to_blk` not found in db")?;
+        let blkhash_cf = self
+            .db
+            .cf_handle(BLKHASH_TO_BLKNUM)
+            .context("Column family `blkhash_to_blknum` not found in db")?;
+        let txhash_cf = self
+            .db
+            .cf_handle(TXHASH_TO_TXRCT)
+            .context("Column family `txhash_to_txrct` not found in db")?;
+        let boundaries_cf = self
+            .db
+            .cf_handle(EPOCH_BOUNDARIES)
+            .context("Column family `epoch_boundaries` not found in db")?;
+        let misc_cf = self
+            .db
+            .cf_handle(MISC)
+            .context("Column family `misc` not found in db")?;
+
+        let mut batch = WriteBatch::default();
+        for num in earliest..cutoff {
+            let blk_num = num.to_le_bytes();
+            if let Some(blk_info) = self.get_block_by_num(&blk_num)? {
+                batch.delete_cf(&blkhash_cf, blk_info.receipt.block_hash);
+                for txn_hash in &blk_info.receipt.txn_hashes {
+                    batch.delete_cf(&txhash_cf, txn_hash);
+                }
+            }
+            batch.delete_cf(&blknum_cf, blk_num);
+            batch.delete_cf(&boundaries_cf, num.to_be_bytes());
+        }
+        batch.put_cf(&misc_cf, EARLIEST, cutoff.to_le_bytes());
+        self.db.write(batch)?;
+
+        tracing::debug!(target: "archive", "Pruned blocks {earliest} to {cutoff} (exclusive)");
+        Ok(())
+    }
+
+    /// Removes the historical states of the epochs older than the last `epochs` epochs, except
+    /// for the preserved checkpoints.
+    fn prune_historical_states(&self, current_epoch: u64, epochs: u64) -> Result<()> {
+        for entry in std::fs::read_dir(&self.historical_state_dir)? {
+            let entry = entry?;
+            let Some(epoch) = entry
+                .file_name()
+                .to_str()
+                .and_then(|name| name.parse::<u64>().ok())
+            else {
+                continue;
+            };
+            if epoch + epochs > current_epoch || self.preserved_checkpoints.contains(&epoch) {
+                continue;
+            }
+            tracing::debug!(target: "archive", "Pruning historical state for epoch {epoch}");
+            std::fs::remove_dir_all(entry.path())?;
+        }
         Ok(())
     }
 }
@@ -347,6 +514,9 @@ async fn insertion_task<C: Collection>(
             }
             Some(n) = epoch_changed_sub.recv() => {
                 let _ = inner.handle_epoch(n.current_epoch, n.last_epoch_hash).await;
+                if let Err(e) = inner.prune(n.current_epoch) {
+                    tracing::error!(target: "archive", "Failed to prune the archive: {e:?}");
+                }
             },
             Some(n) = block_executed_sub.recv() => {
                 let _ = inner.handle_block(n.block, n.response);
@@ -8,6 +8,25 @@ pub struct Config {
     pub is_archive: bool,
     /// Path to the database used by the narwhal implementation.
     pub store_path: ResolvedPathBuf,
+    /// How much history the archive keeps around. Older data is pruned on every epoch change.
+    #[serde(default)]
+    pub retention: RetentionPolicy,
+    /// Epochs whose historical state is never pruned, regardless of the retention policy.
+    #[serde(default)]
+    pub preserved_checkpoints: Vec<u64>,
+}
+
+/// The policy that decides which blocks and historical states are pruned from the archive.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum RetentionPolicy {
+    /// Never prune anything.
+    #[default]
+    KeepAll,
+    /// Keep the blocks and historical states of the last `n` epochs, including the current one.
+    Epochs(u64),
+    /// Keep the last `n` blocks. Historical states are not pruned under this policy.
+    Blocks(u64),
 }
 
 impl Default for Config {
@@ -18,6 +37,8 @@ fn default() -> Self {
                 .join("data/archiver")
                 .try_into()
                 .expect("Failed to resolve path"),
+            retention: RetentionPolicy::default(),
+            preserved_checkpoints: Vec::new(),
         }
     }
 }
@@ -15,11 +15,11 @@
     MockForwarder,
 };
 use lightning_test_utils::json_config::JsonConfigProvider;
-use lightning_test_utils::transaction::get_update_transactions;
-use tempfile::tempdir;
+use lightning_test_utils::transaction::{get_update_transactions, BlockBuilder};
+use tempfile::{tempdir, TempDir};
 
 use crate::archive::Archive;
-use crate::config::Config as ArchiveConfig;
+use crate::config::{Config as ArchiveConfig, RetentionPolicy};
 
 partial!(TestBinding {
     ApplicationInterface = Application<Self>;
@@ -33,6 +33,14 @@
 
 async fn get_node() -> Node<TestBinding> {
     let temp_dir = tempdir().unwrap();
+    get_node_with_retention(&temp_dir, RetentionPolicy::KeepAll, vec![]).await
+}
+
+async fn get_node_with_retention(
+    temp_dir: &TempDir,
+    retention: RetentionPolicy,
+    preserved_checkpoints: Vec<u64>,
+) -> Node<TestBinding> {
     let genesis_path = Genesis::default()
         .write_to_dir(temp_dir.path().to_path_buf().try_into().unwrap())
         .unwrap();
@@ -43,9 +51,12 @@ async fn get_node() -> Node<TestBinding> {
             .with::<Archive<TestBinding>>(ArchiveConfig {
                 is_archive: true,
                 store_path: temp_dir.path().join("archive").try_into().unwrap(),
+                retention,
+                preserved_checkpoints,
             })
             .with::<Blockstore<TestBinding>>(BlockstoreConfig {
                 root: temp_dir.path().join("blockstore").try_into().unwrap(),
+                max_total_bytes: None,
             })
             .with::<MockConsensus<TestBinding>>(MockConsensusConfig {
                 min_ordering_time: 0,
@@ -53,6 +64,7 @@ async fn get_node() -> Node<TestBinding> {
                 probability_txn_lost: 0.0,
                 transactions_to_lose: Default::default(),
                 new_block_interval: Duration::from_secs(0),
+                fault_injector: None,
             }),
     )
     .unwrap();
@@ -62,6 +74,39 @@ async fn get_node() -> Node<TestBinding> {
     node
 }
 
+/// Builds the response the application would return for executing `block`, with every transaction
+/// succeeding.
+fn get_block_response(
+    block: &types::Block,
+    block_number: u64,
+    change_epoch: bool,
+) -> types::BlockExecutionResponse {
+    let txn_receipts = block
+        .transactions
+        .iter()
+        .enumerate()
+        .map(|(index, tx)| types::TransactionReceipt {
+            block_hash: block.digest,
+            block_number,
+            transaction_index: index as u64,
+            transaction_hash: tx.hash(),
+            from: tx.sender(),
+            to: tx.to(),
+            response: types::TransactionResponse::Success(types::ExecutionData::None),
+            event: None,
+        })
+        .collect();
+
+    types::BlockExecutionResponse {
+        block_number,
+        block_hash: block.digest,
+        parent_hash: [0; 32],
+        change_epoch,
+        node_registry_delta: vec![],
+        txn_receipts,
+    }
+}
+
 #[tokio::test]
 async fn test_archive_api() {
     const NUM_TX: usize = 3;
@@ -101,7 +146,7 @@ async fn test_archive_api() {
     // Run the transactions.
 
     for tx in &transactions {
-        socket.run(tx.clone()).await.unwrap();
+        socket.run(tx.clone()).await.unwrap().unwrap();
     }
 
     let mut block_receipts = Vec::new();
@@ -175,3 +220,138 @@ async fn test_archive_api() {
 
     node.shutdown().await;
 }
+
+#[tokio::test]
+async fn test_prune_by_epoch_depth() {
+    let temp_dir = tempdir().unwrap();
+    let mut node = get_node_with_retention(&temp_dir, RetentionPolicy::Epochs(2), vec![0]).await;
+
+    let archive: Ref<Archive<TestBinding>> = node.provider.get();
+    let inner = archive.inner.clone().unwrap();
+
+    // Blocks 2, 5 and 8 end epochs 0, 1 and 2, block 9 is part of the current epoch 3.
+    let mut block_hashes = Vec::new();
+    for block_number in 0..10u64 {
+        let block = BlockBuilder::new()
+            .with_parent_digest([block_number as u8; 32])
+            .build();
+        let response = get_block_response(&block, block_number, block_number % 3 == 2);
+        block_hashes.push(block.digest);
+        inner.handle_block(block, response).unwrap();
+    }
+
+    // Pretend we have stored the historical state of every epoch so far.
+    let historical_dir = temp_dir.path().join("archive/historical");
+    for epoch in 0..=3 {
+        std::fs::create_dir_all(historical_dir.join(epoch.to_string())).unwrap();
+    }
+
+    inner.prune(3).unwrap();
+
+    // Only the blocks of epochs 2 and 3 are left.
+    for (block_number, block_hash) in block_hashes.into_iter().enumerate() {
+        let block_number = block_number as u64;
+        let retained = block_number >= 6;
+        assert_eq!(
+            archive
+                .get_block_by_number(block_number.into())
+                .await
+                .is_some(),
+            retained
+        );
+        assert_eq!(
+            archive.get_block_by_hash(block_hash).await.is_some(),
+            retained
+        );
+    }
+    assert_eq!(
+        archive
+            .get_block_by_number(BlockNumber::Earliest)
+            .await
+            .map(|receipt| receipt.block_number),
+        Some(6)
+    );
+
+    // The preserved checkpoint survives, even though it is older than the retained epochs.
+    assert!(historical_dir.join("0").is_dir());
+    assert!(!historical_dir.join("1").exists());
+    assert!(historical_dir.join("2").is_dir());
+    assert!(historical_dir.join("3").is_dir());
+
+    node.shutdown().await;
+}
+
+#[tokio::test]
+async fn test_get_block_by_hash() {
+    let mut node = get_node().await;
+
+    let archive: Ref<Archive<TestBinding>> = node.provider.get();
+    let inner = archive.inner.clone().unwrap();
+
+    let block = BlockBuilder::new()
+        .with_transactions(
+            get_update_transactions(2)
+                .into_iter()
+                .map(types::TransactionRequest::UpdateRequest),
+        )
+        .build();
+    let block_hash = block.digest;
+    let response = get_block_response(&block, 0, false);
+    inner.handle_block(block, response).unwrap();
+
+    let by_number = archive.get_block_by_number(0u64.into()).await;
+    let by_hash = archive.get_block_by_hash(block_hash).await;
+    assert!(by_hash.is_some());
+    assert_eq!(by_hash, by_number);
+    assert_eq!(by_hash.unwrap().block_hash, block_hash);
+
+    // A hash we have never archived is a miss.
+    assert_eq!(archive.get_block_by_hash([1; 32]).await, None);
+
+    node.shutdown().await;
+}
+
+#[tokio::test]
+async fn test_get_transaction_receipts_of_block() {
+    const NUM_TX: usize = 4;
+
+    let mut node = get_node().await;
+
+    let archive: Ref<Archive<TestBinding>> = node.provider.get();
+    let inner = archive.inner.clone().unwrap();
+
+    let block = BlockBuilder::new()
+        .with_transactions(
+            get_update_transactions(NUM_TX)
+                .into_iter()
+                .map(types::TransactionRequest::UpdateRequest),
+        )
+        .build();
+    let response = get_block_response(&block, 0, false);
+    let txn_receipts = response.txn_receipts.clone();
+    inner.handle_block(block.clone(), response).unwrap();
+
+    assert_eq!(txn_receipts.len(), NUM_TX);
+    for (index, receipt) in txn_receipts.iter().enumerate() {
+        assert_eq!(
+            archive
+                .get_transaction_receipt(receipt.transaction_hash)
+                .await
+                .as_ref(),
+            Some(receipt)
+        );
+        assert_eq!(receipt.transaction_index, index as u64);
+        assert_eq!(
+            archive
+                .get_transaction(receipt.transaction_hash)
+                .await
+                .as_ref(),
+            Some(&block.transactions[index])
+        );
+    }
+
+    // A hash we have never archived is a miss.
+    assert_eq!(archive.get_transaction_receipt([1; 32]).await, None);
+
+    node.shutdown().await;
+}
@@ -5,13 +5,15 @@
 use std::collections::{HashMap, VecDeque};
 use std::mem;
 use std::net::SocketAddr;
-use std::sync::atomic::{AtomicUsize, Ordering};
-use std::sync::Arc;
+use std::ops::Range;
+use std::sync::{Arc, Mutex};
 use std::time::{Duration, Instant};
 
 use affair::{Socket, Task};
 use anyhow::{anyhow, Result};
-use blake3_tree::ProofBuf;
+use blake3_tree::blake3::tree::BlockHasher;
+use blake3_tree::utils::HashTree;
+use blake3_tree::{IncrementalVerifier, ProofBuf};
 use bytes::{Buf, BufMut, Bytes, BytesMut};
 use lightning_interfaces::prelude::*;
 use lightning_interfaces::types::{
@@ -23,10 +25,10 @@
     RejectReason,
     ServerRequest,
 };
-use lightning_interfaces::ServiceScope;
+use lightning_interfaces::{DownloadProgress, ServiceScope};
 use lightning_metrics::increment_counter;
 use serde::{Deserialize, Serialize};
-use tokio::sync::{broadcast, mpsc};
+use tokio::sync::{broadcast, mpsc, Semaphore};
 use tokio::task::JoinSet;
 use tokio::time::timeout;
 use tokio_stream::StreamExt;
@@ -37,16 +39,40 @@
 type ServerRequestTask = Task<ServerRequest, broadcast::Receiver<Result<(), PeerRequestError>>>;
 
 const REQUEST_TIMEOUT: Duration = Duration::from_millis(1000);
+/// The length of a request for a range of blocks: the hash followed by the start and the end of
+/// the range.
+const RANGE_REQUEST_LEN: usize = mem::size_of::<Blake3Hash>() + 2 * mem::size_of::<u32>();
+/// The maximum number of downloads that failed part way we keep, to resume them from another peer.
+const MAX_PARTIAL_DOWNLOADS: usize = 128;
+
+type PartialDownloads<C> =
+    Arc<Mutex<HashMap<Blake3Hash, PartialDownload<c!(C::BlockstoreInterface::Put)>>>>;
 
 pub struct BlockstoreServer<C: Collection> {
     inner: Option<BlockstoreServerInner<C>>,
     socket: BlockstoreServerSocket,
+    download_progress: DownloadProgress,
+    pool_requester: c!(C::PoolInterface::Requester),
+    serve_permits: Arc<Semaphore>,
 }
 
 impl<C: Collection> BlockstoreServerInterface<C> for BlockstoreServer<C> {
     fn get_socket(&self) -> BlockstoreServerSocket {
         self.socket.clone()
     }
+
+    fn get_download_progress(&self) -> DownloadProgress {
+        self.download_progress.clone()
+    }
+
+    async fn request_range(
+        &self,
+        peer: NodeIndex,
+        hash: Blake3Hash,
+        blocks: Range<u32>,
+    ) -> Result<Vec<u8>, PeerRequestError> {
+        send_range_request::<C>(peer, hash, blocks, &self.pool_requester).await
+    }
 }
 
 impl<C: Collection> BlockstoreServer<C> {
@@ -59,17 +85,32 @@ fn init(
         let config = config.get::<Self>();
         let (pool_requester, pool_responder) = pool.open_req_res(ServiceScope::BlockstoreServer);
         let (socket, request_rx) = Socket::raw_bounded(2048);
+        let download_progress = DownloadProgress::default();
+        let serve_permits = Arc::new(Semaphore::new(config.max_conc_res));
         let inner = Some(BlockstoreServerInner::<C>::new(
             blockstore.clone(),
             request_rx,
             config.max_conc_req,
-            config.max_conc_res,
-            pool_requester,
+            serve_permits.clone(),
+            pool_requester.clone(),
             pool_responder,
             rep_aggregator.get_reporter(),
+            download_progress.clone(),
         ));
 
-        Ok(Self { inner, socket })
+        Ok(Self {
+            inner,
+            socket,
+            download_progress,
+            pool_requester,
+            serve_permits,
+        })
+    }
+
+    /// Returns the permits for serving requests from peers, so tests can fill up the limit.
+    #[cfg(test)]
+    pub(crate) fn serve_permits(&self) -> Arc<Semaphore> {
+        self.serve_permits.clone()
     }
 
     /// Start the system, should only be called once
@@ -97,11 +138,12 @@ pub struct BlockstoreServerInner<C: Collection> {
     blockstore: C::BlockstoreInterface,
     request_rx: mpsc::Receiver<ServerRequestTask>,
     max_conc_req: usize,
-    max_conc_res: usize,
-    num_responses: Arc<AtomicUsize>,
+    serve_permits: Arc<Semaphore>,
     pool_requester: c!(C::PoolInterface::Requester),
     pool_responder: c!(C::PoolInterface::Responder),
     rep_reporter: c!(C::ReputationAggregatorInterface::ReputationReporter),
+    download_progress: DownloadProgress,
+    partial_downloads: PartialDownloads<C>,
 }
 
 impl<C: Collection> BlockstoreServerInner<C> {
@@ -110,20 +152,22 @@ pub fn new(
         blockstore: C::BlockstoreInterface,
         request_rx: mpsc::Receiver<ServerRequestTask>,
         max_conc_req: usize,
-        max_conc_res: usize,
+        serve_permits: Arc<Semaphore>,
         pool_requester: c!(C::PoolInterface::Requester),
         pool_responder: c!(C::PoolInterface::Responder),
         rep_reporter: c!(C::ReputationAggregatorInterface::ReputationReporter),
+        download_progress: DownloadProgress,
     ) -> Self {
         Self {
             blockstore,
             request_rx,
             max_conc_req,
-            max_conc_res,
-            num_responses: AtomicUsize::new(0).into(),
+            serve_permits,
             pool_requester,
             pool_responder,
             rep_reporter,
+            download_progress,
+            partial_downloads: Default::default(),
         }
     }
 
@@ -146,10 +190,10 @@ pub async fn start(mut self) {
                             // TODO(matthias): find out which peer the request came from
                             match PeerRequest::try_from(req_header.bytes) {
                                 Ok(request) => {
-                                    let num_res = self.num_responses.fetch_add(1, Ordering::AcqRel);
-                                    if num_res < self.max_conc_res {
+                                    // The permit is held until we are done serving the request.
+                                    let permit = self.serve_permits.clone().try_acquire_owned();
+                                    if let Ok(permit) = permit {
                                         let blockstore = self.blockstore.clone();
-                                        let num_responses = self.num_responses.clone();
                                         let rep_reporter = self.rep_reporter.clone();
                                         spawn!(
                                             async move {
@@ -158,9 +202,9 @@ pub async fn start(mut self) {
                                                     request,
                                                     blockstore,
                                                     responder,
-                                                    num_responses,
                                                     rep_reporter,
                                                 ).await;
+                                                drop(permit);
 
                                                 increment_counter!(
                                                     "blockstore_server_handle_request",
@@ -170,7 +214,10 @@ pub async fn start(mut self) {
                                             "BLOCKSTORE-SERVER: handle request"
                                         );
                                     } else {
-                                        self.num_responses.fetch_sub(1, Ordering::Release);
+                                        increment_counter!(
+                                            "blockstore_server_rejected_request",
+                                            Some("Counter for number of blockstore requests rejected because too many requests were being served")
+                                        );
                                         responder.reject(RejectReason::TooManyRequests);
                                     }
                                 }
@@ -185,7 +232,10 @@ pub async fn start(mut self) {
                 }
                 task = self.request_rx.recv() => {
                     if let Some(task) = task {
-                        let peer_request = PeerRequest { hash: task.request.hash };
+                        let peer_request = PeerRequest {
+                            hash: task.request.hash,
+                            blocks: None,
+                        };
                         let rx = if let Some(tx) = pending_requests.get(&peer_request) {
                             // If a request for this hash is currently pending, subscribe to get
                             // notified about the result.
@@ -197,6 +247,8 @@ pub async fn start(mut self) {
                                 let pool_requester = self.pool_requester.clone();
                                 let peer_request_ = peer_request.clone();
                                 let rep_reporter = self.rep_reporter.clone();
+                                let download_progress = self.download_progress.clone();
+                                let partial_downloads = self.partial_downloads.clone();
                                 tasks.spawn(async move {
                                     let res = send_request::<C>(
                                         task.request.peer,
@@ -204,6 +256,8 @@ pub async fn start(mut self) {
                                         blockstore,
                                         pool_requester,
                                         rep_reporter,
+                                        download_progress,
+                                        partial_downloads,
                                     ).await;
 
                                     if res.is_ok() {
@@ -267,14 +321,18 @@ enum Message {
 #[derive(Debug, Hash, PartialEq, Eq, Clone)]
 pub struct PeerRequest {
     hash: Blake3Hash,
-    //block_counter: u32,
+    /// The range of blocks to send, all of the blocks if it's `None`.
+    blocks: Option<Range<u32>>,
 }
 
 impl From<PeerRequest> for Bytes {
     fn from(value: PeerRequest) -> Self {
-        let mut buf = BytesMut::with_capacity(value.hash.len());
+        let mut buf = BytesMut::with_capacity(RANGE_REQUEST_LEN);
         buf.put_slice(&value.hash);
-        //buf.put_u32(value.block_counter);
+        if let Some(blocks) = value.blocks {
+            buf.put_u32(blocks.start);
+            buf.put_u32(blocks.end);
+        }
         buf.into()
     }
 }
@@ -284,14 +342,22 @@ impl TryFrom<Bytes> for PeerRequest {
 
     fn try_from(mut value: Bytes) -> Result<Self> {
         let hash_len = mem::size_of::<Blake3Hash>();
-        if value.len() != hash_len {
-            return Err(anyhow!("Number of bytes must be {}", hash_len));
+        if value.len() != hash_len && value.len() != RANGE_REQUEST_LEN {
+            return Err(anyhow!(
+                "Number of bytes must be {} or {}",
+                hash_len,
+                RANGE_REQUEST_LEN
+            ));
         }
         let hash = value.split_to(hash_len);
-        //let block_counter = value.get_u32();
+        let blocks = if value.is_empty() {
+            None
+        } else {
+            Some(value.get_u32()..value.get_u32())
+        };
         Ok(Self {
             hash: hash.to_vec().try_into().unwrap(),
-            //block_counter,
+            blocks,
         })
     }
 }
@@ -352,20 +418,47 @@ async fn handle_request<C: Collection>(
     peer_request: PeerRequest,
     blockstore: C::BlockstoreInterface,
     mut request: <c!(C::PoolInterface::Responder) as ResponderInterface>::Request,
-    num_responses: Arc<AtomicUsize>,
     rep_reporter: c!(C::ReputationAggregatorInterface::ReputationReporter),
 ) {
     if let Some(tree) = blockstore.get_tree(&peer_request.hash).await {
+        let blocks = match peer_request.blocks {
+            // A range that runs past the end of the content is cut off there.
+            Some(blocks) => blocks.start as usize..tree.len().min(blocks.end as usize),
+            None => 0..tree.len(),
+        };
+        if blocks.is_empty() {
+            request.reject(RejectReason::Other);
+            return;
+        }
+
         let mut num_bytes = 0;
         let instant = Instant::now();
-        for block in 0..tree.len() {
+        for block in blocks.clone() {
             let compr = CompressionAlgoSet::default(); // rustfmt
             let Some(chunk) = blockstore.get(block as u32, &tree[block], compr).await else {
                 break;
             };
 
-            let proof = if block == 0 {
-                ProofBuf::new(tree.as_ref(), 0)
+            // Never serve a block that got corrupted on disk, the peer would reject it anyway.
+            if !is_valid_block(&tree, block, &chunk.content) {
+                error!(
+                    "Block {block} of {:?} does not match its hash",
+                    peer_request.hash
+                );
+                increment_counter!(
+                    "blockstore_server_corrupted_block",
+                    Some(
+                        "Counter for stored blocks that did not match their hash when serving them"
+                    )
+                );
+                if block == blocks.start {
+                    request.reject(RejectReason::Other);
+                }
+                return;
+            }
+
+            let proof = if block == blocks.start {
+                ProofBuf::new(tree.as_ref(), block)
             } else {
                 ProofBuf::resume(tree.as_ref(), block)
             };
@@ -377,7 +470,6 @@ async fn handle_request<C: Collection>(
                     .await
                 {
                     error!("Failed to send proof: {e:?}");
-                    num_responses.fetch_sub(1, Ordering::Release);
                     return;
                 }
             }
@@ -390,7 +482,6 @@ async fn handle_request<C: Collection>(
                 .await
             {
                 error!("Failed to send chunk: {e:?}");
-                num_responses.fetch_sub(1, Ordering::Release);
                 return;
             }
         }
@@ -402,8 +493,22 @@ async fn handle_request<C: Collection>(
     } else {
         request.reject(RejectReason::ContentNotFound);
     }
+}
+
+fn is_valid_block(tree: &HashTree, block: usize, content: &[u8]) -> bool {
+    let mut hasher = BlockHasher::new();
+    hasher.set_block(block);
+    hasher.update(content);
+    hasher.finalize(tree.len() == 1) == tree[block]
+}
 
-    num_responses.fetch_sub(1, Ordering::Release);
+/// The blocks of a download from a peer that were verified before the peer failed, so that the
+/// download can be resumed from another peer.
+struct PartialDownload<P> {
+    putter: P,
+    /// The first block that was not verified yet.
+    next_block: u32,
+    content_bytes: u64,
 }
 
 async fn send_request<C: Collection>(
@@ -412,77 +517,137 @@ async fn send_request<C: Collection>(
     blockstore: C::BlockstoreInterface,
     pool_requester: c!(C::PoolInterface::Requester),
     rep_reporter: c!(C::ReputationAggregatorInterface::ReputationReporter),
+    download_progress: DownloadProgress,
+    partial_downloads: PartialDownloads<C>,
 ) -> Result<PeerRequest, ErrorResponse> {
-    match timeout(
-        REQUEST_TIMEOUT,
-        pool_requester.request(peer, Bytes::from(request.clone())),
+    let hash = request.hash;
+    // Pick up where the last peer we downloaded the content from stopped.
+    let mut download = partial_downloads
+        .lock()
+        .unwrap()
+        .remove(&hash)
+        .unwrap_or_else(|| PartialDownload {
+            putter: blockstore.put(None),
+            next_block: 0,
+            content_bytes: 0,
+        });
+
+    let instant = Instant::now();
+    let res = receive_range::<C>(
+        peer,
+        hash,
+        download.next_block..u32::MAX,
+        &pool_requester,
+        |chunk| {
+            download
+                .putter
+                .write(chunk, CompressionAlgorithm::Uncompressed)
+                .unwrap();
+            download.next_block += 1;
+            download.content_bytes += chunk.len() as u64;
+            let (content_bytes, blocks) = (download.content_bytes, download.next_block);
+            download_progress.update(&hash, |progress| {
+                progress.bytes_fetched = content_bytes;
+                progress.blocks_verified = blocks;
+            });
+        },
     )
-    .await
-    {
-        Ok(Ok(response)) => {
-            match response.status_code() {
-                Ok(()) => {
-                    let mut body = response.body();
-                    let mut putter = blockstore.put(Some(request.hash));
-                    let mut bytes_recv = 0;
-                    let instant = Instant::now();
-
-                    while let Some(bytes) = body.next().await {
-                        let Ok(bytes) = bytes else {
-                            return Err(ErrorResponse {
-                                error: PeerRequestError::Incomplete,
-                                request,
-                            });
-                        };
-                        bytes_recv += bytes.len();
-                        let Ok(frame) = Frame::try_from(bytes) else {
-                            return Err(ErrorResponse {
-                                error: PeerRequestError::Incomplete,
-                                request,
-                            });
-                        };
-                        match frame {
-                            Frame::Proof(proof) => putter.feed_proof(&proof).unwrap(),
-                            Frame::Chunk(chunk) => putter
-                                .write(&chunk, CompressionAlgorithm::Uncompressed)
-                                .unwrap(),
-                            Frame::Eos => {
-                                // TODO: Handle premature end of stream errors instead of
-                                // unwrapping here, since we there could be an upstream blockstore
-                                // miss where the server would send an EOS frame.
-                                let _hash = putter.finalize().await.unwrap();
-                                // TODO(matthias): do we have to compare this hash to the
-                                // requested hash?
-                                let duration = instant.elapsed();
-                                rep_reporter.report_bytes_received(
-                                    peer,
-                                    bytes_recv as u64,
-                                    Some(duration),
-                                );
-                                return Ok(request);
-                            },
-                        }
-                    }
-                    Err(ErrorResponse {
-                        error: PeerRequestError::Incomplete,
-                        request,
-                    })
-                },
-                Err(reason) => Err(ErrorResponse {
-                    error: PeerRequestError::Rejected(reason),
-                    request,
-                }),
+    .await;
+
+    let bytes_recv = match res {
+        Ok(bytes_recv) => bytes_recv,
+        Err(error) => {
+            let mut partial_downloads = partial_downloads.lock().unwrap();
+            if download.next_block > 0 && partial_downloads.len() < MAX_PARTIAL_DOWNLOADS {
+                partial_downloads.insert(hash, download);
             }
+            return Err(ErrorResponse { error, request });
         },
-        Ok(Err(_)) => Err(ErrorResponse {
-            error: PeerRequestError::Incomplete,
-            request,
-        }),
-        Err(_) => Err(ErrorResponse {
-            error: PeerRequestError::Timeout,
+    };
+
+    // Every block was verified against the root hash already, so the content has to match it.
+    if download.putter.finalize().await.ok() != Some(hash) {
+        return Err(ErrorResponse {
+            error: PeerRequestError::InvalidContent,
             request,
-        }),
+        });
+    }
+    download_progress.update(&hash, |progress| {
+        progress.bytes_fetched = download.content_bytes;
+        progress.blocks_verified = download.next_block;
+        progress.total_blocks = Some(download.next_block);
+    });
+    rep_reporter.report_bytes_received(peer, bytes_recv as u64, Some(instant.elapsed()));
+    Ok(request)
+}
+
+/// Requests a range of blocks of the content from a peer and verifies them against the root hash.
+async fn send_range_request<C: Collection>(
+    peer: NodeIndex,
+    hash: Blake3Hash,
+    blocks: Range<u32>,
+    pool_requester: &c!(C::PoolInterface::Requester),
+) -> Result<Vec<u8>, PeerRequestError> {
+    let mut content = Vec::new();
+    receive_range::<C>(peer, hash, blocks, pool_requester, |chunk| {
+        content.extend_from_slice(chunk)
+    })
+    .await?;
+    Ok(content)
+}
+
+/// Requests a range of blocks of the content from a peer, and hands each block to `on_block` once
+/// it has been verified against the root hash. The range is cut off at the end of the content.
+/// Returns the number of bytes received.
+async fn receive_range<C: Collection>(
+    peer: NodeIndex,
+    hash: Blake3Hash,
+    blocks: Range<u32>,
+    pool_requester: &c!(C::PoolInterface::Requester),
+    mut on_block: impl FnMut(&[u8]),
+) -> Result<usize, PeerRequestError> {
+    let request = PeerRequest {
+        hash,
+        blocks: Some(blocks.clone()),
+    };
+    let response = match timeout(
+        REQUEST_TIMEOUT,
+        pool_requester.request(peer, Bytes::from(request)),
+    )
+    .await
+    {
+        Ok(Ok(response)) => response,
+        Ok(Err(_)) => return Err(PeerRequestError::Incomplete),
+        Err(_) => return Err(PeerRequestError::Timeout),
+    };
+    response.status_code().map_err(PeerRequestError::Rejected)?;
+
+    let mut verifier = IncrementalVerifier::new(hash, blocks.start as usize);
+    let mut bytes_recv = 0;
+    let mut body = response.body();
+    while let Some(bytes) = body.next().await {
+        let bytes = bytes.map_err(|_| PeerRequestError::Incomplete)?;
+        bytes_recv += bytes.len();
+        match Frame::try_from(bytes).map_err(|_| PeerRequestError::Incomplete)? {
+            Frame::Proof(proof) => verifier
+                .feed_proof(&proof)
+                .map_err(|_| PeerRequestError::InvalidContent)?,
+            Frame::Chunk(chunk) => {
+                let mut hasher = BlockHasher::new();
+                hasher.set_block(verifier.get_current_block_counter());
+                hasher.update(&chunk);
+                verifier
+                    .verify(hasher)
+                    .map_err(|_| PeerRequestError::InvalidContent)?;
+                on_block(&chunk);
+            },
+            Frame::Eos => break,
+        }
+    }
+    if verifier.get_current_block_counter() != blocks.end as usize && !verifier.is_done() {
+        return Err(PeerRequestError::Incomplete);
     }
+    Ok(bytes_recv)
 }
 
 impl<C: Collection> ConfigConsumer for BlockstoreServer<C> {
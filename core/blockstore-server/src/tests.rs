@@ -14,7 +14,10 @@
 use lightning_interfaces::types::{
     CompressionAlgoSet,
     CompressionAlgorithm,
+    HandshakePorts,
     NodePorts,
+    PeerRequestError,
+    RejectReason,
     ServerRequest,
 };
 use lightning_notifier::Notifier;
@@ -51,6 +54,24 @@ fn create_content() -> Vec<u8> {
         .collect()
 }
 
+/// Flips a byte of the given block on the disk of the given node.
+fn corrupt_block(temp_dir: &TempDir, node: usize, block: usize) {
+    let block_dir = temp_dir.path().join(format!("node{node}/blockstore/block"));
+    let block_path = std::fs::read_dir(block_dir)
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .find(|path| {
+            path.file_name()
+                .unwrap()
+                .to_string_lossy()
+                .starts_with(&format!("{block}-"))
+        })
+        .unwrap();
+    let mut content = std::fs::read(&block_path).unwrap();
+    content[0] ^= 0xff;
+    std::fs::write(&block_path, content).unwrap();
+}
+
 struct Peer<C: Collection> {
     inner: Node<C>,
     node_public_key: NodePublicKey,
@@ -74,6 +95,24 @@ async fn get_peers(
     temp_dir: &TempDir,
     port_offset: u16,
     num_peers: usize,
+) -> Vec<Peer<TestBinding>> {
+    get_peers_with_config(
+        temp_dir,
+        port_offset,
+        num_peers,
+        Config {
+            max_conc_req: 10,
+            max_conc_res: 10,
+        },
+    )
+    .await
+}
+
+async fn get_peers_with_config(
+    temp_dir: &TempDir,
+    port_offset: u16,
+    num_peers: usize,
+    config: Config,
 ) -> Vec<Peer<TestBinding>> {
     let mut keystores = Vec::new();
     let mut genesis = Genesis::default();
@@ -95,13 +134,17 @@ async fn get_peers(
             "127.0.0.1".parse().unwrap(),
             node_secret_key.to_pk(),
             NodePorts {
-                primary: 48000_u16,
-                worker: 48101_u16,
-                mempool: 48202_u16,
-                rpc: 48300_u16,
+                primary: 20000 + i as u16,
+                worker: 20100 + i as u16,
+                mempool: 20200 + i as u16,
+                rpc: 20300 + i as u16,
                 pool: port_offset + i as u16,
-                pinger: 48600_u16,
-                handshake: Default::default(),
+                pinger: 20600 + i as u16,
+                handshake: HandshakePorts {
+                    http: 20700 + i as u16,
+                    webrtc: 20800 + i as u16,
+                    webtransport: 20900 + i as u16,
+                },
             },
             None,
             true,
@@ -133,11 +176,9 @@ async fn get_peers(
                                 .join(format!("node{i}/blockstore"))
                                 .try_into()
                                 .unwrap(),
+                            max_total_bytes: None,
                         })
-                        .with::<BlockstoreServer<TestBinding>>(Config {
-                            max_conc_req: 10,
-                            max_conc_res: 10,
-                        }),
+                        .with::<BlockstoreServer<TestBinding>>(config.clone()),
                 )
                 .with(keystore.clone()),
         )
@@ -258,3 +299,237 @@ async fn test_send_and_receive() {
         drop(peer);
     }
 }
+
+#[tokio::test]
+async fn test_request_range() {
+    let temp_dir = tempdir().unwrap();
+    let peers = get_peers(&temp_dir, 49300, 2).await;
+    let query_runner = peers[0].app().sync_query();
+    for peer in &peers {
+        peer.inner.start().await;
+    }
+    tokio::time::sleep(Duration::from_millis(500)).await;
+
+    let node_index1 = query_runner
+        .pubkey_to_index(&peers[0].node_public_key)
+        .unwrap();
+
+    let content = create_content();
+    // Put some data into the blockstore of peer 1
+    let mut putter = peers[0].blockstore().put(None);
+    putter
+        .write(&content, CompressionAlgorithm::Uncompressed)
+        .unwrap();
+    let hash = putter.finalize().await.unwrap();
+
+    // Request the two blocks in the middle from peer 1, which are verified against the root hash.
+    let blocks = peers[1]
+        .blockstore_server()
+        .request_range(node_index1, hash, 1..3)
+        .await
+        .expect("Failed to request range");
+    assert_eq!(blocks, &content[BLOCK_SIZE..3 * BLOCK_SIZE]);
+
+    // The content only has four blocks, so the range is cut off after the last one.
+    let blocks = peers[1]
+        .blockstore_server()
+        .request_range(node_index1, hash, 3..5)
+        .await
+        .expect("Failed to request range");
+    assert_eq!(blocks, &content[3 * BLOCK_SIZE..]);
+
+    // A range that starts past the last block has nothing to send.
+    let res = peers[1]
+        .blockstore_server()
+        .request_range(node_index1, hash, 4..6)
+        .await;
+    assert!(matches!(
+        res,
+        Err(PeerRequestError::Rejected(RejectReason::Other))
+    ));
+
+    for mut peer in peers {
+        peer.inner.shutdown().await;
+        drop(peer);
+    }
+}
+
+#[tokio::test]
+async fn test_reject_requests_beyond_limit() {
+    let temp_dir = tempdir().unwrap();
+    let peers = get_peers_with_config(
+        &temp_dir,
+        49400,
+        2,
+        Config {
+            max_conc_req: 10,
+            max_conc_res: 1,
+        },
+    )
+    .await;
+    let query_runner = peers[0].app().sync_query();
+    for peer in &peers {
+        peer.inner.start().await;
+    }
+    tokio::time::sleep(Duration::from_millis(500)).await;
+
+    let node_index1 = query_runner
+        .pubkey_to_index(&peers[0].node_public_key)
+        .unwrap();
+
+    let content = create_content();
+    // Put some data into the blockstore of peer 1
+    let mut putter = peers[0].blockstore().put(None);
+    putter
+        .write(&content, CompressionAlgorithm::Uncompressed)
+        .unwrap();
+    let hash = putter.finalize().await.unwrap();
+
+    // Peer 1 only serves one request at a time, so while it is busy with another request it
+    // rejects ours.
+    let permit = peers[0]
+        .blockstore_server()
+        .serve_permits()
+        .try_acquire_owned()
+        .unwrap();
+    let res = peers[1]
+        .blockstore_server()
+        .request_range(node_index1, hash, 0..4)
+        .await;
+    assert!(matches!(
+        res,
+        Err(PeerRequestError::Rejected(RejectReason::TooManyRequests))
+    ));
+
+    // Once it is done with the other request, it serves ours.
+    drop(permit);
+    let blocks = peers[1]
+        .blockstore_server()
+        .request_range(node_index1, hash, 0..4)
+        .await
+        .expect("Failed to request range");
+    assert_eq!(blocks, content);
+
+    for mut peer in peers {
+        peer.inner.shutdown().await;
+        drop(peer);
+    }
+}
+
+#[tokio::test]
+async fn test_refuse_to_serve_corrupted_block() {
+    let temp_dir = tempdir().unwrap();
+    let peers = get_peers(&temp_dir, 49500, 2).await;
+    let query_runner = peers[0].app().sync_query();
+    for peer in &peers {
+        peer.inner.start().await;
+    }
+    tokio::time::sleep(Duration::from_millis(500)).await;
+
+    let node_index1 = query_runner
+        .pubkey_to_index(&peers[0].node_public_key)
+        .unwrap();
+
+    let content = create_content();
+    // Put some data into the blockstore of peer 1
+    let mut putter = peers[0].blockstore().put(None);
+    putter
+        .write(&content, CompressionAlgorithm::Uncompressed)
+        .unwrap();
+    let hash = putter.finalize().await.unwrap();
+
+    // Flip a byte of the second block on the disk of peer 1.
+    corrupt_block(&temp_dir, 0, 1);
+
+    // A request starting at the corrupted block is rejected right away.
+    let res = peers[1]
+        .blockstore_server()
+        .request_range(node_index1, hash, 1..3)
+        .await;
+    assert!(matches!(
+        res,
+        Err(PeerRequestError::Rejected(RejectReason::Other))
+    ));
+
+    // A request for all of the content stops before the corrupted block.
+    let res = peers[1]
+        .blockstore_server()
+        .request_range(node_index1, hash, 0..4)
+        .await;
+    assert!(matches!(res, Err(PeerRequestError::Incomplete)));
+
+    for mut peer in peers {
+        peer.inner.shutdown().await;
+        drop(peer);
+    }
+}
+
+#[tokio::test]
+async fn test_resume_download_from_another_peer() {
+    let temp_dir = tempdir().unwrap();
+    let peers = get_peers(&temp_dir, 49600, 3).await;
+    let query_runner = peers[0].app().sync_query();
+    for peer in &peers {
+        peer.inner.start().await;
+    }
+    tokio::time::sleep(Duration::from_millis(500)).await;
+
+    let node_index1 = query_runner
+        .pubkey_to_index(&peers[0].node_public_key)
+        .unwrap();
+    let node_index3 = query_runner
+        .pubkey_to_index(&peers[2].node_public_key)
+        .unwrap();
+
+    // Put the same data into the blockstores of peer 1 and peer 3.
+    let content = create_content();
+    let mut hashes = Vec::new();
+    for peer in [&peers[0], &peers[2]] {
+        let mut putter = peer.blockstore().put(None);
+        putter
+            .write(&content, CompressionAlgorithm::Uncompressed)
+            .unwrap();
+        hashes.push(putter.finalize().await.unwrap());
+    }
+    let hash = hashes[0];
+
+    // Peer 1 can only serve the first two blocks, and peer 3 only the last two.
+    corrupt_block(&temp_dir, 0, 2);
+    corrupt_block(&temp_dir, 2, 0);
+    corrupt_block(&temp_dir, 2, 1);
+
+    let socket = peers[1].blockstore_server().get_socket();
+    let mut res = socket
+        .run(ServerRequest {
+            hash,
+            peer: node_index1,
+        })
+        .await
+        .expect("Failed to send request");
+    assert!(matches!(
+        res.recv().await.unwrap(),
+        Err(PeerRequestError::Incomplete)
+    ));
+
+    // Peer 3 would refuse to serve the first block, so the download has to resume after the
+    // blocks we got from peer 1.
+    let mut res = socket
+        .run(ServerRequest {
+            hash,
+            peer: node_index3,
+        })
+        .await
+        .expect("Failed to send request");
+    match res.recv().await.unwrap() {
+        Ok(()) => {
+            let recv_content = peers[1].blockstore().read_all_to_vec(&hash).await.unwrap();
+            assert_eq!(recv_content, content);
+        },
+        Err(e) => panic!("Failed to receive content: {e:?}"),
+    }
+
+    for mut peer in peers {
+        peer.inner.shutdown().await;
+        drop(peer);
+    }
+}
@@ -1,8 +1,10 @@
 #![allow(unused)]
 
+use std::collections::{HashMap, HashSet};
 use std::io;
 use std::marker::PhantomData;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::{Arc, OnceLock};
 
 use blake3_tree::blake3::tree::{BlockHasher, HashTreeBuilder};
@@ -13,7 +15,7 @@
 use lightning_interfaces::prelude::*;
 use lightning_interfaces::types::{Blake3Hash, CompressionAlgoSet, CompressionAlgorithm};
 use lightning_interfaces::ContentChunk;
-use parking_lot::RwLock;
+use parking_lot::{Mutex, RwLock};
 use resolved_pathbuf::ResolvedPathBuf;
 use serde::{Deserialize, Serialize};
 use tokio::fs;
@@ -24,21 +26,47 @@
 
 use crate::config::{Config, BLOCK_DIR, INTERNAL_DIR, TMP_DIR};
 use crate::put::Putter;
-use crate::store::{Block, Store};
+use crate::store::{Block, Store, UsageIndex};
 
 pub const BLOCK_SIZE: usize = 256 << 10;
 
 pub struct Blockstore<C: Collection> {
     root: PathBuf,
     indexer: Arc<OnceLock<C::IndexerInterface>>,
+    /// Only tracked when the store has a size cap.
+    usage: Option<Arc<Mutex<UsageIndex>>>,
+    /// Roots whose tree and blocks are never evicted.
+    pinned: Arc<RwLock<HashSet<Blake3Hash>>>,
+    /// The files written so far by each put that has not finished yet.
+    active_puts: Arc<Mutex<HashMap<u64, HashSet<PathBuf>>>>,
+    next_put_id: Arc<AtomicU64>,
+    /// Set on the handle given to a putter, the files it writes are recorded under this put.
+    active_put: Option<Arc<ActivePut>>,
     collection: PhantomData<C>,
 }
 
+/// Keeps the files of a put from being evicted until every handle of the put is dropped.
+struct ActivePut {
+    id: u64,
+    active_puts: Arc<Mutex<HashMap<u64, HashSet<PathBuf>>>>,
+}
+
+impl Drop for ActivePut {
+    fn drop(&mut self) {
+        self.active_puts.lock().remove(&self.id);
+    }
+}
+
 impl<C: Collection> Clone for Blockstore<C> {
     fn clone(&self) -> Self {
         Self {
             root: self.root.clone(),
             indexer: self.indexer.clone(),
+            usage: self.usage.clone(),
+            pinned: self.pinned.clone(),
+            active_puts: self.active_puts.clone(),
+            next_put_id: self.next_put_id.clone(),
+            active_put: self.active_put.clone(),
             collection: PhantomData,
         }
     }
@@ -73,15 +101,39 @@ pub fn init(config: Config) -> anyhow::Result<Self> {
         let tmp_dir = root.join(TMP_DIR);
 
         std::fs::create_dir_all(&root)?;
-        std::fs::create_dir_all(internal_dir)?;
-        std::fs::create_dir_all(block_dir)?;
+        std::fs::create_dir_all(&internal_dir)?;
+        std::fs::create_dir_all(&block_dir)?;
         std::fs::create_dir_all(tmp_dir)?;
 
-        Ok(Self {
+        let usage = config
+            .max_total_bytes
+            .map(|max_total_bytes| {
+                UsageIndex::load(max_total_bytes, &[&internal_dir, &block_dir])
+                    .map(|usage| Arc::new(Mutex::new(usage)))
+            })
+            .transpose()?;
+
+        let blockstore = Self {
             root,
             indexer: Arc::new(OnceLock::new()),
+            usage,
+            pinned: Default::default(),
+            active_puts: Default::default(),
+            next_put_id: Default::default(),
+            active_put: None,
             collection: PhantomData,
-        })
+        };
+
+        if blockstore.usage.is_some() {
+            for entry in std::fs::read_dir(&internal_dir)? {
+                let path = entry?.path();
+                if path.is_file() {
+                    blockstore.insert_group(path.clone(), &std::fs::read(&path)?);
+                }
+            }
+        }
+
+        Ok(blockstore)
     }
 
     /// Provide the blockstore with the indexer after initialization, this function
@@ -89,6 +141,137 @@ pub fn init(config: Config) -> anyhow::Result<Self> {
     pub fn provide_indexer(&mut self, indexer: C::IndexerInterface) {
         assert!(self.indexer.set(indexer).is_ok());
     }
+
+    /// Protect the tree of `root` and all of its blocks from eviction.
+    pub fn pin(&self, root: Blake3Hash) {
+        self.pinned.write().insert(root);
+    }
+
+    /// Allow the content of `root` to be evicted again.
+    pub fn unpin(&self, root: &Blake3Hash) {
+        self.pinned.write().remove(root);
+    }
+
+    /// Fetch many blocks concurrently, with at most `concurrency` reads in flight at a time.
+    /// The blocks are returned in request order, or `None` as soon as any of them is missing.
+    pub async fn get_many(
+        &self,
+        requests: Vec<(u32, Blake3Hash, CompressionAlgoSet)>,
+        concurrency: usize,
+    ) -> Option<Vec<Arc<ContentChunk>>> {
+        let concurrency = concurrency.max(1);
+        let mut blocks = vec![None; requests.len()];
+        let mut requests = requests.into_iter().enumerate();
+        let mut tasks = JoinSet::new();
+
+        loop {
+            while tasks.len() < concurrency {
+                let Some((index, (block_counter, block_hash, compression))) = requests.next()
+                else {
+                    break;
+                };
+                let store = self.clone();
+                tasks.spawn(async move {
+                    let block = store.get(block_counter, &block_hash, compression).await;
+                    (index, block)
+                });
+            }
+
+            let Some(result) = tasks.join_next().await else {
+                break;
+            };
+            // Returning early drops the join set, which aborts the outstanding reads.
+            let (index, block) = result.ok()?;
+            blocks[index] = Some(block?);
+        }
+
+        blocks.into_iter().collect()
+    }
+
+    /// Returns true if the tree of `root` and every block it references are stored locally.
+    /// Only the tree is read, the presence of the blocks is checked without reading them.
+    pub async fn contains_full(&self, root: &Blake3Hash) -> bool {
+        let Some(tree) = self.get_tree(root).await else {
+            return false;
+        };
+        for i in 0..tree.len() {
+            if !self.contains(BLOCK_DIR, &tree[i], Some(i)).await {
+                return false;
+            }
+        }
+        true
+    }
+
+    /// Returns a handle for a putter, the files written through it are not evicted until the
+    /// putter is dropped.
+    fn start_put(&self) -> Self {
+        let mut store = self.clone();
+        if self.usage.is_some() {
+            store.active_put = Some(Arc::new(ActivePut {
+                id: self.next_put_id.fetch_add(1, Ordering::Relaxed),
+                active_puts: self.active_puts.clone(),
+            }));
+        }
+        store
+    }
+
+    /// Groups the tree file at `path` with its blocks, so that they are evicted together.
+    fn insert_group(&self, path: PathBuf, tree: &[u8]) {
+        let Some(usage) = &self.usage else {
+            return;
+        };
+        if tree.len() & 31 != 0 {
+            error!("Tried to index corrupted proof {path:?}");
+            return;
+        }
+        let tree = HashTree::from_inner(HashVec::from_inner(tree.to_vec().into_boxed_slice()));
+        let blocks = (0..tree.len())
+            .map(|i| self.path(BLOCK_DIR, &tree[i], Some(i)))
+            .collect();
+        usage.lock().insert_group(path, blocks);
+    }
+
+    fn path(&self, location: &str, key: &Blake3Hash, tag: Option<usize>) -> PathBuf {
+        let filename = match tag {
+            Some(tag) => format!("{tag}-{}", Hash::from(*key).to_hex()),
+            None => format!("{}", Hash::from(*key).to_hex()),
+        };
+        self.root.join(location).join(filename)
+    }
+
+    /// Evict the least recently accessed content until the store is back under its size cap.
+    /// Pinned content, the files of puts that have not finished and the file at `keep`, which
+    /// was just written, are never evicted.
+    async fn evict(&self, keep: &Path) {
+        let Some(usage) = &self.usage else {
+            return;
+        };
+        if !usage.lock().is_over_capacity() {
+            return;
+        }
+
+        let mut skip = HashSet::from([keep.to_path_buf()]);
+        for files in self.active_puts.lock().values() {
+            skip.extend(files.iter().cloned());
+        }
+        let pinned = self.pinned.read().iter().copied().collect::<Vec<_>>();
+        for root in pinned {
+            skip.insert(self.path(INTERNAL_DIR, &root, None));
+            if let Some(tree) = self.get_tree(&root).await {
+                for i in 0..tree.len() {
+                    skip.insert(self.path(BLOCK_DIR, &tree[i], Some(i)));
+                }
+            }
+        }
+
+        let evicted = usage.lock().evict(&skip);
+        for path in evicted {
+            trace!("Evicting {path:?}");
+            if let Err(e) = fs::remove_file(&path).await {
+                error!("Failed to evict {path:?}: {e:?}");
+            }
+        }
+    }
 }
 
 impl<C: Collection> BlockstoreInterface<C> for Blockstore<C> {
@@ -126,7 +309,7 @@ async fn get(
     fn put(&self, root: Option<Blake3Hash>) -> Self::Put {
         match root {
             Some(root) => Putter::verifier(
-                self.clone(),
+                self.start_put(),
                 root,
                 self.indexer
                     .get()
@@ -134,7 +317,7 @@ fn put(&self, root: Option<Blake3Hash>) -> Self::Put {
                     .expect("Indexer to have been set"),
             ),
             None => Putter::trust(
-                self.clone(),
+                self.start_put(),
                 self.indexer
                     .get()
                     .cloned()
@@ -157,13 +340,22 @@ impl<C> Store for Blockstore<C>
     C: Collection,
 {
     async fn fetch(&self, location: &str, key: &Blake3Hash, tag: Option<usize>) -> Option<Block> {
-        let filename = match tag {
-            Some(tag) => format!("{tag}-{}", Hash::from(*key).to_hex()),
-            None => format!("{}", Hash::from(*key).to_hex()),
-        };
-        let path = self.root.to_path_buf().join(location).join(filename);
+        let path = self.path(location, key, tag);
         trace!("Fetch {path:?}");
-        fs::read(path).await.ok()
+        let block = fs::read(&path).await.ok()?;
+        if let Some(usage) = &self.usage {
+            usage.lock().touch(&path);
+        }
+        Some(block)
+    }
+
+    async fn contains(&self, location: &str, key: &Blake3Hash, tag: Option<usize>) -> bool {
+        let path = self.path(location, key, tag);
+        trace!("Contains {path:?}");
+        fs::metadata(path)
+            .await
+            .map(|metadata| metadata.is_file())
+            .unwrap_or(false)
     }
 
     async fn insert(
@@ -189,7 +381,22 @@ async fn insert(
 
             trace!("Inserting {store_path:?}");
 
-            fs::rename(tmp_file_path, store_path).await?;
+            fs::rename(tmp_file_path, &store_path).await?;
+
+            if let Some(usage) = &self.usage {
+                if let Some(put) = &self.active_put {
+                    self.active_puts
+                        .lock()
+                        .entry(put.id)
+                        .or_default()
+                        .insert(store_path.clone());
+                }
+                usage.lock().insert(store_path.clone(), block.len() as u64);
+                if location == INTERNAL_DIR {
+                    self.insert_group(store_path.clone(), block);
+                }
+                self.evict(&store_path).await;
+            }
         }
         Ok(())
     }
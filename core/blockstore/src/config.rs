@@ -9,6 +9,10 @@
 #[derive(Serialize, Deserialize)]
 pub struct Config {
     pub root: ResolvedPathBuf,
+    /// Once the store grows past this size, the least recently accessed content that is not
+    /// pinned is evicted. The store is unbounded if this is not set.
+    #[serde(default)]
+    pub max_total_bytes: Option<u64>,
 }
 
 impl Default for Config {
@@ -18,6 +22,7 @@ fn default() -> Self {
                 .join("blockstore")
                 .try_into()
                 .expect("Failed to resolve path"),
+            max_total_bytes: None,
         }
     }
 }
@@ -10,11 +10,11 @@ mod tests {
     use blake3_tree::blake3::tree::{HashTree, HashTreeBuilder};
     use blake3_tree::ProofBuf;
     use lightning_interfaces::prelude::*;
-    use lightning_interfaces::types::{Blake3Hash, CompressionAlgorithm};
+    use lightning_interfaces::types::{Blake3Hash, CompressionAlgoSet, CompressionAlgorithm};
     use tokio::test;
 
     use crate::blockstore::{Blockstore, BLOCK_SIZE};
-    use crate::config::Config;
+    use crate::config::{Config, BLOCK_DIR};
 
     partial!(TestBinding {
         BlockstoreInterface = Blockstore<Self>;
@@ -59,6 +59,7 @@ async fn make_blockstore(test_name: String) -> BlockStoreCleanOnDrop {
 
         let mut blockstore = Blockstore::<TestBinding>::init(Config {
             root: path.clone().try_into().unwrap(),
+            max_total_bytes: None,
         })
         .unwrap();
         blockstore.provide_indexer(Default::default());
@@ -254,6 +255,215 @@ async fn test_put_verify_one_chunk_and_a_half() {
         }
     }
 
+    #[test]
+    async fn test_get_many() {
+        // Given: some content.
+        let content = create_content();
+
+        // Given: app state with a blockstore.
+        let state =
+            make_blockstore(format!("test-{}", std::thread::current().name().unwrap())).await;
+
+        // Given: we put the content in the block store.
+        let mut putter = state.blockstore.put(None);
+        putter
+            .write(content.as_slice(), CompressionAlgorithm::Uncompressed)
+            .unwrap();
+        let root = putter.finalize().await.unwrap();
+
+        // Given: the requests for every block of the content.
+        let tree = state.blockstore.get_tree(&root).await.unwrap();
+        let requests = (0..tree.len())
+            .map(|i| (i as u32, tree[i], CompressionAlgoSet::new()))
+            .collect::<Vec<_>>();
+
+        // When: we fetch the blocks sequentially and in a batch.
+        let mut expected = Vec::new();
+        for (block_counter, block_hash, compression) in requests.iter() {
+            let block = state
+                .blockstore
+                .get(*block_counter, block_hash, *compression)
+                .await
+                .unwrap();
+            expected.push(block.content.clone());
+        }
+        let blocks = state
+            .blockstore
+            .get_many(requests.clone(), 2)
+            .await
+            .unwrap();
+
+        // Then: the batch returns the same blocks in request order.
+        let blocks = blocks
+            .iter()
+            .map(|block| block.content.clone())
+            .collect::<Vec<_>>();
+        assert_eq!(blocks, expected);
+        assert_eq!(blocks.concat(), content);
+
+        // Then: the batch fails if any of the blocks is missing.
+        let mut requests = requests;
+        requests.push((tree.len() as u32, [0; 32], CompressionAlgoSet::new()));
+        assert!(state.blockstore.get_many(requests, 2).await.is_none());
+    }
+
+    #[test]
+    async fn test_contains_full() {
+        // Given: some content.
+        let content = create_content();
+
+        // Given: app state with a blockstore.
+        let state =
+            make_blockstore(format!("test-{}", std::thread::current().name().unwrap())).await;
+
+        // Given: content that was never stored is not contained.
+        let hash_tree = hash_tree(content.as_slice());
+        let root = Blake3Hash::from(hash_tree.hash);
+        assert!(!state.blockstore.contains_full(&root).await);
+
+        // When: we put the content in the block store.
+        let mut putter = state.blockstore.put(None);
+        putter
+            .write(content.as_slice(), CompressionAlgorithm::Uncompressed)
+            .unwrap();
+        assert_eq!(putter.finalize().await.unwrap(), root);
+
+        // Then: the full content is contained.
+        assert!(state.blockstore.contains_full(&root).await);
+
+        // When: we delete one of the blocks.
+        let tree = state.blockstore.get_tree(&root).await.unwrap();
+        let block_path = state.temp_dir_path.join(BLOCK_DIR).join(format!(
+            "1-{}",
+            blake3_tree::blake3::Hash::from(tree[1]).to_hex()
+        ));
+        std::fs::remove_file(block_path).unwrap();
+
+        // Then: the content is no longer fully contained.
+        assert!(!state.blockstore.contains_full(&root).await);
+    }
+
+    #[test]
+    async fn test_evict_least_recently_accessed() {
+        // Given: a blockstore that can hold two and a half blocks.
+        let path =
+            std::env::temp_dir().join(format!("test-{}", std::thread::current().name().unwrap()));
+        let mut blockstore = Blockstore::<TestBinding>::init(Config {
+            root: path.clone().try_into().unwrap(),
+            max_total_bytes: Some((2 * BLOCK_SIZE + BLOCK_SIZE / 2) as u64),
+        })
+        .unwrap();
+        blockstore.provide_indexer(Default::default());
+        let state = BlockStoreCleanOnDrop {
+            blockstore,
+            temp_dir_path: path,
+        };
+
+        async fn put(blockstore: &Blockstore<TestBinding>, content: &[u8]) -> Blake3Hash {
+            let mut putter = blockstore.put(None);
+            putter
+                .write(content, CompressionAlgorithm::Uncompressed)
+                .unwrap();
+            putter.finalize().await.unwrap()
+        }
+
+        // Given: two single block files, where the first one was accessed most recently.
+        let first = put(&state.blockstore, &[1; BLOCK_SIZE]).await;
+        let second = put(&state.blockstore, &[2; BLOCK_SIZE]).await;
+        state.blockstore.read_all_to_vec(&first).await.unwrap();
+
+        // When: we write past the cap.
+        let third = put(&state.blockstore, &[3; BLOCK_SIZE]).await;
+
+        // Then: the least recently accessed content is evicted.
+        assert!(state.blockstore.contains_full(&first).await);
+        assert!(!state.blockstore.contains_full(&second).await);
+        assert!(state.blockstore.contains_full(&third).await);
+
+        // When: we pin the oldest content and write past the cap again.
+        state.blockstore.pin(first);
+        let fourth = put(&state.blockstore, &[4; BLOCK_SIZE]).await;
+
+        // Then: the pinned content survives and the oldest unpinned content is evicted.
+        assert!(state.blockstore.contains_full(&first).await);
+        assert!(!state.blockstore.contains_full(&third).await);
+        assert!(state.blockstore.contains_full(&fourth).await);
+    }
+
+    #[test]
+    async fn test_evict_skips_in_progress_put() {
+        // Given: a blockstore that can hold one and a half blocks.
+        let path =
+            std::env::temp_dir().join(format!("test-{}", std::thread::current().name().unwrap()));
+        let mut blockstore = Blockstore::<TestBinding>::init(Config {
+            root: path.clone().try_into().unwrap(),
+            max_total_bytes: Some((BLOCK_SIZE + BLOCK_SIZE / 2) as u64),
+        })
+        .unwrap();
+        blockstore.provide_indexer(Default::default());
+        let state = BlockStoreCleanOnDrop {
+            blockstore,
+            temp_dir_path: path,
+        };
+
+        // When: a single put writes two blocks, going past the cap before it is finalized.
+        let content = create_content();
+        let mut putter = state.blockstore.put(None);
+        putter
+            .write(
+                &content[..2 * BLOCK_SIZE],
+                CompressionAlgorithm::Uncompressed,
+            )
+            .unwrap();
+        let root = putter.finalize().await.unwrap();
+
+        // Then: none of the blocks of the put were evicted while it was in progress.
+        assert!(state.blockstore.contains_full(&root).await);
+    }
+
+    #[test]
+    async fn test_evict_tree_with_its_blocks() {
+        // Given: a blockstore that can hold two and a half blocks.
+        let path =
+            std::env::temp_dir().join(format!("test-{}", std::thread::current().name().unwrap()));
+        let mut blockstore = Blockstore::<TestBinding>::init(Config {
+            root: path.clone().try_into().unwrap(),
+            max_total_bytes: Some((2 * BLOCK_SIZE + BLOCK_SIZE / 2) as u64),
+        })
+        .unwrap();
+        blockstore.provide_indexer(Default::default());
+        let state = BlockStoreCleanOnDrop {
+            blockstore,
+            temp_dir_path: path,
+        };
+
+        async fn put(blockstore: &Blockstore<TestBinding>, content: &[u8]) -> Blake3Hash {
+            let mut putter = blockstore.put(None);
+            putter
+                .write(content, CompressionAlgorithm::Uncompressed)
+                .unwrap();
+            putter.finalize().await.unwrap()
+        }
+
+        // Given: two single block files, where only the block of the first one was read since.
+        let first = put(&state.blockstore, &[1; BLOCK_SIZE]).await;
+        let second = put(&state.blockstore, &[2; BLOCK_SIZE]).await;
+        let tree = state.blockstore.get_tree(&first).await.unwrap();
+        state
+            .blockstore
+            .get(0, &tree[0], CompressionAlgoSet::new())
+            .await
+            .unwrap();
+
+        // When: we write past the cap.
+        let third = put(&state.blockstore, &[3; BLOCK_SIZE]).await;
+
+        // Then: the first root is kept whole and the second one is evicted with its tree.
+        assert!(state.blockstore.contains_full(&first).await);
+        assert!(state.blockstore.get_tree(&second).await.is_none());
+        assert!(state.blockstore.contains_full(&third).await);
+    }
+
     #[tokio::test]
     async fn hash_consistency() {
         let state =
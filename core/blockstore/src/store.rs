@@ -1,4 +1,6 @@
+use std::collections::{HashMap, HashSet};
 use std::io;
+use std::path::{Path, PathBuf};
 
 use lightning_interfaces::types::Blake3Hash;
 
@@ -6,6 +8,7 @@
 #[trait_variant::make(Store: Send)]
 pub trait _Store: Send + Clone {
     async fn fetch(&self, location: &str, key: &Blake3Hash, tag: Option<usize>) -> Option<Block>;
+    async fn contains(&self, location: &str, key: &Blake3Hash, tag: Option<usize>) -> bool;
     async fn insert(
         &mut self,
         location: &str,
@@ -16,3 +19,168 @@ async fn insert(
 }
 
 pub type Block = Vec<u8>;
+
+/// Sidecar index of the size and the last access of every file in the store, used to evict
+/// the least recently accessed content once the store grows past `max_total_bytes`.
+///
+/// The tree of a root and the blocks it references are grouped so that they are evicted
+/// together, a root is never left with a tree but missing blocks or the other way around.
+pub struct UsageIndex {
+    max_total_bytes: u64,
+    total_bytes: u64,
+    clock: u64,
+    entries: HashMap<PathBuf, Usage>,
+    /// The blocks referenced by each tree file.
+    groups: HashMap<PathBuf, Vec<PathBuf>>,
+    /// The number of trees referencing each block file.
+    refs: HashMap<PathBuf, usize>,
+}
+
+struct Usage {
+    size: u64,
+    last_access: u64,
+}
+
+/// A set of files that is evicted as a whole.
+enum Unit {
+    Group(PathBuf),
+    File(PathBuf),
+}
+
+impl UsageIndex {
+    /// Builds the index from the files already present in `dirs`, ordering their last access
+    /// by modification time.
+    pub fn load(max_total_bytes: u64, dirs: &[&Path]) -> io::Result<Self> {
+        let mut files = Vec::new();
+        for dir in dirs {
+            for entry in std::fs::read_dir(dir)? {
+                let entry = entry?;
+                let metadata = entry.metadata()?;
+                if metadata.is_file() {
+                    files.push((metadata.modified()?, entry.path(), metadata.len()));
+                }
+            }
+        }
+        files.sort();
+
+        let mut index = Self {
+            max_total_bytes,
+            total_bytes: 0,
+            clock: 0,
+            entries: HashMap::new(),
+            groups: HashMap::new(),
+            refs: HashMap::new(),
+        };
+        for (_, path, size) in files {
+            index.insert(path, size);
+        }
+        Ok(index)
+    }
+
+    pub fn is_over_capacity(&self) -> bool {
+        self.total_bytes > self.max_total_bytes
+    }
+
+    /// Records a newly written file as the most recently accessed one.
+    pub fn insert(&mut self, path: PathBuf, size: u64) {
+        self.clock += 1;
+        let usage = Usage {
+            size,
+            last_access: self.clock,
+        };
+        if let Some(previous) = self.entries.insert(path, usage) {
+            self.total_bytes -= previous.size;
+        }
+        self.total_bytes += size;
+    }
+
+    /// Groups the tree file at `tree` with the block files it references, replacing any
+    /// previous group of the same tree.
+    pub fn insert_group(&mut self, tree: PathBuf, blocks: Vec<PathBuf>) {
+        for block in &blocks {
+            *self.refs.entry(block.clone()).or_default() += 1;
+        }
+        if let Some(previous) = self.groups.insert(tree, blocks) {
+            for block in previous {
+                self.release(&block);
+            }
+        }
+    }
+
+    /// Marks a file as the most recently accessed one.
+    pub fn touch(&mut self, path: &Path) {
+        self.clock += 1;
+        if let Some(usage) = self.entries.get_mut(path) {
+            usage.last_access = self.clock;
+        }
+    }
+
+    /// Removes the least recently accessed content from the index until the store is back
+    /// under its cap and returns the paths of the removed files. A tree is evicted along with
+    /// its blocks, blocks still referenced by another tree are kept. Files in `skip` are never
+    /// selected, and neither is the group of a tree in `skip`.
+    pub fn evict(&mut self, skip: &HashSet<PathBuf>) -> Vec<PathBuf> {
+        let mut candidates = Vec::new();
+        for (tree, blocks) in &self.groups {
+            if skip.contains(tree) {
+                continue;
+            }
+            let last_access = std::iter::once(tree)
+                .chain(blocks)
+                .filter_map(|path| self.entries.get(path))
+                .map(|usage| usage.last_access)
+                .max();
+            if let Some(last_access) = last_access {
+                candidates.push((last_access, Unit::Group(tree.clone())));
+            }
+        }
+        for (path, usage) in &self.entries {
+            if skip.contains(path) || self.groups.contains_key(path) || self.refs.contains_key(path)
+            {
+                continue;
+            }
+            candidates.push((usage.last_access, Unit::File(path.clone())));
+        }
+        candidates.sort_unstable_by_key(|(last_access, _)| *last_access);
+
+        let mut evicted = Vec::new();
+        for (_, unit) in candidates {
+            if !self.is_over_capacity() {
+                break;
+            }
+            match unit {
+                Unit::Group(tree) => {
+                    let blocks = self.groups.remove(&tree).unwrap_or_default();
+                    self.remove(tree, &mut evicted);
+                    for block in blocks {
+                        if self.release(&block) && !skip.contains(&block) {
+                            self.remove(block, &mut evicted);
+                        }
+                    }
+                },
+                Unit::File(path) => self.remove(path, &mut evicted),
+            }
+        }
+        evicted
+    }
+
+    /// Drops a reference to `block` and returns true if no tree references it anymore.
+    fn release(&mut self, block: &Path) -> bool {
+        let Some(count) = self.refs.get_mut(block) else {
+            return true;
+        };
+        *count -= 1;
+        if *count == 0 {
+            self.refs.remove(block);
+            return true;
+        }
+        false
+    }
+
+    fn remove(&mut self, path: PathBuf, evicted: &mut Vec<PathBuf>) {
+        if let Some(usage) = self.entries.remove(&path) {
+            self.total_bytes -= usage.size;
+            evicted.push(path);
+        }
+    }
+}
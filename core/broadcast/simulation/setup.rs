@@ -3,7 +3,7 @@
 use std::time::Duration;
 
 use bytes::Bytes;
-use lightning_broadcast::{Context, Database, PubSubI, SimulonBackend};
+use lightning_broadcast::{Config, Context, Database, PubSubI, SimulonBackend};
 use lightning_interfaces::schema::AutoImplSerde;
 use lightning_interfaces::types::Topic;
 use lightning_interfaces::{PubSub, ShutdownController};
@@ -64,7 +64,7 @@ pub async fn exec(n: usize) {
     assert!(!peers.is_empty());
     let backend = SimulonBackend::new(msg_sender_tx, msg_recv_rx, peers);
 
-    let ctx = Context::new(Database::default(), backend);
+    let ctx = Context::new(Database::default(), backend, Config::default());
     let ctx_command_sender = ctx.get_command_sender();
 
     // listener task for node + client connections.
@@ -32,6 +32,8 @@ fn send_to_all<F: Fn(NodeIndex) -> bool + Send + Sync + 'static>(
 
     fn report_sat(&self, peer: NodeIndex, weight: Weight);
 
+    fn report_unsat(&self, peer: NodeIndex, weight: Weight);
+
     fn now() -> u64;
 
     fn sleep(duration: Duration) -> impl Future<Output = ()> + Send;
@@ -109,6 +111,11 @@ fn report_sat(&self, peer: NodeIndex, weight: Weight) {
         self.rep_reporter.report_sat(peer, weight)
     }
 
+    #[inline(always)]
+    fn report_unsat(&self, peer: NodeIndex, weight: Weight) {
+        self.rep_reporter.report_unsat(peer, weight)
+    }
+
     /// Get the current unix timestamp in milliseconds
     #[inline(always)]
     fn now() -> u64 {
@@ -202,6 +209,9 @@ fn get_our_index(&self) -> Option<NodeIndex> {
     #[inline(always)]
     fn report_sat(&self, _peer: NodeIndex, _weight: Weight) {}
 
+    #[inline(always)]
+    fn report_unsat(&self, _peer: NodeIndex, _weight: Weight) {}
+
     #[inline(always)]
     fn now() -> u64 {
         (simulon::api::now() / 1_000_000) as u64
@@ -1,3 +1,4 @@
+use anyhow::Result;
 use lightning_interfaces::prelude::*;
 use lightning_interfaces::schema::broadcast::Frame;
 use lightning_interfaces::schema::LightningMessage;
@@ -7,6 +8,7 @@
 
 use crate::backend::LightningBackend;
 use crate::command::CommandSender;
+use crate::config::Config;
 use crate::db::Database;
 use crate::ev::Context;
 use crate::pubsub::PubSubI;
@@ -18,22 +20,32 @@ pub struct Broadcast<C: Collection> {
 
 impl<C: Collection> Broadcast<C> {
     pub fn new(
+        config: &C::ConfigProviderInterface,
         keystore: &C::KeystoreInterface,
         rep_aggregator: &C::ReputationAggregatorInterface,
         pool: &c!(C::PoolInterface),
         fdi::Cloned(sqr): fdi::Cloned<c!(C::ApplicationInterface::SyncExecutor)>,
-    ) -> Self {
+    ) -> Result<Self> {
+        let config = config.get::<Self>();
+        let db = match &config.seen_filter {
+            Some(seen_filter) => {
+                seen_filter.validate()?;
+                Database::default().with_seen_filter(seen_filter)
+            },
+            None => Database::default(),
+        };
+
         let sk = keystore.get_ed25519_sk();
         let event_handler = pool.open_event(ServiceScope::Broadcast);
         let rep_reporter = rep_aggregator.get_reporter();
 
         let backend = LightningBackend::new(sqr, rep_reporter, event_handler, sk);
-        let ctx = Context::new(Database::default(), backend);
+        let ctx = Context::new(db, backend, config);
 
-        Self {
+        Ok(Self {
             command_sender: ctx.get_command_sender(),
             ctx: Some(ctx),
-        }
+        })
     }
 
     pub fn start(&mut self, fdi::Cloned(waiter): fdi::Cloned<ShutdownWaiter>) {
@@ -42,10 +54,15 @@ pub fn start(&mut self, fdi::Cloned(waiter): fdi::Cloned<ShutdownWaiter>) {
     }
 }
 
+impl<C: Collection> ConfigConsumer for Broadcast<C> {
+    const KEY: &'static str = "broadcast";
+
+    type Config = Config;
+}
+
 impl<C: Collection> BuildGraph for Broadcast<C> {
     fn build_graph() -> fdi::DependencyGraph {
-        fdi::DependencyGraph::new()
-            .with_infallible(Self::new.with_event_handler("start", Self::start))
+        fdi::DependencyGraph::new().with(Self::new.with_event_handler("start", Self::start))
     }
 }
 
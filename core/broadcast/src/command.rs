@@ -40,6 +40,7 @@ pub struct SendCmd {
 /// A propagate call from a pubsub.
 #[derive(Debug)]
 pub struct PropagateCmd {
+    pub topic: Topic,
     pub digest: Digest,
     /// If `filter` is Some(set), then the message will only be send to the nodes in `set`
     pub filter: Option<HashSet<NodeIndex>>,
@@ -69,5 +70,183 @@ pub enum Command {
     MarkInvalidSender(Digest),
 }
 
-pub type CommandSender = mpsc::UnboundedSender<Command>;
-pub type CommandReceiver = mpsc::UnboundedReceiver<Command>;
+impl Command {
+    /// Returns the lane of the command queue this command goes through.
+    pub fn priority(&self) -> Priority {
+        match self {
+            Command::Recv(cmd) => Priority::of(cmd.topic),
+            Command::Send(cmd) => Priority::of(cmd.topic),
+            Command::Propagate(cmd) => Priority::of(cmd.topic),
+            Command::CleanUp(_) | Command::MarkInvalidSender(_) => Priority::Low,
+        }
+    }
+}
+
+/// The commands of the high priority lane are handled by the event loop ahead of the low
+/// priority lane, so that time sensitive messages are not stuck behind the rest of the traffic
+/// when the event loop falls behind. See [`HIGH_PRIORITY_BUDGET`] for how the low priority lane
+/// still makes progress.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Priority {
+    High,
+    Low,
+}
+
+impl Priority {
+    /// Consensus messages are the only ones delivered with a high priority.
+    pub fn of(topic: Topic) -> Self {
+        match topic {
+            Topic::Consensus => Priority::High,
+            Topic::Resolver | Topic::Debug => Priority::Low,
+        }
+    }
+}
+
+/// The number of high priority commands received in a row after which one low priority command
+/// is let through, if there is any waiting.
+pub const HIGH_PRIORITY_BUDGET: usize = 32;
+
+/// Create the two lanes of the command queue.
+pub fn command_channel() -> (CommandSender, CommandReceiver) {
+    let (high_tx, high_rx) = mpsc::unbounded_channel();
+    let (low_tx, low_rx) = mpsc::unbounded_channel();
+    (
+        CommandSender {
+            high: high_tx,
+            low: low_tx,
+        },
+        CommandReceiver {
+            high: high_rx,
+            low: low_rx,
+            high_in_a_row: 0,
+        },
+    )
+}
+
+#[derive(Clone)]
+pub struct CommandSender {
+    high: mpsc::UnboundedSender<Command>,
+    low: mpsc::UnboundedSender<Command>,
+}
+
+impl CommandSender {
+    /// Send the command through the lane of its priority.
+    pub fn send(&self, command: Command) -> Result<(), mpsc::error::SendError<Command>> {
+        match command.priority() {
+            Priority::High => self.high.send(command),
+            Priority::Low => self.low.send(command),
+        }
+    }
+}
+
+pub struct CommandReceiver {
+    high: mpsc::UnboundedReceiver<Command>,
+    low: mpsc::UnboundedReceiver<Command>,
+    /// The number of high priority commands we received since the last low priority one.
+    high_in_a_row: usize,
+}
+
+impl CommandReceiver {
+    /// Receive the next command. The high priority lane is preferred, but once it used up its
+    /// budget a waiting low priority command goes first so that lane is never starved.
+    pub async fn recv(&mut self) -> Option<Command> {
+        if self.high_in_a_row >= HIGH_PRIORITY_BUDGET {
+            if let Ok(command) = self.low.try_recv() {
+                self.high_in_a_row = 0;
+                return Some(command);
+            }
+        }
+
+        let command = tokio::select! {
+            biased;
+            Some(command) = self.high.recv() => command,
+            Some(command) = self.low.recv() => command,
+            else => return None,
+        };
+
+        match command.priority() {
+            Priority::High => self.high_in_a_row += 1,
+            Priority::Low => self.high_in_a_row = 0,
+        }
+
+        Some(command)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn send_cmd(topic: Topic, payload: Vec<u8>) -> Command {
+        let (response, _) = oneshot::channel();
+        Command::Send(SendCmd {
+            topic,
+            filter: None,
+            payload,
+            response,
+        })
+    }
+
+    fn payload(command: Command) -> Vec<u8> {
+        match command {
+            Command::Send(cmd) => cmd.payload,
+            _ => panic!("Unexpected command"),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_high_priority_is_delivered_first() {
+        let (tx, mut rx) = command_channel();
+
+        // Flood the queue with low priority messages before the consensus message comes in.
+        for i in 0..1000u32 {
+            tx.send(send_cmd(Topic::Debug, i.to_le_bytes().to_vec()))
+                .unwrap();
+        }
+        tx.send(send_cmd(Topic::Consensus, b"consensus".to_vec()))
+            .unwrap();
+
+        assert_eq!(payload(rx.recv().await.unwrap()), b"consensus");
+
+        // The low priority messages still come in the order they were sent.
+        for i in 0..1000u32 {
+            assert_eq!(payload(rx.recv().await.unwrap()), i.to_le_bytes());
+        }
+
+        drop(tx);
+        assert!(rx.recv().await.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_low_priority_progresses_under_load() {
+        let (tx, mut rx) = command_channel();
+
+        // Keep the high priority lane busy the whole time.
+        for i in 0..1000u32 {
+            tx.send(send_cmd(Topic::Consensus, i.to_le_bytes().to_vec()))
+                .unwrap();
+        }
+        for i in 0..3u32 {
+            tx.send(send_cmd(Topic::Debug, format!("debug {i}").into_bytes()))
+                .unwrap();
+        }
+
+        // Every time the high priority lane uses up its budget, one low priority command goes.
+        let mut consensus = 0u32;
+        for i in 0..3u32 {
+            for _ in 0..HIGH_PRIORITY_BUDGET {
+                assert_eq!(payload(rx.recv().await.unwrap()), consensus.to_le_bytes());
+                consensus += 1;
+            }
+            assert_eq!(
+                payload(rx.recv().await.unwrap()),
+                format!("debug {i}").into_bytes()
+            );
+        }
+
+        // With the low priority lane empty the high priority lane carries on.
+        for i in consensus..1000 {
+            assert_eq!(payload(rx.recv().await.unwrap()), i.to_le_bytes());
+        }
+    }
+}
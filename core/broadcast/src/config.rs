@@ -0,0 +1,51 @@
+use anyhow::{bail, Result};
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Config {
+    /// The largest frame in bytes we accept from a peer, larger frames are dropped without being
+    /// decoded and count against the reputation of the peer. Frames over the `max_message_size` of
+    /// the pool never reach us, they are rejected before they are read.
+    #[serde(default = "default_max_message_size")]
+    pub max_message_size: usize,
+    /// Keeps remembering the digests of messages that fell out of the cache with a bloom filter,
+    /// so that they are not processed again if they are re-broadcast. Disabled by default since
+    /// false positives drop new messages.
+    #[serde(default)]
+    pub seen_filter: Option<SeenFilterConfig>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SeenFilterConfig {
+    /// How many digests each of the two generations of the filter holds.
+    pub capacity: usize,
+    /// The rate of false positives of a full generation.
+    pub false_positive_rate: f64,
+}
+
+impl SeenFilterConfig {
+    /// Returns an error if the filter can not be sized from this config.
+    pub fn validate(&self) -> Result<()> {
+        // Written so that NaN is rejected too.
+        if !(self.false_positive_rate > 0.0 && self.false_positive_rate < 1.0) {
+            bail!(
+                "The false positive rate of the seen filter must be between 0 and 1 exclusive, got {}",
+                self.false_positive_rate
+            );
+        }
+        Ok(())
+    }
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            max_message_size: default_max_message_size(),
+            seen_filter: None,
+        }
+    }
+}
+
+fn default_max_message_size() -> usize {
+    10 << 20
+}
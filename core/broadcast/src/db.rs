@@ -2,9 +2,14 @@
 use lightning_interfaces::types::Digest;
 use quick_cache::unsync::Cache;
 
+use crate::config::SeenFilterConfig;
+use crate::seen::SeenFilter;
+
 // TODO: Make this persist.
 pub struct Database {
     data: Cache<Digest, Entry>,
+    /// The digests of the messages we have seen, including the ones evicted from the cache.
+    seen: Option<SeenFilter>,
 }
 
 struct Entry {
@@ -16,13 +21,25 @@ impl Default for Database {
     fn default() -> Self {
         // todo(dalton): Figure out a sane cache for broadcast messaging. Ideally a 24 hour epoch
         // worth.
-        Self {
-            data: Cache::new(100_000),
-        }
+        Self::with_capacity(100_000)
     }
 }
 
 impl Database {
+    /// Create a database that keeps at most `capacity` messages in its cache.
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            data: Cache::new(capacity),
+            seen: None,
+        }
+    }
+
+    /// Also remember the digests of the messages that are evicted from the cache.
+    pub fn with_seen_filter(mut self, config: &SeenFilterConfig) -> Self {
+        self.seen = Some(SeenFilter::new(config));
+        self
+    }
+
     /// Insert the id for a digest.
     pub fn insert_id(&mut self, id: MessageInternedId, digest: Digest) {
         #[cfg(debug_assertions)]
@@ -53,6 +70,9 @@ pub fn insert_message(&mut self, digest: &Digest, message: Message) {
             panic!("We should not be inserting payload of what we have not seen.");
         };
         e.message = Some(message);
+        if let Some(seen) = &mut self.seen {
+            seen.insert(digest);
+        }
     }
 
     /// Takes &mut self and uses get_mut() for performance. We save some atomic operations in
@@ -74,6 +94,12 @@ pub fn get_message(&mut self, digest: &Digest) -> Option<Message> {
     pub fn contains_message(&mut self, digest: &Digest) -> bool {
         self.get_message(digest).is_some()
     }
+
+    /// Returns true if we have accepted a message with this digest before, even if it is not in
+    /// the cache anymore. Without a seen filter this is the same as `contains_message`.
+    pub fn has_seen_message(&mut self, digest: &Digest) -> bool {
+        self.contains_message(digest) || self.seen.as_ref().is_some_and(|s| s.contains(digest))
+    }
 }
 
 #[cfg(test)]
@@ -129,4 +155,33 @@ fn test_cache() {
         let first_msg = db.get_message(&first.0);
         assert!(first_msg.is_none());
     }
+
+    #[test]
+    fn test_seen_filter_outlives_the_cache() {
+        let mut db = Database::with_capacity(16).with_seen_filter(&SeenFilterConfig {
+            capacity: 1000,
+            false_positive_rate: 0.001,
+        });
+
+        let message = |i: u32| Message {
+            origin: 0,
+            signature: NodeSignature([0; 64]),
+            topic: Topic::Consensus,
+            timestamp: 0,
+            payload: i.to_le_bytes().into(),
+        };
+        let first = message(0).to_digest();
+
+        for i in 0..100u32 {
+            let message = message(i);
+            let digest = message.to_digest();
+            db.insert_id(0, digest);
+            db.insert_message(&digest, message);
+        }
+
+        // The first message was evicted, but we still know not to process it again.
+        assert!(!db.contains_message(&first));
+        assert!(db.has_seen_message(&first));
+        assert!(!db.has_seen_message(&message(100).to_digest()));
+    }
 }
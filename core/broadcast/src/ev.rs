@@ -19,11 +19,11 @@
 use lightning_interfaces::Weight;
 use lightning_metrics::{histogram, increment_counter};
 use tokio::pin;
-use tokio::sync::mpsc;
-use tracing::{debug, error, info, trace};
+use tracing::{debug, error, info, trace, warn};
 
 use crate::backend::LightningBackend;
-use crate::command::{Command, CommandReceiver, CommandSender, SharedMessage};
+use crate::command::{command_channel, Command, CommandReceiver, CommandSender, SharedMessage};
+use crate::config::Config;
 use crate::db::Database;
 use crate::interner::Interner;
 use crate::pending::PendingStore;
@@ -57,12 +57,14 @@ pub struct Context<B: BroadcastBackend> {
     /// Incoming messages with the same digest
     processing: im::HashMap<Digest, VecDeque<MessageWithSender>>,
     current_node_index: OnceCell<NodeIndex>,
+    /// The largest frame we accept from a peer.
+    max_message_size: usize,
     backend: B,
 }
 
 impl<B: BroadcastBackend> Context<B> {
-    pub fn new(db: Database, backend: B) -> Self {
-        let (command_tx, command_rx) = mpsc::unbounded_channel();
+    pub fn new(db: Database, backend: B, config: Config) -> Self {
+        let (command_tx, command_rx) = command_channel();
         Self {
             db,
             interner: Interner::new(Interner::MAX_CAPACITY),
@@ -79,6 +81,7 @@ pub fn new(db: Database, backend: B) -> Self {
             pending_store: PendingStore::new(),
             processing: im::HashMap::new(),
             current_node_index: OnceCell::new(), // will be set upon spawn.
+            max_message_size: config.max_message_size,
             backend,
         }
     }
@@ -89,6 +92,23 @@ pub fn get_command_sender(&self) -> CommandSender {
 
     /// Handle a message sent from another node.
     fn handle_frame_payload(&mut self, sender: NodeIndex, payload: Bytes) {
+        if payload.len() > self.max_message_size {
+            warn!(
+                "dropping frame of {} bytes from {sender}, the limit is {}",
+                payload.len(),
+                self.max_message_size
+            );
+            self.stats.report(
+                sender,
+                ConnectionStats {
+                    invalid_messages_received_from_peer: 1,
+                    ..Default::default()
+                },
+            );
+            self.backend.report_unsat(sender, Weight::Strong);
+            return;
+        }
+
         let Ok(frame) = Frame::decode(&payload) else {
             self.stats.report(
                 sender,
@@ -119,7 +139,7 @@ fn handle_advr(&mut self, sender: NodeIndex, advr: Advr) {
         let digest = advr.digest;
 
         // If we have already propagated a message we really don't care about it anymore.
-        if self.db.contains_message(&digest) {
+        if self.db.has_seen_message(&digest) {
             trace!("skipping {digest:?}");
             return;
         }
@@ -173,7 +193,7 @@ fn handle_want(&mut self, sender: NodeIndex, req: Want) {
     fn handle_message(&mut self, sender: NodeIndex, msg: Message) {
         let digest = msg.to_digest();
 
-        if self.db.contains_message(&digest) {
+        if self.db.has_seen_message(&digest) {
             // we have seen the message and propagated it already.
             return;
         }
@@ -3,6 +3,7 @@
 mod backend;
 mod broadcast;
 mod command;
+mod config;
 mod db;
 mod ev;
 mod interner;
@@ -10,6 +11,7 @@
 mod pubsub;
 mod recv_buffer;
 mod ring;
+mod seen;
 mod stats;
 
 #[cfg(test)]
@@ -17,6 +19,7 @@
 
 pub use backend::{BroadcastBackend, SimulonBackend};
 pub use broadcast::Broadcast;
+pub use config::{Config, SeenFilterConfig};
 pub use db::Database;
 #[doc(hidden)]
 pub use ev::Context;
@@ -19,6 +19,7 @@ pub struct PubSubI<T: LightningMessage + Clone> {
 }
 
 pub struct Event<T> {
+    topic: Topic,
     digest: Digest,
     message: Option<T>,
     originator: NodeIndex,
@@ -74,9 +75,11 @@ async fn send(&self, msg: &T, filter: Option<HashSet<NodeIndex>>) -> Result<Dige
     /// Propagate a message that we already propagated before.
     async fn repropagate(&self, digest: Digest, filter: Option<HashSet<NodeIndex>>) {
         debug!("repropagate a message on topic {:?}", self.topic);
-        let _ = self
-            .command_sender
-            .send(Command::Propagate(PropagateCmd { digest, filter }));
+        let _ = self.command_sender.send(Command::Propagate(PropagateCmd {
+            topic: self.topic,
+            digest,
+            filter,
+        }));
     }
 
     /// Receive the oldest message we still haven't seen by this receiver. Due to the ring-buf like
@@ -111,6 +114,7 @@ async fn recv(&mut self) -> Option<T> {
 
             if let Ok(decoded) = T::decode(&msg.payload) {
                 let _ = self.command_sender.send(Command::Propagate(PropagateCmd {
+                    topic: self.topic,
                     digest: msg.digest,
                     filter: None,
                 }));
@@ -153,6 +157,7 @@ async fn recv_event(&mut self) -> Option<Self::Event> {
 
             if let Ok(decoded) = T::decode(&msg.payload) {
                 let event = Event::<T> {
+                    topic: self.topic,
                     digest: msg.digest,
                     message: Some(decoded),
                     originator: msg.origin,
@@ -182,6 +187,7 @@ fn take(&mut self) -> Option<T> {
     fn propagate(mut self) {
         self.clean_up = false;
         let _ = self.command_sender.send(Command::Propagate(PropagateCmd {
+            topic: self.topic,
             digest: self.digest,
             filter: None,
         }));
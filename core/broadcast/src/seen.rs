@@ -0,0 +1,155 @@
+//! A memory bounded set of the digests of the messages we have seen, for the messages that fell
+//! out of the exact cache in the [`Database`](crate::db::Database).
+
+use lightning_interfaces::types::Digest;
+
+use crate::config::SeenFilterConfig;
+
+/// Two generations of bloom filters. New digests go into the current generation, and once it is
+/// full it replaces the previous one. So a digest is remembered for at least `capacity` more
+/// insertions, while the memory used stays the same no matter how many messages we see.
+pub struct SeenFilter {
+    current: BloomFilter,
+    previous: BloomFilter,
+    capacity: usize,
+    false_positive_rate: f64,
+}
+
+impl SeenFilter {
+    pub fn new(config: &SeenFilterConfig) -> Self {
+        Self {
+            current: BloomFilter::new(config.capacity, config.false_positive_rate),
+            previous: BloomFilter::new(config.capacity, config.false_positive_rate),
+            capacity: config.capacity,
+            false_positive_rate: config.false_positive_rate,
+        }
+    }
+
+    pub fn insert(&mut self, digest: &Digest) {
+        if self.current.len >= self.capacity {
+            let fresh = BloomFilter::new(self.capacity, self.false_positive_rate);
+            self.previous = std::mem::replace(&mut self.current, fresh);
+        }
+        self.current.insert(digest);
+    }
+
+    /// Returns true if the digest was probably seen. Can return false positives at about the
+    /// configured rate, but never false negatives for the digests that are remembered.
+    pub fn contains(&self, digest: &Digest) -> bool {
+        self.current.contains(digest) || self.previous.contains(digest)
+    }
+}
+
+struct BloomFilter {
+    bits: Vec<u64>,
+    num_bits: u64,
+    num_hashes: u64,
+    len: usize,
+}
+
+impl BloomFilter {
+    fn new(capacity: usize, false_positive_rate: f64) -> Self {
+        let capacity = capacity.max(1) as f64;
+        let ln2 = std::f64::consts::LN_2;
+        let num_bits = (-capacity * false_positive_rate.ln() / (ln2 * ln2))
+            .ceil()
+            .max(64.0) as u64;
+        let num_hashes = (num_bits as f64 / capacity * ln2).round().max(1.0) as u64;
+        Self {
+            bits: vec![0; num_bits.div_ceil(64) as usize],
+            num_bits,
+            num_hashes,
+            len: 0,
+        }
+    }
+
+    fn insert(&mut self, digest: &Digest) {
+        for bit in bit_indices(digest, self.num_bits, self.num_hashes) {
+            self.bits[(bit / 64) as usize] |= 1 << (bit % 64);
+        }
+        self.len += 1;
+    }
+
+    fn contains(&self, digest: &Digest) -> bool {
+        bit_indices(digest, self.num_bits, self.num_hashes)
+            .all(|bit| self.bits[(bit / 64) as usize] & (1 << (bit % 64)) != 0)
+    }
+}
+
+/// The digests are hashes already, so instead of hashing them again we take two words of the
+/// digest and combine them into as many indices as we need (double hashing).
+fn bit_indices(digest: &Digest, num_bits: u64, num_hashes: u64) -> impl Iterator<Item = u64> {
+    let h1 = u64::from_le_bytes(*arrayref::array_ref![digest, 0, 8]);
+    let h2 = u64::from_le_bytes(*arrayref::array_ref![digest, 8, 8]) | 1;
+    (0..num_hashes).map(move |i| h1.wrapping_add(i.wrapping_mul(h2)) % num_bits)
+}
+
+#[cfg(test)]
+mod tests {
+    use fleek_crypto::NodeSignature;
+    use ink_quill::ToDigest;
+    use lightning_interfaces::schema::broadcast::Message;
+    use lightning_interfaces::types::Topic;
+
+    use super::*;
+
+    fn digest(i: u32) -> Digest {
+        Message {
+            origin: 0,
+            signature: NodeSignature([0; 64]),
+            topic: Topic::Consensus,
+            timestamp: 0,
+            payload: i.to_le_bytes().into(),
+        }
+        .to_digest()
+    }
+
+    #[test]
+    fn test_false_positive_rate() {
+        let mut seen = SeenFilter::new(&SeenFilterConfig {
+            capacity: 10_000,
+            false_positive_rate: 0.01,
+        });
+        for i in 0..10_000 {
+            seen.insert(&digest(i));
+        }
+        assert!((0..10_000).all(|i| seen.contains(&digest(i))));
+
+        let false_positives = (10_000..20_000)
+            .filter(|i| seen.contains(&digest(*i)))
+            .count();
+        assert!(false_positives < 200, "{false_positives} false positives");
+    }
+
+    #[test]
+    fn test_validate_false_positive_rate() {
+        let config = |false_positive_rate| SeenFilterConfig {
+            capacity: 100,
+            false_positive_rate,
+        };
+        assert!(config(0.01).validate().is_ok());
+        for rate in [0.0, 1.0, -0.1, 1.5, f64::NAN] {
+            assert!(config(rate).validate().is_err(), "{rate} was accepted");
+        }
+    }
+
+    #[test]
+    fn test_rotation() {
+        let mut seen = SeenFilter::new(&SeenFilterConfig {
+            capacity: 100,
+            false_positive_rate: 0.001,
+        });
+        for i in 0..200 {
+            seen.insert(&digest(i));
+        }
+        // The last two generations are still remembered.
+        assert!((0..200).all(|i| seen.contains(&digest(i))));
+
+        // Another generation pushes the oldest one out.
+        for i in 200..300 {
+            seen.insert(&digest(i));
+        }
+        let remembered = (0..100).filter(|i| seen.contains(&digest(*i))).count();
+        assert!(remembered < 10);
+    }
+}
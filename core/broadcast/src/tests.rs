@@ -7,7 +7,7 @@
 use lightning_application::genesis::{Genesis, GenesisNode};
 use lightning_interfaces::prelude::*;
 use lightning_interfaces::schema::broadcast::{Frame, Message};
-use lightning_interfaces::types::{NodePorts, Topic};
+use lightning_interfaces::types::{HandshakePorts, NodePorts, Topic};
 use lightning_notifier::Notifier;
 use lightning_pool::{Config as PoolConfig, PoolProvider};
 use lightning_rep_collector::ReputationAggregator;
@@ -18,7 +18,7 @@
 use tempfile::{tempdir, TempDir};
 use tokio::sync::oneshot;
 
-use crate::Broadcast;
+use crate::{Broadcast, Config};
 
 partial!(TestBinding {
     ConfigProviderInterface = JsonConfigProvider;
@@ -47,6 +47,15 @@ fn broadcast(&self) -> fdi::Ref<c!(TestBinding::BroadcastInterface)> {
 }
 
 async fn get_broadcasts(temp_dir: &TempDir, port_offset: u16, num_peers: usize) -> Vec<Peer> {
+    get_broadcasts_with_config(temp_dir, port_offset, num_peers, Config::default()).await
+}
+
+async fn get_broadcasts_with_config(
+    temp_dir: &TempDir,
+    port_offset: u16,
+    num_peers: usize,
+    config: Config,
+) -> Vec<Peer> {
     let mut genesis = Genesis::default();
 
     let owner_secret_key = AccountOwnerSecretKey::generate();
@@ -69,14 +78,17 @@ async fn get_broadcasts(temp_dir: &TempDir, port_offset: u16, num_peers: usize)
             "127.0.0.1".parse().unwrap(),
             node_secret_key.to_pk(),
             NodePorts {
-                primary: 48000_u16,
-                worker: 48101_u16,
-                mempool: 48202_u16,
-                rpc: 48300_u16,
+                primary: 20000 + i as u16,
+                worker: 20100 + i as u16,
+                mempool: 20200 + i as u16,
+                rpc: 20300 + i as u16,
                 pool: port_offset + i as u16,
-                pinger: 48600_u16,
-                // Handshake is unused so the defaults are fine.
-                handshake: Default::default(),
+                pinger: 20600 + i as u16,
+                handshake: HandshakePorts {
+                    http: 20700 + i as u16,
+                    webrtc: 20800 + i as u16,
+                    webtransport: 20900 + i as u16,
+                },
             },
             None,
             true,
@@ -93,7 +105,13 @@ async fn get_broadcasts(temp_dir: &TempDir, port_offset: u16, num_peers: usize)
         let address: SocketAddr = format!("0.0.0.0:{}", port_offset + i as u16)
             .parse()
             .unwrap();
-        let peer = create_peer(AppConfig::test(genesis_path.clone()), keystore, address).await;
+        let peer = create_peer(
+            AppConfig::test(genesis_path.clone()),
+            config.clone(),
+            keystore,
+            address,
+        )
+        .await;
         peers.push(peer);
     }
 
@@ -102,6 +120,7 @@ async fn get_broadcasts(temp_dir: &TempDir, port_offset: u16, num_peers: usize)
 
 async fn create_peer(
     app_config: AppConfig,
+    config: Config,
     keystore: EphemeralKeystore<TestBinding>,
     address: SocketAddr,
 ) -> Peer {
@@ -112,6 +131,7 @@ async fn create_peer(
             .with(
                 JsonConfigProvider::default()
                     .with::<Application<TestBinding>>(app_config)
+                    .with::<Broadcast<TestBinding>>(config)
                     .with::<PoolProvider<TestBinding>>(PoolConfig {
                         max_idle_timeout: Duration::from_secs(5),
                         address,
@@ -204,3 +224,142 @@ async fn test_send() {
         peer.inner.shutdown().await;
     }
 }
+
+#[tokio::test]
+async fn test_oversize_message_is_dropped() {
+    lightning_test_utils::logging::setup();
+
+    let temp_dir = tempdir().unwrap();
+
+    let config = Config {
+        max_message_size: 1024,
+        ..Default::default()
+    };
+    let peers = get_broadcasts_with_config(&temp_dir, 28010, 2, config).await;
+    let query_runner = peers[0].sync_query();
+
+    for peer in &peers {
+        peer.inner.start().await;
+    }
+
+    let pub_sub1 = peers[0].broadcast().get_pubsub::<Frame>(Topic::Debug);
+    let mut pub_sub2 = peers[1].broadcast().get_pubsub::<Frame>(Topic::Debug);
+
+    let index = query_runner
+        .pubkey_to_index(&peers[0].node_secret_key.to_pk())
+        .unwrap();
+    let message = |payload: Vec<u8>| Message {
+        origin: index,
+        signature: NodeSignature([0; 64]),
+        topic: Topic::Debug,
+        timestamp: SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_millis() as u64,
+        payload,
+    };
+
+    // give time to pool to make the connections.
+    tokio::time::sleep(Duration::from_millis(300)).await;
+
+    // node2 should never get to see the oversize message.
+    pub_sub1
+        .send(&Frame::Message(message(vec![0; 2048])), None)
+        .await
+        .unwrap();
+    assert!(
+        tokio::time::timeout(Duration::from_millis(500), pub_sub2.recv())
+            .await
+            .is_err()
+    );
+
+    // While messages within the limit still make it through.
+    pub_sub1
+        .send(&Frame::Message(message(b"hello".to_vec())), None)
+        .await
+        .unwrap();
+    let received = tokio::time::timeout(Duration::from_secs(5), pub_sub2.recv())
+        .await
+        .unwrap()
+        .unwrap();
+    match received {
+        Frame::Message(message) => assert_eq!(message.payload, b"hello"),
+        _ => panic!("Unexpected frame"),
+    }
+
+    // Clean up
+    for mut peer in peers {
+        peer.inner.shutdown().await;
+    }
+}
+
+#[tokio::test]
+async fn test_subscribers_only_receive_their_topic() {
+    lightning_test_utils::logging::setup();
+
+    let temp_dir = tempdir().unwrap();
+
+    let peers = get_broadcasts(&temp_dir, 28020, 2).await;
+    let query_runner = peers[0].sync_query();
+
+    for peer in &peers {
+        peer.inner.start().await;
+    }
+
+    let debug_sender = peers[0].broadcast().get_pubsub::<Frame>(Topic::Debug);
+    let resolver_sender = peers[0].broadcast().get_pubsub::<Frame>(Topic::Resolver);
+    let mut debug_sub = peers[1].broadcast().get_pubsub::<Frame>(Topic::Debug);
+    let mut resolver_sub = peers[1].broadcast().get_pubsub::<Frame>(Topic::Resolver);
+
+    let index = query_runner
+        .pubkey_to_index(&peers[0].node_secret_key.to_pk())
+        .unwrap();
+    let message = |topic: Topic, payload: &[u8]| Message {
+        origin: index,
+        signature: NodeSignature([0; 64]),
+        topic,
+        timestamp: SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_millis() as u64,
+        payload: payload.to_vec(),
+    };
+
+    // give time to pool to make the connections.
+    tokio::time::sleep(Duration::from_millis(300)).await;
+
+    debug_sender
+        .send(&Frame::Message(message(Topic::Debug, b"debug")), None)
+        .await
+        .unwrap();
+    resolver_sender
+        .send(&Frame::Message(message(Topic::Resolver, b"resolver")), None)
+        .await
+        .unwrap();
+
+    for (sub, expected) in [
+        (&mut debug_sub, b"debug".as_slice()),
+        (&mut resolver_sub, b"resolver".as_slice()),
+    ] {
+        let received = tokio::time::timeout(Duration::from_secs(5), sub.recv())
+            .await
+            .unwrap()
+            .unwrap();
+        match received {
+            Frame::Message(message) => assert_eq!(message.payload, expected),
+            _ => panic!("Unexpected frame"),
+        }
+
+        // Nothing from the other topic shows up.
+        assert!(
+            tokio::time::timeout(Duration::from_millis(300), sub.recv())
+                .await
+                .is_err()
+        );
+    }
+
+    // Clean up
+    for mut peer in peers {
+        peer.inner.shutdown().await;
+    }
+}
@@ -11,7 +11,7 @@
 use futures::task::LocalSpawnExt;
 use futures::Future;
 use ink_quill::ToDigest;
-use lightning_broadcast::{BroadcastBackend, Context, Database, PubSubI};
+use lightning_broadcast::{BroadcastBackend, Config, Context, Database, PubSubI, SeenFilterConfig};
 use lightning_interfaces::prelude::*;
 use lightning_interfaces::schema::broadcast::{Advr, Frame, Message, MessageInternedId, Want};
 use lightning_interfaces::schema::{AutoImplSerde, LightningMessage};
@@ -238,6 +238,13 @@ fn report_sat(
     ) {
     }
 
+    fn report_unsat(
+        &self,
+        _peer: lightning_interfaces::types::NodeIndex,
+        _weight: lightning_interfaces::Weight,
+    ) {
+    }
+
     fn now() -> u64 {
         (RUNTIME.with(|cell| cell.now()) / 1_000_000) as u64
     }
@@ -277,8 +284,15 @@ fn from(value: ExampleMessage) -> Self {
 }
 
 fn spawn_context(duration: Duration) -> (PubSubI<ExampleMessage>, ControlledBackend) {
+    spawn_context_with_db(Database::default(), duration)
+}
+
+fn spawn_context_with_db(
+    db: Database,
+    duration: Duration,
+) -> (PubSubI<ExampleMessage>, ControlledBackend) {
     let backend = ControlledBackend::default();
-    let ctx = Context::new(Database::default(), backend.clone());
+    let ctx = Context::new(db, backend.clone(), Config::default());
     let ctrl = ShutdownController::new(false);
     let waiter = ctrl.waiter();
     let pubsub = PubSubI::<ExampleMessage>::new(Topic::Debug, ctx.get_command_sender());
@@ -505,3 +519,57 @@ fn test_propagate_message() {
         rt.run_to_completion();
     });
 }
+
+#[test]
+fn test_replayed_message_is_not_delivered_twice() {
+    // In this test a peer sends us a message which we deliver and propagate. Enough messages
+    // follow it to push it out of the message cache, after which another peer replays it. The
+    // seen filter should still remember the digest so the replay never reaches the pubsub
+    // receiver.
+    let db = Database::with_capacity(16).with_seen_filter(&SeenFilterConfig {
+        capacity: 10_000,
+        false_positive_rate: 0.001,
+    });
+    let (mut pubsub, backend) = spawn_context_with_db(db, ONE_HOUR);
+
+    let message = |id: usize| Message {
+        origin: 1,
+        signature: VALID_SIGN,
+        topic: Topic::Debug,
+        timestamp: 0,
+        payload: ExampleMessage { id }.into(),
+    };
+    let send = move |from: NodeIndex, interned_id: MessageInternedId, msg: Message| {
+        backend.push_frame(
+            from,
+            Frame::Advr(Advr {
+                interned_id,
+                digest: msg.to_digest(),
+            }),
+        );
+        backend.push_frame(from, Frame::Message(msg));
+    };
+
+    RUNTIME.with(|rt| {
+        rt.spawn(async move {
+            for id in 0..1_000 {
+                send(1, id as MessageInternedId, message(id));
+                let Ok(msg) = timeout(Duration::from_millis(5000), pubsub.recv()).await else {
+                    panic!("Message did not arrive in time");
+                };
+                assert_eq!(msg.unwrap().id, id);
+            }
+
+            // The first message is long gone from the cache by now.
+            send(2, 0, message(0));
+            assert!(
+                timeout(Duration::from_millis(5000), pubsub.recv())
+                    .await
+                    .is_err(),
+                "Replayed message was delivered again"
+            );
+        });
+
+        rt.run_to_completion();
+    });
+}
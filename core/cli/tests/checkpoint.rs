@@ -69,6 +69,7 @@ fn build_config(
             .join("data/resolver_store")
             .try_into()
             .expect("Failed to resolve path"),
+        ..Default::default()
     });
     config.inject::<Rpc<FinalTypes>>(RpcConfig {
         hmac_secret_dir: Some(temp_dir.path().to_path_buf()),
@@ -80,6 +81,7 @@ fn build_config(
             .join("data/narwhal_store")
             .try_into()
             .expect("Failed to resolve path"),
+        ..Default::default()
     });
 
     //config.inject::<Signer<FinalTypes>>(SignerConfig {
@@ -100,6 +102,7 @@ fn build_config(
             .join("data/blockstore")
             .try_into()
             .expect("Failed to resolve path"),
+        max_total_bytes: None,
     });
 
     config.inject::<BlockstoreServer<FinalTypes>>(BlockstoreServerConfig::default());
@@ -120,6 +123,7 @@ fn build_config(
 
     config.inject::<ReputationAggregator<FinalTypes>>(RepAggConfig {
         reporter_buffer_size: 1,
+        ..Default::default()
     });
 
     config.inject::<PoolProvider<FinalTypes>>(PoolConfig {
@@ -137,6 +141,7 @@ fn build_config(
             .join("data/archive")
             .try_into()
             .expect("Failed to resolve path"),
+        ..Default::default()
     });
 
     config.inject::<Pinger<FinalTypes>>(PingerConfig {
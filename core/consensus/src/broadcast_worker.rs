@@ -5,7 +5,7 @@
 use fleek_crypto::NodePublicKey;
 use lightning_interfaces::prelude::*;
 use lightning_interfaces::types::{Epoch, NodeIndex};
-use lightning_metrics::increment_counter;
+use lightning_metrics::{increment_counter, set_gauge};
 use lightning_utils::application::QueryRunnerExt;
 use quick_cache::unsync::Cache;
 use tokio::pin;
@@ -23,17 +23,67 @@
 };
 
 const MAX_PENDING_TIMEOUTS: usize = 100;
+// How often the transaction store gauges are updated.
+const TXN_STORE_METRICS_INTERVAL: Duration = Duration::from_secs(10);
 
 pub struct BroadcastWorker {
     handle: JoinHandle<()>,
     tx_shutdown: Arc<Notify>,
 }
 
+/// Our view of the current committee.
+#[derive(Debug)]
+pub(crate) struct Membership {
+    pub(crate) quorom_threshold: usize,
+    pub(crate) committee: Vec<NodeIndex>,
+    pub(crate) our_index: NodeIndex,
+    pub(crate) on_committee: bool,
+}
+
+impl Membership {
+    // Loads the committee from the application state and looks up our own index.
+    pub(crate) fn load<Q: SyncQueryRunnerInterface>(
+        query_runner: &Q,
+        node_public_key: &NodePublicKey,
+    ) -> Self {
+        let committee = query_runner.get_committee_members_by_index();
+        let quorom_threshold = (committee.len() * 2) / 3 + 1;
+        let our_index = query_runner
+            .pubkey_to_index(node_public_key)
+            .unwrap_or(u32::MAX);
+        let on_committee = committee.contains(&our_index);
+        Self {
+            quorom_threshold,
+            committee,
+            our_index,
+            on_committee,
+        }
+    }
+
+    // Returns true if a parcel we received from narwhal should be handled.
+    //
+    // This can be called while we are not on the committee right after staking, when narwhal is
+    // already running for us but we have not picked up our node index yet. Refresh our committee
+    // membership instead of crashing.
+    pub(crate) fn accept_narwhal_parcel<Q: SyncQueryRunnerInterface>(
+        &mut self,
+        query_runner: &Q,
+        node_public_key: &NodePublicKey,
+    ) -> bool {
+        if self.on_committee {
+            return true;
+        }
+        error!("Received a parcel from narwhal while not on committee, refreshing our node index");
+        *self = Self::load(query_runner, node_public_key);
+        if !self.on_committee {
+            error!("Dropping parcel from narwhal, we are still not on the committee");
+        }
+        self.on_committee
+    }
+}
+
 struct Context<P: PubSub<PubSubMsg>, Q: SyncQueryRunnerInterface, NE: Emitter> {
-    quorom_threshold: usize,
-    committee: Vec<NodeIndex>,
-    our_index: NodeIndex,
-    on_committee: bool,
+    membership: Membership,
     node_public_key: NodePublicKey,
     pending_timeouts: HashSet<Digest>,
     pending_requests: Cache<Digest, ()>,
@@ -104,12 +154,7 @@ async fn message_receiver_worker<P: PubSub<PubSubMsg>, Q: SyncQueryRunnerInterfa
     reconfigure_notify: Arc<Notify>,
 ) {
     info!("Edge node message worker is running");
-    let committee = query_runner.get_committee_members_by_index();
-    let quorom_threshold = (committee.len() * 2) / 3 + 1;
-    let our_index = query_runner
-        .pubkey_to_index(&node_public_key)
-        .unwrap_or(u32::MAX);
-    let on_committee = committee.contains(&our_index);
+    let membership = Membership::load(&query_runner, &node_public_key);
     let (timeout_tx, mut timeout_rx) = mpsc::channel(128);
     // `pending_timeouts` is not a cache because we already limit the number of timeouts we spawn
     // with `MAX_PENDING_TIMEOUTS`, so `pending_timeouts` is bounded from above by that constant
@@ -119,10 +164,7 @@ async fn message_receiver_worker<P: PubSub<PubSubMsg>, Q: SyncQueryRunnerInterfa
     let timeout = execution.get_parcel_timeout();
 
     let mut ctx = Context {
-        quorom_threshold,
-        committee,
-        our_index,
-        on_committee,
+        membership,
         node_public_key,
         pending_timeouts,
         pending_requests,
@@ -134,6 +176,8 @@ async fn message_receiver_worker<P: PubSub<PubSubMsg>, Q: SyncQueryRunnerInterfa
         timeout,
     };
 
+    let mut metrics_interval = tokio::time::interval(TXN_STORE_METRICS_INTERVAL);
+
     let shutdown_future = shutdown_notify.notified();
     pin!(shutdown_future);
     loop {
@@ -146,9 +190,11 @@ async fn message_receiver_worker<P: PubSub<PubSubMsg>, Q: SyncQueryRunnerInterfa
             },
             Some((parcel, epoch_changed))
                 = rx_narwhal_batches.recv() => {
-                if !ctx.on_committee {
-                    // This should never happen if it somehow does there is critical error somewhere
-                    panic!("We somehow sent ourselves a parcel from narwhal while not on committee");
+                if !ctx
+                    .membership
+                    .accept_narwhal_parcel(&ctx.query_runner, &ctx.node_public_key)
+                {
+                    continue;
                 }
                 handle_batch(parcel, epoch_changed, &mut ctx).await;
             },
@@ -174,10 +220,36 @@ async fn message_receiver_worker<P: PubSub<PubSubMsg>, Q: SyncQueryRunnerInterfa
                     }
                 }
             }
+            _ = metrics_interval.tick() => {
+                report_txn_store_metrics(&ctx);
+            }
         }
     }
 }
 
+fn report_txn_store_metrics<P: PubSub<PubSubMsg>, Q: SyncQueryRunnerInterface, NE: Emitter>(
+    ctx: &Context<P, Q, NE>,
+) {
+    let Ok(stats) = ctx.execution.get_txn_store_stats() else {
+        return;
+    };
+    set_gauge!(
+        "consensus_txn_store_parcels",
+        Some("Number of parcels in the consensus transaction store for this and last epoch"),
+        stats.parcels as i64
+    );
+    set_gauge!(
+        "consensus_txn_store_pending_parcels",
+        Some("Number of parcels from the next epoch buffered in the consensus transaction store"),
+        stats.pending_parcels as i64
+    );
+    set_gauge!(
+        "consensus_txn_store_attestations",
+        Some("Number of attestations in the consensus transaction store for this and last epoch"),
+        stats.attestations.values().sum::<usize>() as i64
+    );
+}
+
 async fn handle_pubsub_event<P: PubSub<PubSubMsg>, Q: SyncQueryRunnerInterface, NE: Emitter>(
     mut msg: P::Event,
     ctx: &mut Context<P, Q, NE>,
@@ -222,7 +294,7 @@ async fn handle_batch<P: PubSub<PubSubMsg>, Q: SyncQueryRunnerInterface, NE: Emi
     let parcel_digest = parcel.to_digest();
     let attestation = CommitteeAttestation {
         digest: parcel_digest,
-        node_index: ctx.our_index,
+        node_index: ctx.membership.our_index,
         epoch: parcel.epoch,
     };
 
@@ -236,23 +308,15 @@ async fn handle_batch<P: PubSub<PubSubMsg>, Q: SyncQueryRunnerInterface, NE: Emi
     // requests. Storing parcels is not critical for their consensus.
     if let Err(e) = ctx
         .execution
-        .store_parcel(parcel, ctx.our_index, msg_digest.ok())
+        .store_parcel(parcel, ctx.membership.our_index, msg_digest.ok())
     {
         error!("Failed to store parcel in txn store as a validator: {e:?}");
     }
     // No need to store the attestation we have already executed it
 
     if epoch_changed {
-        ctx.committee = ctx.query_runner.get_committee_members_by_index();
-        ctx.quorom_threshold = (ctx.committee.len() * 2) / 3 + 1;
-        // We recheck our index incase it was non existant before
-        // and we staked during this epoch and finally got the certificate
-        ctx.our_index = ctx
-            .query_runner
-            .pubkey_to_index(&ctx.node_public_key)
-            .unwrap_or(u32::MAX);
-        ctx.on_committee = ctx.committee.contains(&ctx.our_index);
-        if let Err(e) = ctx.execution.change_epoch(&ctx.committee) {
+        refresh_committee_membership(ctx);
+        if let Err(e) = ctx.execution.change_epoch(&ctx.membership.committee) {
             error!("Failed to rotate epochs in txn store as a validator: {e:?}");
         }
     }
@@ -265,7 +329,7 @@ async fn handle_parcel<P: PubSub<PubSubMsg>, Q: SyncQueryRunnerInterface, NE: Em
 ) {
     let epoch = ctx.query_runner.get_current_epoch();
     let originator = msg.originator();
-    let is_committee = ctx.committee.contains(&originator);
+    let is_committee = ctx.membership.committee.contains(&originator);
     if !is_valid_message(is_committee, parcel.epoch, epoch) {
         msg.mark_invalid_sender();
         return;
@@ -322,7 +386,7 @@ async fn handle_parcel<P: PubSub<PubSubMsg>, Q: SyncQueryRunnerInterface, NE: Em
         );
     }
 
-    if !ctx.on_committee {
+    if !ctx.membership.on_committee {
         // If the node is not on the committee, storing parcels is crucial for the consensus.
         // If storing parcels fails for some reason, we want to panic.
         store_result.expect("Failed to store parcel");
@@ -339,7 +403,7 @@ async fn handle_attestation<P: PubSub<PubSubMsg>, Q: SyncQueryRunnerInterface, N
     let originator = msg.originator();
 
     let epoch = ctx.query_runner.get_current_epoch();
-    let is_committee = ctx.committee.contains(&originator);
+    let is_committee = ctx.membership.committee.contains(&originator);
     if originator != att.node_index || !is_valid_message(is_committee, att.epoch, epoch) {
         msg.mark_invalid_sender();
         return;
@@ -353,7 +417,7 @@ async fn handle_attestation<P: PubSub<PubSubMsg>, Q: SyncQueryRunnerInterface, N
         event = Some(msg);
     }
 
-    if !ctx.on_committee {
+    if !ctx.membership.on_committee {
         info!("Received parcel attestation from gossip as an edge node");
 
         if from_next_epoch {
@@ -381,23 +445,15 @@ async fn try_execute<P: PubSub<PubSubMsg>, Q: SyncQueryRunnerInterface, NE: Emit
 ) {
     match ctx
         .execution
-        .try_execute(digest, ctx.quorom_threshold)
+        .try_execute(digest, ctx.membership.quorom_threshold)
         .await
     {
         Ok(epoch_changed) => {
             if epoch_changed {
-                ctx.committee = ctx.query_runner.get_committee_members_by_index();
-                ctx.quorom_threshold = (ctx.committee.len() * 2) / 3 + 1;
-                // We recheck our index incase it was non existant before and
-                // we staked during this epoch and finally got the certificate
-                ctx.our_index = ctx
-                    .query_runner
-                    .pubkey_to_index(&ctx.node_public_key)
-                    .unwrap_or(u32::MAX);
-                ctx.on_committee = ctx.committee.contains(&ctx.our_index);
+                refresh_committee_membership(ctx);
                 ctx.reconfigure_notify.notify_waiters();
                 ctx.execution
-                    .change_epoch(&ctx.committee)
+                    .change_epoch(&ctx.membership.committee)
                     .expect("Failed to rotate epochs for txn store");
             }
         },
@@ -407,6 +463,14 @@ async fn try_execute<P: PubSub<PubSubMsg>, Q: SyncQueryRunnerInterface, NE: Emit
     }
 }
 
+// Reloads the committee from the application state and rechecks our own index, in case it was
+// non existent before and we staked during this epoch and finally got the certificate.
+fn refresh_committee_membership<P: PubSub<PubSubMsg>, Q: SyncQueryRunnerInterface, NE: Emitter>(
+    ctx: &mut Context<P, Q, NE>,
+) {
+    ctx.membership = Membership::load(&ctx.query_runner, &ctx.node_public_key);
+}
+
 // While trying to connect the chain back to the head, we discovered a missing parcel.
 // This is can happen normally, because parcels or attestations might arrive out of
 // order.
@@ -421,6 +485,16 @@ fn handle_not_executed<P: PubSub<PubSubMsg>, Q: SyncQueryRunnerInterface, NE: Em
     not_executed: NotExecuted,
     ctx: &mut Context<P, Q, NE>,
 ) {
+    let reason = match &not_executed {
+        NotExecuted::MissingParcel { .. } => "missing_parcel",
+        NotExecuted::MissingAttestations(_) => "missing_attestations",
+    };
+    increment_counter!(
+        "consensus_parcel_not_executed",
+        Some("Number of times an edge node could not execute a parcel yet"),
+        "reason" => reason
+    );
+
     if let NotExecuted::MissingParcel { digest, timeout } = not_executed {
         ctx.timeout = timeout;
         set_parcel_timer(
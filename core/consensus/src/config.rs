@@ -2,10 +2,16 @@
 use resolved_pathbuf::ResolvedPathBuf;
 use serde::{Deserialize, Serialize};
 
+use crate::transaction_store::DEFAULT_MAX_PENDING_PARCELS;
+
 #[derive(Serialize, Deserialize)]
+#[serde(default)]
 pub struct Config {
     /// Path to the database used by the narwhal implementation.
     pub store_path: ResolvedPathBuf,
+    /// The maximum number of parcels from the next epoch that are buffered in the transaction
+    /// store. Once this is exceeded, the oldest pending parcels are evicted.
+    pub max_pending_parcels: usize,
 }
 
 impl Default for Config {
@@ -15,6 +21,7 @@ fn default() -> Self {
                 .join("data/narwhal_store")
                 .try_into()
                 .expect("Failed to resolve path"),
+            max_pending_parcels: DEFAULT_MAX_PENDING_PARCELS,
         }
     }
 }
@@ -8,7 +8,7 @@
 use fleek_crypto::{ConsensusPublicKey, NodePublicKey, SecretKey};
 use lightning_interfaces::prelude::*;
 use lightning_interfaces::types::{Epoch, EpochInfo, Topic, UpdateMethod};
-use lightning_interfaces::Events;
+use lightning_interfaces::{Events, ShutdownPhase, ShutdownPhaseWaiters};
 use lightning_utils::application::QueryRunnerExt;
 use mysten_metrics::RegistryService;
 use mysten_network::Multiaddr;
@@ -305,7 +305,7 @@ fn get_narwhal_store_and_garbage_collect(&self, current_epoch: u64) -> NodeStora
 impl<C: Collection> Consensus<C> {
     /// Start the system, should not do anything if the system is already
     /// started.
-    fn start(&mut self, fdi::Cloned(waiter): fdi::Cloned<ShutdownWaiter>) {
+    fn start(&mut self, fdi::Cloned(waiters): fdi::Cloned<ShutdownPhaseWaiters>) {
         let reconfigure_notify = self.reconfigure_notify.clone();
         let shutdown_notify_epoch_state = self.shutdown_notify_epoch_state.clone();
 
@@ -314,7 +314,10 @@ fn start(&mut self, fdi::Cloned(waiter): fdi::Cloned<ShutdownWaiter>) {
             .take()
             .expect("Consensus was tried to start before initialization");
 
-        let panic_waiter = waiter.clone();
+        // Consensus goes down before the rest of the node, but if it panics the whole node should
+        // go down with it.
+        let waiter = waiters.get(ShutdownPhase::Consensus);
+        let panic_waiter = waiters.get(ShutdownPhase::Services);
         spawn!(
             async move {
                 let broadcast_worker =
@@ -413,6 +416,7 @@ fn init(
             tx_narwhal_batches,
             query_runner.clone(),
             notifier.get_emitter(),
+            config.max_pending_parcels,
         ));
 
         let shutdown_notify_epoch_state = Arc::new(Notify::new());
@@ -16,6 +16,7 @@
     TransactionRequest,
 };
 use lightning_interfaces::{Events, ExecutionEngineSocket};
+use lightning_metrics::{increment_counter, increment_counter_by};
 use lightning_utils::application::QueryRunnerExt;
 use narwhal_crypto::DefaultHashFunction;
 use narwhal_executor::ExecutionState;
@@ -25,7 +26,7 @@
 use tracing::{error, info};
 
 use crate::consensus::PubSubMsg;
-use crate::transaction_store::TransactionStore;
+use crate::transaction_store::{TransactionStore, TransactionStoreStats};
 
 pub type Digest = [u8; 32];
 
@@ -110,6 +111,7 @@ pub fn new(
         tx_narwhal_batches: mpsc::Sender<(AuthenticStampedParcel, bool)>,
         query_runner: Q,
         notifier: NE,
+        max_pending_parcels: usize,
     ) -> Self {
         Self {
             executor,
@@ -118,7 +120,9 @@ pub fn new(
             query_runner,
             notifier,
             event_tx: OnceLock::new(),
-            txn_store: RwLock::new(TransactionStore::default()),
+            txn_store: RwLock::new(TransactionStore::with_max_pending_parcels(
+                max_pending_parcels,
+            )),
             executed_digests: RwLock::new(HashSet::with_capacity(512)),
             pending_digests: RwLock::new(HashSet::with_capacity(512)),
             parcel_timeout_data: RwLock::new(ParcelTimeoutData {
@@ -212,6 +216,10 @@ pub fn store_parcel(
     ) -> Result<()> {
         if let Ok(mut txn_store) = self.txn_store.write() {
             txn_store.store_parcel(parcel, originator, message_digest);
+            increment_counter!(
+                "consensus_parcel_stored",
+                Some("Number of parcels stored in the consensus transaction store")
+            );
             Ok(())
         } else {
             Err(anyhow!("Failed to acquire lock"))
@@ -272,6 +280,14 @@ pub fn contains_parcel(&self, digest: &Digest) -> Result<bool> {
         }
     }
 
+    pub fn get_txn_store_stats(&self) -> Result<TransactionStoreStats> {
+        if let Ok(txn_store) = self.txn_store.read() {
+            Ok(txn_store.stats())
+        } else {
+            Err(anyhow!("Failed to acquire lock"))
+        }
+    }
+
     pub fn change_epoch(&self, committee: &[NodeIndex]) -> Result<()> {
         if let Ok(mut txn_store) = self.txn_store.write() {
             txn_store.change_epoch(committee);
@@ -365,6 +381,7 @@ async fn try_execute_chain(&self, digest: Digest, head: Digest) -> Result<bool,
 
             if parcel.inner.last_executed == head {
                 let mut epoch_changed = false;
+                let num_parcels = parcel_chain.len() as u64;
 
                 // We connected the chain now execute all the transactions
                 for (batch, sub_dag_index, digest) in txn_chain {
@@ -388,6 +405,12 @@ async fn try_execute_chain(&self, digest: Digest, head: Digest) -> Result<bool,
                 // call `submit_batch`, but I think this might bias the estimate to be too low.
                 self.update_estimated_tbe();
 
+                increment_counter_by!(
+                    num_parcels,
+                    "consensus_parcel_executed",
+                    Some("Number of parcels an edge node executed after linking them to the chain")
+                );
+
                 return Ok(epoch_changed);
             } else {
                 last_digest = parcel.inner.last_executed;
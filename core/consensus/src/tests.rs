@@ -1,13 +1,26 @@
+use fleek_crypto::{AccountOwnerSecretKey, ConsensusSecretKey, NodeSecretKey, SecretKey};
+use lightning_application::app::Application;
+use lightning_application::config::Config as AppConfig;
+use lightning_application::genesis::{Genesis, GenesisNode};
+use lightning_application::query_runner::QueryRunner;
 use lightning_interfaces::prelude::*;
-use lightning_interfaces::types::{Digest as BroadcastDigest, NodeIndex};
+use lightning_interfaces::types::{Digest as BroadcastDigest, NodeIndex, NodePorts};
+use lightning_test_utils::json_config::JsonConfigProvider;
 use narwhal_types::{Batch, BatchAPI, Transaction};
 use rand::Rng;
 use sui_protocol_config::{Chain, ProtocolConfig, ProtocolVersion};
+use tempfile::tempdir;
 
+use crate::broadcast_worker::Membership;
 use crate::consensus::PubSubMsg;
 use crate::execution::{AuthenticStampedParcel, Digest};
 use crate::transaction_store::TransactionStore;
 
+partial!(TestBinding {
+    ConfigProviderInterface = JsonConfigProvider;
+    ApplicationInterface = Application<Self>;
+});
+
 fn generate_random_tx(length: usize) -> Transaction {
     let mut rng = rand::thread_rng();
     (0..length).map(|_| rng.gen_range(0..255)).collect()
@@ -159,6 +172,141 @@ fn test_ring_buffer_invalid_parcel() {
     assert!(ring_buffer.get_parcel(&digest).is_none());
 }
 
+#[test]
+fn test_ring_buffer_evict_pending_parcels() {
+    let mut ring_buffer = TransactionStore::<Event>::with_max_pending_parcels(2);
+    let digests: Vec<Digest> = (0..4)
+        .map(|_| {
+            let parcel = generate_random_parcel(2, 1, 2, None);
+            let digest = parcel.to_digest();
+            let event = Event {
+                originator: 1,
+                message: None,
+                digest,
+            };
+            ring_buffer.store_pending_parcel(parcel, 2, None, event);
+            digest
+        })
+        .collect();
+    assert_eq!(ring_buffer.num_pending_parcels(), 2);
+
+    // After the epoch change, only the two most recent pending parcels should remain.
+    let new_committee = vec![0, 1, 2, 3];
+    ring_buffer.change_epoch(&new_committee);
+    assert!(ring_buffer.get_parcel(&digests[0]).is_none());
+    assert!(ring_buffer.get_parcel(&digests[1]).is_none());
+    assert!(ring_buffer.get_parcel(&digests[2]).is_some());
+    assert!(ring_buffer.get_parcel(&digests[3]).is_some());
+    assert_eq!(ring_buffer.num_pending_parcels(), 0);
+}
+
+#[test]
+fn test_ring_buffer_stats() {
+    let mut ring_buffer = TransactionStore::<Event>::new();
+    let parcel1 = generate_random_parcel(2, 1, 2, None);
+    let digest1 = parcel1.to_digest();
+    let parcel2 = generate_random_parcel(2, 1, 2, Some(digest1));
+    let digest2 = parcel2.to_digest();
+    ring_buffer.store_parcel(parcel1, 1, None);
+    ring_buffer.store_parcel(parcel2, 2, None);
+    ring_buffer.store_attestation(digest1, 1);
+    ring_buffer.store_attestation(digest1, 2);
+    ring_buffer.store_attestation(digest2, 3);
+
+    let parcel3 = generate_random_parcel(2, 1, 2, None);
+    let digest3 = parcel3.to_digest();
+    let event = Event {
+        originator: 1,
+        message: None,
+        digest: digest3,
+    };
+    ring_buffer.store_pending_parcel(parcel3, 1, None, event);
+
+    let stats = ring_buffer.stats();
+    assert_eq!(stats.parcels, 2);
+    assert_eq!(stats.pending_parcels, 1);
+    assert_eq!(stats.attestations.get(&digest1), Some(&2));
+    assert_eq!(stats.attestations.get(&digest2), Some(&1));
+    assert_eq!(stats.attestations.get(&digest3), None);
+}
+
+#[tokio::test]
+async fn test_narwhal_parcel_before_index_is_known() {
+    let temp_dir = tempdir().unwrap();
+
+    // Given: a genesis committee that we are part of.
+    let node_secret_key = NodeSecretKey::generate();
+    let node_public_key = node_secret_key.to_pk();
+    let mut genesis = Genesis::default();
+    genesis.node_info.push(GenesisNode::new(
+        AccountOwnerSecretKey::generate().to_pk().into(),
+        node_public_key,
+        "127.0.0.1".parse().unwrap(),
+        ConsensusSecretKey::generate().to_pk(),
+        "127.0.0.1".parse().unwrap(),
+        node_public_key,
+        NodePorts {
+            primary: 48000,
+            worker: 48101,
+            mempool: 48102,
+            rpc: 48103,
+            pool: 48104,
+            pinger: 48106,
+            handshake: Default::default(),
+        },
+        None,
+        true,
+    ));
+    let genesis_path = genesis
+        .write_to_dir(temp_dir.path().to_path_buf().try_into().unwrap())
+        .unwrap();
+
+    let mut node = Node::<TestBinding>::init_with_provider(
+        fdi::Provider::default().with(
+            JsonConfigProvider::default()
+                .with::<Application<TestBinding>>(AppConfig::test(genesis_path)),
+        ),
+    )
+    .unwrap();
+    node.start().await;
+    let query_runner = node.provider.get::<QueryRunner>().clone();
+
+    // Given: a worker that has not picked up its node index yet, like right after staking.
+    let mut membership = Membership {
+        quorom_threshold: 1,
+        committee: Vec::new(),
+        our_index: u32::MAX,
+        on_committee: false,
+    };
+
+    // When: narwhal hands us a parcel.
+    let accepted = membership.accept_narwhal_parcel(&query_runner, &node_public_key);
+
+    // Then: we refresh our index instead of panicking and handle the parcel.
+    assert!(accepted);
+    assert_eq!(
+        Some(membership.our_index),
+        query_runner.pubkey_to_index(&node_public_key)
+    );
+    assert!(membership.committee.contains(&membership.our_index));
+
+    // When: narwhal hands a parcel to a node that is not on the committee.
+    let mut membership = Membership {
+        quorom_threshold: 1,
+        committee: Vec::new(),
+        our_index: u32::MAX,
+        on_committee: false,
+    };
+    let accepted =
+        membership.accept_narwhal_parcel(&query_runner, &NodeSecretKey::generate().to_pk());
+
+    // Then: the parcel is dropped without panicking.
+    assert!(!accepted);
+    assert_eq!(membership.our_index, u32::MAX);
+
+    node.shutdown().await;
+}
+
 struct Event {
     originator: NodeIndex,
     message: Option<PubSubMsg>,
@@ -1,8 +1,9 @@
 use std::collections::hash_map::Entry;
-use std::collections::{HashMap, HashSet};
+use std::collections::{HashMap, HashSet, VecDeque};
 
 use lightning_interfaces::prelude::*;
 use lightning_interfaces::types::{Digest as BroadcastDigest, NodeIndex};
+use tracing::warn;
 
 use crate::consensus::PubSubMsg;
 use crate::execution::{AuthenticStampedParcel, Digest};
@@ -25,9 +26,26 @@ pub struct Parcel {
     pub message_digest: Option<BroadcastDigest>,
 }
 
+/// A snapshot of the contents of the transaction store.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct TransactionStoreStats {
+    /// The number of parcels stored for the current and previous epoch.
+    pub parcels: usize,
+    /// The number of parcels from the next epoch that are waiting for the epoch change.
+    pub pending_parcels: usize,
+    /// The number of attestations per parcel digest for the current and previous epoch.
+    pub attestations: HashMap<Digest, usize>,
+}
+
+/// The default maximum number of parcels from the next epoch we buffer.
+pub const DEFAULT_MAX_PENDING_PARCELS: usize = 1024;
+
 pub struct TransactionStore<T: BroadcastEventInterface<PubSubMsg>> {
     ring: Vec<HashMap<Digest, ParcelWrapper<T>>>,
     pointer: usize,
+    // The digests of the parcels from the next epoch, in the order they were stored.
+    pending_parcels: VecDeque<Digest>,
+    max_pending_parcels: usize,
 }
 
 impl<T: BroadcastEventInterface<PubSubMsg>> TransactionStore<T> {
@@ -35,6 +53,19 @@ pub fn new() -> Self {
         Self::default()
     }
 
+    pub fn with_max_pending_parcels(max_pending_parcels: usize) -> Self {
+        Self {
+            ring: vec![
+                HashMap::with_capacity(100),
+                HashMap::with_capacity(100),
+                HashMap::with_capacity(100),
+            ],
+            pointer: 1,
+            pending_parcels: VecDeque::new(),
+            max_pending_parcels,
+        }
+    }
+
     // Returns the parcel for the given digest, if it exists.
     // If the parcel does not exist for the current epoch, we will check for parcels from the
     // previous epoch.
@@ -77,13 +108,42 @@ pub fn store_pending_parcel(
         message_digest: Option<BroadcastDigest>,
         event: T,
     ) {
-        self.store_parcel_internal(
+        let digest = parcel.to_digest();
+        let inserted = self.store_parcel_internal(
             self.next_pointer(),
             parcel,
             originator,
             message_digest,
             Some(event),
         );
+        if inserted {
+            self.pending_parcels.push_back(digest);
+            self.evict_pending_parcels();
+        }
+    }
+
+    // Returns the number of parcels from the next epoch that are currently buffered.
+    pub fn num_pending_parcels(&self) -> usize {
+        self.pending_parcels.len()
+    }
+
+    // Returns a snapshot of the number of stored parcels and attestations.
+    pub fn stats(&self) -> TransactionStoreStats {
+        let mut stats = TransactionStoreStats {
+            pending_parcels: self.num_pending_parcels(),
+            ..Default::default()
+        };
+        for pointer in [self.prev_pointer(), self.pointer] {
+            for (digest, wrapper) in self.ring[pointer].iter() {
+                if wrapper.parcel.is_some() {
+                    stats.parcels += 1;
+                }
+                if let Some(attestations) = &wrapper.attestations {
+                    *stats.attestations.entry(*digest).or_default() += attestations.len();
+                }
+            }
+        }
+        stats
     }
 
     // Store an attestation from the current epoch.
@@ -146,12 +206,35 @@ pub fn change_epoch(&mut self, committee: &[NodeIndex]) {
         // Clear previous epoch map, because this will become the next epoch map
         self.ring[prev_pointer].clear();
         self.pointer = self.next_pointer();
+        // The pending parcels are now part of the current epoch.
+        self.pending_parcels.clear();
+    }
+
+    // Evicts the oldest parcels from the next epoch until we are within the configured bound.
+    // Attestations for an evicted parcel are kept, since they are accounted for separately.
+    fn evict_pending_parcels(&mut self) {
+        let next_pointer = self.next_pointer();
+        while self.pending_parcels.len() > self.max_pending_parcels {
+            let Some(digest) = self.pending_parcels.pop_front() else {
+                break;
+            };
+            warn!("Evicting pending parcel with digest {digest:?}: too many pending parcels");
+            if let Entry::Occupied(mut entry) = self.ring[next_pointer].entry(digest) {
+                let wrapper = entry.get_mut();
+                wrapper.parcel = None;
+                wrapper.parcel_event = None;
+                if wrapper.attestations.is_none() {
+                    entry.remove();
+                }
+            }
+        }
     }
 
     // Store a parcel and optionally provide the digest of the broadcast message that delivered
     // this parcel.
     // If we already store the parcel, we won't overwrite it again. If we already store the parcel,
     // but don't store the broadcast message yet, we will insert the message.
+    // Returns true if the parcel was not stored before.
     fn store_parcel_internal(
         &mut self,
         pointer: usize,
@@ -159,7 +242,7 @@ fn store_parcel_internal(
         originator: NodeIndex,
         message_digest: Option<BroadcastDigest>,
         event: Option<T>,
-    ) {
+    ) -> bool {
         let digest = parcel.to_digest();
         // We are explicitly matching the entry here instead of using `and_modify` together with
         // `or_insert` in order to avoid cloning the parcel.
@@ -175,12 +258,14 @@ fn store_parcel_internal(
                     parcel_event: event,
                     attestation_events: None,
                 });
+                true
             },
             Entry::Occupied(mut entry) => match &mut entry.get_mut().parcel {
                 Some(parcel) => {
                     if parcel.message_digest.is_none() {
                         parcel.message_digest = message_digest;
                     }
+                    false
                 },
                 None => {
                     entry.get_mut().parcel = Some(Parcel {
@@ -188,6 +273,7 @@ fn store_parcel_internal(
                         originator,
                         message_digest,
                     });
+                    true
                 },
             },
         }
@@ -233,13 +319,6 @@ fn prev_pointer(&self) -> usize {
 
 impl<T: BroadcastEventInterface<PubSubMsg>> Default for TransactionStore<T> {
     fn default() -> Self {
-        Self {
-            ring: vec![
-                HashMap::with_capacity(100),
-                HashMap::with_capacity(100),
-                HashMap::with_capacity(100),
-            ],
-            pointer: 1,
-        }
+        Self::with_max_pending_parcels(DEFAULT_MAX_PENDING_PARCELS)
     }
 }
@@ -93,6 +93,7 @@ async fn init_aggregator(temp_dir: &TempDir) -> Node<TestBinding> {
                         probability_txn_lost: 0.0,
                         transactions_to_lose: HashSet::new(),
                         new_block_interval: Duration::from_secs(5),
+                        fault_injector: None,
                     })
                     .with::<DeliveryAcknowledgmentAggregator<TestBinding>>(Config {
                         submit_interval: Duration::from_secs(1),
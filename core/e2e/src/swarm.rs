@@ -445,6 +445,7 @@ fn build_config(
             .join("data/resolver_store")
             .try_into()
             .expect("Failed to resolve path"),
+        ..Default::default()
     });
     config.inject::<Rpc<FinalTypes>>(RpcConfig::default_with_port(ports.rpc));
 
@@ -453,6 +454,7 @@ fn build_config(
             .join("data/narwhal_store")
             .try_into()
             .expect("Failed to resolve path"),
+        ..Default::default()
     });
 
     config.inject::<Keystore<FinalTypes>>(KeystoreConfig {
@@ -471,6 +473,7 @@ fn build_config(
             .join("data/blockstore")
             .try_into()
             .expect("Failed to resolve path"),
+        max_total_bytes: None,
     });
 
     config.inject::<BlockstoreServer<FinalTypes>>(BlockstoreServerConfig::default());
@@ -491,6 +494,7 @@ fn build_config(
 
     config.inject::<ReputationAggregator<FinalTypes>>(RepAggConfig {
         reporter_buffer_size: 1,
+        ..Default::default()
     });
 
     config.inject::<PoolProvider<FinalTypes>>(PoolConfig {
@@ -508,6 +512,7 @@ fn build_config(
             .join("data/archive")
             .try_into()
             .expect("Failed to resolve path"),
+        ..Default::default()
     });
 
     config.inject::<Pinger<FinalTypes>>(PingerConfig {
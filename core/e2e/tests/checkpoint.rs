@@ -5,6 +5,7 @@
 use lightning_e2e::swarm::Swarm;
 use lightning_rpc::interface::Fleek;
 use lightning_rpc::RpcClient;
+use lightning_syncronizer::rpc::attested_epoch_hash;
 use lightning_test_utils::config::LIGHTNING_TEST_HOME_DIR;
 use lightning_test_utils::logging;
 use resolved_pathbuf::ResolvedPathBuf;
@@ -59,6 +60,20 @@ async fn e2e_checkpoint() -> Result<()> {
         // Make sure that all nodes stored the same hash for the epoch state.
         assert_eq!(epoch_hash, target_hash.unwrap());
     }
+
+    // Make sure that every node signs the hash with its own key, and that the signatures of the
+    // committee are enough for a syncing node to accept the hash.
+    let mut attestations = Vec::new();
+    for (public_key, address) in swarm.get_rpc_addresses() {
+        let client = RpcClient::new_no_auth(&address)?;
+        let attestation = client.get_last_epoch_hash_attestation().await?;
+
+        assert_eq!(attestation.node, public_key);
+        assert!(attestation.verify());
+        attestations.push(attestation);
+    }
+    let committee = swarm.get_rpc_addresses().into_keys().collect::<Vec<_>>();
+    assert_eq!(attested_epoch_hash(&attestations, &committee), target_hash);
     // TODO(matthias): read the block stores of all the nodes and make sure they all stored the
     // checkpoint
 
@@ -4,12 +4,21 @@
 pub struct Config {
     // Maximum number of concurrent origin requests we send out.
     pub max_conc_origin_req: usize,
+    // Maximum number of peers and origins we try to fetch a piece of content from before giving
+    // up on it.
+    #[serde(default = "default_max_fetch_attempts")]
+    pub max_fetch_attempts: usize,
 }
 
 impl Default for Config {
     fn default() -> Self {
         Self {
             max_conc_origin_req: 5,
+            max_fetch_attempts: default_max_fetch_attempts(),
         }
     }
 }
+
+fn default_max_fetch_attempts() -> usize {
+    8
+}
@@ -1,4 +1,6 @@
+use std::collections::HashMap;
 use std::marker::PhantomData;
+use std::sync::{Arc, Mutex};
 
 use affair::AsyncWorkerUnordered;
 use anyhow::{anyhow, Context, Result};
@@ -10,9 +12,15 @@
     ImmutablePointer,
     ServerRequest,
 };
-use lightning_interfaces::{spawn_worker, BlockstoreServerSocket, FetcherSocket};
+use lightning_interfaces::{
+    spawn_worker,
+    BlockstoreServerSocket,
+    DownloadProgress,
+    FetchHandle,
+    FetcherSocket,
+};
 use lightning_metrics::increment_counter;
-use tokio::sync::{mpsc, oneshot};
+use tokio::sync::{broadcast, mpsc, oneshot};
 use tracing::info;
 use types::{NodeIndex, PeerRequestError};
 
@@ -21,8 +29,12 @@
 
 pub(crate) type Uri = Vec<u8>;
 
+type PendingFetches = Arc<Mutex<HashMap<Blake3Hash, broadcast::Sender<Result<(), String>>>>>;
+
 pub struct Fetcher<C: Collection> {
     socket: FetcherSocket,
+    blockstore: C::BlockstoreInterface,
+    download_progress: DownloadProgress,
     _collection: PhantomData<C>,
 }
 
@@ -32,7 +44,6 @@ pub fn new(
         config: &C::ConfigProviderInterface,
         blockstore_server: &C::BlockstoreServerInterface,
         origin: &C::OriginProviderInterface,
-        app: &C::ApplicationInterface,
         fdi::Cloned(blockstore): fdi::Cloned<C::BlockstoreInterface>,
         fdi::Cloned(resolver): fdi::Cloned<C::ResolverInterface>,
         fdi::Cloned(shutdown): fdi::Cloned<ShutdownWaiter>,
@@ -58,16 +69,19 @@ pub fn new(
 
         let worker = FetcherWorker::<C> {
             origin_tx,
-            blockstore,
+            blockstore: blockstore.clone(),
             blockstore_server_socket: blockstore_server.get_socket(),
             resolver,
-            query_runner: app.sync_query(),
+            max_fetch_attempts: config.max_fetch_attempts,
+            pending_fetches: Default::default(),
         };
 
         let socket = spawn_worker!(worker, "FETCHER", shutdown, crucial);
 
         Ok(Self {
             socket,
+            blockstore,
+            download_progress: blockstore_server.get_download_progress(),
             _collection: PhantomData,
         })
     }
@@ -77,6 +91,38 @@ impl<C: Collection> FetcherInterface<C> for Fetcher<C> {
     fn get_socket(&self) -> FetcherSocket {
         self.socket.clone()
     }
+
+    fn fetch_with_progress(&self, hash: Blake3Hash) -> FetchHandle {
+        let progress = self.download_progress.subscribe(hash);
+        let download_progress = self.download_progress.clone();
+        let blockstore = self.blockstore.clone();
+        let socket = self.socket.clone();
+        let (result_tx, result_rx) = oneshot::channel();
+
+        spawn!(
+            async move {
+                let res = match socket.run(FetcherRequest::Fetch { hash }).await {
+                    Ok(FetcherResponse::Fetch(res)) => res,
+                    Ok(_) => Err(anyhow!("Unexpected response from the fetcher")),
+                    Err(e) => Err(anyhow!("Failed to send fetch request: {e:?}")),
+                };
+                // Content we had already, or that came from an origin, completes at once.
+                if res.is_ok() {
+                    if let Some(tree) = blockstore.get_tree(&hash).await {
+                        let blocks = tree.len() as u32;
+                        download_progress.update(&hash, |progress| {
+                            progress.blocks_verified = blocks;
+                            progress.total_blocks = Some(blocks);
+                        });
+                    }
+                }
+                let _ = result_tx.send(res);
+            },
+            "FETCHER: fetch with progress"
+        );
+
+        FetchHandle::new(progress, result_rx)
+    }
 }
 
 struct FetcherWorker<C: Collection> {
@@ -84,7 +130,8 @@ struct FetcherWorker<C: Collection> {
     blockstore: C::BlockstoreInterface,
     blockstore_server_socket: BlockstoreServerSocket,
     resolver: C::ResolverInterface,
-    query_runner: c!(C::ApplicationInterface::SyncExecutor),
+    max_fetch_attempts: usize,
+    pending_fetches: PendingFetches,
 }
 
 impl<C: Collection> FetcherWorker<C> {
@@ -103,11 +150,46 @@ impl<C: Collection> FetcherWorker<C> {
         self.fetch_from_origin(pointer).await
     }
 
+    /// Fetches the blake3 content. Concurrent fetches of the same content share a single
+    /// in-progress fetch and all get its result.
+    async fn fetch(&self, hash: Blake3Hash) -> Result<()> {
+        let pending_rx = {
+            let mut pending_fetches = self.pending_fetches.lock().unwrap();
+            match pending_fetches.get(&hash) {
+                Some(tx) => Some(tx.subscribe()),
+                None => {
+                    let (tx, _) = broadcast::channel(1);
+                    pending_fetches.insert(hash, tx);
+                    None
+                },
+            }
+        };
+
+        if let Some(mut rx) = pending_rx {
+            return match rx.recv().await {
+                Ok(res) => res.map_err(|e| anyhow!(e)),
+                Err(e) => Err(e).context("In-progress fetch was dropped"),
+            };
+        }
+
+        let guard = PendingFetchGuard {
+            hash,
+            pending_fetches: self.pending_fetches.clone(),
+            completed: false,
+        };
+        let res = self.fetch_from_providers(hash).await;
+        guard.complete(&res);
+        res
+    }
+
     /// Attempt to fetch the blake3 content. First, we check the blockstore,
     /// then iterate through the provider records, requesting from the provider,
-    /// then falling back to the record's immutable pointer.
+    /// then falling back to the record's immutable pointer. The providers are tried
+    /// best ranked first, and we give up after `max_fetch_attempts` attempts. When a provider
+    /// fails part way, the blockstore server resumes the download from the next provider at the
+    /// first block that was not verified yet.
     #[inline(always)]
-    async fn fetch(&self, hash: Blake3Hash) -> Result<()> {
+    async fn fetch_from_providers(&self, hash: Blake3Hash) -> Result<()> {
         if self.blockstore.get_tree(&hash).await.is_some() {
             increment_counter!(
                 "fetcher_from_cache",
@@ -120,22 +202,21 @@ async fn fetch(&self, hash: Blake3Hash) -> Result<()> {
             .get_origins(hash)
             .unwrap_or_default()
             .into_iter();
-        let mut peers = self
-            .query_runner
-            .get_uri_providers(&hash)
-            .unwrap_or_default()
-            .into_iter();
+        let mut peers = self.resolver.resolve_ranked(&hash).into_iter();
         // TODO(matthias): more optimizations here are possible.
         // For example, we can send concurrent requests to multiple peers and or multiple origins.
-        // Also, the list of peers would ideally be sorted by the latency to the local node.
-        loop {
+        let mut attempts = 0;
+        while attempts < self.max_fetch_attempts {
             let peer = peers.next();
             let pointer = origin_pointers.next();
             if peer.is_none() && pointer.is_none() {
                 break;
             }
             if let Some(peer) = peer {
-                // Try to get the content from the peer that advertised the record.
+                // Try to get the content from the peer that advertised the record. If the peer
+                // fails, we rotate to the next best ranked provider, which picks up the blocks
+                // where this one stopped.
+                attempts += 1;
                 if self.fetch_from_peer(peer, hash).await.is_ok() {
                     return Ok(());
                 }
@@ -150,8 +231,12 @@ async fn fetch(&self, hash: Blake3Hash) -> Result<()> {
                     );
                     return Ok(());
                 }
+                if attempts >= self.max_fetch_attempts {
+                    break;
+                }
                 // If not, attempt to pull from the origin. This strikes a balance between trying
                 // to fetch from a bunch of peers vs going to the origin right away.
+                attempts += 1;
                 if self.fetch_from_origin(pointer.pointer).await.is_ok() {
                     return Ok(());
                 }
@@ -261,6 +346,32 @@ async fn recv(
     }
 }
 
+/// Removes a pending fetch from the map once it completes, or when it is dropped before that, so
+/// that the callers waiting on it don't wait forever.
+struct PendingFetchGuard {
+    hash: Blake3Hash,
+    pending_fetches: PendingFetches,
+    completed: bool,
+}
+
+impl PendingFetchGuard {
+    /// Removes the pending fetch and hands its result to the callers waiting on it.
+    fn complete(mut self, res: &Result<()>) {
+        self.completed = true;
+        if let Some(tx) = self.pending_fetches.lock().unwrap().remove(&self.hash) {
+            let _ = tx.send(res.as_ref().map(|_| ()).map_err(|e| format!("{e:#}")));
+        }
+    }
+}
+
+impl Drop for PendingFetchGuard {
+    fn drop(&mut self) {
+        if !self.completed {
+            self.pending_fetches.lock().unwrap().remove(&self.hash);
+        }
+    }
+}
+
 impl<C: Collection> ConfigConsumer for Fetcher<C> {
     const KEY: &'static str = "fetcher";
     type Config = Config;
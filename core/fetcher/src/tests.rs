@@ -1,3 +1,5 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
 use std::time::Duration;
 
 use cid::Cid;
@@ -12,12 +14,15 @@
 use lightning_indexer::Indexer;
 use lightning_interfaces::prelude::*;
 use lightning_interfaces::types::{
+    Blake3Hash,
     FetcherRequest,
     FetcherResponse,
+    HandshakePorts,
     ImmutablePointer,
     NodePorts,
     OriginProvider,
 };
+use lightning_interfaces::FetcherSocket;
 use lightning_notifier::Notifier;
 use lightning_origin_demuxer::{Config as DemuxerOriginConfig, OriginDemuxer};
 use lightning_origin_ipfs::config::{Gateway, Protocol, RequestFormat};
@@ -36,7 +41,7 @@
 };
 use lightning_test_utils::json_config::JsonConfigProvider;
 use lightning_test_utils::keys::EphemeralKeystore;
-use lightning_test_utils::server::spawn_server;
+use lightning_test_utils::server::{spawn_server, spawn_server_with_counter};
 use lightning_topology::Topology;
 use tempfile::{tempdir, TempDir};
 use tokio::sync::oneshot;
@@ -89,13 +94,17 @@ async fn get_fetchers(
                     "127.0.0.1".parse().unwrap(),
                     keystore.get_ed25519_pk(),
                     NodePorts {
-                        primary: 48000_u16,
-                        worker: 48101_u16,
-                        mempool: 48202_u16,
-                        rpc: 48300_u16,
+                        primary: 20000 + i as u16,
+                        worker: 20100 + i as u16,
+                        mempool: 20200 + i as u16,
+                        rpc: 20300 + i as u16,
                         pool: pool_port_offset + i as u16,
-                        pinger: 48600_u16,
-                        handshake: Default::default(),
+                        pinger: 20600 + i as u16,
+                        handshake: HandshakePorts {
+                            http: 20700 + i as u16,
+                            webrtc: 20800 + i as u16,
+                            webtransport: 20900 + i as u16,
+                        },
                     },
                     None,
                     true,
@@ -131,6 +140,7 @@ async fn get_fetchers(
                             })
                             .with::<ReputationAggregator<TestBinding>>(RepCollConfig {
                                 reporter_buffer_size: 1,
+                                ..Default::default()
                             })
                             .with::<Resolver<TestBinding>>(ResolverConfig {
                                 store_path: temp_dir
@@ -138,6 +148,7 @@ async fn get_fetchers(
                                     .join(format!("node-{i}/resolver"))
                                     .try_into()
                                     .unwrap(),
+                                ..Default::default()
                             })
                             .with::<Blockstore<TestBinding>>(BlockstoreConfig {
                                 root: temp_dir
@@ -145,6 +156,7 @@ async fn get_fetchers(
                                     .join(format!("node-{i}/store"))
                                     .try_into()
                                     .unwrap(),
+                                max_total_bytes: None,
                             })
                             .with::<OriginDemuxer<TestBinding>>(DemuxerOriginConfig {
                                 ipfs: IPFSOriginConfig {
@@ -162,6 +174,7 @@ async fn get_fetchers(
                             })
                             .with::<Fetcher<TestBinding>>(Config {
                                 max_conc_origin_req: 3,
+                                ..Default::default()
                             }),
                     ),
             )
@@ -172,6 +185,44 @@ async fn get_fetchers(
     peers
 }
 
+/// Puts the content behind the pointer onto a peer through its fetcher, while serving the content
+/// from a dummy ipfs gateway on the given port, and returns the hash of the content.
+async fn put_from_gateway(
+    socket: FetcherSocket,
+    pointer: ImmutablePointer,
+    gateway_port: u16,
+) -> Blake3Hash {
+    let put_fut = async move {
+        let response = socket.run(FetcherRequest::Put { pointer }).await.unwrap();
+        match response {
+            FetcherResponse::Put(Ok(hash)) => hash,
+            FetcherResponse::Put(Err(e)) => panic!("Failed to put hash: {e:?}"),
+            _ => panic!("Unexpected response"),
+        }
+    };
+
+    tokio::select! {
+        biased;
+        res = spawn_server(gateway_port) => panic!("Dummy gateway stopped: {res:?}"),
+        hash = put_fut => hash,
+    }
+}
+
+/// Waits until a peer learned about the origin of the content, and about the given number of
+/// providers of it.
+async fn wait_for_providers(peer: &Node<TestBinding>, hash: Blake3Hash, num_providers: usize) {
+    let resolver = peer.provider.get::<Resolver<TestBinding>>().clone();
+    tokio::time::timeout(Duration::from_secs(30), async {
+        while resolver.get_origins(hash).is_none()
+            || resolver.resolve_ranked(&hash).len() < num_providers
+        {
+            tokio::time::sleep(Duration::from_millis(100)).await;
+        }
+    })
+    .await
+    .expect("Timed out waiting for the providers of the content");
+}
+
 #[tokio::test]
 async fn test_simple_origin_fetch() {
     let temp_dir = tempdir().unwrap();
@@ -274,3 +325,164 @@ async fn test_fetch_from_peer() {
     peer1.shutdown().await;
     peer2.shutdown().await;
 }
+
+#[tokio::test]
+async fn test_fetch_rotates_to_next_provider() {
+    let temp_dir = tempdir().unwrap();
+    let mut peers = get_fetchers(&temp_dir, 30501, 40501, 3).await;
+    let mut peer3 = peers.pop().unwrap();
+    let mut peer2 = peers.pop().unwrap();
+    let mut peer1 = peers.pop().unwrap();
+    let blockstore2 = peer2.provider.get::<Blockstore<TestBinding>>().clone();
+    let blockstore3 = peer3.provider.get::<Blockstore<TestBinding>>().clone();
+    let socket1 = peer1.provider.get::<Fetcher<TestBinding>>().get_socket();
+    let socket2 = peer2.provider.get::<Fetcher<TestBinding>>().get_socket();
+    let socket3 = peer3.provider.get::<Fetcher<TestBinding>>().get_socket();
+
+    peer1.start().await;
+    peer2.start().await;
+    peer3.start().await;
+
+    let req_cid =
+        Cid::try_from("bafybeigdyrzt5sfp7udm7hu76uh7y26nf3efuylqabf3oclgtqy55fbzdi").unwrap();
+    let pointer = ImmutablePointer {
+        origin: OriginProvider::IPFS,
+        uri: req_cid.to_bytes(),
+    };
+
+    // Put the data onto peer1 and peer2, so that both of them become providers.
+    let hash = put_from_gateway(socket1, pointer.clone(), 40501).await;
+    assert_eq!(put_from_gateway(socket2, pointer, 40502).await, hash);
+
+    // Wait for the content registry to be updated.
+    wait_for_providers(&peer3, hash, 2).await;
+
+    // Peer1 is ranked first, since there are neither latencies nor reputation scores to tell the
+    // providers apart, but it is gone. So peer3 has to rotate to peer2 to get the content.
+    peer1.shutdown().await;
+
+    let response = socket3.run(FetcherRequest::Fetch { hash }).await.unwrap();
+    match response {
+        FetcherResponse::Fetch(Ok(())) => {
+            let content2 = blockstore2.read_all_to_vec(&hash).await.unwrap();
+            let content3 = blockstore3.read_all_to_vec(&hash).await.unwrap();
+            assert_eq!(content2, content3);
+        },
+        FetcherResponse::Fetch(Err(e)) => panic!("Failed to fetch hash: {e:?}"),
+        _ => panic!("Unexpected response"),
+    }
+
+    peer2.shutdown().await;
+    peer3.shutdown().await;
+}
+
+#[tokio::test]
+async fn test_concurrent_fetches_are_deduplicated() {
+    let temp_dir = tempdir().unwrap();
+    let mut peers = get_fetchers(&temp_dir, 30601, 40601, 2).await;
+    let mut peer2 = peers.pop().unwrap();
+    let mut peer1 = peers.pop().unwrap();
+    let socket1 = peer1.provider.get::<Fetcher<TestBinding>>().get_socket();
+    let socket2 = peer2.provider.get::<Fetcher<TestBinding>>().get_socket();
+
+    peer1.start().await;
+    peer2.start().await;
+
+    let req_cid =
+        Cid::try_from("bafybeigdyrzt5sfp7udm7hu76uh7y26nf3efuylqabf3oclgtqy55fbzdi").unwrap();
+    let pointer = ImmutablePointer {
+        origin: OriginProvider::IPFS,
+        uri: req_cid.to_bytes(),
+    };
+
+    // Put some data onto peer1.
+    let hash = put_from_gateway(socket1, pointer, 40601).await;
+
+    // Wait for peer1 to broadcast the record.
+    wait_for_providers(&peer2, hash, 1).await;
+
+    // Once peer1 is gone, peer2 can only get the content from its own gateway.
+    peer1.shutdown().await;
+
+    let cid_requests = Arc::new(AtomicUsize::new(0));
+    let fetch_fut = async move {
+        let responses =
+            futures::future::join_all((0..10).map(|_| socket2.run(FetcherRequest::Fetch { hash })))
+                .await;
+        for response in responses {
+            match response.unwrap() {
+                FetcherResponse::Fetch(Ok(())) => {},
+                FetcherResponse::Fetch(Err(e)) => panic!("Failed to fetch hash: {e:?}"),
+                _ => panic!("Unexpected response"),
+            }
+        }
+    };
+
+    tokio::select! {
+        biased;
+        _ = spawn_server_with_counter(40602, cid_requests.clone()) => {}
+        _ = fetch_fut => {}
+    }
+    assert_eq!(cid_requests.load(Ordering::Relaxed), 1);
+
+    peer2.shutdown().await;
+}
+
+#[tokio::test]
+async fn test_fetch_with_progress() {
+    let temp_dir = tempdir().unwrap();
+    let mut peers = get_fetchers(&temp_dir, 30701, 40701, 2).await;
+    let mut peer2 = peers.pop().unwrap();
+    let mut peer1 = peers.pop().unwrap();
+    let socket1 = peer1.provider.get::<Fetcher<TestBinding>>().get_socket();
+
+    peer1.start().await;
+    peer2.start().await;
+
+    // A video that spans many blocks.
+    let req_cid =
+        Cid::try_from("bafybeibi5vlbuz3jstustlxbxk7tmxsyjjrxak6us4yqq6z2df3jwidiwi").unwrap();
+    let pointer = ImmutablePointer {
+        origin: OriginProvider::IPFS,
+        uri: req_cid.to_bytes(),
+    };
+
+    // Put the data onto peer1.
+    let hash = put_from_gateway(socket1, pointer, 40701).await;
+
+    // Wait for peer1 to broadcast the record.
+    wait_for_providers(&peer2, hash, 1).await;
+
+    // Fetch the data from peer1 onto peer2, recording the progress as it changes.
+    let handle = peer2
+        .provider
+        .get::<Fetcher<TestBinding>>()
+        .fetch_with_progress(hash);
+    let mut progress_rx = handle.progress();
+    let progress_fut = tokio::spawn(async move {
+        let mut progress = vec![*progress_rx.borrow_and_update()];
+        while progress_rx.changed().await.is_ok() {
+            let current = *progress_rx.borrow_and_update();
+            progress.push(current);
+            if current.total_blocks.is_some() {
+                break;
+            }
+        }
+        progress
+    });
+
+    handle.finish().await.unwrap();
+    let progress = progress_fut.await.unwrap();
+
+    for (prev, next) in progress.iter().zip(progress.iter().skip(1)) {
+        assert!(prev.bytes_fetched <= next.bytes_fetched);
+        assert!(prev.blocks_verified <= next.blocks_verified);
+    }
+    let last = progress.last().unwrap();
+    assert!(last.blocks_verified > 1);
+    assert_eq!(last.total_blocks, Some(last.blocks_verified));
+    assert!(last.bytes_fetched > 0);
+
+    peer1.shutdown().await;
+    peer2.shutdown().await;
+}
@@ -3,8 +3,10 @@
 pub mod rate_limiting;
 pub mod service;
 
+use std::collections::HashSet;
 use std::net::IpAddr;
 use std::sync::Arc;
+use std::time::Duration;
 
 pub use commands::{CommandCenter, FireWallRequest, FirewallCommand};
 use lightning_interfaces::ShutdownWaiter;
@@ -23,8 +25,8 @@ pub enum AdminError {
 
 #[derive(Debug, thiserror::Error)]
 pub enum FirewallError {
-    #[error("Rate limit exceeded, current count: {}", .0)]
-    RateLimitExceeded(f64),
+    #[error("Rate limit exceeded, retry after {:?}", .retry_after)]
+    RateLimitExceeded { retry_after: Duration },
     #[error("IP is blacklisted")]
     Blacklisted,
     #[error("IP is not whitelisted")]
@@ -33,16 +35,24 @@ pub enum FirewallError {
 
 impl From<FirewallError> for hyper::Response<hyper::Body> {
     fn from(e: FirewallError) -> Self {
-        let status = match e {
-            FirewallError::RateLimitExceeded(_) => hyper::StatusCode::TOO_MANY_REQUESTS,
-            FirewallError::Blacklisted => hyper::StatusCode::FORBIDDEN,
-            FirewallError::NotWhitelisted => hyper::StatusCode::FORBIDDEN,
+        let builder = match e {
+            FirewallError::RateLimitExceeded { retry_after } => {
+                // Retry-After is in whole seconds, round up so the client doesn't retry too early.
+                let secs = retry_after.as_secs() + u64::from(retry_after.subsec_nanos() > 0);
+
+                hyper::Response::builder()
+                    .status(hyper::StatusCode::TOO_MANY_REQUESTS)
+                    .header(hyper::header::RETRY_AFTER, secs.max(1))
+            },
+            FirewallError::Blacklisted => {
+                hyper::Response::builder().status(hyper::StatusCode::FORBIDDEN)
+            },
+            FirewallError::NotWhitelisted => {
+                hyper::Response::builder().status(hyper::StatusCode::FORBIDDEN)
+            },
         };
 
-        hyper::Response::builder()
-            .status(status)
-            .body(hyper::Body::empty())
-            .unwrap()
+        builder.body(hyper::Body::empty()).unwrap()
     }
 }
 
@@ -69,9 +79,10 @@ pub fn new(
         name: String,
         policy: ConnectionPolicy,
         rate_limiting: RateLimiting,
+        allowlist: HashSet<IpAddr>,
         shutdown: ShutdownWaiter,
     ) -> Self {
-        let inner = Inner::new(&name, policy, rate_limiting);
+        let inner = Inner::new(&name, policy, rate_limiting, allowlist);
 
         let (command_tx, command_rx) = mpsc::channel(100);
         commands::CommandCenter::global().register(name, command_tx);
@@ -95,6 +106,7 @@ pub fn from_config(config: FirewallConfig, shutdown: ShutdownWaiter) -> Self {
             name,
             connection_policy,
             rate_limiting,
+            allowlist,
         } = config;
 
         let policy = match connection_policy {
@@ -109,7 +121,13 @@ pub fn from_config(config: FirewallConfig, shutdown: ShutdownWaiter) -> Self {
             RateLimitingConfig::Global { rules } => RateLimiting::global(rules),
         };
 
-        Self::new(name, policy, rate, shutdown)
+        Self::new(
+            name,
+            policy,
+            rate,
+            allowlist.into_iter().collect(),
+            shutdown,
+        )
     }
 
     pub async fn check(&self, ip: IpAddr) -> Result<(), FirewallError> {
@@ -176,11 +194,18 @@ async fn update_loop(self, mut rx: mpsc::Receiver<commands::FireWallRequest>) {
 pub(crate) struct Inner {
     policy: ConnectionPolicy,
     rate_limiting: RateLimiting,
+    /// Trusted peers that bypass the rate limiting.
+    allowlist: HashSet<IpAddr>,
 }
 
 /// Admin functionality for the firewall
 impl Inner {
-    pub fn new(name: &str, policy: ConnectionPolicy, rate_limiting: RateLimiting) -> Self {
+    pub fn new(
+        name: &str,
+        policy: ConnectionPolicy,
+        rate_limiting: RateLimiting,
+        allowlist: HashSet<IpAddr>,
+    ) -> Self {
         if rate_limiting.policy_type() == RateLimitingMode::Per
             && policy.mode() != ConnectionPolicyMode::Whitelist
         {
@@ -194,6 +219,7 @@ pub fn new(name: &str, policy: ConnectionPolicy, rate_limiting: RateLimiting) ->
         Self {
             policy,
             rate_limiting,
+            allowlist,
         }
     }
 }
@@ -204,8 +230,58 @@ pub fn check(&mut self, ip: IpAddr) -> Result<(), FirewallError> {
         // someone shouldnt need ratelimiting if theyre not even
         // allowed to connect
         self.policy.check(ip)?;
-        self.rate_limiting.check(ip)?;
+
+        if !self.allowlist.contains(&ip) {
+            self.rate_limiting.check(ip)?;
+        }
 
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::net::{IpAddr, Ipv4Addr};
+
+    use lightning_interfaces::ShutdownController;
+    use lightning_types::{FirewallConfig, Period, RateLimitingConfig, RateLimitingRule};
+
+    use crate::{Firewall, FirewallError};
+
+    #[tokio::test]
+    async fn test_rate_limit_with_allowlist() {
+        let shutdown = ShutdownController::default();
+
+        let limited = IpAddr::V4(Ipv4Addr::new(10, 0, 0, 1));
+        let trusted = IpAddr::V4(Ipv4Addr::new(10, 0, 0, 2));
+
+        let firewall = Firewall::from_config(
+            FirewallConfig {
+                rate_limiting: RateLimitingConfig::Global {
+                    rules: vec![RateLimitingRule {
+                        period: Period::Second,
+                        max_requests: 1,
+                        burst: Some(2),
+                    }],
+                },
+                allowlist: vec![trusted],
+                ..FirewallConfig::none("test-rate-limit-with-allowlist".to_string())
+            },
+            shutdown.waiter(),
+        );
+
+        for _ in 0..2 {
+            assert!(firewall.check(limited).await.is_ok());
+        }
+        let err = firewall.check(limited).await.unwrap_err();
+        assert!(matches!(err, FirewallError::RateLimitExceeded { .. }));
+
+        let response: hyper::Response<hyper::Body> = err.into();
+        assert_eq!(response.status(), hyper::StatusCode::TOO_MANY_REQUESTS);
+        assert_eq!(response.headers()[hyper::header::RETRY_AFTER], "1");
+
+        for _ in 0..10 {
+            assert!(firewall.check(trusted).await.is_ok());
+        }
+    }
+}
@@ -235,8 +235,8 @@ pub fn check(&mut self, ip: IpAddr) -> Result<(), FirewallError> {
 /// see [`RateLimitingPolicy::new`] for more information
 #[derive(Debug, Clone)]
 pub struct RateLimitingPolicy {
-    period: Period,
-    max_requests: f64,
+    /// The size of the bucket
+    capacity: f64,
     last_request: std::time::Instant,
 
     rate: f64,
@@ -244,12 +244,17 @@ pub struct RateLimitingPolicy {
 }
 
 impl RateLimitingPolicy {
+    /// A bucket allowing `max_requests` per `period`, all of which can be made at once.
     pub fn new(period: Period, max_requests: u64) -> Self {
+        Self::with_burst(period, max_requests, max_requests)
+    }
+
+    /// A bucket refilling at `max_requests` per `period` that holds at most `burst` requests.
+    pub fn with_burst(period: Period, max_requests: u64, burst: u64) -> Self {
         Self {
-            period,
-            max_requests: max_requests as f64,
+            capacity: burst as f64,
             last_request: std::time::Instant::now(),
-            allowed: max_requests as f64,
+            allowed: burst as f64,
             rate: max_requests as f64 / period.as_millis() as f64,
         }
     }
@@ -257,31 +262,25 @@ pub fn new(period: Period, max_requests: u64) -> Self {
     /// Check (and increment the counter) if a request is allowed
     pub fn check(&mut self) -> Result<(), FirewallError> {
         let now = std::time::Instant::now();
-        let elapsed = now.duration_since(self.last_request).as_millis();
-
-        if elapsed > self.period.as_millis() as u128 {
-            self.allowed = self.max_requests - 1.0;
-            self.last_request = now;
-
-            return Ok(());
-        }
-
-        // cast is safe since we checked above
-        let elapsed = elapsed as f64;
+        let elapsed = now.duration_since(self.last_request).as_millis() as f64;
+        self.last_request = now;
 
         // top up the bucket
-        let new_allowed = self.max_requests.min(self.allowed + elapsed * self.rate);
+        let new_allowed = self.capacity.min(self.allowed + elapsed * self.rate);
         if new_allowed >= 1.0 {
             // include the new request
             self.allowed = new_allowed - 1.0;
-            self.last_request = now;
 
             Ok(())
         } else {
             self.allowed = new_allowed;
-            self.last_request = now;
 
-            Err(FirewallError::RateLimitExceeded(self.max_requests))
+            // the time it takes for the bucket to have room for one more request
+            let retry_after = (1.0 - new_allowed) / self.rate;
+
+            Err(FirewallError::RateLimitExceeded {
+                retry_after: std::time::Duration::from_millis(retry_after.ceil() as u64),
+            })
         }
     }
 }
@@ -291,9 +290,10 @@ fn from(
         RateLimitingRule {
             period,
             max_requests,
+            burst,
         }: RateLimitingRule,
     ) -> Self {
-        Self::new(period, max_requests)
+        Self::with_burst(period, max_requests, burst.unwrap_or(max_requests))
     }
 }
 
@@ -376,4 +376,18 @@ fn test_decays_as_expected() {
 
         assert!(policy.check().is_err());
     }
+
+    #[test]
+    fn test_burst_caps_the_bucket() {
+        let mut policy = super::RateLimitingPolicy::with_burst(super::Period::Second, 10, 2);
+
+        for _ in 0..2 {
+            assert!(policy.check().is_ok());
+        }
+
+        let Err(crate::FirewallError::RateLimitExceeded { retry_after }) = policy.check() else {
+            panic!("expected the request to be rate limited");
+        };
+        assert!(retry_after <= std::time::Duration::from_millis(100));
+    }
 }
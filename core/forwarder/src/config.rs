@@ -1,4 +1,32 @@
+use std::time::Duration;
+
 use serde::{Deserialize, Serialize};
 
-#[derive(Default, Clone, Debug, Deserialize, Serialize)]
-pub struct ForwarderConfig {}
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct ForwarderConfig {
+    /// For how long a forwarded transaction is remembered, submitting the same transaction again
+    /// within this window does not forward it a second time.
+    #[serde(with = "humantime_serde", default = "default_dedup_ttl")]
+    pub dedup_ttl: Duration,
+    /// How many transactions can wait to be forwarded, new transactions are rejected once the
+    /// queue is full.
+    #[serde(default = "default_max_queue_size")]
+    pub max_queue_size: usize,
+}
+
+impl Default for ForwarderConfig {
+    fn default() -> Self {
+        Self {
+            dedup_ttl: default_dedup_ttl(),
+            max_queue_size: default_max_queue_size(),
+        }
+    }
+}
+
+fn default_dedup_ttl() -> Duration {
+    Duration::from_secs(30)
+}
+
+fn default_max_queue_size() -> usize {
+    1024
+}
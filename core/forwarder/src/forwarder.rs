@@ -5,6 +5,7 @@
 use lightning_interfaces::{spawn_worker, ShutdownWaiter};
 
 use crate::config::ForwarderConfig;
+use crate::queue::{queue, QueueWorker};
 use crate::worker::Worker;
 
 pub struct Forwarder<C> {
@@ -26,13 +27,29 @@ impl<C> ConfigConsumer for Forwarder<C> {
 impl<C: Collection> BuildGraph for Forwarder<C> {
     fn build_graph() -> fdi::DependencyGraph {
         fdi::DependencyGraph::new().with_infallible(
-            |keystore: &C::KeystoreInterface,
+            |config: &C::ConfigProviderInterface,
+             keystore: &C::KeystoreInterface,
              app: &C::ApplicationInterface,
              fdi::Cloned(waiter): fdi::Cloned<ShutdownWaiter>| {
                 let consensus_key = keystore.get_bls_pk();
                 let query_runner = app.sync_query();
-                let worker = Worker::new(consensus_key, query_runner);
-                let socket = spawn_worker!(worker, "FORWARDER", waiter, crucial);
+                let config = config.get::<Self>();
+                let worker = Worker::new(consensus_key, query_runner, config.dedup_ttl);
+                let (queue, receiver) = queue(config.max_queue_size);
+
+                let forward_waiter = waiter.clone();
+                let panic_waiter = waiter.clone();
+                spawn!(
+                    async move {
+                        forward_waiter
+                            .run_until_shutdown(worker.run(receiver))
+                            .await;
+                    },
+                    "FORWARDER",
+                    crucial(panic_waiter)
+                );
+                let socket =
+                    spawn_worker!(QueueWorker::new(queue), "FORWARDER#QUEUE", waiter, crucial);
 
                 Self {
                     socket,
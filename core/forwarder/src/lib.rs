@@ -1,6 +1,7 @@
 mod forwarder;
 
 pub mod config;
+mod queue;
 #[cfg(test)]
 mod tests;
 mod worker;
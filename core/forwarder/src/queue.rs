@@ -0,0 +1,95 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+use affair::AsyncWorker;
+use lightning_interfaces::types::TransactionRequest;
+use lightning_interfaces::ForwarderError;
+use lightning_metrics::set_gauge;
+use tokio::sync::mpsc;
+
+/// Creates a queue for the transactions waiting to be forwarded that holds at most `capacity`
+/// transactions.
+pub(crate) fn queue(capacity: usize) -> (ForwarderQueue, ForwarderQueueReceiver) {
+    let (tx, rx) = mpsc::channel(capacity);
+    let depth = Arc::new(AtomicUsize::new(0));
+    (
+        ForwarderQueue {
+            tx,
+            depth: depth.clone(),
+        },
+        ForwarderQueueReceiver { rx, depth },
+    )
+}
+
+#[derive(Clone)]
+pub(crate) struct ForwarderQueue {
+    tx: mpsc::Sender<TransactionRequest>,
+    depth: Arc<AtomicUsize>,
+}
+
+pub(crate) struct ForwarderQueueReceiver {
+    rx: mpsc::Receiver<TransactionRequest>,
+    depth: Arc<AtomicUsize>,
+}
+
+impl ForwarderQueue {
+    /// Queues the transaction, or returns an error right away when the queue is full.
+    pub(crate) fn try_push(&self, req: TransactionRequest) -> Result<(), ForwarderError> {
+        // Count the transaction before sending it, so that the receiver never sees the depth go
+        // below zero.
+        self.depth.fetch_add(1, Ordering::Relaxed);
+        if let Err(e) = self.tx.try_send(req) {
+            self.depth.fetch_sub(1, Ordering::Relaxed);
+            return Err(match e {
+                mpsc::error::TrySendError::Full(_) => ForwarderError::Backpressure,
+                mpsc::error::TrySendError::Closed(_) => ForwarderError::Closed,
+            });
+        }
+        report_depth(self.depth());
+        Ok(())
+    }
+
+    /// Returns the number of transactions waiting to be forwarded.
+    pub(crate) fn depth(&self) -> usize {
+        self.depth.load(Ordering::Relaxed)
+    }
+}
+
+impl ForwarderQueueReceiver {
+    pub(crate) async fn recv(&mut self) -> Option<TransactionRequest> {
+        let req = self.rx.recv().await?;
+        let depth = self.depth.fetch_sub(1, Ordering::Relaxed) - 1;
+        report_depth(depth);
+        Some(req)
+    }
+}
+
+fn report_depth(depth: usize) {
+    set_gauge!(
+        "forwarder_queue_depth",
+        Some("Number of transactions waiting to be forwarded to the mempool"),
+        depth as i64
+    );
+}
+
+/// The worker behind the mempool socket. It only puts the transactions into the queue, so that a
+/// slow worker on the other end does not block the callers. A transaction that does not fit is
+/// rejected back to the caller.
+pub(crate) struct QueueWorker {
+    queue: ForwarderQueue,
+}
+
+impl QueueWorker {
+    pub(crate) fn new(queue: ForwarderQueue) -> Self {
+        Self { queue }
+    }
+}
+
+impl AsyncWorker for QueueWorker {
+    type Request = TransactionRequest;
+    type Response = Result<(), ForwarderError>;
+
+    async fn handle(&mut self, req: Self::Request) -> Self::Response {
+        self.queue.try_push(req)
+    }
+}
@@ -1 +1,210 @@
-//! TODO
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use affair::AsyncWorker;
+use anyhow::{bail, Result};
+use lightning_interfaces::types::TransactionRequest;
+use lightning_interfaces::ForwarderError;
+use lightning_test_utils::transaction::get_update_transactions;
+use narwhal_types::TransactionProto;
+
+use crate::queue::{queue, QueueWorker};
+use crate::worker::{submit_round_robin, submit_unseen, MempoolClient, SeenTransactions};
+
+struct TestClient {
+    index: usize,
+    fail: bool,
+    received: Arc<Mutex<Vec<usize>>>,
+}
+
+impl MempoolClient for TestClient {
+    async fn submit(&mut self, _request: TransactionProto) -> Result<()> {
+        if self.fail {
+            bail!("worker {} is down", self.index);
+        }
+        self.received.lock().unwrap().push(self.index);
+        Ok(())
+    }
+}
+
+fn connections(failing: &[usize], received: &Arc<Mutex<Vec<usize>>>) -> HashMap<usize, TestClient> {
+    (0..3)
+        .map(|index| {
+            let client = TestClient {
+                index,
+                fail: failing.contains(&index),
+                received: received.clone(),
+            };
+            (index, client)
+        })
+        .collect()
+}
+
+#[tokio::test]
+async fn test_forward_falls_back_to_next_peer() {
+    let received = Arc::new(Mutex::new(Vec::new()));
+    let mut connections = connections(&[0], &received);
+    let mut next_peer = 0;
+    let request = TransactionProto {
+        transaction: vec![0; 32].into(),
+    };
+
+    // The primary target is down, so the transaction should end up at the next worker.
+    let peer = submit_round_robin(&mut connections, &mut next_peer, &request)
+        .await
+        .unwrap();
+    assert_eq!(peer, 1);
+    assert!(!connections.contains_key(&0));
+
+    // The following transactions are spread over the remaining workers.
+    for _ in 0..3 {
+        submit_round_robin(&mut connections, &mut next_peer, &request)
+            .await
+            .unwrap();
+    }
+    assert_eq!(*received.lock().unwrap(), vec![1, 2, 1, 2]);
+}
+
+#[tokio::test]
+async fn test_forward_fails_when_every_peer_fails() {
+    let received = Arc::new(Mutex::new(Vec::new()));
+    let mut connections = connections(&[0, 1, 2], &received);
+    let mut next_peer = 1;
+    let request = TransactionProto {
+        transaction: vec![0; 32].into(),
+    };
+
+    assert!(
+        submit_round_robin(&mut connections, &mut next_peer, &request)
+            .await
+            .is_err()
+    );
+    assert!(connections.is_empty());
+    assert!(received.lock().unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn test_forward_skips_duplicate_transactions() {
+    let received = Arc::new(Mutex::new(Vec::new()));
+    let mut connections = connections(&[], &received);
+    let mut next_peer = 0;
+    let mut seen = SeenTransactions::new(Duration::from_secs(30));
+    let request = TransactionProto {
+        transaction: vec![0; 32].into(),
+    };
+
+    let forwarded = submit_unseen(
+        &mut seen,
+        &mut connections,
+        &mut next_peer,
+        [1; 32],
+        &request,
+    )
+    .await
+    .unwrap();
+    assert!(forwarded);
+    let forwarded = submit_unseen(
+        &mut seen,
+        &mut connections,
+        &mut next_peer,
+        [1; 32],
+        &request,
+    )
+    .await
+    .unwrap();
+    assert!(!forwarded);
+    assert_eq!(received.lock().unwrap().len(), 1);
+
+    // A different transaction is still forwarded.
+    let forwarded = submit_unseen(
+        &mut seen,
+        &mut connections,
+        &mut next_peer,
+        [2; 32],
+        &request,
+    )
+    .await
+    .unwrap();
+    assert!(forwarded);
+    assert_eq!(received.lock().unwrap().len(), 2);
+}
+
+#[tokio::test]
+async fn test_forward_again_after_dedup_ttl() {
+    let received = Arc::new(Mutex::new(Vec::new()));
+    let mut connections = connections(&[], &received);
+    let mut next_peer = 0;
+    let mut seen = SeenTransactions::new(Duration::from_millis(10));
+    let request = TransactionProto {
+        transaction: vec![0; 32].into(),
+    };
+
+    for _ in 0..2 {
+        let forwarded = submit_unseen(
+            &mut seen,
+            &mut connections,
+            &mut next_peer,
+            [1; 32],
+            &request,
+        )
+        .await
+        .unwrap();
+        assert!(forwarded);
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+    assert_eq!(received.lock().unwrap().len(), 2);
+}
+
+#[test]
+fn test_seen_transactions_forget_the_oldest_when_full() {
+    let mut seen = SeenTransactions::with_max_len(Duration::from_secs(30), 2);
+    seen.insert([1; 32]);
+    seen.insert([2; 32]);
+    seen.insert([3; 32]);
+
+    assert!(!seen.contains(&[1; 32]));
+    assert!(seen.contains(&[2; 32]));
+    assert!(seen.contains(&[3; 32]));
+}
+
+#[tokio::test]
+async fn test_queue_rejects_transactions_when_full() {
+    // Nothing takes transactions out of the queue until we say so, as if the workers we forward
+    // to were stalled.
+    let (queue, mut receiver) = queue(2);
+    let mut transactions = get_update_transactions(4)
+        .into_iter()
+        .map(TransactionRequest::UpdateRequest);
+
+    queue.try_push(transactions.next().unwrap()).unwrap();
+    queue.try_push(transactions.next().unwrap()).unwrap();
+    assert_eq!(queue.depth(), 2);
+
+    assert_eq!(
+        queue.try_push(transactions.next().unwrap()),
+        Err(ForwarderError::Backpressure)
+    );
+    assert_eq!(queue.depth(), 2);
+
+    // Once a transaction is taken out there is room for a new one.
+    receiver.recv().await.unwrap();
+    assert_eq!(queue.depth(), 1);
+    queue.try_push(transactions.next().unwrap()).unwrap();
+    assert_eq!(queue.depth(), 2);
+}
+
+#[tokio::test]
+async fn test_queue_worker_returns_backpressure_to_the_caller() {
+    let (queue, _receiver) = queue(1);
+    let mut worker = QueueWorker::new(queue);
+    let mut transactions = get_update_transactions(2)
+        .into_iter()
+        .map(TransactionRequest::UpdateRequest);
+
+    assert_eq!(worker.handle(transactions.next().unwrap()).await, Ok(()));
+    assert_eq!(
+        worker.handle(transactions.next().unwrap()).await,
+        Err(ForwarderError::Backpressure)
+    );
+}
@@ -5,22 +5,24 @@
 */
 use std::cmp;
 use std::collections::hash_map::Entry;
-use std::collections::HashMap;
+use std::collections::{HashMap, VecDeque};
 
-use affair::AsyncWorker;
 use anyhow::{bail, Result};
 use fleek_crypto::ConsensusPublicKey;
-use lightning_interfaces::types::{Epoch, EpochInfo, NodeInfo, TransactionRequest};
+use lightning_interfaces::types::{Epoch, EpochInfo, NodeInfo, TransactionRequest, TxHash};
 use lightning_interfaces::SyncQueryRunnerInterface;
 use lightning_utils::application::QueryRunnerExt;
 use narwhal_types::{TransactionProto, TransactionsClient};
 use rand::seq::SliceRandom;
-use tokio::time::{timeout, Duration};
+use tokio::time::{timeout, Duration, Instant};
 use tonic::transport::channel::Channel;
-use tracing::error;
+use tracing::{debug, error, warn};
+
+use crate::queue::ForwarderQueueReceiver;
 
 const TARGETED_CONNECTION_NUM: usize = 10;
 const TIMEOUT_DURATION: Duration = Duration::new(4, 0);
+const MAX_SEEN_TRANSACTIONS: usize = 100_000;
 
 pub struct Worker<Q: SyncQueryRunnerInterface> {
     /// Query runner used to read application state
@@ -40,10 +42,133 @@ pub struct Worker<Q: SyncQueryRunnerInterface> {
     min_connections: usize,
     /// Open connections to committee workers
     active_connections: HashMap<usize, TransactionsClient<Channel>>,
+    /// The committee index of the worker we will try to forward the next transaction to first
+    next_peer: usize,
+    /// The transactions we forwarded recently
+    seen: SeenTransactions,
+}
+
+/// Remembers the hashes of the transactions we forwarded for a while, so that a transaction that
+/// is submitted more than once is only forwarded the first time.
+pub(crate) struct SeenTransactions {
+    ttl: Duration,
+    max_len: usize,
+    expires_at: HashMap<TxHash, Instant>,
+    /// The transactions in the order we saw them, which is also the order they expire in.
+    order: VecDeque<(TxHash, Instant)>,
+}
+
+impl SeenTransactions {
+    pub(crate) fn new(ttl: Duration) -> Self {
+        Self::with_max_len(ttl, MAX_SEEN_TRANSACTIONS)
+    }
+
+    /// Remembers at most `max_len` transactions, forgetting the oldest ones first.
+    pub(crate) fn with_max_len(ttl: Duration, max_len: usize) -> Self {
+        Self {
+            ttl,
+            max_len,
+            expires_at: HashMap::new(),
+            order: VecDeque::new(),
+        }
+    }
+
+    /// Returns true if the transaction was seen within the ttl.
+    pub(crate) fn contains(&self, hash: &TxHash) -> bool {
+        self.expires_at
+            .get(hash)
+            .is_some_and(|expires_at| *expires_at > Instant::now())
+    }
+
+    pub(crate) fn insert(&mut self, hash: TxHash) {
+        let now = Instant::now();
+        // Forget the expired transactions, and the oldest ones while there are too many.
+        while let Some((_, expires_at)) = self.order.front() {
+            if *expires_at > now && self.expires_at.len() < self.max_len {
+                break;
+            }
+            let (old_hash, expires_at) = self.order.pop_front().unwrap();
+            // The transaction might have been seen again since.
+            if self.expires_at.get(&old_hash) == Some(&expires_at) {
+                self.expires_at.remove(&old_hash);
+            }
+        }
+
+        let expires_at = now + self.ttl;
+        self.expires_at.insert(hash, expires_at);
+        self.order.push_back((hash, expires_at));
+    }
+}
+
+/// The part of a mempool client that the worker uses to forward transactions.
+pub(crate) trait MempoolClient {
+    async fn submit(&mut self, request: TransactionProto) -> Result<()>;
+}
+
+impl MempoolClient for TransactionsClient<Channel> {
+    async fn submit(&mut self, request: TransactionProto) -> Result<()> {
+        timeout(TIMEOUT_DURATION, self.submit_transaction(request)).await??;
+        Ok(())
+    }
+}
+
+/// Submits the request to one of the connections. The connections are tried in round robin
+/// order, starting at `next_peer`, until one of them accepts the request. Connections that fail
+/// are assumed to be bad and are dropped.
+///
+/// Returns the committee index of the worker that accepted the request.
+pub(crate) async fn submit_round_robin<M: MempoolClient>(
+    connections: &mut HashMap<usize, M>,
+    next_peer: &mut usize,
+    request: &TransactionProto,
+) -> Result<usize> {
+    let mut peers = connections.keys().copied().collect::<Vec<_>>();
+    peers.sort_unstable();
+
+    // Start at the first peer at or after the cursor, wrapping around.
+    let start = peers.partition_point(|peer| peer < next_peer);
+    let len = peers.len();
+    for peer in peers.into_iter().cycle().skip(start).take(len) {
+        let client = connections.get_mut(&peer).expect("peer has a connection");
+        match client.submit(request.clone()).await {
+            Ok(()) => {
+                *next_peer = peer + 1;
+                return Ok(peer);
+            },
+            Err(e) => {
+                warn!("Failed to forward transaction to worker {peer}: {e}");
+                connections.remove(&peer);
+            },
+        }
+    }
+
+    bail!("Failed sending transaction to any worker")
+}
+
+/// Submits the request to one of the connections, unless the transaction was already forwarded
+/// recently. Returns whether the request was submitted.
+pub(crate) async fn submit_unseen<M: MempoolClient>(
+    seen: &mut SeenTransactions,
+    connections: &mut HashMap<usize, M>,
+    next_peer: &mut usize,
+    hash: TxHash,
+    request: &TransactionProto,
+) -> Result<bool> {
+    if seen.contains(&hash) {
+        debug!("Not forwarding a transaction that was forwarded recently");
+        return Ok(false);
+    }
+
+    submit_round_robin(connections, next_peer, request).await?;
+    // Only remember the transaction once it was forwarded, so that it can be submitted again if
+    // it did not make it to any worker.
+    seen.insert(hash);
+
+    Ok(true)
 }
 
 impl<Q: SyncQueryRunnerInterface> Worker<Q> {
-    pub fn new(primary_name: ConsensusPublicKey, query_runner: Q) -> Self {
+    pub fn new(primary_name: ConsensusPublicKey, query_runner: Q, dedup_ttl: Duration) -> Self {
         Self {
             query_runner,
             primary_name,
@@ -53,6 +178,8 @@ pub fn new(primary_name: ConsensusPublicKey, query_runner: Q) -> Self {
             max_connections: 0,
             min_connections: 0,
             active_connections: HashMap::with_capacity(TARGETED_CONNECTION_NUM),
+            next_peer: 0,
+            seen: SeenTransactions::new(dedup_ttl),
         }
     }
 
@@ -78,33 +205,16 @@ async fn handle_forward(&mut self, req: &TransactionRequest) -> Result<()> {
             transaction: txn_bytes.into(),
         };
 
-        // Here we take ownership of self.active_connections by swapping it with an empty vec, so we
-        // can try and send the transaction to each worker we have a connection with. If the
-        // send was successful we keep the connection, if the send was not successful we assume it a
-        // bad connection and drop the client
-        let cap = self.active_connections.capacity();
-        let active_connections =
-            std::mem::replace(&mut self.active_connections, HashMap::with_capacity(cap));
-
-        self.active_connections.extend(
-            futures::future::join_all(active_connections.into_iter().map(|mut e| async {
-                timeout(TIMEOUT_DURATION, e.1.submit_transaction(request.clone()))
-                    .await
-                    .ok()
-                    .and_then(|res| res.ok())
-                    .map(|_| e)
-            }))
-            .await
-            .into_iter()
-            // Note that `Option<T>: Iter<T>`. So here we can use flatten. If the option is Some,
-            // then the inner iterator will yield one item of type `T` and if the option is None
-            // then it will be an empty iterator.
-            .flatten(),
-        );
-
-        if self.active_connections.is_empty() {
-            bail!("Failed sending transaction to any worker")
-        }
+        // Spread the transactions over the workers we are connected to, falling back to the next
+        // one if a worker does not accept the transaction.
+        submit_unseen(
+            &mut self.seen,
+            &mut self.active_connections,
+            &mut self.next_peer,
+            req.hash(),
+            &request,
+        )
+        .await?;
 
         Ok(())
     }
@@ -124,6 +234,7 @@ fn refresh_epoch(&mut self) {
         {
             // Reset cursor
             self.cursor = 0;
+            self.next_peer = 0;
             self.committee = vec![item.clone()];
         } else {
             // shuffle the order with thread_range so nodes accross the network are not all
@@ -131,6 +242,7 @@ fn refresh_epoch(&mut self) {
             committee.shuffle(&mut rand::thread_rng());
             self.committee = committee;
             self.cursor = 0;
+            self.next_peer = 0;
         }
 
         // Set the epoch info to the newest epoch
@@ -173,11 +285,15 @@ async fn make_connections(&mut self) -> Result<()> {
     }
 }
 
-impl<Q: SyncQueryRunnerInterface + 'static> AsyncWorker for Worker<Q> {
-    type Request = TransactionRequest;
-    type Response = ();
+impl<Q: SyncQueryRunnerInterface + 'static> Worker<Q> {
+    /// Forwards the queued transactions one after the other, until the queue is closed.
+    pub async fn run(mut self, mut queue: ForwarderQueueReceiver) {
+        while let Some(req) = queue.recv().await {
+            self.handle(req).await;
+        }
+    }
 
-    async fn handle(&mut self, req: Self::Request) -> Self::Response {
+    async fn handle(&mut self, req: TransactionRequest) {
         // if it fails we should retry once to cover all edge cases
         let mut retried = 0;
         while retried < 2 {
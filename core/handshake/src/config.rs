@@ -19,6 +19,8 @@ pub struct HandshakeConfig {
     /// Timeout for disconnected sessions
     #[serde(with = "humantime_serde")]
     pub timeout: Duration,
+    /// Cross-origin resource sharing settings of the http servers
+    pub cors: CorsConfig,
 }
 
 impl Default for HandshakeConfig {
@@ -33,6 +35,7 @@ fn default() -> Self {
             http_address: ([0, 0, 0, 0], 4220).into(),
             https: None,
             timeout: Duration::from_secs(1),
+            cors: Default::default(),
         }
     }
 }
@@ -52,4 +55,39 @@ pub struct HttpsConfig {
     pub cert: PathBuf,
     pub key: PathBuf,
     pub address: SocketAddr,
+    /// Offer HTTP/2 to clients through ALPN. Clients that do not support it fall back to
+    /// HTTP/1.1.
+    #[serde(default = "default_http2")]
+    pub http2: bool,
+}
+
+fn default_http2() -> bool {
+    true
+}
+
+/// Which cross-origin requests browsers are allowed to make to the http servers. A list that
+/// contains `*` allows anything.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(default)]
+pub struct CorsConfig {
+    /// Origins that are allowed to make requests.
+    pub allowed_origins: Vec<String>,
+    /// Methods that are allowed in requests.
+    pub allowed_methods: Vec<String>,
+    /// Headers that are allowed in requests.
+    pub allowed_headers: Vec<String>,
+    /// Response headers that are exposed to the scripts making the requests.
+    pub exposed_headers: Vec<String>,
+}
+
+impl Default for CorsConfig {
+    fn default() -> Self {
+        let any = vec!["*".to_string()];
+        Self {
+            allowed_origins: any.clone(),
+            allowed_methods: any.clone(),
+            allowed_headers: any.clone(),
+            exposed_headers: any,
+        }
+    }
 }
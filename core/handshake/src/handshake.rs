@@ -11,6 +11,7 @@
 use futures::StreamExt;
 use lightning_interfaces::prelude::*;
 use lightning_interfaces::schema::handshake::{HandshakeRequestFrame, TerminationReason};
+use lightning_interfaces::{ShutdownPhase, ShutdownPhaseWaiters};
 use rand::RngCore;
 use tracing::warn;
 use triomphe::Arc;
@@ -45,8 +46,10 @@ pub fn new(
         config: &C::ConfigProviderInterface,
         keystore: &C::KeystoreInterface,
         service_executor: &C::ServiceExecutorInterface,
-        fdi::Cloned(waiter): fdi::Cloned<ShutdownWaiter>,
+        fdi::Cloned(waiters): fdi::Cloned<ShutdownPhaseWaiters>,
     ) -> Self {
+        // Stop accepting connections before the rest of the node goes down.
+        let waiter = waiters.get(ShutdownPhase::Ingress);
         let config = config.get::<Self>();
         let provider = service_executor.get_provider();
         let pk = keystore.get_ed25519_pk();
@@ -62,8 +65,9 @@ pub fn new(
 
     async fn start(
         fdi::Consume(mut this): fdi::Consume<Self>,
-        fdi::Cloned(waiter): fdi::Cloned<ShutdownWaiter>,
+        fdi::Cloned(waiters): fdi::Cloned<ShutdownPhaseWaiters>,
     ) {
+        let waiter = waiters.get(ShutdownPhase::Ingress);
         let run = this.status.take().expect("restart not implemented.");
 
         // Spawn transports in parallel for accepting incoming handshakes.
@@ -88,13 +92,15 @@ async fn start(
             let router = router
                 .layer(Extension(run.ctx.clone()))
                 .route_layer(http::fleek_node_response_header(this.pk));
+            let cors = http::cors_layer(&this.config.cors).expect("invalid cors config");
 
             // Start optional HTTPS server.
             if let Some(https) = this.config.https.clone() {
                 let https_router = router.clone();
+                let cors = cors.clone();
                 let handle = run.handle.clone();
                 spawn!(
-                    async move { spawn_https_server(https_router, https, handle).await },
+                    async move { spawn_https_server(https_router, https, cors, handle).await },
                     "HANDSHAKE: start optional http server"
                 );
             }
@@ -103,7 +109,7 @@ async fn start(
             let waiter2 = waiter.clone();
             let http_addr = this.config.http_address;
             spawn!(
-                async move { spawn_http_server(http_addr, router, waiter2).await },
+                async move { spawn_http_server(http_addr, router, cors, waiter2).await },
                 "HANDSHAKE: start http server"
             );
 
@@ -241,7 +247,7 @@ pub async fn handle_new_connection<S: TransportSender, R: TransportReceiver>(
                 let connection_id = u64::from_be_bytes(*arrayref::array_ref![access_token, 0, 8]);
 
                 let Some(connection) = self.connections.get(&connection_id) else {
-                    sender.terminate(TerminationReason::InvalidToken).await;
+                    sender.terminate(TerminationReason::UnknownConnection).await;
                     return;
                 };
 
@@ -256,7 +262,9 @@ pub async fn handle_new_connection<S: TransportSender, R: TransportReceiver>(
                         .expect("Failed to get current time")
                         .as_millis()
                 {
-                    sender.terminate(TerminationReason::InvalidToken).await;
+                    sender
+                        .terminate(TerminationReason::AccessTokenExpired)
+                        .await;
                     return;
                 }
 
@@ -270,7 +278,7 @@ pub async fn handle_new_connection<S: TransportSender, R: TransportReceiver>(
                 retry: Some(id), ..
             } => {
                 let Some(connection) = self.connections.get(&id) else {
-                    sender.terminate(TerminationReason::InvalidToken).await;
+                    sender.terminate(TerminationReason::UnknownConnection).await;
                     return;
                 };
 
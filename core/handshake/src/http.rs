@@ -1,27 +1,30 @@
 use std::net::SocketAddr;
+use std::str::FromStr;
+use std::sync::Arc;
 
 use anyhow::Context;
 use axum::body::Body;
-use axum::http::{HeaderName, HeaderValue, Response};
+use axum::http::{HeaderName, HeaderValue, Method, Response};
 use axum::Router;
 use axum_server::tls_rustls::RustlsConfig;
 use axum_server::Handle;
 use fleek_crypto::NodePublicKey;
 use lightning_interfaces::ShutdownWaiter;
-use tower_http::cors::CorsLayer;
+use tower_http::cors::{AllowHeaders, AllowMethods, AllowOrigin, CorsLayer, ExposeHeaders};
 use tower_http::set_header::SetResponseHeaderLayer;
 
-use crate::config::HttpsConfig;
+use crate::config::{CorsConfig, HttpsConfig};
 
 pub const FLEEK_NODE_HEADER: &str = "x-fleek-node";
 
 pub async fn spawn_http_server(
     addr: SocketAddr,
     router: Router,
+    cors: CorsLayer,
     waiter: ShutdownWaiter,
 ) -> anyhow::Result<()> {
     let app = router
-        .layer(CorsLayer::permissive())
+        .layer(cors)
         .into_make_service_with_connect_info::<SocketAddr>();
 
     let listener = tokio::net::TcpListener::bind(&addr).await?;
@@ -40,15 +43,17 @@ pub async fn spawn_http_server(
 pub async fn spawn_https_server(
     router: Router,
     https_config: HttpsConfig,
+    cors: CorsLayer,
     handle: Handle,
 ) -> anyhow::Result<()> {
     let app = router
-        .layer(CorsLayer::permissive())
+        .layer(cors)
         .into_make_service_with_connect_info::<SocketAddr>();
 
     let config =
         RustlsConfig::from_pem_file(https_config.cert.as_path(), https_config.key.as_path())
             .await?;
+    let config = with_alpn(config, https_config.http2);
     axum_server::bind_rustls(https_config.address, config)
         .handle(handle)
         .serve(app)
@@ -56,6 +61,71 @@ pub async fn spawn_https_server(
         .context("failed to run http server")
 }
 
+/// Builds the layer that answers the CORS preflight requests and sets the CORS headers of the
+/// responses, as configured.
+pub fn cors_layer(config: &CorsConfig) -> anyhow::Result<CorsLayer> {
+    let origins = if allows_any(&config.allowed_origins) {
+        AllowOrigin::any()
+    } else {
+        AllowOrigin::list(parse_list::<HeaderValue>(
+            &config.allowed_origins,
+            "origin",
+        )?)
+    };
+    let methods = if allows_any(&config.allowed_methods) {
+        AllowMethods::any()
+    } else {
+        AllowMethods::list(parse_list::<Method>(&config.allowed_methods, "method")?)
+    };
+    let headers = if allows_any(&config.allowed_headers) {
+        AllowHeaders::any()
+    } else {
+        AllowHeaders::list(parse_list::<HeaderName>(&config.allowed_headers, "header")?)
+    };
+    let exposed = if allows_any(&config.exposed_headers) {
+        ExposeHeaders::any()
+    } else {
+        ExposeHeaders::list(parse_list::<HeaderName>(&config.exposed_headers, "header")?)
+    };
+
+    Ok(CorsLayer::new()
+        .allow_origin(origins)
+        .allow_methods(methods)
+        .allow_headers(headers)
+        .expose_headers(exposed))
+}
+
+fn allows_any(values: &[String]) -> bool {
+    values.iter().any(|value| value == "*")
+}
+
+fn parse_list<T>(values: &[String], kind: &str) -> anyhow::Result<Vec<T>>
+where
+    T: FromStr,
+    T::Err: std::error::Error + Send + Sync + 'static,
+{
+    values
+        .iter()
+        .map(|value| {
+            value
+                .parse()
+                .with_context(|| format!("invalid cors {kind}: {value}"))
+        })
+        .collect()
+}
+
+/// Sets the protocols that are offered to clients during the TLS handshake. HTTP/2 is preferred
+/// when it is enabled, and HTTP/1.1 is the fallback for the clients that do not support it.
+fn with_alpn(config: RustlsConfig, http2: bool) -> RustlsConfig {
+    let mut server_config = (*config.get_inner()).clone();
+    server_config.alpn_protocols = if http2 {
+        vec![b"h2".to_vec(), b"http/1.1".to_vec()]
+    } else {
+        vec![b"http/1.1".to_vec()]
+    };
+    RustlsConfig::from_config(Arc::new(server_config))
+}
+
 pub fn fleek_node_response_header(
     pk: NodePublicKey,
 ) -> SetResponseHeaderLayer<impl FnMut(&Response<Body>) -> Option<HeaderValue> + Clone> {
@@ -64,3 +134,196 @@ pub fn fleek_node_response_header(
         Some(HeaderValue::from_str(&pk).expect("Base58 alphabet contains only valid characters"))
     })
 }
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use axum::extract::ConnectInfo;
+    use axum::http::header::{
+        ACCESS_CONTROL_ALLOW_HEADERS,
+        ACCESS_CONTROL_ALLOW_METHODS,
+        ACCESS_CONTROL_ALLOW_ORIGIN,
+        ACCESS_CONTROL_EXPOSE_HEADERS,
+        ACCESS_CONTROL_REQUEST_HEADERS,
+        ACCESS_CONTROL_REQUEST_METHOD,
+        ORIGIN,
+    };
+    use axum::http::Request;
+    use axum::routing::get;
+    use reqwest::{Client, Version};
+    use tower::ServiceExt;
+
+    use super::*;
+
+    /// Serves a route that answers with the address of the client, over https with a self signed
+    /// certificate.
+    async fn start_https_server(http2: bool) -> anyhow::Result<(Handle, SocketAddr)> {
+        let cert = rcgen::generate_simple_self_signed(vec!["localhost".to_string()])?;
+        let dir = tempfile::tempdir()?;
+        let https_config = HttpsConfig {
+            cert: dir.path().join("cert.pem"),
+            key: dir.path().join("key.pem"),
+            address: ([127, 0, 0, 1], 0).into(),
+            http2,
+        };
+        std::fs::write(&https_config.cert, cert.serialize_pem()?)?;
+        std::fs::write(&https_config.key, cert.serialize_private_key_pem())?;
+
+        let router = Router::new().route(
+            "/peer",
+            get(|ConnectInfo(peer): ConnectInfo<SocketAddr>| async move { peer.to_string() }),
+        );
+        let handle = Handle::new();
+        let server_handle = handle.clone();
+        tokio::spawn(async move {
+            // The certificate is removed once the server is done with it.
+            let _dir = dir;
+            spawn_https_server(router, https_config, CorsLayer::permissive(), server_handle).await
+        });
+        let addr = tokio::time::timeout(Duration::from_secs(5), handle.listening())
+            .await?
+            .expect("the server should be listening");
+
+        Ok((handle, addr))
+    }
+
+    async fn get_peer(client: &Client, addr: SocketAddr) -> anyhow::Result<(Version, String)> {
+        let response = client
+            .get(format!("https://localhost:{}/peer", addr.port()))
+            .send()
+            .await?;
+        Ok((response.version(), response.text().await?))
+    }
+
+    fn cors_router(config: &CorsConfig) -> Router {
+        Router::new()
+            .route("/", get(|| async { "ok" }))
+            .layer(cors_layer(config).unwrap())
+    }
+
+    #[tokio::test]
+    async fn test_cors_preflight() -> anyhow::Result<()> {
+        let config = CorsConfig {
+            allowed_origins: vec!["https://app.fleek.xyz".to_string()],
+            allowed_methods: vec!["GET".to_string(), "POST".to_string()],
+            allowed_headers: vec!["x-custom".to_string()],
+            exposed_headers: vec![FLEEK_NODE_HEADER.to_string()],
+        };
+
+        let request = Request::builder()
+            .method(Method::OPTIONS)
+            .uri("/")
+            .header(ORIGIN, "https://app.fleek.xyz")
+            .header(ACCESS_CONTROL_REQUEST_METHOD, "POST")
+            .header(ACCESS_CONTROL_REQUEST_HEADERS, "x-custom")
+            .body(Body::empty())?;
+        let response = cors_router(&config).oneshot(request).await?;
+        let headers = response.headers();
+        assert_eq!(
+            headers[ACCESS_CONTROL_ALLOW_ORIGIN],
+            "https://app.fleek.xyz"
+        );
+        let methods = headers[ACCESS_CONTROL_ALLOW_METHODS].to_str()?;
+        assert!(methods.contains("GET") && methods.contains("POST"));
+        assert_eq!(headers[ACCESS_CONTROL_ALLOW_HEADERS], "x-custom");
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_cors_request() -> anyhow::Result<()> {
+        let config = CorsConfig {
+            allowed_origins: vec!["https://app.fleek.xyz".to_string()],
+            allowed_methods: vec!["GET".to_string()],
+            allowed_headers: vec![],
+            exposed_headers: vec![FLEEK_NODE_HEADER.to_string()],
+        };
+
+        let request = Request::get("/")
+            .header(ORIGIN, "https://app.fleek.xyz")
+            .body(Body::empty())?;
+        let response = cors_router(&config).oneshot(request).await?;
+        let headers = response.headers();
+        assert_eq!(
+            headers[ACCESS_CONTROL_ALLOW_ORIGIN],
+            "https://app.fleek.xyz"
+        );
+        assert_eq!(headers[ACCESS_CONTROL_EXPOSE_HEADERS], FLEEK_NODE_HEADER);
+
+        // Origins that are not configured do not get the headers, so browsers block the response.
+        let request = Request::get("/")
+            .header(ORIGIN, "https://other.xyz")
+            .body(Body::empty())?;
+        let response = cors_router(&config).oneshot(request).await?;
+        assert!(
+            response
+                .headers()
+                .get(ACCESS_CONTROL_ALLOW_ORIGIN)
+                .is_none()
+        );
+
+        // By default any origin is allowed.
+        let request = Request::get("/")
+            .header(ORIGIN, "https://other.xyz")
+            .body(Body::empty())?;
+        let response = cors_router(&CorsConfig::default()).oneshot(request).await?;
+        assert_eq!(response.headers()[ACCESS_CONTROL_ALLOW_ORIGIN], "*");
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_invalid_cors_config() {
+        let config = CorsConfig {
+            allowed_methods: vec!["NOT A METHOD".to_string()],
+            ..Default::default()
+        };
+        assert!(cors_layer(&config).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_https_negotiates_http2() -> anyhow::Result<()> {
+        let (handle, addr) = start_https_server(true).await?;
+        let client = Client::builder()
+            .use_rustls_tls()
+            .danger_accept_invalid_certs(true)
+            .build()?;
+
+        // Open the connection first, so that the concurrent requests can be multiplexed on it.
+        let (version, peer) = get_peer(&client, addr).await?;
+        assert_eq!(version, Version::HTTP_2);
+
+        let responses =
+            futures::future::try_join_all((0..8).map(|_| get_peer(&client, addr))).await?;
+        for response in responses {
+            assert_eq!(response, (Version::HTTP_2, peer.clone()));
+        }
+
+        handle.shutdown();
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_https_falls_back_to_http1() -> anyhow::Result<()> {
+        let (handle, addr) = start_https_server(true).await?;
+        let client = Client::builder()
+            .use_rustls_tls()
+            .danger_accept_invalid_certs(true)
+            .http1_only()
+            .build()?;
+        assert_eq!(get_peer(&client, addr).await?.0, Version::HTTP_11);
+        handle.shutdown();
+
+        // Clients that would prefer HTTP/2 are served over HTTP/1.1 when it is not offered.
+        let (handle, addr) = start_https_server(false).await?;
+        let client = Client::builder()
+            .use_rustls_tls()
+            .danger_accept_invalid_certs(true)
+            .build()?;
+        assert_eq!(get_peer(&client, addr).await?.0, Version::HTTP_11);
+        handle.shutdown();
+
+        Ok(())
+    }
+}
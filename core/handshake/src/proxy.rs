@@ -58,6 +58,7 @@ pub enum State {
 enum HandleRequestResult {
     Ok,
     DropTransport,
+    /// The payload could not be forwarded to the service.
     TerminateConnection,
 }
 
@@ -190,7 +191,7 @@ async fn run_with_one_connection<S: TransportSender, R: TransportReceiver>(
                         },
                         Some(HandleRequestResult::Ok) => {},
                         Some(HandleRequestResult::TerminateConnection) => {
-                            break 'outer TerminationReason::InternalError;
+                            break 'outer TerminationReason::ServiceTerminated;
                         },
                         Some(HandleRequestResult::DropTransport) | None => {
                             // We're possibly switching connection. If there are any pending bytes from
@@ -322,7 +323,7 @@ pub async fn run_with_primary_and_secondary<
                             self.maybe_flush_primary_queue(false, &mut p_sender).await;
                         },
                         Some(HandleRequestResult::TerminateConnection) => {
-                            break 'outer TerminationReason::InternalError;
+                            break 'outer TerminationReason::ServiceTerminated;
                         },
                         Some(HandleRequestResult::DropTransport) | None => {
                             // We lost connection with primary. So if we're currently writing to it
@@ -339,7 +340,7 @@ pub async fn run_with_primary_and_secondary<
                     match async_map(res, |r| self.handle_incoming(false, r)).await {
                         Some(HandleRequestResult::Ok) => {},
                         Some(HandleRequestResult::TerminateConnection) => {
-                            break 'outer TerminationReason::InternalError;
+                            break 'outer TerminationReason::ServiceTerminated;
                         },
                         Some(HandleRequestResult::DropTransport) | None => {
                          if !self.is_primary_the_current_sender {
@@ -548,6 +549,7 @@ mod tests {
     use std::time::Duration;
 
     use anyhow::Result;
+    use bytes::Bytes;
     use fleek_crypto::{ClientPublicKey, ClientSignature};
     use fn_sdk::header::read_header;
     use futures::{SinkExt, StreamExt};
@@ -623,6 +625,20 @@ async fn start_mock_node<P: ExecutorProviderInterface>(id: u16) -> Result<Shutdo
         Ok(shutdown)
     }
 
+    async fn expect_termination(
+        rx: &async_channel::Receiver<Bytes>,
+        reason: TerminationReason,
+    ) -> Result<()> {
+        let bytes = timeout(Duration::from_secs(1), rx.recv())
+            .await
+            .expect("termination frame should be sent within 1 second")?;
+        assert_eq!(
+            ResponseFrame::decode(&bytes)?,
+            ResponseFrame::Termination { reason }
+        );
+        Ok(())
+    }
+
     #[tokio::test]
     async fn primary_connection() -> Result<()> {
         // start and connect to the mock node
@@ -766,15 +782,106 @@ async fn reject_expired_token() -> Result<()> {
             .await?;
 
         // connection should be immediately terminated
-        let bytes = timeout(Duration::from_secs(1), secondary_rx.recv())
+        expect_termination(&secondary_rx, TerminationReason::AccessTokenExpired).await?;
+
+        shutdown.shutdown().await;
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn reject_invalid_token() -> Result<()> {
+        // start and connect to the mock node
+        let mut shutdown = start_mock_node::<MockServiceProvider>(4).await?;
+        let (primary_tx, primary_rx) = dial_mock(4)
             .await
-            .expect("termination frame should be sent within 1 second")?;
-        assert_eq!(
-            ResponseFrame::decode(&bytes)?,
-            ResponseFrame::Termination {
-                reason: TerminationReason::InvalidToken
+            .expect("failed to dial primary connection");
+
+        // send handshake request
+        primary_tx
+            .send(
+                HandshakeRequestFrame::Handshake {
+                    retry: None,
+                    service: ECHO_SERVICE,
+                    pk: ClientPublicKey([0; 96]),
+                    pop: ClientSignature([0; 48]),
+                }
+                .encode(),
+            )
+            .await?;
+
+        // request and get access token
+        primary_tx
+            .send(RequestFrame::AccessToken { ttl: 1 }.encode())
+            .await?;
+        let mut access_token = match ResponseFrame::decode(&primary_rx.recv().await?)? {
+            ResponseFrame::AccessToken { access_token, .. } => *access_token,
+            f => panic!("expected access token, got {f:?}"),
+        };
+
+        // keep the connection id, but tamper with the random part of the token
+        access_token[47] ^= 1;
+
+        let (secondary_tx, secondary_rx) = dial_mock(4)
+            .await
+            .expect("failed to dial secondary connection");
+        secondary_tx
+            .send(HandshakeRequestFrame::JoinRequest { access_token }.encode())
+            .await?;
+        expect_termination(&secondary_rx, TerminationReason::InvalidToken).await?;
+
+        shutdown.shutdown().await;
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn reject_unknown_connection() -> Result<()> {
+        let mut shutdown = start_mock_node::<MockServiceProvider>(5).await?;
+
+        // join a connection that was never opened
+        let (tx, rx) = dial_mock(5).await.expect("failed to dial");
+        tx.send(
+            HandshakeRequestFrame::JoinRequest {
+                access_token: [0xAB; 48],
             }
-        );
+            .encode(),
+        )
+        .await?;
+        expect_termination(&rx, TerminationReason::UnknownConnection).await?;
+
+        // retry a connection that was never opened
+        let (tx, rx) = dial_mock(5).await.expect("failed to dial");
+        tx.send(
+            HandshakeRequestFrame::Handshake {
+                retry: Some(1234),
+                service: ECHO_SERVICE,
+                pk: ClientPublicKey([0; 96]),
+                pop: ClientSignature([0; 48]),
+            }
+            .encode(),
+        )
+        .await?;
+        expect_termination(&rx, TerminationReason::UnknownConnection).await?;
+
+        shutdown.shutdown().await;
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn reject_invalid_service() -> Result<()> {
+        let mut shutdown = start_mock_node::<MockServiceProvider>(6).await?;
+        let (tx, rx) = dial_mock(6).await.expect("failed to dial");
+
+        tx.send(
+            HandshakeRequestFrame::Handshake {
+                retry: None,
+                service: ECHO_SERVICE + 1,
+                pk: ClientPublicKey([0; 96]),
+                pop: ClientSignature([0; 48]),
+            }
+            .encode(),
+        )
+        .await?;
+        expect_termination(&rx, TerminationReason::InvalidService).await?;
 
         shutdown.shutdown().await;
         Ok(())
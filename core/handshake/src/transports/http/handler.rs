@@ -7,18 +7,32 @@
 use axum::http::{HeaderMap, Method, StatusCode};
 use axum::response::{IntoResponse, Response};
 use axum::Extension;
-use bytes::Bytes;
+use bytes::{Bytes, BytesMut};
 use fleek_crypto::{ClientPublicKey, ClientSignature};
 use fn_sdk::header::{HttpMethod, HttpOverrides, TransportDetail};
-use lightning_interfaces::schema::handshake::{HandshakeRequestFrame, RequestFrame};
-use lightning_interfaces::ExecutorProviderInterface;
+use fn_sdk::trace::TraceContext;
+use futures::StreamExt;
+use lightning_interfaces::schema::handshake::{
+    HandshakeRequestFrame,
+    RequestFrame,
+    TerminationReason,
+};
+use lightning_interfaces::{spawn, ExecutorProviderInterface};
 use lightning_metrics::increment_counter;
 use tokio::sync::oneshot;
+use tracing::{info_span, warn, Instrument};
 use url::Url;
 
 use crate::handshake::Context;
 use crate::transports::http::{HttpReceiver, HttpSender, Service};
 
+/// The largest body we buffer for the services that take it as a single payload. This is the
+/// default body limit of axum.
+const MAX_BODY_SIZE: usize = 2 << 20;
+/// The largest body we stream to the services that read it as it comes in. The services enforce
+/// their own, lower, limits on top of this.
+const MAX_STREAMED_BODY_SIZE: usize = 64 << 20;
+
 pub async fn handler<P: ExecutorProviderInterface>(
     method: Method,
     headers: HeaderMap,
@@ -26,7 +40,7 @@ pub async fn handler<P: ExecutorProviderInterface>(
     Path((service_id, _)): Path<(String, String)>,
     Query(params): Query<HashMap<String, String>>,
     Extension(provider): Extension<Context<P>>,
-    payload: Bytes,
+    body: Body,
 ) -> Result<impl IntoResponse, (StatusCode, String)> {
     let service_id = u32::from_str(&service_id)
         .map_err(|_| (StatusCode::NOT_FOUND, "route not found".to_string()))
@@ -42,8 +56,6 @@ pub async fn handler<P: ExecutorProviderInterface>(
         _ => return Err((StatusCode::NOT_FOUND, "invalid method".to_string())),
     };
 
-    let body_frame = RequestFrame::ServicePayload { bytes: payload };
-
     let handshake_frame = HandshakeRequestFrame::Handshake {
         service: service_id as u32,
         pk: ClientPublicKey([0; 96]),
@@ -61,35 +73,41 @@ pub async fn handler<P: ExecutorProviderInterface>(
     url.set_path(&path);
     url.set_query(uri.query());
 
+    let (header, trace) = service_headers(headers);
+    let span = info_span!(
+        "handshake_http_request",
+        service = service_id as u32,
+        trace_id = %trace.trace_id(),
+        span_id = %trace.span_id(),
+    );
+
     let sender = HttpSender::new(service_id, frame_tx, body_tx, termination_tx);
     let receiver = HttpReceiver::new(
         frame_rx,
         TransportDetail::HttpRequest {
             method,
             url,
-            header: headers
-                .into_iter()
-                .filter_map(|(name, val)| {
-                    if let Some(name) = name {
-                        if let Ok(val) = val.to_str() {
-                            Some((name.to_string(), val.to_string()))
-                        } else {
-                            None
-                        }
-                    } else {
-                        None
-                    }
-                })
-                .collect(),
+            header,
         },
     );
 
-    sender.frame_tx.try_send(Some(body_frame)).map_err(|_| {
-        (
-            StatusCode::INTERNAL_SERVER_ERROR,
-            "unexpected error".to_string(),
-        )
-    })?;
+    if service_id.supports_streaming_body() {
+        spawn!(
+            forward_body(body, sender.frame_tx.clone(), MAX_STREAMED_BODY_SIZE),
+            "HANDSHAKE: http request body"
+        );
+    } else {
+        let bytes = read_body(body, MAX_BODY_SIZE).await?;
+        sender
+            .frame_tx
+            .try_send(Some(RequestFrame::ServicePayload { bytes }))
+            .map_err(|_| {
+                (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    "unexpected error".to_string(),
+                )
+            })?;
+    }
 
     {
         let service_id = format!("{}", service_id as usize);
@@ -102,6 +120,7 @@ pub async fn handler<P: ExecutorProviderInterface>(
 
     provider
         .handle_new_connection(handshake_frame, sender, receiver)
+        .instrument(span)
         .await;
 
     let mut response_builder = Response::builder();
@@ -140,13 +159,100 @@ pub async fn handler<P: ExecutorProviderInterface>(
     // If there is an error while streaming, the status header has already been sent,
     // this is a hacky way of returning an error status before beginning streaming the body.
     match termination_rx.await {
-        Ok(reason) => Err(bad_request(format!("handshake failed: {reason:?}"))),
+        Ok(reason) => Err((
+            termination_status(reason),
+            format!("handshake failed: {reason:?} ({})", reason.code()),
+        )),
         Err(_) => response_builder
             .body(body)
             .map_err(|_| bad_request("invalid type value")),
     }
 }
 
+/// Reads the whole body of a request, as long as it is no larger than `limit`.
+async fn read_body(body: Body, limit: usize) -> Result<Bytes, (StatusCode, String)> {
+    let mut stream = body.into_data_stream();
+    let mut buffer = BytesMut::new();
+    while let Some(chunk) = stream.next().await {
+        let chunk = chunk.map_err(|e| bad_request(format!("failed to read body: {e}")))?;
+        if buffer.len() + chunk.len() > limit {
+            return Err((
+                StatusCode::PAYLOAD_TOO_LARGE,
+                format!("body is larger than {limit} bytes"),
+            ));
+        }
+        buffer.extend_from_slice(&chunk);
+    }
+    Ok(buffer.freeze())
+}
+
+/// Forwards the body of a request to the service as it comes in, one payload per chunk, followed
+/// by an empty payload that marks the end of the body. A body larger than `limit` closes the
+/// connection once the limit is crossed.
+async fn forward_body(
+    body: Body,
+    frame_tx: async_channel::Sender<Option<RequestFrame>>,
+    limit: usize,
+) {
+    let mut stream = body.into_data_stream();
+    let mut forwarded = 0;
+    while let Some(chunk) = stream.next().await {
+        let frame = match chunk {
+            Ok(bytes) if bytes.is_empty() => continue,
+            Ok(bytes) if forwarded + bytes.len() > limit => {
+                warn!("http request body is larger than {limit} bytes");
+                None
+            },
+            Ok(bytes) => {
+                forwarded += bytes.len();
+                Some(RequestFrame::ServicePayload { bytes })
+            },
+            Err(e) => {
+                // Close the connection, the service must not mistake the partial body for a
+                // complete one.
+                warn!("failed to read http request body: {e}");
+                None
+            },
+        };
+        let closed = frame.is_none();
+        if frame_tx.send(frame).await.is_err() || closed {
+            return;
+        }
+    }
+    let _ = frame_tx
+        .send(Some(RequestFrame::ServicePayload {
+            bytes: Bytes::new(),
+        }))
+        .await;
+}
+
+/// Returns the headers that are passed on to the service, along with the trace context of the
+/// request. The trace is continued from the `traceparent` header when the client sent one, and the
+/// service is handed the context of our own span so that its spans are linked to it.
+fn service_headers(headers: HeaderMap) -> (HashMap<String, String>, TraceContext) {
+    let mut header: HashMap<String, String> = headers
+        .into_iter()
+        .filter_map(|(name, val)| {
+            if let Some(name) = name {
+                if let Ok(val) = val.to_str() {
+                    Some((name.to_string(), val.to_string()))
+                } else {
+                    None
+                }
+            } else {
+                None
+            }
+        })
+        .collect();
+
+    let trace = TraceContext::from_headers(&header)
+        .map(|parent| parent.child())
+        .unwrap_or_else(TraceContext::new_root);
+    trace.inject(&mut header);
+
+    (header, trace)
+}
+
 /// To support blinks on solana running in our javascript service wallets will be looking for this
 /// at /actions.json
 pub async fn blink_support() -> Response {
@@ -164,7 +270,151 @@ pub async fn blink_support() -> Response {
         .unwrap()
 }
 
+/// Returns the http status that a session terminated with the given reason is answered with.
+fn termination_status(reason: TerminationReason) -> StatusCode {
+    match reason {
+        TerminationReason::Timeout => StatusCode::GATEWAY_TIMEOUT,
+        TerminationReason::InvalidHandshake | TerminationReason::InvalidDeliveryAcknowledgment => {
+            StatusCode::BAD_REQUEST
+        },
+        TerminationReason::InvalidToken
+        | TerminationReason::AccessTokenExpired
+        | TerminationReason::UnknownConnection => StatusCode::UNAUTHORIZED,
+        TerminationReason::WrongPermssion => StatusCode::FORBIDDEN,
+        TerminationReason::InvalidService => StatusCode::NOT_FOUND,
+        TerminationReason::ConnectionInUse => StatusCode::CONFLICT,
+        TerminationReason::ResourcesUnavailable => StatusCode::TOO_MANY_REQUESTS,
+        TerminationReason::ServiceTerminated => StatusCode::BAD_GATEWAY,
+        TerminationReason::Shutdown => StatusCode::SERVICE_UNAVAILABLE,
+        _ => StatusCode::INTERNAL_SERVER_ERROR,
+    }
+}
+
 #[inline(always)]
 fn bad_request<T: AsRef<str> + Display>(msg: T) -> (StatusCode, String) {
     (StatusCode::BAD_REQUEST, msg.to_string())
 }
+
+#[cfg(test)]
+mod tests {
+    use axum::http::HeaderValue;
+
+    use super::*;
+
+    #[test]
+    fn test_service_headers_continue_trace() {
+        let parent = TraceContext::new_root();
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            "traceparent",
+            HeaderValue::from_str(&parent.to_string()).unwrap(),
+        );
+        headers.insert("x-custom", HeaderValue::from_static("value"));
+
+        let (header, trace) = service_headers(headers);
+        assert_eq!(trace.trace_id(), parent.trace_id());
+        assert_ne!(trace.span_id(), parent.span_id());
+        assert_eq!(TraceContext::from_headers(&header), Some(trace));
+        assert_eq!(header.get("x-custom").map(String::as_str), Some("value"));
+    }
+
+    #[tokio::test]
+    async fn test_forward_body() {
+        let chunks = (0..32u8).map(|i| Ok::<_, std::io::Error>(Bytes::from(vec![i; 64 << 10])));
+        let body = Body::from_stream(futures::stream::iter(chunks));
+        let (frame_tx, frame_rx) = async_channel::bounded(8);
+        tokio::spawn(forward_body(body, frame_tx, MAX_STREAMED_BODY_SIZE));
+
+        let mut received = Vec::new();
+        loop {
+            match frame_rx.recv().await.unwrap() {
+                Some(RequestFrame::ServicePayload { bytes }) if bytes.is_empty() => break,
+                Some(RequestFrame::ServicePayload { bytes }) => received.push(bytes),
+                frame => panic!("expected payload, got {frame:?}"),
+            }
+        }
+
+        assert_eq!(received.len(), 32);
+        for (i, chunk) in received.iter().enumerate() {
+            assert_eq!(chunk, &vec![i as u8; 64 << 10]);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_forward_body_error_closes_connection() {
+        let chunks = vec![
+            Ok(Bytes::from_static(b"partial")),
+            Err(std::io::Error::other("client went away")),
+        ];
+        let body = Body::from_stream(futures::stream::iter(chunks));
+        let (frame_tx, frame_rx) = async_channel::bounded(8);
+        tokio::spawn(forward_body(body, frame_tx, MAX_STREAMED_BODY_SIZE));
+
+        assert!(matches!(
+            frame_rx.recv().await.unwrap(),
+            Some(RequestFrame::ServicePayload { .. })
+        ));
+        assert!(frame_rx.recv().await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_forward_body_over_limit_closes_connection() {
+        let chunks = (0..4u8).map(|i| Ok::<_, std::io::Error>(Bytes::from(vec![i; 1024])));
+        let body = Body::from_stream(futures::stream::iter(chunks));
+        let (frame_tx, frame_rx) = async_channel::bounded(8);
+        tokio::spawn(forward_body(body, frame_tx, 2048));
+
+        for _ in 0..2 {
+            assert!(matches!(
+                frame_rx.recv().await.unwrap(),
+                Some(RequestFrame::ServicePayload { bytes }) if bytes.len() == 1024
+            ));
+        }
+        assert!(frame_rx.recv().await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_read_body_over_limit() {
+        let body = Body::from(vec![0; 2048]);
+        assert_eq!(read_body(body, 2048).await.unwrap().len(), 2048);
+
+        let body = Body::from(vec![0; 2049]);
+        let (status, _) = read_body(body, 2048).await.unwrap_err();
+        assert_eq!(status, StatusCode::PAYLOAD_TOO_LARGE);
+    }
+
+    #[test]
+    fn test_termination_status() {
+        for (reason, status) in [
+            (TerminationReason::Timeout, StatusCode::GATEWAY_TIMEOUT),
+            (TerminationReason::InvalidHandshake, StatusCode::BAD_REQUEST),
+            (TerminationReason::InvalidToken, StatusCode::UNAUTHORIZED),
+            (
+                TerminationReason::AccessTokenExpired,
+                StatusCode::UNAUTHORIZED,
+            ),
+            (TerminationReason::InvalidService, StatusCode::NOT_FOUND),
+            (
+                TerminationReason::ServiceTerminated,
+                StatusCode::BAD_GATEWAY,
+            ),
+            (
+                TerminationReason::ResourcesUnavailable,
+                StatusCode::TOO_MANY_REQUESTS,
+            ),
+            (
+                TerminationReason::InternalError,
+                StatusCode::INTERNAL_SERVER_ERROR,
+            ),
+            (TerminationReason::Shutdown, StatusCode::SERVICE_UNAVAILABLE),
+        ] {
+            assert_eq!(termination_status(reason), status, "{reason:?}");
+        }
+    }
+
+    #[test]
+    fn test_service_headers_start_trace() {
+        let (header, trace) = service_headers(HeaderMap::new());
+        assert_eq!(TraceContext::from_headers(&header), Some(trace));
+    }
+}
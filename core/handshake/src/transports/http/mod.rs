@@ -68,6 +68,12 @@ impl Service {
     pub fn supports_http_overrides(&self) -> bool {
         matches!(self, Service::Js)
     }
+
+    /// Returns true if the service reads the request body as a stream of payloads that ends with
+    /// an empty one, instead of a single payload.
+    pub fn supports_streaming_body(&self) -> bool {
+        matches!(self, Service::Js)
+    }
 }
 
 pub struct HttpSender {
@@ -7,7 +7,7 @@
 use lightning_application::genesis::{Genesis, GenesisNode};
 use lightning_application::query_runner::QueryRunner;
 use lightning_interfaces::prelude::*;
-use lightning_interfaces::types::NodePorts;
+use lightning_interfaces::types::{HandshakePorts, NodePorts};
 use lightning_notifier::Notifier;
 use lightning_signer::Signer;
 use lightning_test_utils::consensus::{Config as ConsensusConfig, MockConsensus, MockForwarder};
@@ -83,7 +83,11 @@ async fn test_submission() {
             rpc: 38103,
             pool: 38104,
             pinger: 38106,
-            handshake: Default::default(),
+            handshake: HandshakePorts {
+                http: 38220,
+                webrtc: 38320,
+                webtransport: 38321,
+            },
         },
         None,
         true,
@@ -111,6 +115,7 @@ async fn test_submission() {
                         probability_txn_lost: 0.0,
                         transactions_to_lose: HashSet::new(),
                         new_block_interval: Duration::from_secs(5),
+                        fault_injector: None,
                     }),
             )
             .with(keystore),
@@ -39,6 +39,7 @@
     Epoch,
     NodeInfo,
     NodeServed,
+    Participation,
     ProtocolParams,
     ReportedReputationMeasurements,
     Service,
@@ -248,4 +249,7 @@ pub struct PagingParams {
     pub ignore_stake: bool,
     pub start: NodeIndex,
     pub limit: usize,
+    // Only return nodes with the given participation status.
+    #[serde(default)]
+    pub participation: Option<Participation>,
 }
@@ -1,8 +1,12 @@
+use std::collections::HashMap;
+use std::ops::Range;
+use std::sync::{Arc, Mutex};
+
 use affair::Socket;
 use anyhow::Result;
 use fdi::BuildGraph;
-use lightning_types::{PeerRequestError, ServerRequest};
-use tokio::sync::broadcast;
+use lightning_types::{Blake3Hash, FetchProgress, NodeIndex, PeerRequestError, ServerRequest};
+use tokio::sync::{broadcast, watch};
 
 use crate::collection::Collection;
 use crate::ConfigConsumer;
@@ -16,4 +20,44 @@ pub trait BlockstoreServerInterface<C: Collection>:
 {
     #[socket]
     fn get_socket(&self) -> BlockstoreServerSocket;
+
+    /// Returns the progress of the content we download from peers.
+    fn get_download_progress(&self) -> DownloadProgress;
+
+    /// Requests the given range of blocks of the content from a peer, and returns the content of
+    /// those blocks once it has been verified against the root hash. A range that runs past the
+    /// end of the content is cut off there.
+    async fn request_range(
+        &self,
+        peer: NodeIndex,
+        hash: Blake3Hash,
+        blocks: Range<u32>,
+    ) -> Result<Vec<u8>, PeerRequestError>;
+}
+
+/// The progress of the content downloads, for the callers that subscribed to it.
+#[derive(Clone, Default)]
+pub struct DownloadProgress {
+    downloads: Arc<Mutex<HashMap<Blake3Hash, watch::Sender<FetchProgress>>>>,
+}
+
+impl DownloadProgress {
+    /// Returns a receiver that observes the progress of downloading the content with the given
+    /// hash. The progress persists across downloads from different peers while it is observed.
+    pub fn subscribe(&self, hash: Blake3Hash) -> watch::Receiver<FetchProgress> {
+        let mut downloads = self.downloads.lock().unwrap();
+        // Forget about the downloads nobody observes anymore.
+        downloads.retain(|_, tx| tx.receiver_count() > 0);
+        downloads
+            .entry(hash)
+            .or_insert_with(|| watch::channel(FetchProgress::default()).0)
+            .subscribe()
+    }
+
+    /// Updates the progress of downloading the content with the given hash, if it is observed.
+    pub fn update(&self, hash: &Blake3Hash, modify: impl FnOnce(&mut FetchProgress)) {
+        if let Some(tx) = self.downloads.lock().unwrap().get(hash) {
+            tx.send_modify(modify);
+        }
+    }
 }
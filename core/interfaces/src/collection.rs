@@ -1,9 +1,7 @@
 use std::marker::PhantomData;
-use std::time::Duration;
 
 use anyhow::Result;
 use fdi::Provider;
-use tokio::time::sleep;
 
 use super::*;
 
@@ -40,7 +38,7 @@
 /// The Fleek Network node.
 pub struct Node<C: Collection> {
     pub provider: Provider,
-    pub shutdown: Option<ShutdownController>,
+    pub shutdown: Option<ShutdownCoordinator>,
     _p: PhantomData<C>,
 }
 
@@ -75,10 +73,13 @@ pub fn init_with_provider(mut provider: Provider) -> Result<Self> {
     }
 
     pub async fn start(&self) {
+        if let Some(shutdown) = &self.shutdown {
+            tokio::spawn(shutdown.propagate_shutdown());
+        }
         self.provider.trigger("start");
     }
 
-    /// Shutdown the node
+    /// Shutdown the node, one [ShutdownPhase] after the other.
     pub async fn shutdown(&mut self) {
         let mut shutdown = self
             .shutdown
@@ -86,39 +87,6 @@ pub async fn shutdown(&mut self) {
             .expect("cannot call shutdown more than once");
 
         tracing::trace!("Shutting node down.");
-        shutdown.trigger_shutdown();
-
-        for i in 0.. {
-            tokio::select! {
-                biased;
-                _ = shutdown.wait_for_completion() => {
-                    return;
-                },
-                _ = sleep(Duration::from_secs(5)) => {
-                    match i {
-                        0 => {
-                            tracing::trace!("Still shutting down...");
-                            continue;
-                        },
-                        1 => {
-                            tracing::warn!("Still shutting down...");
-                            continue;
-                        },
-                        _ => {
-                            tracing::error!("Shutdown taking too long")
-                        }
-                    }
-                }
-            }
-
-            let Some(iter) = shutdown.pending_backtraces() else {
-                continue;
-            };
-
-            eprintln!("Printing pending backtraces:");
-            for (i, trace) in iter.enumerate() {
-                eprintln!("Pending task backtrace #{i}:\n{trace:#?}");
-            }
-        }
+        shutdown.shutdown().await;
     }
 }
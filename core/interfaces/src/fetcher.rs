@@ -1,6 +1,8 @@
 use affair::Socket;
+use anyhow::{Context, Result};
 use fdi::BuildGraph;
-use lightning_types::{FetcherRequest, FetcherResponse};
+use lightning_types::{Blake3Hash, FetchProgress, FetcherRequest, FetcherResponse};
+use tokio::sync::{oneshot, watch};
 
 use crate::collection::Collection;
 
@@ -11,4 +13,33 @@ pub trait FetcherInterface<C: Collection>: BuildGraph + Sized + Send + Sync {
     /// Returns a socket that can be used to submit requests to the fetcher.
     #[socket]
     fn get_socket(&self) -> FetcherSocket;
+
+    /// Fetches the content with the given hash like a [`FetcherRequest::Fetch`] does, and returns
+    /// a handle that reports the progress of the fetch.
+    fn fetch_with_progress(&self, hash: Blake3Hash) -> FetchHandle;
+}
+
+/// A handle to a fetch that is in progress.
+pub struct FetchHandle {
+    progress: watch::Receiver<FetchProgress>,
+    result: oneshot::Receiver<Result<()>>,
+}
+
+impl FetchHandle {
+    pub fn new(
+        progress: watch::Receiver<FetchProgress>,
+        result: oneshot::Receiver<Result<()>>,
+    ) -> Self {
+        Self { progress, result }
+    }
+
+    /// Returns a receiver that observes the progress of the fetch.
+    pub fn progress(&self) -> watch::Receiver<FetchProgress> {
+        self.progress.clone()
+    }
+
+    /// Waits for the fetch to finish.
+    pub async fn finish(self) -> Result<()> {
+        self.result.await.context("Fetch was dropped")?
+    }
 }
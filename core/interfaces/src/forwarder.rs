@@ -1,6 +1,7 @@
 use affair::Socket;
 use fdi::BuildGraph;
 use lightning_types::TransactionRequest;
+use thiserror::Error;
 
 use crate::collection::Collection;
 
@@ -12,7 +13,20 @@
 /// This socket is safe to freely pass around, sending transactions through this socket
 /// does not guarantee their execution on the application layer. You can think about
 /// this as if the current node was only an external client to the network.
-pub type MempoolSocket = Socket<TransactionRequest, ()>;
+///
+/// A transaction the forwarder can not take right now is rejected with a [`ForwarderError`].
+pub type MempoolSocket = Socket<TransactionRequest, Result<(), ForwarderError>>;
+
+/// The reasons a transaction can not be queued for forwarding.
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum ForwarderError {
+    /// The queue is full because the workers we forward to can not keep up.
+    #[error("The forwarder queue is full")]
+    Backpressure,
+    /// The forwarder is not running anymore.
+    #[error("The forwarder is shut down")]
+    Closed,
+}
 
 #[interfaces_proc::blank]
 pub trait ForwarderInterface<C: Collection>: BuildGraph + Sized + Send + 'static {
@@ -14,11 +14,13 @@ fn build_graph() -> fdi::DependencyGraph {
                 use $crate::prelude::*;
 
                 let trace_shutdown = std::env::var("TRACE_SHUTDOWN").is_ok();
-                let shutdown = $crate::ShutdownController::new(trace_shutdown);
-                let waiter = shutdown.waiter();
+                let shutdown = $crate::ShutdownCoordinator::new(trace_shutdown);
+                let waiters = shutdown.waiters();
+                let waiter = waiters.get($crate::ShutdownPhase::Services);
 
                 fdi::DependencyGraph::new()
                     .with_value(shutdown)
+                    .with_value(waiters)
                     .with_value(waiter)
                     .with_value($crate::_hacks::Blanket::default())
                     .with_infallible(|this: &<Self as Collection>::ApplicationInterface|
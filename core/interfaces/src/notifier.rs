@@ -1,7 +1,7 @@
 use std::time::Duration;
 
 use fdi::BuildGraph;
-use lightning_types::{Block, BlockExecutionResponse};
+use lightning_types::{Blake3Hash, Block, BlockExecutionResponse, NodeIndex};
 
 use crate::collection::Collection;
 
@@ -17,6 +17,23 @@ pub struct EpochChangedNotification {
     pub last_epoch_hash: [u8; 32],
 }
 
+/// Emitted when a successful `UpdateContentRegistry` transaction changed the content a node
+/// provides.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ContentRegistryChangedNotification {
+    pub node: NodeIndex,
+    pub added: Vec<Blake3Hash>,
+    pub removed: Vec<Blake3Hash>,
+}
+
+/// Any of the notifications emitted by the notifier, delivered by filtered subscriptions.
+#[derive(Clone, Debug)]
+pub enum Notification {
+    BlockExecuted(BlockExecutedNotification),
+    EpochChanged(EpochChangedNotification),
+    ContentRegistryChanged(ContentRegistryChangedNotification),
+}
+
 /// # Notifier
 #[interfaces_proc::blank]
 pub trait NotifierInterface<C: Collection>: BuildGraph + Sync + Send + Clone {
@@ -33,6 +50,19 @@ pub trait NotifierInterface<C: Collection>: BuildGraph + Sync + Send + Clone {
     #[blank = crate::_hacks::Blanket]
     fn subscribe_epoch_changed(&self) -> impl Subscriber<EpochChangedNotification>;
 
+    #[blank = crate::_hacks::Blanket]
+    fn subscribe_content_registry_changed(
+        &self,
+    ) -> impl Subscriber<ContentRegistryChangedNotification>;
+
+    /// Subscribe to the notifications for which `filter` returns `true`. The filter is applied
+    /// when a notification is emitted, so the subscriber is not woken up for the others.
+    #[blank = crate::_hacks::Blanket]
+    fn subscribe_filtered(
+        &self,
+        filter: impl Fn(&Notification) -> bool + Send + Sync + 'static,
+    ) -> impl Subscriber<Notification>;
+
     #[blank = crate::_hacks::Blanket]
     fn subscribe_before_epoch_change(&self, duration: Duration) -> impl Subscriber<()>;
 }
@@ -2,7 +2,7 @@
 use lightning_schema::broadcast::ResolvedImmutablePointerRecord;
 
 use crate::collection::Collection;
-use crate::types::{Blake3Hash, ImmutablePointer};
+use crate::types::{Blake3Hash, ImmutablePointer, NodeIndex};
 
 /// The resolver is responsible to resolve an FNIP (Fleek Network Immutable Pointer),
 /// into a Blake3 hash of the content.
@@ -25,6 +25,10 @@ pub trait ResolverInterface<C: Collection>: BuildGraph + Sized + Send + Sync + C
 
     /// Returns all origins in the local db
     fn get_origins(&self, hash: Blake3Hash) -> Option<Vec<ResolvedImmutablePointerRecord>>;
+
+    /// Returns the nodes that provide the content with the given blake3 hash, with the best
+    /// candidates to fetch it from first.
+    fn resolve_ranked(&self, uri: &Blake3Hash) -> Vec<NodeIndex>;
 }
 
 /// An `async-iterator`-like interface that tries to find the immutable pointers of
@@ -1,2 +1,219 @@
+use std::time::Duration;
+
 pub use better_shutdown::*;
 pub use fdi::{Cloned, Consume, Ref, RefMut}; // TODO(qti3e): To be removed!
+use futures::Future;
+use tokio::time::sleep;
+
+/// The phases of a node shutdown, in the order in which they are shut down. A phase is only
+/// signaled once every component of the previous phases is done.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ShutdownPhase {
+    /// Components accepting work from outside of the node, such as the rpc and the handshake.
+    Ingress,
+    /// The consensus, so that no new blocks are ordered while the rest of the node goes down.
+    Consensus,
+    /// Everything else, this is the phase of the [ShutdownWaiter] in the provider.
+    Services,
+}
+
+impl ShutdownPhase {
+    /// All of the phases in shutdown order.
+    pub const ALL: [ShutdownPhase; 3] = [Self::Ingress, Self::Consensus, Self::Services];
+}
+
+/// The waiters of every [ShutdownPhase]. Components that have to go down before the rest of the
+/// node get their waiter from here instead of depending on the [ShutdownWaiter] directly.
+#[derive(Clone)]
+pub struct ShutdownPhaseWaiters {
+    waiters: Vec<(ShutdownPhase, ShutdownWaiter)>,
+}
+
+impl ShutdownPhaseWaiters {
+    /// Returns the waiter of the given phase.
+    pub fn get(&self, phase: ShutdownPhase) -> ShutdownWaiter {
+        self.waiters
+            .iter()
+            .find(|(p, _)| *p == phase)
+            .map(|(_, waiter)| waiter.clone())
+            .expect("every phase has a waiter")
+    }
+}
+
+/// Owns one [ShutdownController] per [ShutdownPhase] and shuts them down in order.
+pub struct ShutdownCoordinator {
+    phases: Vec<(ShutdownPhase, ShutdownController)>,
+}
+
+impl Default for ShutdownCoordinator {
+    fn default() -> Self {
+        Self::new(false)
+    }
+}
+
+impl ShutdownCoordinator {
+    pub fn new(capture_backtrace: bool) -> Self {
+        Self {
+            phases: ShutdownPhase::ALL
+                .into_iter()
+                .map(|phase| (phase, ShutdownController::new(capture_backtrace)))
+                .collect(),
+        }
+    }
+
+    /// Returns the controller of the given phase.
+    pub fn controller(&self, phase: ShutdownPhase) -> &ShutdownController {
+        self.phases
+            .iter()
+            .find(|(p, _)| *p == phase)
+            .map(|(_, controller)| controller)
+            .expect("every phase has a controller")
+    }
+
+    /// Returns the waiter of the given phase.
+    pub fn waiter(&self, phase: ShutdownPhase) -> ShutdownWaiter {
+        self.controller(phase).waiter()
+    }
+
+    /// Returns the waiters of all the phases.
+    pub fn waiters(&self) -> ShutdownPhaseWaiters {
+        ShutdownPhaseWaiters {
+            waiters: self
+                .phases
+                .iter()
+                .map(|(phase, controller)| (*phase, controller.waiter()))
+                .collect(),
+        }
+    }
+
+    /// Returns the phases along with their controllers in shutdown order.
+    pub fn phases_mut(&mut self) -> impl Iterator<Item = (ShutdownPhase, &mut ShutdownController)> {
+        self.phases
+            .iter_mut()
+            .map(|(phase, controller)| (*phase, controller))
+    }
+
+    /// Trigger the shutdown of every phase at once, without waiting for any of them.
+    pub fn trigger_shutdown(&self) {
+        for (_, controller) in &self.phases {
+            controller.trigger_shutdown();
+        }
+    }
+
+    /// Returns a future that triggers the shutdown of the other phases when the last phase is
+    /// triggered without going through the coordinator, for example by a crucial task that
+    /// panicked. Should be spawned once the node is started.
+    pub fn propagate_shutdown(&self) -> impl Future<Output = ()> + Send + 'static {
+        let (_, last) = self.phases.last().expect("there is at least one phase");
+        let signal = last.waiter().wait_for_shutdown_owned();
+        let permits = self
+            .phases
+            .iter()
+            .map(|(_, controller)| controller.permit())
+            .collect::<Vec<_>>();
+
+        async move {
+            signal.await;
+            for permit in permits {
+                permit.trigger_shutdown();
+            }
+        }
+    }
+
+    /// Shut the phases down one after the other. Each phase is triggered once the futures waiting
+    /// for the previous phase have all dropped.
+    pub async fn shutdown(&mut self) {
+        for (phase, controller) in self.phases_mut() {
+            tracing::trace!("Shutting down the {phase:?} phase.");
+            controller.trigger_shutdown();
+
+            for i in 0.. {
+                tokio::select! {
+                    biased;
+                    _ = controller.wait_for_completion() => {
+                        break;
+                    },
+                    _ = sleep(Duration::from_secs(5)) => {
+                        match i {
+                            0 => {
+                                tracing::trace!("Still shutting down the {phase:?} phase...");
+                                continue;
+                            },
+                            1 => {
+                                tracing::warn!("Still shutting down the {phase:?} phase...");
+                                continue;
+                            },
+                            _ => {
+                                tracing::error!("Shutdown taking too long ({phase:?})")
+                            }
+                        }
+                    }
+                }
+
+                let Some(iter) = controller.pending_backtraces() else {
+                    continue;
+                };
+
+                eprintln!("Printing pending backtraces:");
+                for (i, trace) in iter.enumerate() {
+                    eprintln!("Pending task backtrace #{i}:\n{trace:#?}");
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
+
+    use futures::FutureExt;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_phases_shut_down_in_order() {
+        let mut coordinator = ShutdownCoordinator::default();
+        let stopped = Arc::new(Mutex::new(Vec::new()));
+
+        // The earlier phases take longer to drain, to make sure that the later ones wait for them.
+        for (phase, drain) in [
+            (ShutdownPhase::Services, 0),
+            (ShutdownPhase::Consensus, 20),
+            (ShutdownPhase::Ingress, 50),
+        ] {
+            // Poll the signal once so it is registered before we trigger the shutdown.
+            let mut signal = coordinator.waiter(phase).wait_for_shutdown_owned();
+            assert!((&mut signal).now_or_never().is_none());
+
+            let stopped = stopped.clone();
+            tokio::spawn(async move {
+                (&mut signal).await;
+                tokio::time::sleep(Duration::from_millis(drain)).await;
+                stopped.lock().unwrap().push(phase);
+                // The phase is only done once the signal is dropped.
+                drop(signal);
+            });
+        }
+
+        coordinator.shutdown().await;
+
+        assert_eq!(*stopped.lock().unwrap(), ShutdownPhase::ALL.to_vec());
+    }
+
+    #[tokio::test]
+    async fn test_propagate_shutdown() {
+        let coordinator = ShutdownCoordinator::default();
+        let ingress = coordinator.waiter(ShutdownPhase::Ingress);
+        tokio::spawn(coordinator.propagate_shutdown());
+
+        coordinator
+            .waiter(ShutdownPhase::Services)
+            .trigger_shutdown();
+
+        tokio::time::timeout(Duration::from_secs(1), ingress.wait_for_shutdown())
+            .await
+            .expect("the ingress phase should have been triggered");
+    }
+}
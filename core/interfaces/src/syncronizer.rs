@@ -1,5 +1,6 @@
 use fdi::BuildGraph;
-use lightning_types::Blake3Hash;
+use lightning_types::{Blake3Hash, Epoch};
+use tokio::sync::watch;
 
 use crate::collection::Collection;
 
@@ -11,4 +12,44 @@ pub trait SyncronizerInterface<C: Collection>: BuildGraph + Sized + Send + Sync
     /// down
     #[pending]
     async fn next_checkpoint_hash(&self) -> Option<Blake3Hash>;
+
+    /// Returns a receiver that observes how far the node got with catching up to the network.
+    #[blank = tokio::sync::watch::channel(SyncProgress::default()).1]
+    fn sync_progress(&self) -> watch::Receiver<SyncProgress>;
+}
+
+/// How far the node got with catching up to the network.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct SyncProgress {
+    /// The epoch the node is on.
+    pub current_epoch: Epoch,
+    /// The epoch the bootstrap nodes are on.
+    pub target_epoch: Epoch,
+    /// The number of blocks of the checkpoint of the target epoch that are downloaded and
+    /// verified.
+    pub blocks_verified: u32,
+    /// The total number of blocks of the checkpoint of the target epoch, once known.
+    pub total_blocks: Option<u32>,
+    /// Whether the checkpoint of the target epoch is downloaded and ready to be loaded.
+    pub complete: bool,
+}
+
+impl SyncProgress {
+    /// Returns true if the node is behind the network and still has to download a checkpoint.
+    pub fn is_syncing(&self) -> bool {
+        !self.complete && self.target_epoch > self.current_epoch
+    }
+
+    /// Returns how much of the target checkpoint is downloaded, in percent.
+    pub fn percentage(&self) -> u8 {
+        if !self.is_syncing() {
+            return 100;
+        }
+        match self.total_blocks {
+            Some(total) if total > 0 => {
+                (u64::from(self.blocks_verified.min(total)) * 100 / u64::from(total)) as u8
+            },
+            _ => 0,
+        }
+    }
 }
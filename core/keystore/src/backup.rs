@@ -0,0 +1,86 @@
+use aes_gcm::aead::{Aead, AeadCore, KeyInit, OsRng};
+use aes_gcm::{Aes256Gcm, Key, Nonce};
+use anyhow::{anyhow, bail, Context};
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+
+/// Number of PBKDF2 rounds used to derive the encryption key from the passphrase.
+const KDF_ROUNDS: u32 = 600_000;
+const VERSION: u8 = 1;
+
+/// The secret key held by a key backup.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum KeyKind {
+    Node,
+    Consensus,
+}
+
+/// A pem encoded secret key encrypted with a passphrase, serialized as json.
+#[derive(Serialize, Deserialize)]
+struct KeyBackup {
+    version: u8,
+    kind: KeyKind,
+    rounds: u32,
+    salt: String,
+    nonce: String,
+    ciphertext: String,
+}
+
+/// Encrypt the pem encoded secret key with the passphrase.
+pub(crate) fn encrypt(kind: KeyKind, pem: &str, passphrase: &str) -> anyhow::Result<String> {
+    let salt = rand::random::<[u8; 16]>();
+    let cipher = cipher(passphrase, &salt, KDF_ROUNDS);
+    let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+    let ciphertext = cipher
+        .encrypt(&nonce, pem.as_bytes())
+        .map_err(|_| anyhow!("Failed to encrypt the secret key"))?;
+
+    let backup = KeyBackup {
+        version: VERSION,
+        kind,
+        rounds: KDF_ROUNDS,
+        salt: STANDARD.encode(salt),
+        nonce: STANDARD.encode(nonce),
+        ciphertext: STANDARD.encode(ciphertext),
+    };
+    serde_json::to_string_pretty(&backup).map_err(Into::into)
+}
+
+/// Decrypt a key backup with the passphrase, returning the pem encoded secret key.
+pub(crate) fn decrypt(encoded: &str, passphrase: &str) -> anyhow::Result<(KeyKind, String)> {
+    let backup: KeyBackup = serde_json::from_str(encoded).context("Invalid key backup")?;
+    if backup.version != VERSION {
+        bail!("Unsupported key backup version {}", backup.version);
+    }
+    // The rounds are read from an untrusted file, only accept the ones we write so that a
+    // crafted backup can't make us spin on the key derivation.
+    if backup.rounds != KDF_ROUNDS {
+        bail!("Unsupported key backup rounds {}", backup.rounds);
+    }
+
+    let salt = STANDARD.decode(&backup.salt).context("Invalid salt")?;
+    let nonce = STANDARD.decode(&backup.nonce).context("Invalid nonce")?;
+    let ciphertext = STANDARD
+        .decode(&backup.ciphertext)
+        .context("Invalid ciphertext")?;
+    if nonce.len() != 12 {
+        bail!("Invalid nonce");
+    }
+
+    let cipher = cipher(passphrase, &salt, backup.rounds);
+    let pem = cipher
+        .decrypt(Nonce::from_slice(&nonce), ciphertext.as_ref())
+        .map_err(|_| anyhow!("Failed to decrypt the key backup, is the passphrase correct?"))?;
+    let pem = String::from_utf8(pem).context("Invalid pem in key backup")?;
+
+    Ok((backup.kind, pem))
+}
+
+fn cipher(passphrase: &str, salt: &[u8], rounds: u32) -> Aes256Gcm {
+    let mut key = [0; 32];
+    pbkdf2::pbkdf2_hmac::<Sha256>(passphrase.as_bytes(), salt, rounds, &mut key);
+    Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(&key))
+}
@@ -16,6 +16,7 @@
 use tracing::info;
 use triomphe::Arc;
 
+use crate::backup::{self, KeyKind};
 use crate::KeystoreConfig;
 
 #[derive(Clone)]
@@ -65,6 +66,52 @@ pub fn init(config: &C::ConfigProviderInterface) -> anyhow::Result<Self> {
     }
 }
 
+impl<C: Collection> Keystore<C> {
+    /// Export a secret key as a json backup encrypted with the passphrase.
+    pub fn export_key(
+        config: &KeystoreConfig,
+        kind: KeyKind,
+        passphrase: &str,
+    ) -> anyhow::Result<String> {
+        let path = match kind {
+            KeyKind::Node => &config.node_key_path,
+            KeyKind::Consensus => &config.consensus_key_path,
+        };
+        let encoded = read_to_string(path).with_context(|| format!("Failed to read {path:?}"))?;
+        backup::encrypt(kind, &encoded, passphrase)
+    }
+
+    /// Import a secret key from a backup created by [`Keystore::export_key`]. An existing key
+    /// is only overwritten if `force` is set.
+    pub fn import_key(
+        config: &KeystoreConfig,
+        encoded: &str,
+        passphrase: &str,
+        force: bool,
+    ) -> anyhow::Result<()> {
+        let (kind, pem) = backup::decrypt(encoded, passphrase)?;
+        let path = match kind {
+            KeyKind::Node => {
+                let sk = NodeSecretKey::decode_pem(&pem).context("Invalid node key in backup")?;
+                info!("Importing node key: {}", sk.to_pk());
+                &config.node_key_path
+            },
+            KeyKind::Consensus => {
+                let sk = ConsensusSecretKey::decode_pem(&pem)
+                    .context("Invalid consensus key in backup")?;
+                info!("Importing consensus key: {}", sk.to_pk());
+                &config.consensus_key_path
+            },
+        };
+
+        if path.exists() && !force {
+            bail!("Cannot overwrite existing key {path:?}");
+        }
+
+        save(path, pem)
+    }
+}
+
 impl<C: Collection> BuildGraph for Keystore<C> {
     fn build_graph() -> fdi::DependencyGraph {
         fdi::DependencyGraph::default().with(Self::init)
@@ -1,8 +1,10 @@
+mod backup;
 mod config;
 mod keystore;
 
 #[cfg(test)]
 mod tests;
 
+pub use backup::KeyKind;
 pub use config::*;
 pub use keystore::*;
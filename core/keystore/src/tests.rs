@@ -1 +1,77 @@
+use fleek_crypto::SecretKey;
+use lightning_interfaces::prelude::*;
+use tempfile::tempdir;
 
+use crate::{KeyKind, Keystore, KeystoreConfig};
+
+partial!(TestBinding {
+    KeystoreInterface = Keystore<Self>;
+});
+
+fn config(dir: &std::path::Path) -> KeystoreConfig {
+    KeystoreConfig {
+        node_key_path: dir.join("node.pem").try_into().unwrap(),
+        consensus_key_path: dir.join("consensus.pem").try_into().unwrap(),
+    }
+}
+
+#[test]
+fn test_export_import_round_trip() {
+    let temp_dir = tempdir().unwrap();
+    let source = config(&temp_dir.path().join("source"));
+    let target = config(&temp_dir.path().join("target"));
+    Keystore::<TestBinding>::generate_keys(config(&temp_dir.path().join("source")), false).unwrap();
+
+    let node_backup =
+        Keystore::<TestBinding>::export_key(&source, KeyKind::Node, "passphrase").unwrap();
+    let consensus_backup =
+        Keystore::<TestBinding>::export_key(&source, KeyKind::Consensus, "passphrase").unwrap();
+
+    // The wrong passphrase can't decrypt the backup.
+    assert!(Keystore::<TestBinding>::import_key(&target, &node_backup, "wrong", false).is_err());
+    assert!(!target.node_key_path.exists());
+
+    Keystore::<TestBinding>::import_key(&target, &node_backup, "passphrase", false).unwrap();
+    Keystore::<TestBinding>::import_key(&target, &consensus_backup, "passphrase", false).unwrap();
+
+    // The imported keys match the exported ones.
+    let (source_consensus, source_node) = source.load_test_keys();
+    let (target_consensus, target_node) = target.load_test_keys();
+    assert_eq!(source_node.to_pk(), target_node.to_pk());
+    assert_eq!(source_consensus.to_pk(), target_consensus.to_pk());
+
+    // An existing key is only overwritten when forced.
+    let other = config(&temp_dir.path().join("other"));
+    Keystore::<TestBinding>::generate_keys(config(&temp_dir.path().join("other")), false).unwrap();
+    let other_backup =
+        Keystore::<TestBinding>::export_key(&other, KeyKind::Node, "passphrase").unwrap();
+    assert!(
+        Keystore::<TestBinding>::import_key(&target, &other_backup, "passphrase", false).is_err()
+    );
+    assert_eq!(target.load_test_keys().1.to_pk(), source_node.to_pk());
+
+    Keystore::<TestBinding>::import_key(&target, &other_backup, "passphrase", true).unwrap();
+    assert_eq!(
+        target.load_test_keys().1.to_pk(),
+        other.load_test_keys().1.to_pk()
+    );
+}
+
+#[test]
+fn test_import_rejects_unexpected_rounds() {
+    let temp_dir = tempdir().unwrap();
+    let source = config(&temp_dir.path().join("source"));
+    let target = config(&temp_dir.path().join("target"));
+    Keystore::<TestBinding>::generate_keys(config(&temp_dir.path().join("source")), false).unwrap();
+
+    let backup = Keystore::<TestBinding>::export_key(&source, KeyKind::Node, "passphrase").unwrap();
+    let mut backup: serde_json::Value = serde_json::from_str(&backup).unwrap();
+    backup["rounds"] = u32::MAX.into();
+
+    // A backup asking for a different number of rounds is rejected before deriving the key.
+    assert!(
+        Keystore::<TestBinding>::import_key(&target, &backup.to_string(), "passphrase", false)
+            .is_err()
+    );
+    assert!(!target.node_key_path.exists());
+}
@@ -0,0 +1,58 @@
+use dashmap::DashMap;
+use once_cell::sync::Lazy;
+use prometheus::{register_int_gauge_vec, IntGaugeVec};
+pub use stdext::function_name;
+
+use crate::labels::Labels;
+
+static GAUGES: Lazy<DashMap<String, IntGaugeVec>> = Lazy::new(DashMap::new);
+
+pub trait Gauge {
+    fn set(
+        value: i64,
+        family: &str,
+        description: Option<&str>,
+        labels: &[&str],
+        label_values: &[&str],
+    );
+}
+
+impl Gauge for Labels {
+    fn set(
+        value: i64,
+        family: &str,
+        description: Option<&str>,
+        labels: &[&str],
+        label_values: &[&str],
+    ) {
+        let gauge = GAUGES.entry(family.to_string()).or_insert_with(|| {
+            register_int_gauge_vec!(family, description.unwrap_or_default(), labels).unwrap()
+        });
+
+        gauge.with_label_values(label_values).set(value);
+    }
+}
+
+#[macro_export]
+macro_rules! set_gauge {
+    ($family:expr, $description:expr, $value:expr $(, $($label:expr => $label_value:expr),*)?) => {
+        {
+            let function =
+                $crate::labels::Labels::extract_fn_name($crate::gauge::function_name!());
+            let default_labels = $crate::labels::Labels::new(function, module_path!());
+            let default_labels = default_labels.to_vec();
+
+            let additional_labels = vec![$($($label),*)?];
+            let additional_values = vec![$($($label_value),*)?];
+
+            let all_labels: Vec<_> = default_labels
+                .iter().map(|a| a.0).chain(additional_labels).collect();
+            let all_values: Vec<_> = default_labels
+                .iter().map(|a| a.1).chain(additional_values).collect();
+
+            <$crate::labels::Labels as $crate::gauge::Gauge>::set(
+                $value, $family, $description, &all_labels, &all_values
+            );
+        }
+    };
+}
@@ -1,4 +1,5 @@
 pub mod counter;
+pub mod gauge;
 pub mod histogram;
 pub mod labels;
 #[cfg(test)]
@@ -1,6 +1,12 @@
 use autometrics::settings::AutometricsSettingsBuilder;
 
-use crate::{histogram, increment_counter, DEFAULT_HISTOGRAM_BUCKETS, METRICS_SERVICE_NAME};
+use crate::{
+    histogram,
+    increment_counter,
+    set_gauge,
+    DEFAULT_HISTOGRAM_BUCKETS,
+    METRICS_SERVICE_NAME,
+};
 
 fn init() {
     let _ = AutometricsSettingsBuilder::default()
@@ -76,3 +82,20 @@ fn test_histogram_macro() {
         }
     }
 }
+
+#[test]
+fn test_gauge_macro() {
+    init();
+    set_gauge!("Test_Custom_Gauge", Some("A custom gauge"), 7);
+    set_gauge!("Test_Custom_Gauge", Some("A custom gauge"), 3);
+
+    let metric_families = prometheus::gather();
+    let gauge = metric_families
+        .iter()
+        .find(|mf| mf.get_name() == "Test_Custom_Gauge")
+        .expect("the gauge should be registered");
+
+    let metrics = gauge.get_metric();
+    assert_eq!(metrics.len(), 1);
+    assert_eq!(metrics[0].get_gauge().get_value(), 3.0);
+}
@@ -5,7 +5,7 @@
 
 use anyhow::Result;
 use lightning_interfaces::prelude::*;
-use lightning_interfaces::ShutdownController;
+use lightning_interfaces::{ShutdownController, ShutdownCoordinator, ShutdownPhase};
 use tokio::runtime::{Handle, Runtime};
 use tokio::task::JoinHandle;
 use tokio::time::timeout;
@@ -19,8 +19,8 @@ pub struct ContainedNode<C: Collection> {
     /// a node.
     provider: fdi::MultiThreadedProvider,
 
-    /// The shutdown controller that has its waiter in the provider.
-    shutdown: ShutdownController,
+    /// The shutdown coordinator that has its waiters in the provider.
+    shutdown: ShutdownCoordinator,
 
     /// A handle to the tokio runtime.
     runtime: Option<Runtime>,
@@ -32,11 +32,12 @@ impl<C: Collection> ContainedNode<C> {
     pub fn new(provider: fdi::MultiThreadedProvider, name: Option<String>) -> Self {
         let name = name.unwrap_or_else(|| "LIGHTNING".into());
 
-        // Create and insert the shutdown controller to the provider.
+        // Create and insert the shutdown waiters to the provider.
         let trace_shutdown = std::env::var("TRACE_SHUTDOWN").is_ok();
-        let shutdown = ShutdownController::new(trace_shutdown);
-        let waiter = shutdown.waiter();
-        provider.insert(waiter);
+        let shutdown = ShutdownCoordinator::new(trace_shutdown);
+        let waiters = shutdown.waiters();
+        provider.insert(waiters.get(ShutdownPhase::Services));
+        provider.insert(waiters);
 
         // Get the trigger permit from the shutdown controller to be passed into each thread.
         //let permit = shutdown.permit();
@@ -64,8 +65,13 @@ pub fn new(provider: fdi::MultiThreadedProvider, name: Option<String>) -> Self {
 
         // Run the `install_ctrlc_handlers` in the context of Tokio.
         let guard = runtime.enter();
-        // Will make the shutdown controller listen for ctrl+c.
-        shutdown.install_ctrlc_handlers();
+        // Will make the first phase listen for ctrl+c, the rest of the phases follow once
+        // `shutdown` is called.
+        shutdown
+            .controller(ShutdownPhase::Ingress)
+            .install_ctrlc_handlers();
+        // Bring the whole node down if a crucial task triggers the shutdown.
+        tokio::spawn(shutdown.propagate_shutdown());
         drop(guard);
 
         Self {
@@ -82,7 +88,7 @@ pub fn new(provider: fdi::MultiThreadedProvider, name: Option<String>) -> Self {
     pub fn spawn(&self) -> JoinHandle<Result<()>> {
         let provider = self.provider.clone();
 
-        let waiter = self.shutdown.waiter();
+        let waiter = self.shutdown.waiter(ShutdownPhase::Services);
         self.runtime.as_ref().unwrap().spawn_blocking(move || {
             let graph = C::build_graph();
             let mut provider = provider.get_local_provider();
@@ -107,62 +113,28 @@ pub fn spawn(&self) -> JoinHandle<Result<()>> {
     }
 
     /// Shut down the node and return a future that will be resolved when the node is fully down.
+    /// The [ShutdownPhase]s are shut down one after the other.
     ///
     /// Unlike other async method this function can trigger the shutdown without it being polled.
     /// In other words you can still trigger the shutdown event by calling this method and never
-    /// awaiting the returned future.
+    /// awaiting the returned future, in which case every phase goes down at once when the future
+    /// is dropped.
     pub fn shutdown(mut self) -> impl Future<Output = ()> {
         let handle =
             Handle::try_current().expect("calling from a non-tokio context not supported yet.");
 
-        // Take state out (because we implement drop).
-        let mut shutdown = std::mem::take(&mut self.shutdown);
-
-        // Tell the controller it's time to go down.
-        shutdown.trigger_shutdown();
+        // Tell the first phase it's time to go down.
+        self.shutdown
+            .controller(ShutdownPhase::Ingress)
+            .trigger_shutdown();
 
         let task_name = format!("{}::RuntimeDrop", self.name);
 
         async move {
-            for i in 0.. {
-                if timeout(Duration::from_secs(3), shutdown.wait_for_completion())
-                    .await
-                    .is_ok()
-                {
-                    // shutdown completed.
-                    break;
-                }
-
-                match i {
-                    0 | 1 => {
-                        // 3s, 6s
-                        tracing::trace!("Still shutting down...");
-                        continue;
-                    },
-                    2 => {
-                        // 9s
-                        tracing::warn!("Still shutting down...");
-                        continue;
-                    },
-                    _ => {
-                        // 12s
-                        tracing::error!("Shutdown taking too long..")
-                    },
-                }
-
-                if i == 9 {
-                    // 30s: timeout
-                    tracing::error!("Shutdown timed out. Force killing the runtime.");
-                    break;
-                }
-
-                let Some(iter) = shutdown.pending_backtraces() else {
-                    continue;
-                };
-
-                for (i, trace) in iter.enumerate() {
-                    eprintln!("Pending task backtrace #{i}:\n{trace:#?}");
-                }
+            for (phase, shutdown) in self.shutdown.phases_mut() {
+                tracing::trace!("Shutting down the {phase:?} phase.");
+                shutdown.trigger_shutdown();
+                wait_for_completion(shutdown).await;
             }
 
             let runtime = self.runtime.take().unwrap();
@@ -209,3 +181,48 @@ fn drop(&mut self) {
         }
     }
 }
+
+/// Wait for the futures waiting on the shutdown of a controller to drop, printing the pending
+/// backtraces if it takes too long. Gives up after 30 seconds.
+async fn wait_for_completion(shutdown: &mut ShutdownController) {
+    for i in 0.. {
+        if timeout(Duration::from_secs(3), shutdown.wait_for_completion())
+            .await
+            .is_ok()
+        {
+            // shutdown completed.
+            break;
+        }
+
+        match i {
+            0 | 1 => {
+                // 3s, 6s
+                tracing::trace!("Still shutting down...");
+                continue;
+            },
+            2 => {
+                // 9s
+                tracing::warn!("Still shutting down...");
+                continue;
+            },
+            _ => {
+                // 12s
+                tracing::error!("Shutdown taking too long..")
+            },
+        }
+
+        if i == 9 {
+            // 30s: timeout
+            tracing::error!("Shutdown timed out. Force killing the runtime.");
+            break;
+        }
+
+        let Some(iter) = shutdown.pending_backtraces() else {
+            continue;
+        };
+
+        for (i, trace) in iter.enumerate() {
+            eprintln!("Pending task backtrace #{i}:\n{trace:#?}");
+        }
+    }
+}
@@ -1,16 +1,26 @@
+use std::sync::{Arc, Mutex};
 use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
+use fleek_crypto::TransactionSender;
 use futures::future::{select, Either};
 use lightning_interfaces::prelude::*;
-use lightning_interfaces::types::{Block, BlockExecutionResponse};
+use lightning_interfaces::types::{
+    Block,
+    BlockExecutionResponse,
+    TransactionDestination,
+    TransactionReceipt,
+    UpdateMethod,
+};
 use lightning_interfaces::{
     BlockExecutedNotification,
+    ContentRegistryChangedNotification,
     EpochChangedNotification,
+    Notification,
     OwnedShutdownSignal,
 };
 use lightning_utils::application::QueryRunnerExt;
 use tokio::pin;
-use tokio::sync::broadcast;
+use tokio::sync::{broadcast, mpsc};
 use tokio::time::sleep;
 
 #[cfg(test)]
@@ -18,7 +28,7 @@
 
 pub struct Notifier<C: Collection> {
     query_runner: c![C::ApplicationInterface::SyncExecutor],
-    notify: NotificationsEmitter,
+    notify: NotificationsEmitter<c![C::ApplicationInterface::SyncExecutor]>,
     waiter: ShutdownWaiter,
 }
 
@@ -37,9 +47,10 @@ fn new(
         app: &c![C::ApplicationInterface],
         fdi::Cloned(waiter): fdi::Cloned<ShutdownWaiter>,
     ) -> Self {
+        let query_runner = app.sync_query();
         Self {
-            query_runner: app.sync_query(),
-            notify: NotificationsEmitter::default(),
+            notify: NotificationsEmitter::new(query_runner.clone()),
+            query_runner,
             waiter,
         }
     }
@@ -52,7 +63,7 @@ fn build_graph() -> fdi::DependencyGraph {
 }
 
 impl<C: Collection> NotifierInterface<C> for Notifier<C> {
-    type Emitter = NotificationsEmitter;
+    type Emitter = NotificationsEmitter<c![C::ApplicationInterface::SyncExecutor]>;
 
     fn get_emitter(&self) -> Self::Emitter {
         self.notify.clone()
@@ -72,6 +83,31 @@ fn subscribe_epoch_changed(&self) -> impl Subscriber<EpochChangedNotification> {
         )
     }
 
+    fn subscribe_content_registry_changed(
+        &self,
+    ) -> impl Subscriber<ContentRegistryChangedNotification> {
+        BroadcastSub(
+            self.notify.content_registry_changed.subscribe(),
+            self.waiter.wait_for_shutdown_owned(),
+        )
+    }
+
+    fn subscribe_filtered(
+        &self,
+        filter: impl Fn(&Notification) -> bool + Send + Sync + 'static,
+    ) -> impl Subscriber<Notification> {
+        let (tx, rx) = mpsc::channel(64);
+        self.notify
+            .filtered
+            .lock()
+            .expect("Failed to acquire lock")
+            .push(FilteredSender {
+                filter: Box::new(filter),
+                tx,
+            });
+        FilteredSub(rx, self.waiter.wait_for_shutdown_owned())
+    }
+
     fn subscribe_before_epoch_change(&self, duration: Duration) -> impl Subscriber<()> {
         let (sender, rx) = broadcast::channel(8);
         let epoch_changed = BroadcastSub(
@@ -87,22 +123,112 @@ fn subscribe_before_epoch_change(&self, duration: Duration) -> impl Subscriber<(
 }
 
 #[derive(Clone)]
-pub struct NotificationsEmitter {
+pub struct NotificationsEmitter<Q> {
+    /// Used to resolve the index of the nodes that updated the content registry.
+    query_runner: Q,
     block_executed: broadcast::Sender<BlockExecutedNotification>,
     epoch_changed: broadcast::Sender<EpochChangedNotification>,
+    content_registry_changed: broadcast::Sender<ContentRegistryChangedNotification>,
+    filtered: Arc<Mutex<Vec<FilteredSender>>>,
+}
+
+/// The sending half of a filtered subscription.
+struct FilteredSender {
+    filter: Box<dyn Fn(&Notification) -> bool + Send + Sync>,
+    tx: mpsc::Sender<Notification>,
 }
 
-impl Default for NotificationsEmitter {
-    fn default() -> Self {
+impl<Q: SyncQueryRunnerInterface> NotificationsEmitter<Q> {
+    fn new(query_runner: Q) -> Self {
         Self {
+            query_runner,
             block_executed: broadcast::channel(64).0,
             epoch_changed: broadcast::channel(16).0,
+            content_registry_changed: broadcast::channel(64).0,
+            filtered: Default::default(),
+        }
+    }
+
+    fn has_filtered_subscribers(&self) -> bool {
+        !self
+            .filtered
+            .lock()
+            .expect("Failed to acquire lock")
+            .is_empty()
+    }
+
+    /// Sends the notification to the filtered subscribers that are interested in it, and forgets
+    /// about the subscribers that were dropped.
+    fn emit_filtered(&self, notification: Notification) {
+        self.filtered
+            .lock()
+            .expect("Failed to acquire lock")
+            .retain(|sub| {
+                if sub.tx.is_closed() {
+                    return false;
+                }
+                if (sub.filter)(&notification) {
+                    // Like a lagging broadcast receiver, a subscriber that does not keep up misses
+                    // notifications.
+                    let _ = sub.tx.try_send(notification.clone());
+                }
+                true
+            });
+    }
+
+    /// Returns the content registry change made by a transaction, if it was a successful
+    /// `UpdateContentRegistry` from a known node.
+    fn get_content_registry_change(
+        &self,
+        receipt: &TransactionReceipt,
+    ) -> Option<ContentRegistryChangedNotification> {
+        let TransactionDestination::Fleek(UpdateMethod::UpdateContentRegistry { updates }) =
+            &receipt.to
+        else {
+            return None;
+        };
+        if !receipt.response.is_success() {
+            return None;
         }
+        let TransactionSender::NodeMain(public_key) = &receipt.from else {
+            return None;
+        };
+        let node = self.query_runner.pubkey_to_index(public_key)?;
+
+        let (removed, added) = updates
+            .iter()
+            .partition::<Vec<_>, _>(|update| update.remove);
+        Some(ContentRegistryChangedNotification {
+            node,
+            added: added.into_iter().map(|update| update.uri).collect(),
+            removed: removed.into_iter().map(|update| update.uri).collect(),
+        })
     }
 }
 
-impl Emitter for NotificationsEmitter {
+impl<Q: SyncQueryRunnerInterface> Emitter for NotificationsEmitter<Q> {
     fn new_block(&self, block: Block, response: BlockExecutionResponse) {
+        let has_filtered_subscribers = self.has_filtered_subscribers();
+
+        // Only look for content registry changes if someone is interested in them.
+        if self.content_registry_changed.receiver_count() > 0 || has_filtered_subscribers {
+            for receipt in &response.txn_receipts {
+                if let Some(change) = self.get_content_registry_change(receipt) {
+                    if has_filtered_subscribers {
+                        self.emit_filtered(Notification::ContentRegistryChanged(change.clone()));
+                    }
+                    let _ = self.content_registry_changed.send(change);
+                }
+            }
+        }
+
+        if has_filtered_subscribers {
+            self.emit_filtered(Notification::BlockExecuted(BlockExecutedNotification {
+                block: block.clone(),
+                response: response.clone(),
+            }));
+        }
+
         // The send could only fail if there are no active listeners at the moment
         // which is something we don't really care about and is expected by us.
         let _ = self
@@ -111,10 +237,14 @@ fn new_block(&self, block: Block, response: BlockExecutionResponse) {
     }
 
     fn epoch_changed(&self, current_epoch: u64, last_epoch_hash: [u8; 32]) {
-        let _ = self.epoch_changed.send(EpochChangedNotification {
+        let notification = EpochChangedNotification {
             current_epoch,
             last_epoch_hash,
-        });
+        };
+        if self.has_filtered_subscribers() {
+            self.emit_filtered(Notification::EpochChanged(notification.clone()));
+        }
+        let _ = self.epoch_changed.send(notification);
     }
 }
 
@@ -173,6 +303,41 @@ async fn last(&mut self) -> Option<T> {
     }
 }
 
+/// Provides an implementation for [`Subscriber`] for filtered subscriptions.
+pub(crate) struct FilteredSub(pub mpsc::Receiver<Notification>, pub OwnedShutdownSignal);
+
+impl Subscriber<Notification> for FilteredSub {
+    async fn recv(&mut self) -> Option<Notification> {
+        let recv = self.0.recv();
+        pin!(recv);
+
+        match select(recv, &mut self.1).await {
+            Either::Left((item, _)) => item,
+            Either::Right(_) => None,
+        }
+    }
+
+    async fn last(&mut self) -> Option<Notification> {
+        let mut maybe_last = None;
+
+        loop {
+            match self.0.try_recv() {
+                Ok(item) => {
+                    maybe_last = Some(item);
+                },
+                Err(mpsc::error::TryRecvError::Empty) => break,
+                Err(mpsc::error::TryRecvError::Disconnected) => return maybe_last,
+            }
+        }
+
+        if maybe_last.is_some() {
+            return maybe_last;
+        }
+
+        self.recv().await
+    }
+}
+
 async fn before_epoch_change<Q>(
     sender: broadcast::Sender<()>,
     query_runner: Q,
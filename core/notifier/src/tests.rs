@@ -1,9 +1,48 @@
-use lightning_interfaces::{ShutdownController, Subscriber};
+use fleek_crypto::{
+    AccountOwnerSecretKey,
+    ConsensusSecretKey,
+    NodeSecretKey,
+    SecretKey,
+    TransactionSender,
+    TransactionSignature,
+};
+use lightning_application::app::Application;
+use lightning_application::config::Config as AppConfig;
+use lightning_application::genesis::{Genesis, GenesisNode};
+use lightning_application::query_runner::QueryRunner;
+use lightning_interfaces::prelude::*;
+use lightning_interfaces::types::{
+    Block,
+    BlockExecutionResponse,
+    ContentUpdate,
+    NodePorts,
+    TransactionRequest,
+    UpdateMethod,
+    UpdatePayload,
+    UpdateRequest,
+};
+use lightning_interfaces::{
+    partial,
+    ContentRegistryChangedNotification,
+    Notification,
+    ShutdownController,
+};
+use lightning_test_utils::consensus::{Config as ConsensusConfig, MockConsensus, MockForwarder};
+use lightning_test_utils::json_config::JsonConfigProvider;
+use tempfile::{tempdir, TempDir};
 use tokio::sync::broadcast;
 use tokio::test;
 use tokio::time::{sleep, timeout, Duration};
 
-use crate::BroadcastSub;
+use crate::{BroadcastSub, Notifier};
+
+partial!(TestBinding {
+    ConfigProviderInterface = JsonConfigProvider;
+    ApplicationInterface = Application<Self>;
+    NotifierInterface = Notifier<Self>;
+    ConsensusInterface = MockConsensus<Self>;
+    ForwarderInterface = MockForwarder<Self>;
+});
 
 #[test]
 async fn sub_is_cancel_safe() {
@@ -150,3 +189,163 @@ async fn sub_shutdown_3() {
     let ret = timeout(Duration::from_millis(100), sub1.last()).await;
     assert_eq!(ret, Ok(None));
 }
+
+async fn init_node(temp_dir: &TempDir, genesis: &Genesis) -> Node<TestBinding> {
+    let genesis_path = genesis
+        .write_to_dir(temp_dir.path().to_path_buf().try_into().unwrap())
+        .unwrap();
+
+    let node = Node::<TestBinding>::init(
+        JsonConfigProvider::default()
+            .with::<Application<TestBinding>>(AppConfig::test(genesis_path))
+            .with::<MockConsensus<TestBinding>>(ConsensusConfig {
+                min_ordering_time: 0,
+                max_ordering_time: 0,
+                probability_txn_lost: 0.0,
+                transactions_to_lose: Default::default(),
+                new_block_interval: Duration::from_secs(0),
+                fault_injector: None,
+            }),
+    )
+    .unwrap();
+    node.start().await;
+    node
+}
+
+#[test]
+async fn content_registry_changed_is_emitted() {
+    let temp_dir = tempdir().unwrap();
+
+    let node_secret_key = NodeSecretKey::generate();
+    let node_public_key = node_secret_key.to_pk();
+    let mut genesis = Genesis::default();
+    genesis.node_info.push(GenesisNode::new(
+        AccountOwnerSecretKey::generate().to_pk().into(),
+        node_public_key,
+        "127.0.0.1".parse().unwrap(),
+        ConsensusSecretKey::generate().to_pk(),
+        "127.0.0.1".parse().unwrap(),
+        node_public_key,
+        NodePorts::default(),
+        None,
+        true,
+    ));
+    let mut node = init_node(&temp_dir, &genesis).await;
+
+    let query_runner = node.provider.get::<QueryRunner>().clone();
+    let node_index = query_runner.pubkey_to_index(&node_public_key).unwrap();
+
+    let notifier = node.provider.get::<Notifier<TestBinding>>();
+    let mut sub = notifier.subscribe_content_registry_changed();
+
+    // Provide one uri and stop providing another one we provided before.
+    let socket = node
+        .provider
+        .get::<MockForwarder<TestBinding>>()
+        .mempool_socket();
+    let updates = [
+        vec![ContentUpdate {
+            uri: [2; 32],
+            remove: false,
+        }],
+        vec![
+            ContentUpdate {
+                uri: [1; 32],
+                remove: false,
+            },
+            ContentUpdate {
+                uri: [2; 32],
+                remove: true,
+            },
+        ],
+    ];
+    for (nonce, updates) in (1..).zip(updates) {
+        let payload = UpdatePayload {
+            sender: TransactionSender::NodeMain(node_public_key),
+            nonce,
+            method: UpdateMethod::UpdateContentRegistry { updates },
+            chain_id: genesis.chain_id,
+        };
+        let signature = TransactionSignature::NodeMain(node_secret_key.sign(&payload.to_digest()));
+        socket
+            .run(TransactionRequest::UpdateRequest(UpdateRequest {
+                signature,
+                payload,
+            }))
+            .await
+            .unwrap()
+            .unwrap();
+    }
+
+    let first = timeout(Duration::from_secs(5), sub.recv()).await.unwrap();
+    assert_eq!(
+        first,
+        Some(ContentRegistryChangedNotification {
+            node: node_index,
+            added: vec![[2; 32]],
+            removed: vec![],
+        })
+    );
+    let second = timeout(Duration::from_secs(5), sub.recv()).await.unwrap();
+    assert_eq!(
+        second,
+        Some(ContentRegistryChangedNotification {
+            node: node_index,
+            added: vec![[1; 32]],
+            removed: vec![[2; 32]],
+        })
+    );
+
+    node.shutdown().await;
+}
+
+#[test]
+async fn filtered_sub_only_receives_matching_notifications() {
+    let temp_dir = tempdir().unwrap();
+    let mut node = init_node(&temp_dir, &Genesis::default()).await;
+
+    let notifier = node.provider.get::<Notifier<TestBinding>>();
+    let mut epoch_sub = notifier
+        .subscribe_filtered(|notification| matches!(notification, Notification::EpochChanged(_)));
+    let mut all_sub = notifier.subscribe_filtered(|_| true);
+
+    let emitter = notifier.get_emitter();
+    emitter.new_block(
+        Block {
+            digest: [1; 32],
+            sub_dag_index: 0,
+            transactions: vec![],
+        },
+        BlockExecutionResponse {
+            block_number: 1,
+            block_hash: [1; 32],
+            parent_hash: [0; 32],
+            change_epoch: true,
+            node_registry_delta: vec![],
+            txn_receipts: vec![],
+        },
+    );
+    emitter.epoch_changed(1, [2; 32]);
+
+    assert!(matches!(
+        all_sub.recv().await,
+        Some(Notification::BlockExecuted(n)) if n.response.block_number == 1
+    ));
+    assert!(matches!(
+        all_sub.recv().await,
+        Some(Notification::EpochChanged(n)) if n.current_epoch == 1
+    ));
+
+    assert!(matches!(
+        epoch_sub.recv().await,
+        Some(Notification::EpochChanged(n)) if n.current_epoch == 1
+    ));
+    // The block was never delivered to the filtered subscriber.
+    assert!(
+        timeout(Duration::from_millis(100), epoch_sub.recv())
+            .await
+            .is_err()
+    );
+
+    node.shutdown().await;
+}
@@ -10,7 +10,7 @@
 use lightning_blockstore::config::Config as BlockstoreConfig;
 use lightning_indexer::Indexer;
 use lightning_interfaces::prelude::*;
-use lightning_interfaces::types::NodePorts;
+use lightning_interfaces::types::{HandshakePorts, NodePorts};
 use lightning_signer::Signer;
 use lightning_test_utils::consensus::{Config as ConsensusConfig, MockConsensus, MockForwarder};
 use lightning_test_utils::json_config::JsonConfigProvider;
@@ -95,7 +95,11 @@ async fn create_app_state(temp_dir: &TempDir) -> AppState {
             rpc: 38103,
             pool: 38104,
             pinger: 38106,
-            handshake: Default::default(),
+            handshake: HandshakePorts {
+                http: 38220,
+                webrtc: 38320,
+                webtransport: 38321,
+            },
         },
         None,
         true,
@@ -123,6 +127,7 @@ async fn create_app_state(temp_dir: &TempDir) -> AppState {
                             .clone()
                             .try_into()
                             .unwrap(),
+                        max_total_bytes: None,
                     })
                     .with::<Application<TestBinding>>(AppConfig::test(genesis_path))
                     .with::<MockConsensus<TestBinding>>(ConsensusConfig {
@@ -131,6 +136,7 @@ async fn create_app_state(temp_dir: &TempDir) -> AppState {
                         probability_txn_lost: 0.0,
                         transactions_to_lose: HashSet::new(),
                         new_block_interval: Duration::from_secs(5),
+                        fault_injector: None,
                     }),
             )
             .with(keystore),
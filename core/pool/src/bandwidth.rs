@@ -0,0 +1,42 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+
+/// The bytes exchanged with a peer over the connections with it, since we last had no connection
+/// with the peer.
+///
+/// Only the bytes of the messages, requests and responses of the services are counted. The
+/// framing added by the pool is not.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct PeerBandwidthStats {
+    pub bytes_sent: u64,
+    pub bytes_received: u64,
+}
+
+/// The counters behind [PeerBandwidthStats], shared by the connections with a peer.
+#[derive(Clone, Default)]
+pub struct Bandwidth(Arc<Counters>);
+
+#[derive(Default)]
+struct Counters {
+    sent: AtomicU64,
+    received: AtomicU64,
+}
+
+impl Bandwidth {
+    #[inline]
+    pub fn record_sent(&self, bytes: usize) {
+        self.0.sent.fetch_add(bytes as u64, Ordering::Relaxed);
+    }
+
+    #[inline]
+    pub fn record_received(&self, bytes: usize) {
+        self.0.received.fetch_add(bytes as u64, Ordering::Relaxed);
+    }
+
+    pub fn stats(&self) -> PeerBandwidthStats {
+        PeerBandwidthStats {
+            bytes_sent: self.0.sent.load(Ordering::Relaxed),
+            bytes_received: self.0.received.load(Ordering::Relaxed),
+        }
+    }
+}
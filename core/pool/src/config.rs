@@ -9,6 +9,32 @@ pub struct Config {
     pub max_idle_timeout: Duration,
     pub address: SocketAddr,
     pub http: Option<SocketAddr>,
+    /// Close connections that have not been used for this long. They are dialed again the next
+    /// time we send something to the peer. Connections with the peers in our topology are always
+    /// kept open, and all connections are if this is not set.
+    #[serde(default, with = "humantime_serde")]
+    pub idle_timeout: Option<Duration>,
+    /// The number of connections we keep with a single peer at the same time. New connections
+    /// with a peer that is already at the limit are closed, whichever side dialed.
+    #[serde(default = "default_max_connections_per_peer")]
+    pub max_connections_per_peer: usize,
+    /// The ciphers we prefer for the connections we dial, fastest first. The first cipher in
+    /// this list that the peer also supports is used. Ciphers that are not listed are still
+    /// accepted after the listed ones.
+    #[serde(default)]
+    pub cipher_preference: Vec<Cipher>,
+    /// The largest message in bytes we read from a peer. Messages are rejected from their length
+    /// prefix, before any of them is buffered, and the stream they came on is dropped.
+    #[serde(default = "default_max_message_size")]
+    pub max_message_size: usize,
+}
+
+/// The TLS 1.3 ciphers the pool can use.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Cipher {
+    ChaCha20Poly1305,
+    Aes256Gcm,
+    Aes128Gcm,
 }
 
 impl Default for Config {
@@ -17,6 +43,19 @@ fn default() -> Self {
             max_idle_timeout: Duration::from_millis(30000),
             address: "0.0.0.0:4300".parse().expect("Hardcoded socket address"),
             http: None,
+            idle_timeout: None,
+            max_connections_per_peer: default_max_connections_per_peer(),
+            cipher_preference: Vec::new(),
+            max_message_size: default_max_message_size(),
         }
     }
 }
+
+fn default_max_connections_per_peer() -> usize {
+    // One connection, plus a redundant one for when both peers dial each other at once.
+    2
+}
+
+fn default_max_message_size() -> usize {
+    10 << 20
+}
@@ -1,4 +1,6 @@
 use std::io;
+use std::sync::Arc;
+use std::time::Duration;
 
 use anyhow::Result;
 use bytes::{Buf, Bytes};
@@ -8,8 +10,10 @@
 use tokio::io::{AsyncReadExt, AsyncWriteExt};
 use tokio::sync::mpsc::{Receiver, Sender};
 use tokio::sync::oneshot;
+use tokio::time::Instant;
 use tokio_util::codec::{FramedRead, FramedWrite, LengthDelimitedCodec};
 
+use crate::bandwidth::Bandwidth;
 use crate::event::{Event, Message};
 use crate::muxer::{ConnectionInterface, NetChannel};
 use crate::provider;
@@ -26,6 +30,15 @@ pub struct Context<C> {
     service_request_rx: Receiver<Request>,
     /// Send events from this connection.
     connection_event_tx: Sender<Event>,
+    /// Close the connection after this long without any traffic.
+    idle_timeout: Option<Duration>,
+    /// The peers in our topology, the connection is not closed for being idle while the peer is
+    /// one of them.
+    topology: Arc<scc::HashSet<NodeIndex>>,
+    /// The largest message we read from the peer.
+    max_message_size: usize,
+    /// The bytes exchanged with the peer.
+    bandwidth: Bandwidth,
 }
 
 impl<C: ConnectionInterface> Context<C> {
@@ -34,20 +47,84 @@ pub fn new(
         peer: NodeIndex,
         service_request_rx: Receiver<Request>,
         connection_event_tx: Sender<Event>,
+        idle_timeout: Option<Duration>,
+        topology: Arc<scc::HashSet<NodeIndex>>,
+        max_message_size: usize,
+        bandwidth: Bandwidth,
     ) -> Self {
         Self {
             connection,
             peer,
             service_request_rx,
             connection_event_tx,
+            idle_timeout,
+            topology,
+            max_message_size,
+            bandwidth,
         }
     }
 }
 
+/// Handed out to everything that uses a stream of the connection, so that we know if there is
+/// still a request in flight when the connection is idle, and to count the bytes exchanged.
+#[derive(Clone)]
+pub struct Activity {
+    in_flight: Arc<()>,
+    bandwidth: Bandwidth,
+}
+
+impl Activity {
+    fn new(bandwidth: Bandwidth) -> Self {
+        Self {
+            in_flight: Arc::new(()),
+            bandwidth,
+        }
+    }
+
+    fn in_flight(&self) -> bool {
+        // One reference is held by the connection loop.
+        Arc::strong_count(&self.in_flight) > 1
+    }
+
+    #[inline]
+    pub fn record_sent(&self, bytes: usize) {
+        self.bandwidth.record_sent(bytes);
+    }
+
+    #[inline]
+    pub fn record_received(&self, bytes: usize) {
+        self.bandwidth.record_received(bytes);
+    }
+}
+
+/// Resolves at the deadline, or never if there is none.
+async fn idle(deadline: Option<Instant>) {
+    match deadline {
+        Some(deadline) => tokio::time::sleep_until(deadline).await,
+        None => futures::future::pending().await,
+    }
+}
+
 pub async fn connection_loop<C: ConnectionInterface>(mut ctx: Context<C>) -> Result<()> {
     let mut connection = ctx.connection.clone();
+    let activity = Activity::new(ctx.bandwidth.clone());
+    let idle_timeout = ctx.idle_timeout;
+    let idle_deadline = || idle_timeout.map(|timeout| Instant::now() + timeout);
+    let mut deadline = idle_deadline();
     loop {
         tokio::select! {
+            _ = idle(deadline) => {
+                if activity.in_flight() || ctx.topology.contains(&ctx.peer) {
+                    deadline = idle_deadline();
+                    continue;
+                }
+                tracing::trace!(
+                    "closing the idle connection with peer {}",
+                    ctx.peer
+                );
+                ctx.connection.close(0u8, b"close from idle");
+                break
+            }
             accept_result = ctx.connection.accept_bi_stream() => {
                 let (stream_tx, stream_rx) = match accept_result {
                     Ok(streams) => streams,
@@ -55,14 +132,17 @@ pub async fn connection_loop<C: ConnectionInterface>(mut ctx: Context<C>) -> Res
                         return Err(e.into());
                     }
                 };
+                deadline = idle_deadline();
                 let connection_event_tx = ctx.connection_event_tx.clone();
                 let peer = ctx.peer;
+                let activity = activity.clone();
                 spawn!(async move {
                     if let Err(e) =
                         handle_incoming_bi_stream::<C>(
                             peer,
                             (stream_tx, stream_rx),
-                            connection_event_tx
+                            connection_event_tx,
+                            activity,
                         ).await
                     {
                         tracing::error!(
@@ -78,14 +158,19 @@ pub async fn connection_loop<C: ConnectionInterface>(mut ctx: Context<C>) -> Res
                         return Err(e.into());
                     }
                 };
+                deadline = idle_deadline();
                 let connection_event_tx = ctx.connection_event_tx.clone();
                 let peer = ctx.peer;
+                let activity = activity.clone();
+                let max_message_size = ctx.max_message_size;
                 spawn!(async move {
                     if let Err(e) =
                         handle_incoming_uni_stream::<C>(
                             peer,
                             stream_rx,
-                            connection_event_tx
+                            connection_event_tx,
+                            activity,
+                            max_message_size,
                         ).await
                     {
                         tracing::error!(
@@ -98,11 +183,13 @@ pub async fn connection_loop<C: ConnectionInterface>(mut ctx: Context<C>) -> Res
                 match request {
                     Some(Request::SendMessage(message)) => {
                         tracing::trace!("handling a broadcast message request");
+                        deadline = idle_deadline();
                         // We need to create a new stream on the connection.
                         let connection = ctx.connection.clone();
                         let peer = ctx.peer;
+                        let activity = activity.clone();
                         spawn!(async move{
-                            if let Err(e) = send_message(connection, message).await {
+                            if let Err(e) = send_message(connection, message, activity).await {
                                 tracing::error!(
                                     "failed to send message to peer with index {peer}: {e:?}"
                                 );
@@ -111,15 +198,18 @@ pub async fn connection_loop<C: ConnectionInterface>(mut ctx: Context<C>) -> Res
                     },
                     Some(Request::SendReqResp { service, request, respond }) => {
                         tracing::trace!("handling new outgoing request");
+                        deadline = idle_deadline();
                         // We need to create a new stream on the connection for the channel.
                         let connection = ctx.connection.clone();
                         let peer = ctx.peer;
+                        let activity = activity.clone();
                         spawn!(async move {
                             if let Err(e) = send_request(
                                 connection,
                                 service,
                                 request,
-                                respond
+                                respond,
+                                activity,
                             ).await {
                                 tracing::error!(
                                     "there was an error when sending request to {peer}: {e:?}"
@@ -150,10 +240,18 @@ async fn handle_incoming_uni_stream<C: ConnectionInterface>(
     peer: NodeIndex,
     stream_rx: C::RecvStream,
     connection_event_tx: Sender<Event>,
+    activity: Activity,
+    max_message_size: usize,
 ) -> Result<()> {
-    let mut stream = FramedRead::new(stream_rx, LengthDelimitedCodec::new());
+    // The codec checks the length prefix before it reads the frame, so a larger message is never
+    // buffered. The frame also carries the service scope byte.
+    let codec = LengthDelimitedCodec::builder()
+        .max_frame_length(max_message_size + 1)
+        .new_codec();
+    let mut stream = FramedRead::new(stream_rx, codec);
     while let Some(message) = stream.next().await {
         let message = Message::try_from(message?)?;
+        activity.record_received(message.payload.len());
         connection_event_tx
             .send(Event::MessageReceived {
                 remote: peer,
@@ -169,6 +267,7 @@ async fn handle_incoming_bi_stream<C: ConnectionInterface>(
     peer: NodeIndex,
     (stream_tx, mut stream_rx): (C::SendStream, C::RecvStream),
     connection_event_tx: Sender<Event>,
+    activity: Activity,
 ) -> Result<()> {
     // The peer opened a stream.
     // The first byte identifies the service.
@@ -182,11 +281,12 @@ async fn handle_incoming_bi_stream<C: ConnectionInterface>(
         .next()
         .await
         .ok_or(anyhow::anyhow!("missing expected header"))??;
+    activity.record_received(bytes_header.len());
     let header = RequestHeader {
         peer,
         bytes: bytes_header,
     };
-    let request = provider::Request::new(channel);
+    let request = provider::Request::new(channel, activity);
 
     connection_event_tx
         .send(Event::RequestReceived {
@@ -198,10 +298,16 @@ async fn handle_incoming_bi_stream<C: ConnectionInterface>(
         .map_err(|_| anyhow::anyhow!("failed to send incoming network event"))
 }
 
-async fn send_message<C: ConnectionInterface>(mut connection: C, message: Message) -> Result<()> {
+async fn send_message<C: ConnectionInterface>(
+    mut connection: C,
+    message: Message,
+    activity: Activity,
+) -> Result<()> {
     let stream_tx = connection.open_uni_stream().await?;
     let mut writer = FramedWrite::new(stream_tx, LengthDelimitedCodec::new());
+    let len = message.payload.len();
     writer.send(message.into()).await?;
+    activity.record_sent(len);
     writer.close().await.map_err(Into::into)
 }
 
@@ -210,6 +316,7 @@ async fn send_request<C: ConnectionInterface>(
     service: ServiceScope,
     request: Bytes,
     respond: oneshot::Sender<io::Result<Response>>,
+    activity: Activity,
 ) -> Result<()> {
     let sending_request = async {
         let (mut stream_tx, stream_rx) = connection.open_bi_stream().await?;
@@ -221,7 +328,9 @@ async fn send_request<C: ConnectionInterface>(
         let mut channel = Box::new(NetChannel::new(stream_rx, stream_tx));
 
         // Send our request.
+        let len = request.len();
         channel.send(request).await?;
+        activity.record_sent(len);
 
         // Read the response header.
         let mut header = channel.next().await.ok_or(io::ErrorKind::BrokenPipe)??;
@@ -238,7 +347,7 @@ async fn send_request<C: ConnectionInterface>(
             .try_into()
             .map_err(|_| io::ErrorKind::Other)?;
 
-        Ok::<Response, io::Error>(Response::new(status, channel))
+        Ok::<Response, io::Error>(Response::new(status, channel, activity))
     };
 
     match sending_request.await {
@@ -271,3 +380,227 @@ pub enum Request {
         respond: oneshot::Sender<Stats>,
     },
 }
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use std::net::SocketAddr;
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::Mutex;
+
+    use fleek_crypto::NodePublicKey;
+    use tokio::io::DuplexStream;
+    use tokio::sync::mpsc;
+
+    use super::*;
+
+    /// A connection to a peer that never opens streams and never answers our requests.
+    #[derive(Clone, Default)]
+    pub(crate) struct SilentConnection {
+        identity: Option<NodePublicKey>,
+        id: usize,
+        closed: Arc<AtomicBool>,
+        // The ends of the streams of the peer.
+        remote: Arc<Mutex<Vec<DuplexStream>>>,
+    }
+
+    impl SilentConnection {
+        pub(crate) fn new(identity: NodePublicKey, id: usize) -> Self {
+            Self {
+                identity: Some(identity),
+                id,
+                ..Default::default()
+            }
+        }
+
+        pub(crate) fn is_closed(&self) -> bool {
+            self.closed.load(Ordering::Relaxed)
+        }
+    }
+
+    impl ConnectionInterface for SilentConnection {
+        type SendStream = DuplexStream;
+        type RecvStream = DuplexStream;
+
+        async fn open_bi_stream(&mut self) -> io::Result<(Self::SendStream, Self::RecvStream)> {
+            let (tx, remote_rx) = tokio::io::duplex(1024);
+            let (rx, remote_tx) = tokio::io::duplex(1024);
+            self.remote.lock().unwrap().extend([remote_rx, remote_tx]);
+            Ok((tx, rx))
+        }
+
+        async fn open_uni_stream(&mut self) -> io::Result<Self::SendStream> {
+            let (tx, remote_rx) = tokio::io::duplex(1024);
+            self.remote.lock().unwrap().push(remote_rx);
+            Ok(tx)
+        }
+
+        async fn accept_bi_stream(&mut self) -> io::Result<(Self::SendStream, Self::RecvStream)> {
+            futures::future::pending().await
+        }
+
+        async fn accept_uni_stream(&mut self) -> io::Result<Self::RecvStream> {
+            futures::future::pending().await
+        }
+
+        fn peer_identity(&self) -> Option<NodePublicKey> {
+            self.identity
+        }
+
+        fn remote_address(&self) -> SocketAddr {
+            SocketAddr::from(([127, 0, 0, 1], 0))
+        }
+
+        fn connection_id(&self) -> usize {
+            self.id
+        }
+
+        fn stats(&self) -> Stats {
+            Stats {
+                rtt: Duration::ZERO,
+                lost_packets: 0,
+                sent_packets: 0,
+                congestion_events: 0,
+                cwnd: 0,
+                black_holes_detected: 0,
+            }
+        }
+
+        fn close(&self, _error_code: u8, _reason: &[u8]) {
+            self.closed.store(true, Ordering::Relaxed);
+        }
+    }
+
+    fn spawn_loop(
+        connection: SilentConnection,
+        idle_timeout: Duration,
+        topology: Arc<scc::HashSet<NodeIndex>>,
+    ) -> (Sender<Request>, tokio::task::JoinHandle<Result<()>>) {
+        let (request_tx, request_rx) = mpsc::channel(8);
+        let (event_tx, _) = mpsc::channel(8);
+        let ctx = Context::new(
+            connection,
+            0,
+            request_rx,
+            event_tx,
+            Some(idle_timeout),
+            topology,
+            10 << 20,
+            Bandwidth::default(),
+        );
+        (request_tx, tokio::spawn(connection_loop(ctx)))
+    }
+
+    #[tokio::test]
+    async fn test_idle_connection_is_closed() {
+        let connection = SilentConnection::default();
+        let (_request_tx, handle) = spawn_loop(
+            connection.clone(),
+            Duration::from_millis(100),
+            Arc::default(),
+        );
+
+        tokio::time::timeout(Duration::from_secs(1), handle)
+            .await
+            .expect("the idle connection should have been closed")
+            .unwrap()
+            .unwrap();
+        assert!(connection.is_closed());
+    }
+
+    #[tokio::test]
+    async fn test_idle_connection_with_topology_peer_is_kept_open() {
+        let connection = SilentConnection::default();
+        let topology = Arc::new(scc::HashSet::default());
+        let _ = topology.insert(0);
+        let (_request_tx, handle) = spawn_loop(
+            connection.clone(),
+            Duration::from_millis(100),
+            topology.clone(),
+        );
+
+        tokio::time::sleep(Duration::from_millis(300)).await;
+        assert!(!handle.is_finished());
+        assert!(!connection.is_closed());
+
+        // Once the peer leaves our topology the connection is closed when it is idle.
+        let _ = topology.remove(&0);
+        tokio::time::timeout(Duration::from_secs(1), handle)
+            .await
+            .expect("the idle connection should have been closed")
+            .unwrap()
+            .unwrap();
+        assert!(connection.is_closed());
+    }
+
+    #[tokio::test]
+    async fn test_in_flight_request_keeps_connection_open() {
+        let connection = SilentConnection::default();
+        let (request_tx, handle) = spawn_loop(
+            connection.clone(),
+            Duration::from_millis(100),
+            Arc::default(),
+        );
+
+        // The peer never answers, so the request stays in flight.
+        let (respond, response) = oneshot::channel();
+        request_tx
+            .send(Request::SendReqResp {
+                service: ServiceScope::BlockstoreServer,
+                request: Bytes::from("a hash"),
+                respond,
+            })
+            .await
+            .unwrap();
+
+        tokio::time::sleep(Duration::from_millis(300)).await;
+        assert!(!handle.is_finished());
+        assert!(!connection.is_closed());
+
+        // Once the peer hangs up the request is done and the connection goes idle.
+        connection.remote.lock().unwrap().clear();
+        assert!(response.await.unwrap().is_err());
+
+        tokio::time::timeout(Duration::from_secs(1), handle)
+            .await
+            .expect("the idle connection should have been closed")
+            .unwrap()
+            .unwrap();
+        assert!(connection.is_closed());
+    }
+
+    #[tokio::test]
+    async fn test_oversize_message_is_rejected_before_it_is_read() {
+        let (mut remote, stream_rx) = tokio::io::duplex(4096);
+        let (event_tx, mut event_rx) = mpsc::channel(8);
+
+        // A message at the limit goes through.
+        remote.write_all(&1025u32.to_be_bytes()).await.unwrap();
+        remote
+            .write_all(&[ServiceScope::Broadcast as u8])
+            .await
+            .unwrap();
+        remote.write_all(&[0; 1024]).await.unwrap();
+        // A larger one is only announced, the peer never sends it.
+        remote.write_all(&1026u32.to_be_bytes()).await.unwrap();
+
+        let result = tokio::time::timeout(
+            Duration::from_secs(1),
+            handle_incoming_uni_stream::<SilentConnection>(
+                0,
+                stream_rx,
+                event_tx,
+                Activity::new(Bandwidth::default()),
+                1024,
+            ),
+        )
+        .await
+        .expect("the message should have been rejected without waiting for it");
+        assert!(result.is_err());
+
+        let Ok(Event::MessageReceived { message, .. }) = event_rx.try_recv() else {
+            panic!("expected the message at the limit to be received");
+        };
+        assert_eq!(message.payload.len(), 1024);
+        assert!(event_rx.try_recv().is_err());
+    }
+}
@@ -20,6 +20,8 @@
 use tokio_stream::StreamExt;
 use tokio_util::sync::CancellationToken;
 
+use crate::bandwidth::Bandwidth;
+use crate::config::Config as PoolConfig;
 use crate::connection;
 use crate::connection::Context;
 use crate::event::{Event, Message};
@@ -47,7 +49,7 @@ pub struct Endpoint<C, M>
     // themselves when the idle-timeout triggers.
     // These will need to be garbage collected.
     // Todo: Look into avoiding to maintain two tables.
-    redundant_pool: HashMap<NodeIndex, OngoingConnectionHandle>,
+    redundant_pool: HashMap<NodeIndex, Vec<OngoingConnectionHandle>>,
     /// Queue of incoming tasks.
     task_queue: Receiver<EndpointTask>,
     /// Queue of dial tasks.
@@ -67,8 +69,21 @@ pub struct Endpoint<C, M>
     muxer: Option<M>,
     /// Information about attempted connection dials.
     dial_info: Arc<scc::HashMap<NodeIndex, DialInfo>>,
+    /// The bytes exchanged with each peer we are connected to.
+    bandwidth: Arc<scc::HashMap<NodeIndex, Bandwidth>>,
+    /// Reports the bytes exchanged with a peer once we are no longer connected to it.
+    rep_reporter: c!(C::ReputationAggregatorInterface::ReputationReporter),
     /// Config for the multiplexed transport.
     config: M::Config,
+    /// Connections without any traffic for this long are closed.
+    idle_timeout: Option<Duration>,
+    /// The peers in our topology. Connections with them are never closed for being idle, since
+    /// we would dial them again right away.
+    topology: Arc<scc::HashSet<NodeIndex>>,
+    /// The number of connections, including the redundant ones, we accept with a single peer.
+    max_connections_per_peer: usize,
+    /// The largest message we read from a peer.
+    max_message_size: usize,
 }
 
 impl<C, M> Endpoint<C, M>
@@ -81,7 +96,10 @@ pub fn new(
         task_queue: Receiver<EndpointTask>,
         event_queue: Sender<Event>,
         dial_info: Arc<scc::HashMap<NodeIndex, DialInfo>>,
+        bandwidth: Arc<scc::HashMap<NodeIndex, Bandwidth>>,
+        rep_reporter: c!(C::ReputationAggregatorInterface::ReputationReporter),
         config: M::Config,
+        pool_config: &PoolConfig,
     ) -> Self {
         Self {
             pool: HashMap::new(),
@@ -95,7 +113,14 @@ pub fn new(
             query_runner,
             muxer: None,
             dial_info,
+            bandwidth,
+            rep_reporter,
             config,
+            idle_timeout: pool_config.idle_timeout,
+            topology: Arc::default(),
+            // We always need at least one connection to talk to a peer.
+            max_connections_per_peer: pool_config.max_connections_per_peer.max(1),
+            max_message_size: pool_config.max_message_size,
         }
     }
 
@@ -320,14 +345,22 @@ fn handle_update(
     ) -> anyhow::Result<()> {
         let empty_drop_set = drop.is_empty();
 
+        self.topology
+            .retain(|index| keep.get(index).is_some_and(|info| info.from_topology));
+        for (index, info) in &keep {
+            if info.from_topology {
+                let _ = self.topology.insert(*index);
+            }
+        }
+
         // Move the connections to be dropped into a buffer.
         drop.into_iter().for_each(|index| {
             if let Some(conn_handle) = self.pool.remove(&index) {
                 self.connection_buffer.push(conn_handle);
             }
             // Todo: add unit test for this.
-            if let Some(conn_handle) = self.redundant_pool.remove(&index) {
-                self.connection_buffer.push(conn_handle);
+            if let Some(conn_handles) = self.redundant_pool.remove(&index) {
+                self.connection_buffer.extend(conn_handles);
             }
 
             // Cancel ongoing dial task, if one exists.
@@ -414,7 +447,18 @@ fn spawn_connection_task(
     ) -> Sender<connection::Request> {
         let (request_tx, request_rx) = mpsc::channel(1024);
         let connection_id = connection.connection_id();
-        let ctx = Context::new(connection, remote, request_rx, self.event_queue.clone());
+        // The counters are shared by all of the connections with the same peer.
+        let bandwidth = self.bandwidth.entry(remote).or_default().get().clone();
+        let ctx = Context::new(
+            connection,
+            remote,
+            request_rx,
+            self.event_queue.clone(),
+            self.idle_timeout,
+            self.topology.clone(),
+            self.max_message_size,
+            bandwidth,
+        );
         self.ongoing_async_tasks.push(spawn!(
             async move {
                 if let Err(e) = connection::connection_loop(ctx).await {
@@ -431,7 +475,7 @@ fn spawn_connection_task(
         request_tx
     }
 
-    fn handle_new_connection(&mut self, connection: M::Connection) {
+    pub(crate) fn handle_new_connection(&mut self, connection: M::Connection) {
         let Some(pk) = connection.peer_identity() else {
             tracing::error!("failed to get peer identity from connection");
             return;
@@ -445,10 +489,9 @@ fn handle_new_connection(&mut self, connection: M::Connection) {
         if let Some(peer_index) = self.query_runner.pubkey_to_index(&pk) {
             self.cancel_dial(&peer_index);
 
-            // We only allow one redundant connection per peer.
-            if self.pool.contains_key(&peer_index) && self.redundant_pool.contains_key(&peer_index)
-            {
-                tracing::warn!("too many redundant connections with peer {peer_index:?}");
+            // We only allow a limited number of connections per peer.
+            if self.connection_count(&peer_index) >= self.max_connections_per_peer {
+                tracing::warn!("too many connections with peer {peer_index:?}");
                 connection.close(0u8, b"close from disconnect");
                 return;
             }
@@ -491,11 +534,10 @@ fn handle_new_connection(&mut self, connection: M::Connection) {
 
             match self.pool.entry(peer_index) {
                 Entry::Occupied(_) => {
-                    if self.redundant_pool.insert(peer_index, handle).is_some() {
-                        tracing::info!(
-                            "replacing connection with node with index {peer_index} in redundant pool"
-                        );
-                    }
+                    self.redundant_pool
+                        .entry(peer_index)
+                        .or_default()
+                        .push(handle);
                 },
                 Entry::Vacant(vacant) => {
                     vacant.insert(handle);
@@ -509,6 +551,12 @@ fn handle_new_connection(&mut self, connection: M::Connection) {
         }
     }
 
+    /// Returns the number of connections we have with the peer, including the redundant ones.
+    pub(crate) fn connection_count(&self, peer: &NodeIndex) -> usize {
+        usize::from(self.pool.contains_key(peer))
+            + self.redundant_pool.get(peer).map(Vec::len).unwrap_or(0)
+    }
+
     #[inline]
     fn enqueue_event(&self, event: Event) {
         let sender = self.event_queue.clone();
@@ -566,7 +614,11 @@ fn handle_stats_request(&mut self, respond: oneshot::Sender<EndpointInfo>) {
         let redundant_connections = self
             .redundant_pool
             .iter()
-            .map(|(peer, info)| (*peer, info.service_request_tx.clone()))
+            .flat_map(|(peer, infos)| {
+                infos
+                    .iter()
+                    .map(|info| (*peer, info.service_request_tx.clone()))
+            })
             .collect::<Vec<_>>();
 
         let ongoing_async_tasks = self.ongoing_async_tasks.len();
@@ -665,7 +717,11 @@ fn garbage_collect_closed_connections(&mut self, peer: NodeIndex, connection_id:
             // this one so we need to rely on this identifier instead of just the key.
             if entry.get().connection_id == connection_id {
                 // Connection ID is unique so this is safe.
-                if let Some(handle) = self.redundant_pool.remove(&peer) {
+                if let Some(handle) = self
+                    .redundant_pool
+                    .get_mut(&peer)
+                    .and_then(|handles| handles.pop())
+                {
                     entry.insert(handle);
                 } else {
                     entry.remove();
@@ -673,10 +729,13 @@ fn garbage_collect_closed_connections(&mut self, peer: NodeIndex, connection_id:
             }
         }
 
-        if let Entry::Occupied(entry) = self.redundant_pool.entry(peer) {
+        if let Entry::Occupied(mut entry) = self.redundant_pool.entry(peer) {
             // If the connection IDs do not match, another connection was opened or superseded
             // this one so we need to rely on this identifier instead of just the key.
-            if entry.get().connection_id == connection_id {
+            entry
+                .get_mut()
+                .retain(|handle| handle.connection_id != connection_id);
+            if entry.get().is_empty() {
                 entry.remove();
             }
         }
@@ -685,10 +744,28 @@ fn garbage_collect_closed_connections(&mut self, peer: NodeIndex, connection_id:
         // we must make sure that we don't have any active
         // transport connection.
         if !self.redundant_pool.contains_key(&peer) && !self.pool.contains_key(&peer) {
+            self.report_bandwidth(peer);
             self.enqueue_event(Event::ConnectionEnded { remote: peer });
         }
     }
 
+    /// Report the bytes exchanged with the peer to the reputation aggregator and reset the
+    /// counters, once we have no connection left with the peer.
+    fn report_bandwidth(&self, peer: NodeIndex) {
+        let Some((_, bandwidth)) = self.bandwidth.remove(&peer) else {
+            return;
+        };
+        let stats = bandwidth.stats();
+        if stats.bytes_sent > 0 {
+            self.rep_reporter
+                .report_bytes_sent(peer, stats.bytes_sent, None);
+        }
+        if stats.bytes_received > 0 {
+            self.rep_reporter
+                .report_bytes_received(peer, stats.bytes_received, None);
+        }
+    }
+
     fn handle_finished_async_task(&mut self, task_result: AsyncTaskResult<M::Connection>) {
         match task_result {
             AsyncTaskResult::ConnectionSuccess { conn, .. } => {
@@ -772,7 +849,7 @@ pub fn spawn(mut self, shutdown: ShutdownWaiter) {
                         .await;
                 }
 
-                for (_, handle) in self.redundant_pool.iter() {
+                for handle in self.redundant_pool.values().flatten() {
                     let _ = handle
                         .service_request_tx
                         .clone()
@@ -1,3 +1,4 @@
+mod bandwidth;
 mod config;
 mod connection;
 mod endpoint;
@@ -11,5 +12,5 @@
 mod tests;
 mod tls;
 
-pub use config::Config;
+pub use config::{Cipher, Config};
 pub use provider::PoolProvider;
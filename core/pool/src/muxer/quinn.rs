@@ -10,6 +10,7 @@
 use quinn::{ClientConfig, Endpoint, RecvStream, SendStream, ServerConfig, TransportConfig};
 use rustls::Certificate;
 
+use crate::config::Cipher;
 use crate::muxer::{ConnectionInterface, MuxerInterface};
 use crate::state::{NodeInfo, Stats};
 use crate::tls;
@@ -20,6 +21,7 @@ pub struct Config {
     pub address: SocketAddr,
     pub sk: NodeSecretKey,
     pub max_idle_timeout: Duration,
+    pub cipher_preference: Vec<Cipher>,
 }
 
 #[derive(Clone)]
@@ -27,6 +29,7 @@ pub struct QuinnMuxer {
     endpoint: Endpoint,
     sk: NodeSecretKey,
     max_idle_timeout: Duration,
+    cipher_preference: Vec<Cipher>,
 }
 
 impl MuxerInterface for QuinnMuxer {
@@ -44,11 +47,12 @@ fn init(config: Self::Config) -> io::Result<Self> {
             endpoint,
             sk: config.sk,
             max_idle_timeout: config.max_idle_timeout,
+            cipher_preference: config.cipher_preference,
         })
     }
 
     async fn connect(&self, peer: NodeInfo, server_name: &str) -> io::Result<Self::Connecting> {
-        let tls_config = tls::make_client_config(&self.sk, Some(peer.pk))
+        let tls_config = tls::make_client_config(&self.sk, Some(peer.pk), &self.cipher_preference)
             .map_err(|e| io::Error::new(io::ErrorKind::Other, e))?;
         let mut client_config = ClientConfig::new(Arc::new(tls_config));
         let mut transport_config = TransportConfig::default();
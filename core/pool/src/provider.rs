@@ -12,7 +12,10 @@
 use tokio::sync::mpsc::{Receiver, Sender};
 use tokio::sync::{mpsc, oneshot};
 
+#[cfg(test)]
+use crate::bandwidth::{Bandwidth, PeerBandwidthStats};
 use crate::config::Config;
+use crate::connection::Activity;
 use crate::endpoint::{Endpoint, EndpointTask};
 use crate::event::{Event, EventReceiver, Param};
 use crate::muxer::quinn::QuinnMuxer;
@@ -28,6 +31,8 @@ pub struct PoolProvider<C, M = QuinnMuxer>
     state: Mutex<Option<(Endpoint<C, M>, EventReceiver<C>)>>,
     event_queue: Sender<Event>,
     endpoint_task_queue: Sender<EndpointTask>,
+    #[cfg(test)]
+    bandwidth: Arc<scc::HashMap<NodeIndex, Bandwidth>>,
     config: Config,
 }
 
@@ -37,6 +42,7 @@ fn init(
         keystore: &C::KeystoreInterface,
         topology: &C::TopologyInterface,
         sync_query: fdi::Cloned<c!(C::ApplicationInterface::SyncExecutor)>,
+        rep_aggregator: &C::ReputationAggregatorInterface,
     ) -> Result<Self> {
         let config: Config = config.get::<Self>();
         let sk = keystore.get_ed25519_sk();
@@ -44,7 +50,8 @@ fn init(
 
         let mut transport_config = quinn::TransportConfig::default();
         transport_config.max_idle_timeout(Some(config.max_idle_timeout.try_into()?));
-        let tls_config = tls::make_server_config(&sk).expect("Secret key to be valid");
+        let tls_config = tls::make_server_config(&sk, &config.cipher_preference)
+            .expect("Secret key to be valid");
         let mut server_config = quinn::ServerConfig::with_crypto(Arc::new(tls_config));
         server_config.transport_config(Arc::new(transport_config));
         let muxer_config = muxer::quinn::Config {
@@ -52,9 +59,11 @@ fn init(
             address: config.address,
             sk,
             max_idle_timeout: config.max_idle_timeout,
+            cipher_preference: config.cipher_preference.clone(),
         };
 
         let dial_info = Arc::new(scc::HashMap::default());
+        let bandwidth = Arc::new(scc::HashMap::default());
         let (endpoint_task_tx, endpoint_task_rx) = mpsc::channel(1024);
         let (event_tx, event_rx) = mpsc::channel(1024);
         let receiver = EventReceiver::<C>::new(
@@ -70,13 +79,18 @@ fn init(
             endpoint_task_rx,
             event_tx.clone(),
             dial_info,
+            bandwidth.clone(),
+            rep_aggregator.get_reporter(),
             muxer_config,
+            &config,
         );
 
         Ok(Self {
             state: Some((endpoint, receiver)).into(),
             event_queue: event_tx,
             endpoint_task_queue: endpoint_task_tx,
+            #[cfg(test)]
+            bandwidth,
             config,
         })
     }
@@ -113,6 +127,14 @@ async fn start(this: fdi::Ref<Self>, fdi::Cloned(shutdown): fdi::Cloned<Shutdown
         receiver.spawn(shutdown);
     }
 
+    /// Returns the bytes exchanged with the peer since we connected to it.
+    #[cfg(test)]
+    pub(crate) fn stats(&self, peer: NodeIndex) -> PeerBandwidthStats {
+        self.bandwidth
+            .read(&peer, |_, bandwidth| bandwidth.stats())
+            .unwrap_or_default()
+    }
+
     fn register_broadcast_service(
         &self,
         service: ServiceScope,
@@ -283,11 +305,16 @@ async fn request(&self, peer: NodeIndex, request: Bytes) -> io::Result<Self::Res
 pub struct Response {
     status: Status,
     channel: BoxedChannel,
+    activity: Activity,
 }
 
 impl Response {
-    pub(crate) fn new(status: Status, channel: BoxedChannel) -> Self {
-        Self { status, channel }
+    pub(crate) fn new(status: Status, channel: BoxedChannel, activity: Activity) -> Self {
+        Self {
+            status,
+            channel,
+            activity,
+        }
     }
 }
 
@@ -303,12 +330,15 @@ fn status_code(&self) -> Result<(), RejectReason> {
     fn body(self) -> Self::Body {
         Body {
             channel: self.channel,
+            activity: self.activity,
         }
     }
 }
 
 pub struct Body {
     channel: BoxedChannel,
+    // Keeps the connection from being reaped while the body is read.
+    activity: Activity,
 }
 
 impl Stream for Body {
@@ -318,9 +348,13 @@ fn poll_next(
         mut self: Pin<&mut Self>,
         cx: &mut std::task::Context<'_>,
     ) -> Poll<Option<Self::Item>> {
-        Pin::new(&mut self.channel)
+        let poll: Poll<Option<Self::Item>> = Pin::new(&mut self.channel)
             .poll_next(cx)
-            .map_err(Into::into)
+            .map_err(Into::into);
+        if let Poll::Ready(Some(Ok(chunk))) = &poll {
+            self.activity.record_received(chunk.len());
+        }
+        poll
     }
 }
 
@@ -345,13 +379,16 @@ pub struct Request {
     // Please see Drop impl.
     channel: Option<BoxedChannel>,
     ok_header_sent: bool,
+    // Keeps the connection from being reaped while we respond.
+    activity: Activity,
 }
 
 impl Request {
-    pub(crate) fn new(channel: BoxedChannel) -> Self {
+    pub(crate) fn new(channel: BoxedChannel, activity: Activity) -> Self {
         Self {
             channel: Some(channel),
             ok_header_sent: false,
+            activity,
         }
     }
 }
@@ -378,7 +415,10 @@ async fn send(&mut self, frame: Bytes) -> io::Result<()> {
             channel.send(header.into()).await?;
             self.ok_header_sent = true;
         }
-        channel.send(frame).await
+        let len = frame.len();
+        channel.send(frame).await?;
+        self.activity.record_sent(len);
+        Ok(())
     }
 }
 
@@ -12,8 +12,8 @@
 use lightning_application::genesis::{Genesis, GenesisNode};
 use lightning_application::query_runner::QueryRunner;
 use lightning_interfaces::prelude::*;
-use lightning_interfaces::types::{NodeIndex, NodePorts};
-use lightning_interfaces::ServiceScope;
+use lightning_interfaces::types::{HandshakePorts, NodeIndex, NodePorts};
+use lightning_interfaces::{ServiceScope, ShutdownController};
 use lightning_notifier::Notifier;
 use lightning_rep_collector::ReputationAggregator;
 use lightning_signer::Signer;
@@ -24,8 +24,13 @@
 use tokio::sync::mpsc::{Receiver, Sender};
 use tokio::sync::{mpsc, oneshot};
 
-use crate::endpoint::EndpointTask;
-use crate::event::{Event, EventReceiver, Param};
+use crate::bandwidth::PeerBandwidthStats;
+use crate::connection::tests::SilentConnection;
+use crate::endpoint::{Endpoint, EndpointTask};
+use crate::event::{Event, EventReceiver, Message, Param};
+use crate::logical_pool::ConnectionInfo;
+use crate::muxer::{ConnectionInterface, MuxerInterface};
+use crate::state::NodeInfo;
 use crate::{provider, Config, PoolProvider};
 
 partial!(TestBinding {
@@ -58,6 +63,9 @@ fn pool(&self) -> fdi::Ref<PoolProvider<TestBinding>> {
     fn notifier(&self) -> fdi::Ref<Notifier<TestBinding>> {
         self.inner.provider.get()
     }
+    fn rep_aggregator(&self) -> fdi::Ref<ReputationAggregator<TestBinding>> {
+        self.inner.provider.get()
+    }
 }
 
 async fn get_pools(
@@ -89,14 +97,17 @@ async fn get_pools(
             "127.0.0.1".parse().unwrap(),
             node_secret_key.to_pk(),
             NodePorts {
-                primary: 48000_u16,
-                worker: 48101_u16,
-                mempool: 48202_u16,
-                rpc: 48300_u16,
+                primary: 20000 + i as u16,
+                worker: 20100 + i as u16,
+                mempool: 20200 + i as u16,
+                rpc: 20300 + i as u16,
                 pool: port_offset + i as u16,
-                pinger: 48600_u16,
-                // Handshake is unused so the defaults are fine.
-                handshake: Default::default(),
+                pinger: 20600 + i as u16,
+                handshake: HandshakePorts {
+                    http: 20700 + i as u16,
+                    webrtc: 20800 + i as u16,
+                    webtransport: 20900 + i as u16,
+                },
             },
             None,
             true,
@@ -150,6 +161,7 @@ fn create_peer(
                         address,
                         http: state_server_address_port
                             .map(|port| SocketAddr::from((IpAddr::from([127, 0, 0, 1]), port))),
+                        ..Default::default()
                     })
                     .with::<Application<TestBinding>>(app_config),
             )
@@ -206,6 +218,60 @@ fn event_receiver(peer: &Peer) -> (EventReceiver<TestBinding>, EventReceiverTest
     )
 }
 
+/// A muxer for endpoints that are handed their connections directly by the tests.
+#[derive(Clone)]
+struct TestMuxer;
+
+impl MuxerInterface for TestMuxer {
+    type Connecting = futures::future::Ready<io::Result<SilentConnection>>;
+    type Connection = SilentConnection;
+    type Config = ();
+
+    fn init(_config: Self::Config) -> io::Result<Self> {
+        Ok(Self)
+    }
+
+    async fn connect(&self, _peer: NodeInfo, _server_name: &str) -> io::Result<Self::Connecting> {
+        Err(io::ErrorKind::Unsupported.into())
+    }
+
+    async fn accept(&self) -> Option<Self::Connecting> {
+        None
+    }
+
+    async fn close(&self) {}
+}
+
+/// A muxer that dials peers by handing out a silent connection to them, and keeps every
+/// connection it handed out.
+#[derive(Clone)]
+struct DialingMuxer {
+    dialed: Arc<std::sync::Mutex<Vec<SilentConnection>>>,
+}
+
+impl MuxerInterface for DialingMuxer {
+    type Connecting = futures::future::Ready<io::Result<SilentConnection>>;
+    type Connection = SilentConnection;
+    type Config = Arc<std::sync::Mutex<Vec<SilentConnection>>>;
+
+    fn init(dialed: Self::Config) -> io::Result<Self> {
+        Ok(Self { dialed })
+    }
+
+    async fn connect(&self, peer: NodeInfo, _server_name: &str) -> io::Result<Self::Connecting> {
+        let mut dialed = self.dialed.lock().unwrap();
+        let connection = SilentConnection::new(peer.pk, dialed.len());
+        dialed.push(connection.clone());
+        Ok(futures::future::ready(Ok(connection)))
+    }
+
+    async fn accept(&self) -> Option<Self::Connecting> {
+        futures::future::pending().await
+    }
+
+    async fn close(&self) {}
+}
+
 #[tokio::test(flavor = "multi_thread")]
 async fn test_send_to_one() {
     // Given: two peers.
@@ -383,6 +449,79 @@ async fn test_open_req_res() {
     }
 }
 
+#[tokio::test]
+async fn test_bandwidth_stats() {
+    // Given: two peers.
+    let temp_dir = tempdir().unwrap();
+    let (peers, _) = get_pools(&temp_dir, 50500, 2, None).await;
+    let query_runner = peers[0].app().sync_query();
+
+    let node_index1 = query_runner
+        .pubkey_to_index(&peers[0].node_public_key)
+        .unwrap();
+    let node_index2 = query_runner
+        .pubkey_to_index(&peers[1].node_public_key)
+        .unwrap();
+    let (_requester1, mut responder1) =
+        peers[0].pool().open_req_res(ServiceScope::BlockstoreServer);
+    let (requester2, _responder2) = peers[1].pool().open_req_res(ServiceScope::BlockstoreServer);
+
+    for peer in &peers {
+        peer.inner.start().await;
+    }
+
+    // When: one of the peers sends a request of 6 bytes and gets a response of 14 bytes.
+    let chunks = vec![
+        Bytes::from("one"),
+        Bytes::from("two"),
+        Bytes::from("three"),
+        Bytes::from("end"),
+    ];
+    let chunks_clone = chunks.clone();
+
+    let sender_fut = async move {
+        let (_, mut request) = responder1.get_next_request().await.unwrap();
+        for chunk in chunks_clone {
+            request.send(chunk).await.unwrap();
+        }
+    };
+
+    let recv_fut = async move {
+        let response = requester2
+            .request(node_index1, Bytes::from("a hash"))
+            .await
+            .unwrap();
+        response.status_code().unwrap();
+        let mut body = response.body();
+        for _ in 0..chunks.len() {
+            body.next().await.unwrap().unwrap();
+        }
+    };
+
+    futures::join!(sender_fut, recv_fut);
+
+    // Then: both peers account for the bytes they exchanged.
+    assert_eq!(
+        peers[1].pool().stats(node_index1),
+        PeerBandwidthStats {
+            bytes_sent: 6,
+            bytes_received: 14,
+        }
+    );
+    assert_eq!(
+        peers[0].pool().stats(node_index2),
+        PeerBandwidthStats {
+            bytes_sent: 14,
+            bytes_received: 6,
+        }
+    );
+
+    // Clean up.
+    for mut peer in peers {
+        peer.inner.shutdown().await;
+    }
+}
+
 #[tokio::test]
 async fn test_open_req_res_unknown_peer() {
     // Give: a peer.
@@ -408,6 +547,140 @@ async fn test_open_req_res_unknown_peer() {
     peers[0].inner.shutdown().await;
 }
 
+#[tokio::test]
+async fn test_max_connections_per_peer() {
+    // Given: an endpoint that accepts two connections per peer.
+    // We never bind.
+    let temp_dir = tempdir().unwrap();
+    let (peers, _) = get_pools(&temp_dir, 8100, 3, None).await;
+    let (_task_tx, task_rx) = mpsc::channel(8);
+    let (event_tx, _event_rx) = mpsc::channel(8);
+    let mut endpoint = Endpoint::<TestBinding, TestMuxer>::new(
+        peers[0].app().sync_query(),
+        task_rx,
+        event_tx,
+        Arc::new(scc::HashMap::default()),
+        Arc::new(scc::HashMap::default()),
+        peers[0].rep_aggregator().get_reporter(),
+        (),
+        &Config {
+            max_connections_per_peer: 2,
+            ..Default::default()
+        },
+    );
+
+    // When: one peer opens more connections than that, and another peer opens one.
+    let noisy = (0..4)
+        .map(|id| SilentConnection::new(peers[1].node_public_key, id))
+        .collect::<Vec<_>>();
+    for connection in &noisy {
+        endpoint.handle_new_connection(connection.clone());
+    }
+    let quiet = SilentConnection::new(peers[2].node_public_key, 4);
+    endpoint.handle_new_connection(quiet.clone());
+
+    // Then: the excess connections are rejected.
+    assert_eq!(endpoint.connection_count(&peers[1].node_index), 2);
+    assert!(!noisy[0].is_closed());
+    assert!(!noisy[1].is_closed());
+    assert!(noisy[2].is_closed());
+    assert!(noisy[3].is_closed());
+
+    // Then: the other peer is unaffected.
+    assert_eq!(endpoint.connection_count(&peers[2].node_index), 1);
+    assert!(!quiet.is_closed());
+}
+
+#[tokio::test]
+async fn test_idle_connections_outside_of_topology_are_reaped() {
+    // Given: an endpoint that closes idle connections, with one peer in our topology.
+    // We never bind.
+    let temp_dir = tempdir().unwrap();
+    let (peers, _) = get_pools(&temp_dir, 8200, 3, None).await;
+    let (task_tx, task_rx) = mpsc::channel(8);
+    let (event_tx, _event_rx) = mpsc::channel(64);
+    let dialed = Arc::new(std::sync::Mutex::new(Vec::new()));
+    let bandwidth = Arc::new(scc::HashMap::default());
+    let endpoint = Endpoint::<TestBinding, DialingMuxer>::new(
+        peers[0].app().sync_query(),
+        task_rx,
+        event_tx,
+        Arc::new(scc::HashMap::default()),
+        bandwidth.clone(),
+        peers[0].rep_aggregator().get_reporter(),
+        dialed.clone(),
+        &Config {
+            idle_timeout: Some(Duration::from_millis(200)),
+            ..Default::default()
+        },
+    );
+    let shutdown = ShutdownController::default();
+    endpoint.spawn(shutdown.waiter());
+
+    let connection_info = |peer: &Peer, from_topology: bool| ConnectionInfo {
+        pinned: !from_topology,
+        from_topology,
+        node_info: NodeInfo {
+            index: peer.node_index,
+            pk: peer.node_public_key,
+            socket_address: SocketAddr::from(([127, 0, 0, 1], 0)),
+        },
+        connect: true,
+    };
+    let send_message = |peer: &Peer| EndpointTask::SendMessage {
+        peers: vec![connection_info(peer, false)],
+        message: Message {
+            service: ServiceScope::Broadcast,
+            payload: b"hello".to_vec(),
+        },
+    };
+    let dialed_to = |peer: &Peer| {
+        dialed
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|connection| connection.peer_identity() == Some(peer.node_public_key))
+            .cloned()
+            .collect::<Vec<_>>()
+    };
+
+    task_tx
+        .send(EndpointTask::Update {
+            keep: [(peers[1].node_index, connection_info(&peers[1], true))].into(),
+            drop: Vec::new(),
+        })
+        .await
+        .unwrap();
+
+    // When: we use a connection with a peer outside of our topology once.
+    task_tx.send(send_message(&peers[2])).await.unwrap();
+    tokio::time::sleep(Duration::from_millis(500)).await;
+
+    // Then: only the connection outside of our topology is reaped.
+    let topology_connections = dialed_to(&peers[1]);
+    assert_eq!(topology_connections.len(), 1);
+    assert!(!topology_connections[0].is_closed());
+    let other_connections = dialed_to(&peers[2]);
+    assert_eq!(other_connections.len(), 1);
+    assert!(other_connections[0].is_closed());
+
+    // Then: the bandwidth of the peer we are no longer connected to is reported and forgotten.
+    assert!(bandwidth.contains(&peers[1].node_index));
+    assert!(!bandwidth.contains(&peers[2].node_index));
+
+    // When: we use the reaped connection again.
+    task_tx.send(send_message(&peers[2])).await.unwrap();
+    tokio::time::sleep(Duration::from_millis(50)).await;
+
+    // Then: the peer is dialed again.
+    let other_connections = dialed_to(&peers[2]);
+    assert_eq!(other_connections.len(), 2);
+    assert!(!other_connections[1].is_closed());
+
+    // Clean up.
+    shutdown.trigger_shutdown();
+}
+
 #[tokio::test]
 async fn test_log_pool_get_index() {
     // We never bind.
@@ -10,6 +10,9 @@
 
 pub use certificate::parse_unverified;
 use fleek_crypto::{NodePublicKey, NodeSecretKey};
+use rustls::SupportedCipherSuite;
+
+use crate::config::Cipher;
 
 const LIGHTNING_ALPN: &[u8] = b"fleek/lightning";
 
@@ -18,11 +21,12 @@
 pub fn make_client_config(
     secret_key: &NodeSecretKey,
     remote_peer_id: Option<NodePublicKey>,
+    cipher_preference: &[Cipher],
 ) -> Result<rustls::ClientConfig, certificate::GenError> {
     let (certificate, secret_key) = certificate::generate(secret_key)?;
 
     let mut crypto = rustls::ClientConfig::builder()
-        .with_cipher_suites(verifier::CIPHERSUITES)
+        .with_cipher_suites(&cipher_suites(cipher_preference))
         .with_safe_default_kx_groups()
         .with_protocol_versions(verifier::PROTOCOL_VERSIONS)
         .expect("Cipher suites and kx groups are configured; qed")
@@ -40,11 +44,12 @@ pub fn make_client_config(
 #[allow(unused)]
 pub fn make_server_config(
     secret_key: &NodeSecretKey,
+    cipher_preference: &[Cipher],
 ) -> Result<rustls::ServerConfig, certificate::GenError> {
     let (certificate, secret_key) = certificate::generate(secret_key)?;
 
     let mut crypto = rustls::ServerConfig::builder()
-        .with_cipher_suites(verifier::CIPHERSUITES)
+        .with_cipher_suites(&cipher_suites(cipher_preference))
         .with_safe_default_kx_groups()
         .with_protocol_versions(verifier::PROTOCOL_VERSIONS)
         .expect("Cipher suites and kx groups are configured; qed")
@@ -52,5 +57,98 @@ pub fn make_server_config(
         .with_single_cert(vec![certificate], secret_key)
         .expect("Server cert key DER is valid; qed");
     crypto.alpn_protocols = vec![LIGHTNING_ALPN.to_vec()];
+    // Pick the first cipher of the dialer that we support, so that the preference of the node
+    // that opens the connection decides between the ciphers both of them support. Our own
+    // preference is only used to break ties.
+    crypto.ignore_client_order = false;
     Ok(crypto)
 }
+
+impl Cipher {
+    fn cipher_suite(&self) -> SupportedCipherSuite {
+        match self {
+            Cipher::ChaCha20Poly1305 => rustls::cipher_suite::TLS13_CHACHA20_POLY1305_SHA256,
+            Cipher::Aes256Gcm => rustls::cipher_suite::TLS13_AES_256_GCM_SHA384,
+            Cipher::Aes128Gcm => rustls::cipher_suite::TLS13_AES_128_GCM_SHA256,
+        }
+    }
+}
+
+/// Returns every supported cipher suite, with the preferred ones first. The other suites are
+/// kept so that we can still fall back to them with peers that have a different preference.
+fn cipher_suites(preference: &[Cipher]) -> Vec<SupportedCipherSuite> {
+    let mut suites: Vec<SupportedCipherSuite> = Vec::with_capacity(verifier::CIPHERSUITES.len());
+    for suite in preference
+        .iter()
+        .map(Cipher::cipher_suite)
+        .chain(verifier::CIPHERSUITES.iter().copied())
+    {
+        if !suites.contains(&suite) {
+            suites.push(suite);
+        }
+    }
+    suites
+}
+
+#[cfg(test)]
+mod tests {
+    use fleek_crypto::SecretKey;
+    use rustls::{ClientConnection, ServerConnection, ServerName};
+
+    use super::*;
+
+    /// Runs a handshake between two peers in memory and returns the cipher they agreed on.
+    fn negotiate(dialer: &[Cipher], listener: &[Cipher]) -> SupportedCipherSuite {
+        let dialer_sk = NodeSecretKey::generate();
+        let listener_sk = NodeSecretKey::generate();
+        let client_config =
+            make_client_config(&dialer_sk, Some(listener_sk.to_pk()), dialer).unwrap();
+        let server_config = make_server_config(&listener_sk, listener).unwrap();
+
+        let mut client = ClientConnection::new(
+            Arc::new(client_config),
+            ServerName::try_from("localhost").unwrap(),
+        )
+        .unwrap();
+        let mut server = ServerConnection::new(Arc::new(server_config)).unwrap();
+
+        while client.is_handshaking() || server.is_handshaking() {
+            let mut buf = Vec::new();
+            while client.wants_write() {
+                client.write_tls(&mut buf).unwrap();
+            }
+            server.read_tls(&mut buf.as_slice()).unwrap();
+            server.process_new_packets().unwrap();
+
+            let mut buf = Vec::new();
+            while server.wants_write() {
+                server.write_tls(&mut buf).unwrap();
+            }
+            client.read_tls(&mut buf.as_slice()).unwrap();
+            client.process_new_packets().unwrap();
+        }
+
+        let suite = client.negotiated_cipher_suite().unwrap();
+        assert_eq!(server.negotiated_cipher_suite(), Some(suite));
+        suite
+    }
+
+    #[test]
+    fn test_negotiate_shared_preference() {
+        let suite = negotiate(&[Cipher::Aes128Gcm], &[Cipher::Aes128Gcm]);
+        assert_eq!(suite.suite(), Cipher::Aes128Gcm.cipher_suite().suite());
+    }
+
+    #[test]
+    fn test_negotiate_uses_dialer_preference() {
+        let suite = negotiate(&[Cipher::Aes256Gcm], &[Cipher::Aes128Gcm]);
+        assert_eq!(suite.suite(), Cipher::Aes256Gcm.cipher_suite().suite());
+    }
+
+    #[test]
+    fn test_negotiate_without_preference() {
+        // Without a preference we fall back to the default order of the suites.
+        let suite = negotiate(&[], &[]);
+        assert_eq!(suite.suite(), verifier::CIPHERSUITES[0].suite());
+    }
+}
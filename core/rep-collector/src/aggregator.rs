@@ -16,6 +16,7 @@
 use crate::buffered_mpsc;
 use crate::config::Config;
 use crate::measurement_manager::MeasurementManager;
+use crate::scoring::ScoringStrategy;
 
 #[cfg(all(not(test), not(debug_assertions)))]
 const BEFORE_EPOCH_CHANGE: Duration = Duration::from_secs(300);
@@ -29,6 +30,7 @@ pub struct ReputationAggregator<C: Collection> {
     notifier: c![C::NotifierInterface],
     submit_tx: SubmitTxSocket,
     report_rx: buffered_mpsc::BufferedReceiver<ReportMessage>,
+    score_decay: f64,
 }
 
 impl<C: Collection> ReputationAggregator<C> {
@@ -39,6 +41,7 @@ pub fn new(
         fdi::Cloned(notifier): fdi::Cloned<C::NotifierInterface>,
     ) -> anyhow::Result<Self> {
         let config = config.get::<Self>();
+        config.validate()?;
         let submit_tx = signer.get_socket();
 
         let (report_tx, report_rx) =
@@ -53,9 +56,20 @@ pub fn new(
             submit_tx,
             notifier,
             report_rx,
+            score_decay: config.score_decay,
         })
     }
 
+    /// Replaces the strategy used to turn the measurements of a peer into its local reputation
+    /// score, the [`DefaultScoringStrategy`](crate::DefaultScoringStrategy) is used otherwise. Must
+    /// be called before the node is started.
+    pub fn set_scoring_strategy(&self, strategy: impl ScoringStrategy) {
+        self.measurement_manager
+            .lock()
+            .unwrap()
+            .set_scoring_strategy(Box::new(strategy));
+    }
+
     pub async fn start(mut self, fdi::Cloned(waiter): fdi::Cloned<ShutdownWaiter>) {
         let shutdown_future = waiter.wait_for_shutdown();
         pin!(shutdown_future);
@@ -63,6 +77,7 @@ pub async fn start(mut self, fdi::Cloned(waiter): fdi::Cloned<ShutdownWaiter>) {
         let mut before_epoch_change_sub = self
             .notifier
             .subscribe_before_epoch_change(BEFORE_EPOCH_CHANGE);
+        let mut epoch_changed_sub = self.notifier.subscribe_epoch_changed();
 
         loop {
             tokio::select! {
@@ -76,6 +91,12 @@ pub async fn start(mut self, fdi::Cloned(waiter): fdi::Cloned<ShutdownWaiter>) {
                     self.submit_aggregation().await;
                     self.measurement_manager.lock().unwrap().clear_measurements();
                 }
+                Some(_) = epoch_changed_sub.recv() => {
+                    self.measurement_manager
+                        .lock()
+                        .unwrap()
+                        .decay_local_reputation(self.score_decay);
+                }
                 else => {
                     error!("Failed to receive message");
 
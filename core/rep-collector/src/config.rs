@@ -1,14 +1,38 @@
+use anyhow::bail;
 use serde::{Deserialize, Serialize};
 
 #[derive(Clone, Serialize, Deserialize)]
 pub struct Config {
     pub reporter_buffer_size: usize,
+    /// The fraction by which the distance of a local reputation score from the neutral score
+    /// shrinks on every epoch change, so that old behavior loses weight against recent one.
+    /// Must be between `0.0` (no decay) and `1.0` (reset every epoch).
+    #[serde(default = "default_score_decay")]
+    pub score_decay: f64,
+}
+
+impl Config {
+    /// Returns an error if the score decay is not between `0.0` and `1.0`.
+    pub fn validate(&self) -> anyhow::Result<()> {
+        if !(0.0..=1.0).contains(&self.score_decay) {
+            bail!(
+                "score_decay must be between 0.0 and 1.0, got {}",
+                self.score_decay
+            );
+        }
+        Ok(())
+    }
+}
+
+fn default_score_decay() -> f64 {
+    0.1
 }
 
 impl Default for Config {
     fn default() -> Self {
         Self {
             reporter_buffer_size: 1,
+            score_decay: default_score_decay(),
         }
     }
 }
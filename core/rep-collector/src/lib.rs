@@ -2,7 +2,9 @@
 pub mod buffered_mpsc;
 pub mod config;
 pub(crate) mod measurement_manager;
+pub mod scoring;
 pub use aggregator::{MyReputationQuery, MyReputationReporter, ReputationAggregator};
+pub use scoring::{DefaultScoringStrategy, NormalizedMeasurements, ScoringStrategy};
 
 #[cfg(test)]
 mod tests;
@@ -9,6 +9,8 @@
 use lightning_reputation::statistics::try_min_max_normalize;
 use lru::LruCache;
 
+use crate::scoring::{DefaultScoringStrategy, NormalizedMeasurements, ScoringStrategy};
+
 /// Maximum capacity for the lru cache that stores the peer measurements.
 const MAX_CAPACITY: usize = 200;
 
@@ -18,6 +20,9 @@
 /// epochs and 30% is based on the current epoch.
 const REP_EWMA_WEIGHT: f64 = 0.7;
 
+/// The local reputation score of a node we know nothing about.
+pub(crate) const NEUTRAL_SCORE: u8 = 50;
+
 /// The minimum number of pings that must be recorded for a peer, in order to report uptime
 /// measurements for that peer.
 #[cfg(not(debug_assertions))]
@@ -30,6 +35,7 @@ pub struct MeasurementManager {
     peers: LruCache<NodeIndex, MeasurementStore>,
     summary_stats: SummaryStatistics,
     local_reputation: Arc<scc::HashMap<NodeIndex, u8>>,
+    scoring_strategy: Box<dyn ScoringStrategy>,
 }
 
 impl MeasurementManager {
@@ -38,9 +44,14 @@ pub fn new() -> Self {
             peers: LruCache::new(NonZeroUsize::new(MAX_CAPACITY).unwrap()),
             summary_stats: SummaryStatistics::default(),
             local_reputation: Arc::new(scc::HashMap::new()),
+            scoring_strategy: Box::new(DefaultScoringStrategy),
         }
     }
 
+    pub fn set_scoring_strategy(&mut self, strategy: Box<dyn ScoringStrategy>) {
+        self.scoring_strategy = strategy;
+    }
+
     pub fn clear_measurements(&mut self) {
         self.peers.clear();
         self.summary_stats.clear();
@@ -57,6 +68,17 @@ pub fn get_local_reputation_ref(&self) -> Arc<scc::HashMap<NodeIndex, u8>> {
         self.local_reputation.clone()
     }
 
+    /// Moves every local reputation score towards the neutral score by the given fraction of its
+    /// distance from it.
+    pub fn decay_local_reputation(&self, decay: f64) {
+        let decay = decay.clamp(0.0, 1.0);
+        self.local_reputation.retain(|_, score| {
+            let distance = *score as f64 - NEUTRAL_SCORE as f64;
+            *score = (NEUTRAL_SCORE as f64 + distance * (1.0 - decay)).round() as u8;
+            true
+        });
+    }
+
     pub fn report_sat(&mut self, peer: NodeIndex, weight: Weight) {
         self.insert_if_not_exists(&peer);
         let (old_val, new_val) = self
@@ -164,45 +186,15 @@ fn update_local_reputation_score(&mut self, peer: NodeIndex) {
         if let Some(measurements) = self.peers.get(&peer) {
             let measurements: ReputationMeasurements = measurements.into();
             let norm_measurements = NormalizedMeasurements::new(measurements, &self.summary_stats);
-            let mut score = 0.0;
-            let mut count = 0;
-            if let Some(latency) = norm_measurements.latency {
-                score += 1.0 - latency;
-                count += 1;
-            }
-            if let Some(interactions) = norm_measurements.interactions {
-                score += interactions;
-                count += 1;
-            }
-            if let Some(inbound_bandwidth) = norm_measurements.inbound_bandwidth {
-                score += inbound_bandwidth;
-                count += 1;
-            }
-            if let Some(outbound_bandwidth) = norm_measurements.outbound_bandwidth {
-                score += outbound_bandwidth;
-                count += 1;
-            }
-            if let Some(bytes_received) = norm_measurements.bytes_received {
-                score += bytes_received;
-                count += 1;
-            }
-            if let Some(bytes_sent) = norm_measurements.bytes_sent {
-                score += bytes_sent;
-                count += 1;
-            }
-            if let Some(uptime) = norm_measurements.uptime {
-                score += uptime;
-                count += 1;
-            }
-            if count != 0 {
-                score /= count as f64;
-                score *= 100.0;
+            if let Some(score) = self.scoring_strategy.score(&norm_measurements) {
+                let score = score.min(100);
                 self.local_reputation
                     .entry(peer)
                     .and_modify(|s| {
-                        *s = (*s as f64 * REP_EWMA_WEIGHT + (1.0 - REP_EWMA_WEIGHT) * score) as u8
+                        *s = (*s as f64 * REP_EWMA_WEIGHT + (1.0 - REP_EWMA_WEIGHT) * score as f64)
+                            as u8
                     })
-                    .or_insert(score as u8);
+                    .or_insert(score);
             }
         }
     }
@@ -712,17 +704,6 @@ fn from(value: &MeasurementStore) -> Self {
     }
 }
 
-#[derive(Debug)]
-struct NormalizedMeasurements {
-    latency: Option<f64>,
-    interactions: Option<f64>,
-    inbound_bandwidth: Option<f64>,
-    outbound_bandwidth: Option<f64>,
-    bytes_received: Option<f64>,
-    bytes_sent: Option<f64>,
-    uptime: Option<f64>,
-}
-
 impl NormalizedMeasurements {
     fn new(values: ReputationMeasurements, summary_stats: &SummaryStatistics) -> Self {
         let latency = if let (Some(min_val), Some(max_val)) =
@@ -1124,6 +1105,22 @@ fn test_get_local_reputation_ref() {
         assert!(reputation_map.contains(&peer2));
     }
 
+    #[test]
+    fn test_decay_local_reputation() {
+        let manager = MeasurementManager::new();
+        let reputation_map = manager.get_local_reputation_ref();
+        reputation_map.insert(0, 90).unwrap();
+        reputation_map.insert(1, 10).unwrap();
+
+        manager.decay_local_reputation(0.5);
+        assert_eq!(reputation_map.read(&0, |_, s| *s), Some(70));
+        assert_eq!(reputation_map.read(&1, |_, s| *s), Some(30));
+
+        manager.decay_local_reputation(1.0);
+        assert_eq!(reputation_map.read(&0, |_, s| *s), Some(NEUTRAL_SCORE));
+        assert_eq!(reputation_map.read(&1, |_, s| *s), Some(NEUTRAL_SCORE));
+    }
+
     #[test]
     fn test_get_measurements_contains() {
         let mut manager = MeasurementManager::new();
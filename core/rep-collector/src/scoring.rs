@@ -0,0 +1,45 @@
+/// The measurements we have for a peer, each one min-max normalized against the other peers we
+/// have measurements for, so that `0.0` is the lowest and `1.0` the highest value observed. The
+/// uptime is the fraction of our pings that the peer answered.
+#[derive(Debug, Clone, Default)]
+pub struct NormalizedMeasurements {
+    pub latency: Option<f64>,
+    pub interactions: Option<f64>,
+    pub inbound_bandwidth: Option<f64>,
+    pub outbound_bandwidth: Option<f64>,
+    pub bytes_received: Option<f64>,
+    pub bytes_sent: Option<f64>,
+    pub uptime: Option<f64>,
+}
+
+/// Turns the measurements of a peer into a local reputation score between `0` and `100`.
+pub trait ScoringStrategy: Send + Sync + 'static {
+    /// Returns the score of a peer or `None` if there is not enough data to score it.
+    fn score(&self, measurements: &NormalizedMeasurements) -> Option<u8>;
+}
+
+/// Weighs all of the available measurements equally.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct DefaultScoringStrategy;
+
+impl ScoringStrategy for DefaultScoringStrategy {
+    fn score(&self, measurements: &NormalizedMeasurements) -> Option<u8> {
+        let values = [
+            // A lower latency is better.
+            measurements.latency.map(|latency| 1.0 - latency),
+            measurements.interactions,
+            measurements.inbound_bandwidth,
+            measurements.outbound_bandwidth,
+            measurements.bytes_received,
+            measurements.bytes_sent,
+            measurements.uptime,
+        ];
+
+        let (sum, count) = values
+            .into_iter()
+            .flatten()
+            .fold((0.0, 0), |(sum, count), value| (sum + value, count + 1));
+
+        (count != 0).then(|| (sum / count as f64 * 100.0) as u8)
+    }
+}
@@ -7,7 +7,14 @@
 use lightning_application::genesis::{Genesis, GenesisNode};
 use lightning_application::query_runner::QueryRunner;
 use lightning_interfaces::prelude::*;
-use lightning_interfaces::types::{NodePorts, UpdateMethod, UpdatePayload, UpdateRequest};
+use lightning_interfaces::types::{
+    HandshakePorts,
+    NodeIndex,
+    NodePorts,
+    UpdateMethod,
+    UpdatePayload,
+    UpdateRequest,
+};
 use lightning_interfaces::Weight;
 use lightning_notifier::Notifier;
 use lightning_signer::Signer;
@@ -20,12 +27,12 @@
 use lightning_test_utils::json_config::JsonConfigProvider;
 use lightning_test_utils::keys::EphemeralKeystore;
 use lightning_utils::application::QueryRunnerExt;
-use tempfile::tempdir;
+use tempfile::{tempdir, TempDir};
 
 use crate::aggregator::ReputationAggregator;
 use crate::config::Config;
-use crate::measurement_manager::Interactions;
-use crate::{MyReputationQuery, MyReputationReporter};
+use crate::measurement_manager::{Interactions, NEUTRAL_SCORE};
+use crate::{MyReputationQuery, MyReputationReporter, NormalizedMeasurements, ScoringStrategy};
 
 partial!(TestBinding {
     ConfigProviderInterface = JsonConfigProvider;
@@ -66,7 +73,11 @@ fn get_genesis_committee(
                 rpc: 8300 + i as u16,
                 pool: 8400 + i as u16,
                 pinger: 8600 + i as u16,
-                handshake: Default::default(),
+                handshake: HandshakePorts {
+                    http: 8700 + i as u16,
+                    webrtc: 8800 + i as u16,
+                    webtransport: 8900 + i as u16,
+                },
             },
             None,
             true,
@@ -76,6 +87,35 @@ fn get_genesis_committee(
     (committee, keystores)
 }
 
+fn build_node(temp_dir: &TempDir, config: Config) -> Node<TestBinding> {
+    let (committee, mut keystores) = get_genesis_committee(1);
+    let mut genesis = Genesis::default();
+    genesis.node_info = committee;
+
+    let genesis_path = genesis
+        .write_to_dir(temp_dir.path().to_path_buf().try_into().unwrap())
+        .unwrap();
+
+    Node::<TestBinding>::init_with_provider(
+        fdi::Provider::default()
+            .with(
+                JsonConfigProvider::default()
+                    .with::<Application<TestBinding>>(AppConfig::test(genesis_path))
+                    .with::<MockConsensus<TestBinding>>(ConsensusConfig {
+                        min_ordering_time: 0,
+                        max_ordering_time: 1,
+                        probability_txn_lost: 0.0,
+                        transactions_to_lose: HashSet::new(),
+                        new_block_interval: Duration::from_secs(5),
+                        fault_injector: None,
+                    })
+                    .with::<ReputationAggregator<TestBinding>>(config),
+            )
+            .with(keystores.remove(0)),
+    )
+    .expect("failed to initialize node")
+}
+
 #[tokio::test]
 async fn test_query() {
     let keystore = EphemeralKeystore::<TestBinding>::default();
@@ -124,9 +164,11 @@ async fn test_query() {
                         probability_txn_lost: 0.0,
                         transactions_to_lose: HashSet::new(),
                         new_block_interval: Duration::from_secs(5),
+                        fault_injector: None,
                     })
                     .with::<ReputationAggregator<TestBinding>>(Config {
                         reporter_buffer_size: 1,
+                        ..Default::default()
                     }),
             )
             .with(keystore),
@@ -225,7 +267,11 @@ async fn test_submit_measurements() {
             rpc: 38103,
             pool: 38104,
             pinger: 38106,
-            handshake: Default::default(),
+            handshake: HandshakePorts {
+                http: 38220,
+                webrtc: 38320,
+                webtransport: 38321,
+            },
         },
         None,
         true,
@@ -254,9 +300,11 @@ async fn test_submit_measurements() {
                         probability_txn_lost: 0.0,
                         transactions_to_lose: HashSet::new(),
                         new_block_interval: Duration::from_secs(5),
+                        fault_injector: None,
                     })
                     .with::<ReputationAggregator<TestBinding>>(Config {
                         reporter_buffer_size: 1,
+                        ..Default::default()
                     }),
             )
             .with(keystore),
@@ -341,6 +389,7 @@ async fn test_reputation_calculation_and_query() {
         probability_txn_lost: 0.0,
         transactions_to_lose: HashSet::new(),
         new_block_interval: Duration::from_secs(5),
+        fault_injector: None,
     });
 
     genesis.node_info.push(GenesisNode::new(
@@ -377,13 +426,17 @@ async fn test_reputation_calculation_and_query() {
         "127.0.0.1".parse().unwrap(),
         node_public_key2,
         NodePorts {
-            primary: 48000,
-            worker: 48101,
-            mempool: 48102,
-            rpc: 48103,
-            pool: 48104,
-            pinger: 48106,
-            handshake: Default::default(),
+            primary: 38000,
+            worker: 38101,
+            mempool: 38102,
+            rpc: 38103,
+            pool: 38104,
+            pinger: 38106,
+            handshake: HandshakePorts {
+                http: 38220,
+                webrtc: 38320,
+                webtransport: 38321,
+            },
         },
         None,
         true,
@@ -409,6 +462,7 @@ async fn test_reputation_calculation_and_query() {
                     .with::<Application<TestBinding>>(AppConfig::test(genesis_path.clone()))
                     .with::<ReputationAggregator<TestBinding>>(Config {
                         reporter_buffer_size: 1,
+                        ..Default::default()
                     }),
             )
             .with(consensus_group.clone())
@@ -430,6 +484,7 @@ async fn test_reputation_calculation_and_query() {
                     .with::<Application<TestBinding>>(AppConfig::test(genesis_path))
                     .with::<ReputationAggregator<TestBinding>>(Config {
                         reporter_buffer_size: 1,
+                        ..Default::default()
                     }),
             )
             .with(consensus_group)
@@ -510,7 +565,7 @@ async fn test_reputation_calculation_and_query() {
             payload,
         };
 
-        forwarder_socket.run(req.into()).await.unwrap();
+        forwarder_socket.run(req.into()).await.unwrap().unwrap();
     }
     // Make sure that the epoch change happened.
     assert_eq!(query_runner.get_epoch_info().epoch, 1);
@@ -530,3 +585,149 @@ async fn test_reputation_calculation_and_query() {
     node1.shutdown().await;
     node2.shutdown().await;
 }
+
+/// Scores every peer we have measurements for the same, so that reports that arrive late can not
+/// move the score.
+struct ConstantScoringStrategy(u8);
+
+impl ScoringStrategy for ConstantScoringStrategy {
+    fn score(&self, _: &NormalizedMeasurements) -> Option<u8> {
+        Some(self.0)
+    }
+}
+
+async fn wait_for_reputation(query: &MyReputationQuery, peer: NodeIndex, expected: u8) {
+    tokio::time::timeout(Duration::from_secs(10), async {
+        while query.get_reputation_of(&peer) != Some(expected) {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+    })
+    .await
+    .unwrap_or_else(|_| {
+        panic!(
+            "expected a reputation of {expected}, got {:?}",
+            query.get_reputation_of(&peer)
+        )
+    });
+}
+
+#[tokio::test]
+async fn test_score_decays_on_epoch_change() {
+    let temp_dir = tempdir().unwrap();
+    let mut node = build_node(
+        &temp_dir,
+        Config {
+            score_decay: 0.5,
+            ..Default::default()
+        },
+    );
+    node.provider
+        .get::<ReputationAggregator<TestBinding>>()
+        .set_scoring_strategy(ConstantScoringStrategy(90));
+    node.start().await;
+
+    let rep_reporter = node.provider.get::<MyReputationReporter>();
+    let rep_query = node.provider.get::<MyReputationQuery>();
+    let emitter = node.provider.get::<Notifier<TestBinding>>().get_emitter();
+
+    // A single report, so that nothing is still in flight once the score shows up.
+    let alice = 1;
+    rep_reporter.report_sat(alice, Weight::VeryStrong);
+    let mut expected = 90;
+    wait_for_reputation(&rep_query, alice, expected).await;
+
+    // Advance a few epochs without any new measurements.
+    for epoch in 1..=4 {
+        emitter.epoch_changed(epoch, [0; 32]);
+        let distance = (expected - NEUTRAL_SCORE) as f64;
+        expected = (NEUTRAL_SCORE as f64 + distance * 0.5).round() as u8;
+        wait_for_reputation(&rep_query, alice, expected).await;
+    }
+    assert!(expected < NEUTRAL_SCORE + 10);
+
+    node.shutdown().await;
+}
+
+#[tokio::test]
+async fn test_score_decay_is_validated() {
+    let temp_dir = tempdir().unwrap();
+    for score_decay in [f64::NAN, -0.1, 1.1] {
+        let config = Config {
+            score_decay,
+            ..Default::default()
+        };
+        assert!(config.validate().is_err());
+
+        let (committee, mut keystores) = get_genesis_committee(1);
+        let genesis = Genesis {
+            node_info: committee,
+            ..Default::default()
+        };
+        let genesis_path = genesis
+            .write_to_dir(temp_dir.path().to_path_buf().try_into().unwrap())
+            .unwrap();
+        assert!(
+            Node::<TestBinding>::init_with_provider(
+                fdi::Provider::default()
+                    .with(
+                        JsonConfigProvider::default()
+                            .with::<Application<TestBinding>>(AppConfig::test(genesis_path))
+                            .with::<ReputationAggregator<TestBinding>>(config),
+                    )
+                    .with(keystores.remove(0)),
+            )
+            .is_err()
+        );
+    }
+    for score_decay in [0.0, 0.5, 1.0] {
+        let config = Config {
+            score_decay,
+            ..Default::default()
+        };
+        assert!(config.validate().is_ok());
+    }
+}
+
+struct UptimeScoringStrategy;
+
+impl ScoringStrategy for UptimeScoringStrategy {
+    fn score(&self, measurements: &NormalizedMeasurements) -> Option<u8> {
+        measurements.uptime.map(|uptime| (uptime * 100.0) as u8)
+    }
+}
+
+#[tokio::test]
+async fn test_custom_scoring_strategy() {
+    let temp_dir = tempdir().unwrap();
+    let mut node = build_node(&temp_dir, Config::default());
+    node.provider
+        .get::<ReputationAggregator<TestBinding>>()
+        .set_scoring_strategy(UptimeScoringStrategy);
+    node.start().await;
+
+    let rep_reporter = node.provider.get::<MyReputationReporter>();
+    let rep_query = node.provider.get::<MyReputationQuery>();
+
+    // Alice answers our pings but is worse on everything else, so only a strategy that looks
+    // at the uptime alone ranks her above bob.
+    let alice = 1;
+    let bob = 2;
+    let mut interval = tokio::time::interval(Duration::from_millis(100));
+    for _ in 0..50 {
+        rep_reporter.report_ping(alice, Some(Duration::from_millis(300)));
+        rep_reporter.report_ping(bob, None);
+        rep_reporter.report_unsat(alice, Weight::Strong);
+        rep_reporter.report_sat(bob, Weight::Strong);
+
+        interval.tick().await;
+        if rep_query.get_reputation_of(&alice) == Some(100)
+            && rep_query.get_reputation_of(&bob) == Some(0)
+        {
+            break;
+        }
+    }
+    assert_eq!(rep_query.get_reputation_of(&alice), Some(100));
+    assert_eq!(rep_query.get_reputation_of(&bob), Some(0));
+
+    node.shutdown().await;
+}
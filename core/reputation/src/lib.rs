@@ -12,11 +12,15 @@
     WeightedReputationMeasurements,
 };
 
+/// Calculates the reputation score and uptime of every node from the weighted measurements
+/// reported about it. Measurements more than `mad_outlier_threshold` median absolute deviations
+/// away from the median are dropped, if it is set.
 pub fn calculate_reputation_scores(
     weighted_measurements_map: HashMap<NodeIndex, Vec<WeightedReputationMeasurements>>,
+    mad_outlier_threshold: Option<i128>,
 ) -> HashMap<NodeIndex, (Option<u8>, Option<u8>)> {
     let mut normalized_measurements_map =
-        calculate_normalized_measurements(weighted_measurements_map);
+        calculate_normalized_measurements(weighted_measurements_map, mad_outlier_threshold);
 
     let min_max_vals: MinMaxValues = (&normalized_measurements_map).into();
 
@@ -32,12 +36,14 @@ pub fn calculate_reputation_scores(
 
 fn calculate_normalized_measurements(
     weighted_measurements_map: HashMap<NodeIndex, Vec<WeightedReputationMeasurements>>,
+    mad_outlier_threshold: Option<i128>,
 ) -> HashMap<NodeIndex, NormalizedMeasurements> {
     weighted_measurements_map
         .into_iter()
         .map(|(node, rm)| {
             let collected_measurements: CollectedMeasurements = rm.into();
-            let normalized_measurements: NormalizedMeasurements = collected_measurements.into();
+            let normalized_measurements =
+                NormalizedMeasurements::new(collected_measurements, mad_outlier_threshold);
             (node, normalized_measurements)
         })
         .collect()
@@ -289,7 +295,7 @@ fn test_normalized_measurements_min_max_normalize() {
         let rng = random::get_seedable_rng();
         let weighted_measurements_map = generate_weighted_measurements_map(10, Some(rng));
         let mut normalized_measurements_map =
-            calculate_normalized_measurements(weighted_measurements_map);
+            calculate_normalized_measurements(weighted_measurements_map, None);
 
         let min_max_vals: MinMaxValues = (&normalized_measurements_map).into();
         normalized_measurements_map.iter_mut().for_each(|(_, m)| {
@@ -346,7 +352,7 @@ fn test_calculate_reputation_scores() {
         let node_index2 = rng.gen_range(0..=NodeIndex::MAX);
         map.insert(node_index2, generate_weighted_measurements(10, Some(rng)));
 
-        let rep_scores = calculate_reputation_scores(map);
+        let rep_scores = calculate_reputation_scores(map, None);
         assert!(rep_scores.contains_key(&node_index1));
         assert!(rep_scores.contains_key(&node_index2));
     }
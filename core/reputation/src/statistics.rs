@@ -1,3 +1,4 @@
+use std::cmp::Ordering;
 use std::ops::{Add, Div, Mul, Sub};
 
 use hp_fixed::signed::HpFixed;
@@ -122,6 +123,74 @@ fn z_score_normalize_filter<T>(values: &mut Vec<T>)
     }
 }
 
+/// Returns the median of the given values, which must already be sorted.
+fn calculate_median<T>(sorted_values: &[T]) -> Option<T>
+where
+    T: Add<T, Output = T> + Div<Output = T> + From<i128> + Clone,
+{
+    let n = sorted_values.len();
+    if n == 0 {
+        None
+    } else if n % 2 == 1 {
+        Some(sorted_values[n / 2].clone())
+    } else {
+        Some((sorted_values[n / 2 - 1].clone() + sorted_values[n / 2].clone()) / 2.into())
+    }
+}
+
+fn sort_values<T: PartialOrd>(values: &mut [T]) {
+    values.sort_by(|a, b| a.partial_cmp(b).unwrap_or(Ordering::Equal));
+}
+
+/// Drops the values that are more than `threshold` median absolute deviations away from the
+/// median. Unlike the z-score, the median and its deviation are not skewed by the outliers
+/// themselves, so a single extreme value is caught even among a handful of values.
+fn mad_outlier_filter<T>(values: &mut Vec<T>, threshold: i128)
+where
+    T: Add<T, Output = T>
+        + Div<Output = T>
+        + Mul<T, Output = T>
+        + From<i128>
+        + Sub<T, Output = T>
+        + PartialOrd<T>
+        + Clone,
+{
+    // With less than three values there is no majority to tell which one is off.
+    if values.len() < 3 {
+        return;
+    }
+
+    let mut sorted = values.clone();
+    sort_values(&mut sorted);
+    let Some(median) = calculate_median(&sorted) else {
+        return;
+    };
+
+    let mut deviations: Vec<T> = sorted
+        .into_iter()
+        .map(|v| abs_difference(v, median.clone()))
+        .collect();
+    sort_values(&mut deviations);
+    let Some(mad) = calculate_median(&deviations) else {
+        return;
+    };
+
+    // If most of the values are identical the deviation is zero and every other value would be
+    // dropped, leave those to the z-score filter.
+    if mad <= 0.into() {
+        return;
+    }
+
+    let max_deviation = mad * threshold.into();
+    values.retain(|v| abs_difference(v.clone(), median.clone()) <= max_deviation);
+}
+
+fn total_weight<T: WeightedValue>(values: &[T]) -> HpFixed<PRECISION> {
+    values
+        .iter()
+        .fold(0.into(), |acc: HpFixed<PRECISION>, v| acc + v.get_weight())
+}
+
 fn calculate_weighted_mean<T>(values: &[T]) -> Option<HpFixed<PRECISION>>
 where
     T: Default
@@ -145,7 +214,16 @@ fn calculate_weighted_mean<T>(values: &[T]) -> Option<HpFixed<PRECISION>>
     }
 }
 
-pub fn calculate_z_normalized_weighted_mean<T>(mut values: Vec<T>) -> Option<HpFixed<PRECISION>>
+/// Computes the weighted mean of the values that pass the z-score filter.
+///
+/// If `mad_outlier_threshold` is set, values more than that many median absolute deviations away
+/// from the median are dropped first, and the weight of every dropped value is spread over the
+/// remaining ones so that the mean is not pulled towards zero. Reputation scores are part of the
+/// consensus, so the threshold comes from the protocol parameters rather than a constant.
+pub fn calculate_z_normalized_weighted_mean<T>(
+    mut values: Vec<T>,
+    mad_outlier_threshold: Option<i128>,
+) -> Option<HpFixed<PRECISION>>
 where
     T: Default
         + WeightedValue
@@ -157,7 +235,25 @@ pub fn calculate_z_normalized_weighted_mean<T>(mut values: Vec<T>) -> Option<HpF
         + PartialOrd<T>
         + Clone,
 {
+    let Some(threshold) = mad_outlier_threshold else {
+        z_score_normalize_filter(&mut values);
+        return calculate_weighted_mean(&values);
+    };
+
+    let len = values.len();
+    let weight = total_weight(&values);
+    mad_outlier_filter(&mut values, threshold);
     z_score_normalize_filter(&mut values);
+
+    if values.len() < len {
+        let remaining_weight = total_weight(&values);
+        if remaining_weight > 0.into() {
+            for v in values.iter_mut() {
+                v.set_weight(v.get_weight() * weight.clone() / remaining_weight.clone());
+            }
+        }
+    }
+
     calculate_weighted_mean(&values)
 }
 
@@ -166,6 +262,8 @@ mod tests {
     use super::*;
     use crate::types::WeightedFloat;
 
+    const MAD_OUTLIER_THRESHOLD: i128 = 5;
+
     #[test]
     fn test_mean_basic() {
         let values = [1, 2, 3, 4, 5, 6, 7, 8, 9];
@@ -403,7 +501,70 @@ fn test_calculate_z_normalized_weighted_mean() {
                 weight: 0.05.into(),
             },
         ];
-        let weighted_mean = calculate_z_normalized_weighted_mean(values);
+        // Without the outlier filter the z-score drops the 50 and its weight is lost.
+        let weighted_mean = calculate_z_normalized_weighted_mean(values.clone(), None);
         assert_eq!(weighted_mean, Some(2.91.into()));
+
+        // With the outlier filter the 50 is dropped as well, but its weight of 0.05 is spread over
+        // the remaining values, which gives 2.91 / 0.95.
+        let weighted_mean =
+            calculate_z_normalized_weighted_mean(values, Some(MAD_OUTLIER_THRESHOLD)).unwrap();
+        let expected = HpFixed::<PRECISION>::from(2.91) / HpFixed::<PRECISION>::from(0.95);
+        assert!((weighted_mean - expected).try_abs().unwrap() < 0.000001.into());
+    }
+
+    #[test]
+    fn test_mad_outlier_filter() {
+        // Too few values for the z-score to flag the outlier.
+        let mut values = vec![100, 102, 98, 101, 10000];
+        z_score_normalize_filter(&mut values);
+        assert_eq!(values, vec![100, 102, 98, 101, 10000]);
+
+        mad_outlier_filter(&mut values, MAD_OUTLIER_THRESHOLD);
+        assert_eq!(values, vec![100, 102, 98, 101]);
+    }
+
+    #[test]
+    fn test_mad_outlier_filter_identical_values() {
+        let mut values = vec![5, 5, 5, 5, 6];
+        mad_outlier_filter(&mut values, MAD_OUTLIER_THRESHOLD);
+        assert_eq!(values, vec![5, 5, 5, 5, 6]);
+    }
+
+    #[test]
+    fn test_mad_outlier_filter_two_elements() {
+        let mut values = vec![1, 1000];
+        mad_outlier_filter(&mut values, MAD_OUTLIER_THRESHOLD);
+        assert_eq!(values, vec![1, 1000]);
+    }
+
+    #[test]
+    fn test_calculate_z_normalized_weighted_mean_with_outlier() {
+        let consistent = [100.0, 102.0, 98.0, 101.0];
+        let weighted = |values: &[f64]| {
+            values
+                .iter()
+                .map(|v| WeightedFloat {
+                    value: (*v).into(),
+                    weight: (1.0 / values.len() as f64).into(),
+                })
+                .collect::<Vec<_>>()
+        };
+
+        let expected = calculate_z_normalized_weighted_mean(
+            weighted(&consistent),
+            Some(MAD_OUTLIER_THRESHOLD),
+        )
+        .unwrap();
+
+        let mut with_outlier = consistent.to_vec();
+        with_outlier.push(10000.0);
+        let weighted_mean = calculate_z_normalized_weighted_mean(
+            weighted(&with_outlier),
+            Some(MAD_OUTLIER_THRESHOLD),
+        )
+        .unwrap();
+
+        assert!((weighted_mean - expected).try_abs().unwrap() < 0.01.into());
     }
 }
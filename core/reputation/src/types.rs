@@ -8,6 +8,10 @@
 
 pub trait WeightedValue {
     fn get_weighted_value(&self) -> HpFixed<PRECISION>;
+
+    fn get_weight(&self) -> HpFixed<PRECISION>;
+
+    fn set_weight(&mut self, weight: HpFixed<PRECISION>);
 }
 
 #[derive(Debug, Clone)]
@@ -29,6 +33,14 @@ impl WeightedValue for WeightedFloat {
     fn get_weighted_value(&self) -> HpFixed<PRECISION> {
         self.value.clone() * self.weight.clone()
     }
+
+    fn get_weight(&self) -> HpFixed<PRECISION> {
+        self.weight.clone()
+    }
+
+    fn set_weight(&mut self, weight: HpFixed<PRECISION>) {
+        self.weight = weight;
+    }
 }
 
 impl Default for WeightedFloat {
@@ -414,22 +426,45 @@ fn from(normalized_measurements: &HashMap<NodeIndex, NormalizedMeasurements>) ->
 
 impl From<CollectedMeasurements> for NormalizedMeasurements {
     fn from(collected_measurements: CollectedMeasurements) -> Self {
-        let latency =
-            statistics::calculate_z_normalized_weighted_mean(collected_measurements.latency);
-        let interactions =
-            statistics::calculate_z_normalized_weighted_mean(collected_measurements.interactions);
+        Self::new(collected_measurements, None)
+    }
+}
+
+impl NormalizedMeasurements {
+    /// Averages the collected measurements, dropping outliers that are more than
+    /// `mad_outlier_threshold` median absolute deviations away from the median if it is set.
+    pub fn new(
+        collected_measurements: CollectedMeasurements,
+        mad_outlier_threshold: Option<i128>,
+    ) -> Self {
+        let latency = statistics::calculate_z_normalized_weighted_mean(
+            collected_measurements.latency,
+            mad_outlier_threshold,
+        );
+        let interactions = statistics::calculate_z_normalized_weighted_mean(
+            collected_measurements.interactions,
+            mad_outlier_threshold,
+        );
         let inbound_bandwidth = statistics::calculate_z_normalized_weighted_mean(
             collected_measurements.inbound_bandwidth,
+            mad_outlier_threshold,
         );
         let outbound_bandwidth = statistics::calculate_z_normalized_weighted_mean(
             collected_measurements.outbound_bandwidth,
+            mad_outlier_threshold,
+        );
+        let bytes_received = statistics::calculate_z_normalized_weighted_mean(
+            collected_measurements.bytes_received,
+            mad_outlier_threshold,
+        );
+        let bytes_sent = statistics::calculate_z_normalized_weighted_mean(
+            collected_measurements.bytes_sent,
+            mad_outlier_threshold,
+        );
+        let uptime = statistics::calculate_z_normalized_weighted_mean(
+            collected_measurements.uptime,
+            mad_outlier_threshold,
         );
-        let bytes_received =
-            statistics::calculate_z_normalized_weighted_mean(collected_measurements.bytes_received);
-        let bytes_sent =
-            statistics::calculate_z_normalized_weighted_mean(collected_measurements.bytes_sent);
-        let uptime =
-            statistics::calculate_z_normalized_weighted_mean(collected_measurements.uptime);
 
         Self {
             latency,
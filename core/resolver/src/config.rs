@@ -1,3 +1,5 @@
+use std::time::Duration;
+
 use lightning_utils::config::LIGHTNING_HOME_DIR;
 use resolved_pathbuf::ResolvedPathBuf;
 use serde::{Deserialize, Serialize};
@@ -6,6 +8,12 @@
 pub struct Config {
     /// Path to the database used by the resolver.
     pub store_path: ResolvedPathBuf,
+    /// How long a resolved pointer is served from the in-memory cache.
+    #[serde(with = "humantime_serde", default = "default_cache_ttl")]
+    pub cache_ttl: Duration,
+    /// How long a pointer that could not be resolved is remembered as missing.
+    #[serde(with = "humantime_serde", default = "default_negative_cache_ttl")]
+    pub negative_cache_ttl: Duration,
 }
 
 impl Default for Config {
@@ -15,6 +23,16 @@ fn default() -> Self {
                 .join("data/resolver_store")
                 .try_into()
                 .expect("Failed to resolve path"),
+            cache_ttl: default_cache_ttl(),
+            negative_cache_ttl: default_negative_cache_ttl(),
         }
     }
 }
+
+fn default_cache_ttl() -> Duration {
+    Duration::from_secs(300)
+}
+
+fn default_negative_cache_ttl() -> Duration {
+    Duration::from_secs(10)
+}
@@ -1,9 +1,13 @@
-use std::sync::Arc;
+use std::cmp::Ordering;
+use std::num::NonZeroUsize;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
 
 use fleek_crypto::{NodeSecretKey, PublicKey, SecretKey};
 use lightning_interfaces::prelude::*;
 use lightning_interfaces::schema::broadcast::ResolvedImmutablePointerRecord;
 use lightning_interfaces::types::{Blake3Hash, ImmutablePointer, NodeIndex, Topic};
+use lru::LruCache;
 use rocksdb::{Options, DB};
 use tokio::sync::OnceCell;
 use tracing::warn;
@@ -13,6 +17,10 @@
 
 const B3_TO_URI: &str = "b3_to_uri";
 const URI_TO_B3: &str = "uri_to_b3";
+/// The most entries the cache holds, inserting more evicts the least recently used ones.
+const MAX_CACHE_ENTRIES: usize = 10_000;
+/// The reputation assumed for providers that don't have a reputation score yet.
+const NEUTRAL_REPUTATION: u8 = 50;
 
 #[derive(Clone)]
 pub struct Resolver<C: Collection> {
@@ -54,6 +62,11 @@ fn init(
             node_index: OnceCell::new(),
             db,
             query_runner,
+            cache: ResolverCache::new(
+                config.cache_ttl,
+                config.negative_cache_ttl,
+                NonZeroUsize::new(MAX_CACHE_ENTRIES).unwrap(),
+            ),
         };
 
         Ok(Self {
@@ -101,6 +114,10 @@ fn get_origin_finder(&self, _hash: Blake3Hash) -> Self::OriginFinder {
     fn get_origins(&self, hash: Blake3Hash) -> Option<Vec<ResolvedImmutablePointerRecord>> {
         self.inner.get_origins(hash)
     }
+
+    fn resolve_ranked(&self, uri: &Blake3Hash) -> Vec<NodeIndex> {
+        self.inner.resolve_ranked(uri)
+    }
 }
 
 struct ResolverInner<C: Collection> {
@@ -109,6 +126,7 @@ struct ResolverInner<C: Collection> {
     node_index: OnceCell<NodeIndex>,
     db: Arc<DB>,
     query_runner: c!(C::ApplicationInterface::SyncExecutor),
+    cache: ResolverCache,
 }
 
 impl<C: Collection> ResolverInner<C> {
@@ -122,7 +140,9 @@ async fn start(&self) {
                     let digest = record.to_digest();
                     peer_public_key.verify(&record.signature, &digest);
                     if peer_public_key.verify(&record.signature, &digest) {
+                        let pointer = record.pointer.clone();
                         ResolverInner::<C>::store_mapping(record, &db);
+                        self.cache.invalidate(&pointer);
                     } else {
                         warn!("Received record with invalid signature")
                     }
@@ -158,6 +178,7 @@ async fn publish(&self, hash: Blake3Hash, pointers: &[ImmutablePointer]) {
             let digest = resolved_pointer.to_digest();
             resolved_pointer.signature = self.node_sk.sign(&digest);
             ResolverInner::<C>::store_mapping(resolved_pointer.clone(), &self.db);
+            self.cache.invalidate(&resolved_pointer.pointer);
 
             for (index, pointer) in pointers.iter().enumerate() {
                 if index > 0 {
@@ -173,12 +194,17 @@ async fn publish(&self, hash: Blake3Hash, pointers: &[ImmutablePointer]) {
     ///
     /// This can return [`None`] if no local record is found.
     async fn get_blake3_hash(&self, pointer: ImmutablePointer) -> Option<Blake3Hash> {
+        self.cache
+            .get_or_resolve(pointer, |pointer| self.lookup_blake3_hash(pointer))
+    }
+
+    fn lookup_blake3_hash(&self, pointer: &ImmutablePointer) -> Option<Blake3Hash> {
         let cf = self
             .db
             .cf_handle(URI_TO_B3)
             .expect("No uri_to_b3 column family in resolver db");
 
-        let pointer_bytes = bincode::serialize(&pointer).ok()?;
+        let pointer_bytes = bincode::serialize(pointer).ok()?;
 
         let res = self
             .db
@@ -199,6 +225,27 @@ fn get_origins(&self, hash: Blake3Hash) -> Option<Vec<ResolvedImmutablePointerRe
         bincode::deserialize(&res).ok()
     }
 
+    fn resolve_ranked(&self, uri: &Blake3Hash) -> Vec<NodeIndex> {
+        let providers = self.query_runner.get_uri_providers(uri).unwrap_or_default();
+        let node_index = self.query_runner.pubkey_to_index(&self.node_sk.to_pk());
+
+        rank_providers(
+            providers,
+            |provider| node_index.and_then(|node_index| self.get_latency(node_index, provider)),
+            |provider| self.query_runner.get_reputation_score(&provider),
+        )
+    }
+
+    /// Returns the latency between the two nodes that was measured in the last epoch.
+    fn get_latency(&self, lhs: NodeIndex, rhs: NodeIndex) -> Option<Duration> {
+        if lhs == rhs {
+            return Some(Duration::ZERO);
+        }
+        // The latencies are keyed by the node with the smaller index first.
+        self.query_runner
+            .get_latencies(&(lhs.min(rhs), lhs.max(rhs)))
+    }
+
     fn store_mapping(record: ResolvedImmutablePointerRecord, db: &DB) {
         let b3_hash = record.hash;
         let b3_cf = db
@@ -234,3 +281,208 @@ fn store_mapping(record: ResolvedImmutablePointerRecord, db: &DB) {
             .expect("Failed to insert mapping to db in resolver")
     }
 }
+
+/// Orders the providers by their latency to us, weighted by their reputation so that the latency
+/// of a provider with the worst reputation counts twice as much as the one of a provider with the
+/// best reputation. Providers without a latency measurement come last, ordered by reputation.
+fn rank_providers(
+    providers: impl IntoIterator<Item = NodeIndex>,
+    latency: impl Fn(NodeIndex) -> Option<Duration>,
+    reputation: impl Fn(NodeIndex) -> Option<u8>,
+) -> Vec<NodeIndex> {
+    let mut ranked = providers
+        .into_iter()
+        .map(|provider| {
+            let reputation = reputation(provider).unwrap_or(NEUTRAL_REPUTATION).min(100);
+            let penalty = 2.0 - reputation as f64 / 100.0;
+            let latency = latency(provider).map(|latency| latency.as_secs_f64() * penalty);
+            (provider, latency, reputation)
+        })
+        .collect::<Vec<_>>();
+
+    ranked.sort_by(
+        |(lhs, lhs_latency, lhs_reputation), (rhs, rhs_latency, rhs_reputation)| {
+            match (lhs_latency, rhs_latency) {
+                (Some(lhs_latency), Some(rhs_latency)) => lhs_latency.total_cmp(rhs_latency),
+                (Some(_), None) => Ordering::Less,
+                (None, Some(_)) => Ordering::Greater,
+                (None, None) => rhs_reputation.cmp(lhs_reputation),
+            }
+            .then(lhs.cmp(rhs))
+        },
+    );
+
+    ranked.into_iter().map(|(provider, ..)| provider).collect()
+}
+
+/// In-memory cache of the pointers we resolved, including the ones we could not resolve, so
+/// that repeated lookups don't hit the database.
+///
+/// New mappings must be stored before the pointer is invalidated. Lookups resolve while holding
+/// the lock, so a lookup that raced with the store is either invalidated afterwards or already
+/// sees the new mapping.
+struct ResolverCache {
+    ttl: Duration,
+    negative_ttl: Duration,
+    entries: Mutex<LruCache<ImmutablePointer, CacheEntry>>,
+}
+
+struct CacheEntry {
+    hash: Option<Blake3Hash>,
+    expires_at: Instant,
+}
+
+impl ResolverCache {
+    fn new(ttl: Duration, negative_ttl: Duration, capacity: NonZeroUsize) -> Self {
+        Self {
+            ttl,
+            negative_ttl,
+            entries: Mutex::new(LruCache::new(capacity)),
+        }
+    }
+
+    /// Returns the cached result for the pointer, or calls `resolve` and caches its result if
+    /// there is no unexpired entry.
+    fn get_or_resolve(
+        &self,
+        pointer: ImmutablePointer,
+        resolve: impl FnOnce(&ImmutablePointer) -> Option<Blake3Hash>,
+    ) -> Option<Blake3Hash> {
+        let now = Instant::now();
+        let mut entries = self.entries.lock().unwrap();
+        if let Some(entry) = entries.get(&pointer) {
+            if entry.expires_at > now {
+                return entry.hash;
+            }
+        }
+
+        let hash = resolve(&pointer);
+        let ttl = if hash.is_some() {
+            self.ttl
+        } else {
+            self.negative_ttl
+        };
+        entries.put(
+            pointer,
+            CacheEntry {
+                hash,
+                expires_at: now + ttl,
+            },
+        );
+        hash
+    }
+
+    /// Drops the cached result for the pointer, to be called whenever we learn a new mapping.
+    fn invalidate(&self, pointer: &ImmutablePointer) {
+        self.entries.lock().unwrap().pop(pointer);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::Cell;
+
+    use lightning_interfaces::types::OriginProvider;
+
+    use super::*;
+
+    fn pointer() -> ImmutablePointer {
+        ImmutablePointer {
+            origin: OriginProvider::IPFS,
+            uri: b"missing".to_vec(),
+        }
+    }
+
+    #[test]
+    fn test_rank_providers_by_latency() {
+        let latency = |provider: NodeIndex| match provider {
+            1 => Some(Duration::from_millis(80)),
+            2 => Some(Duration::from_millis(20)),
+            _ => None,
+        };
+
+        assert_eq!(
+            rank_providers([1, 2, 3], latency, |_| Some(100)),
+            vec![2, 1, 3]
+        );
+    }
+
+    #[test]
+    fn test_rank_providers_by_reputation() {
+        let latency = |provider: NodeIndex| match provider {
+            1 | 2 => Some(Duration::from_millis(50)),
+            3 => Some(Duration::from_millis(60)),
+            _ => None,
+        };
+        let reputation = |provider: NodeIndex| match provider {
+            1 => Some(10),
+            2 | 3 | 5 => Some(90),
+            _ => None,
+        };
+
+        assert_eq!(
+            rank_providers([1, 2, 3, 4, 5], latency, reputation),
+            vec![2, 3, 1, 5, 4]
+        );
+    }
+
+    #[test]
+    fn test_missing_pointer_is_negatively_cached() {
+        let cache = ResolverCache::new(
+            Duration::from_secs(60),
+            Duration::from_secs(60),
+            NonZeroUsize::new(16).unwrap(),
+        );
+        let lookups = Cell::new(0);
+        let resolve = |_: &ImmutablePointer| {
+            lookups.set(lookups.get() + 1);
+            None
+        };
+
+        assert_eq!(cache.get_or_resolve(pointer(), resolve), None);
+        assert_eq!(cache.get_or_resolve(pointer(), resolve), None);
+        assert_eq!(lookups.get(), 1);
+    }
+
+    #[test]
+    fn test_expired_and_invalidated_entries_are_resolved_again() {
+        let cache = ResolverCache::new(
+            Duration::from_secs(60),
+            Duration::ZERO,
+            NonZeroUsize::new(16).unwrap(),
+        );
+        let hash = [1; 32];
+
+        assert_eq!(cache.get_or_resolve(pointer(), |_| None), None);
+        assert_eq!(cache.get_or_resolve(pointer(), |_| Some(hash)), Some(hash));
+        assert_eq!(cache.get_or_resolve(pointer(), |_| None), Some(hash));
+
+        cache.invalidate(&pointer());
+        assert_eq!(cache.get_or_resolve(pointer(), |_| None), None);
+    }
+
+    #[test]
+    fn test_cache_evicts_least_recently_used_entries() {
+        let cache = ResolverCache::new(
+            Duration::from_secs(60),
+            Duration::from_secs(60),
+            NonZeroUsize::new(2).unwrap(),
+        );
+        let pointer = |uri: &[u8]| ImmutablePointer {
+            origin: OriginProvider::IPFS,
+            uri: uri.to_vec(),
+        };
+
+        cache.get_or_resolve(pointer(b"a"), |_| Some([1; 32]));
+        cache.get_or_resolve(pointer(b"b"), |_| Some([2; 32]));
+        // Use `a` again so that `b` is the least recently used entry.
+        cache.get_or_resolve(pointer(b"a"), |_| None);
+        cache.get_or_resolve(pointer(b"c"), |_| Some([3; 32]));
+
+        let entries = cache.entries.lock().unwrap();
+        assert_eq!(entries.len(), 2);
+        assert!(entries.contains(&pointer(b"a")));
+        assert!(!entries.contains(&pointer(b"b")));
+        assert!(entries.contains(&pointer(b"c")));
+    }
+}
@@ -74,6 +74,7 @@ async fn test_start_shutdown() {
                     .with::<Application<TestBinding>>(AppConfig::test(genesis_path))
                     .with::<Resolver<TestBinding>>(Config {
                         store_path: temp_dir.path().join("store").clone().try_into().unwrap(),
+                        ..Default::default()
                     }),
             )
             .with(keystore),
@@ -7,7 +7,9 @@
 use lightning_interfaces::types::{
     AccountInfo,
     Blake3Hash,
+    BlockNotification,
     Epoch,
+    EpochHashAttestation,
     EpochInfo,
     Event,
     EventType,
@@ -182,6 +184,9 @@ async fn get_latencies(
     #[method(name = "get_last_epoch_hash")]
     async fn get_last_epoch_hash(&self) -> RpcResult<([u8; 32], Epoch)>;
 
+    #[method(name = "get_last_epoch_hash_attestation")]
+    async fn get_last_epoch_hash_attestation(&self) -> RpcResult<EpochHashAttestation>;
+
     #[method(name = "get_sub_dag_index")]
     async fn get_sub_dag_index(&self) -> RpcResult<(u64, Epoch)>;
 
@@ -199,4 +204,7 @@ async fn get_latencies(
 
     #[subscription(name = "subscribe", item = Event)]
     async fn handle_subscription(&self, event_type: Option<EventType>) -> SubscriptionResult;
+
+    #[subscription(name = "subscribe_blocks", item = BlockNotification)]
+    async fn subscribe_blocks(&self) -> SubscriptionResult;
 }
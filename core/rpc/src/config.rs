@@ -5,6 +5,13 @@
 use lightning_types::FirewallConfig;
 use serde::{Deserialize, Serialize};
 
+/// The default maximum number of requests accepted in a single JSON-RPC batch.
+pub const DEFAULT_MAX_BATCH_SIZE: u32 = 100;
+
+fn default_max_batch_size() -> u32 {
+    DEFAULT_MAX_BATCH_SIZE
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Config {
     pub addr: SocketAddr,
@@ -12,6 +19,10 @@ pub struct Config {
     pub disallowed_methods: Option<Arc<Vec<String>>>,
     pub firewall: lightning_types::FirewallConfig,
     pub hmac_secret_dir: Option<PathBuf>,
+    /// The maximum number of requests accepted in a single JSON-RPC batch, larger batches are
+    /// rejected as a whole.
+    #[serde(default = "default_max_batch_size")]
+    pub max_batch_size: u32,
 }
 
 impl From<Config> for FirewallConfig {
@@ -34,6 +45,7 @@ pub fn new(
             addr,
             rpc_selection,
             disallowed_methods: disallowed_methods.map(Arc::new),
+            max_batch_size: DEFAULT_MAX_BATCH_SIZE,
         }
     }
 
@@ -64,6 +76,7 @@ fn default() -> Self {
             rpc_selection: Default::default(),
             disallowed_methods: None,
             firewall: FirewallConfig::none("rpc-4230".to_string()),
+            max_batch_size: DEFAULT_MAX_BATCH_SIZE,
         }
     }
 }
@@ -3,6 +3,7 @@
 use ethers::utils::rlp;
 use jsonrpsee::types::error::INTERNAL_ERROR_CODE;
 use jsonrpsee::types::ErrorObject;
+use lightning_interfaces::ForwarderError;
 use ruint::ParseError;
 
 #[derive(Debug)]
@@ -50,6 +51,9 @@ pub enum RPCError {
     #[error("Not an archive node")]
     NotArchiveNode,
 
+    #[error("Failed to forward the transaction: {}", .0)]
+    Forwarder(#[from] ForwarderError),
+
     #[error("Error: ")]
     Anyhow(#[from] anyhow::Error),
 }
@@ -92,6 +96,7 @@ fn from(e: RPCError) -> Self {
             RPCError::BadEpoch => internal_err_from_string("Bad Epoch".to_string()),
             RPCError::Anyhow(e) => internal_err_from_string(e.to_string()),
             RPCError::NotArchiveNode => internal_err_from_string(e.to_string()),
+            RPCError::Forwarder(e) => internal_err(e),
         }
     }
 }
@@ -4,11 +4,19 @@
 
 use anyhow::Context;
 use fleek_crypto::{ConsensusPublicKey, NodePublicKey};
-use jsonrpsee::server::{stop_channel, Server as JSONRPCServer};
+use jsonrpsee::server::{stop_channel, BatchRequestConfig, Server as JSONRPCServer};
 use jsonrpsee::{Methods, RpcModule};
 use lightning_firewall::Firewall;
 use lightning_interfaces::prelude::*;
-use lightning_interfaces::{Events, FetcherSocket, MempoolSocket};
+use lightning_interfaces::types::BlockSummary;
+use lightning_interfaces::{
+    Events,
+    FetcherSocket,
+    MempoolSocket,
+    ShutdownPhase,
+    ShutdownPhaseWaiters,
+    SyncProgress,
+};
 use lightning_utils::config::LIGHTNING_HOME_DIR;
 use once_cell::sync::Lazy;
 use rand::{RngCore, SeedableRng};
@@ -52,6 +60,9 @@ pub mod interface {
 
 pub static HMAC_SALT: &[u8] = b"lightning-hmac-salt";
 
+/// The number of blocks buffered for each block subscriber before the oldest ones are dropped.
+const BLOCK_SUBSCRIPTION_BUFFER: usize = 32;
+
 static VERSION: Lazy<String> = Lazy::new(|| {
     format!(
         "lightning-rpc {}-{}",
@@ -106,10 +117,13 @@ pub(crate) struct Data<C: Collection> {
     pub mempool_socket: MempoolSocket,
     pub fetcher_socket: FetcherSocket,
     pub _blockstore: C::BlockstoreInterface,
+    pub keystore: C::KeystoreInterface,
     pub node_public_key: NodePublicKey,
     pub consensus_public_key: ConsensusPublicKey,
     pub archive: C::ArchiveInterface,
     pub events: Events,
+    pub blocks: tokio::sync::broadcast::Sender<BlockSummary>,
+    pub sync_progress: tokio::sync::watch::Receiver<SyncProgress>,
 }
 
 impl<C: Collection> Data<C> {
@@ -136,11 +150,17 @@ pub struct Rpc<C: Collection> {
     /// RPC module for admin methods.
     admin_module: RpcModule<()>,
     data: Arc<Data<C>>,
+    notifier: C::NotifierInterface,
     secret: [u8; 32],
 }
 
-pub async fn health() -> &'static str {
-    "OK"
+/// Returns `OK` once the node caught up with the network, and how far the sync got before that.
+pub fn health(progress: &SyncProgress) -> String {
+    if progress.is_syncing() {
+        format!("SYNCING {}%", progress.percentage())
+    } else {
+        "OK".to_string()
+    }
 }
 
 pub async fn metrics() -> (StatusCode, String) {
@@ -158,6 +178,8 @@ fn init(
         blockstore: &C::BlockstoreInterface,
         fetcher: &C::FetcherInterface,
         keystore: &C::KeystoreInterface,
+        notifier: &C::NotifierInterface,
+        syncronizer: &C::SyncronizerInterface,
         fdi::Cloned(archive): fdi::Cloned<c!(C::ArchiveInterface)>,
         fdi::Cloned(query_runner): fdi::Cloned<c!(C::ApplicationInterface::SyncExecutor)>,
     ) -> anyhow::Result<Self> {
@@ -168,6 +190,7 @@ fn init(
             mempool_socket: forwarder.mempool_socket(),
             fetcher_socket: fetcher.get_socket(),
             _blockstore: blockstore.clone(),
+            keystore: keystore.clone(),
             node_public_key: keystore.get_ed25519_pk(),
             consensus_public_key: keystore.get_bls_pk(),
             archive,
@@ -175,6 +198,8 @@ fn init(
                 let (tx, _) = tokio::sync::broadcast::channel(8);
                 tx.into()
             },
+            blocks: tokio::sync::broadcast::channel(BLOCK_SUBSCRIPTION_BUFFER).0,
+            sync_progress: syncronizer.sync_progress(),
         });
         let module = Self::create_modules_from_config(&config, data.clone())?;
         let admin_module = Self::create_admin_module_from_config(&config, data.clone())?;
@@ -185,26 +210,40 @@ fn init(
             module,
             admin_module,
             data,
+            notifier: notifier.clone(),
             secret,
         })
     }
 
-    fn start(&self, shutdown: fdi::Cloned<ShutdownWaiter>) {
+    fn start(&self, fdi::Cloned(waiters): fdi::Cloned<ShutdownPhaseWaiters>) {
+        // Stop accepting requests before the rest of the node goes down.
+        let shutdown = waiters.get(ShutdownPhase::Ingress);
         let (stop, server_handle) = stop_channel();
 
         let disallowed = self.config.disallowed_methods.as_ref().map(|s| s.as_ref());
-        let json_rpc_service = JSONRPCServer::builder().to_service_builder().build(
-            filter_methods(self.module.clone(), disallowed),
-            stop.clone(),
-        );
+        let max_batch_size = self.config.max_batch_size;
+        let json_rpc_service = JSONRPCServer::builder()
+            .set_batch_request_config(BatchRequestConfig::Limit(max_batch_size))
+            .to_service_builder()
+            .build(
+                filter_methods(self.module.clone(), disallowed),
+                stop.clone(),
+            );
 
-        let admin_json_rpc_service = JSONRPCServer::builder().to_service_builder().build(
-            filter_methods(self.admin_module.clone(), disallowed),
-            stop.clone(),
-        );
+        let admin_json_rpc_service = JSONRPCServer::builder()
+            .set_batch_request_config(BatchRequestConfig::Limit(max_batch_size))
+            .to_service_builder()
+            .build(
+                filter_methods(self.admin_module.clone(), disallowed),
+                stop.clone(),
+            );
 
-        let rpc_server =
-            server::RpcService::new(json_rpc_service, admin_json_rpc_service, self.secret);
+        let rpc_server = server::RpcService::new(
+            json_rpc_service,
+            admin_json_rpc_service,
+            self.secret,
+            self.data.sync_progress.clone(),
+        );
 
         let firewall = Firewall::from_config(self.config.firewall.clone(), shutdown.clone());
         let rpc_server = firewall.service(rpc_server);
@@ -212,7 +251,7 @@ fn start(&self, shutdown: fdi::Cloned<ShutdownWaiter>) {
         let addr = self.config.addr();
         let server = hyper::Server::bind(&addr).serve(rpc_server);
 
-        let panic_waiter = shutdown.clone();
+        let panic_waiter = waiters.get(ShutdownPhase::Services);
         spawn!(
             async move {
                 let graceful = server.with_graceful_shutdown(async move { stop.shutdown().await });
@@ -222,6 +261,18 @@ fn start(&self, shutdown: fdi::Cloned<ShutdownWaiter>) {
             crucial(panic_waiter)
         );
 
+        let mut block_executed = self.notifier.subscribe_block_executed();
+        let blocks = self.data.blocks.clone();
+        spawn!(
+            async move {
+                while let Some(notification) = block_executed.recv().await {
+                    // The send could only fail if there are no active subscribers at the moment.
+                    let _ = blocks.send(BlockSummary::from(&notification.response));
+                }
+            },
+            "RPC: block subscriptions"
+        );
+
         spawn!(
             async move {
                 shutdown.wait_for_shutdown().await;
@@ -169,6 +169,7 @@ async fn send_raw_transaction(&self, tx: Bytes) -> RpcResult<H256> {
             .mempool_socket
             .run(transaction.into())
             .await
+            .map_err(RPCError::from)?
             .map_err(RPCError::from)?;
 
         Ok(hash)
@@ -9,7 +9,9 @@
 use lightning_interfaces::types::{
     AccountInfo,
     Blake3Hash,
+    BlockNotification,
     Epoch,
+    EpochHashAttestation,
     EpochInfo,
     EventType,
     FetcherRequest,
@@ -30,6 +32,7 @@
 };
 use lightning_interfaces::PagingParams;
 use lightning_utils::application::QueryRunnerExt;
+use tokio::sync::broadcast::error::RecvError;
 
 use crate::api::FleekApiServer;
 use crate::error::RPCError;
@@ -379,6 +382,15 @@ async fn get_latencies(
         ))
     }
 
+    async fn get_last_epoch_hash_attestation(&self) -> RpcResult<EpochHashAttestation> {
+        let (hash, epoch) = self.get_last_epoch_hash().await?;
+        Ok(EpochHashAttestation::sign(
+            hash,
+            epoch,
+            &self.data.keystore.get_ed25519_sk(),
+        ))
+    }
+
     async fn get_sub_dag_index(&self) -> RpcResult<(u64, Epoch)> {
         let sub_dag_index = match self.data.query_runner.get_metadata(&Metadata::SubDagIndex) {
             Some(Value::SubDagIndex(index)) => index,
@@ -391,9 +403,10 @@ async fn send_txn(&self, tx: TransactionRequest) -> RpcResult<()> {
         Ok(self
             .data
             .mempool_socket
-            .enqueue(tx)
+            .run(tx)
             .await
-            .map_err(|e| RPCError::socket(e.to_string()))?)
+            .map_err(|e| RPCError::socket(e.to_string()))?
+            .map_err(RPCError::from)?)
     }
 
     async fn put(&self, data: Vec<u8>) -> RpcResult<Blake3Hash> {
@@ -423,7 +436,7 @@ async fn put(&self, data: Vec<u8>) -> RpcResult<Blake3Hash> {
     }
 
     async fn health(&self) -> RpcResult<String> {
-        Ok("OK".to_string())
+        Ok(crate::health(&self.data.sync_progress.borrow()))
     }
 
     async fn metrics(&self) -> RpcResult<String> {
@@ -464,4 +477,35 @@ async fn handle_subscription(
 
         Ok(())
     }
+
+    async fn subscribe_blocks(&self, pending: PendingSubscriptionSink) -> SubscriptionResult {
+        // Subscribe before accepting so no block is missed once the client is notified.
+        let mut rx = self.data.blocks.subscribe();
+
+        let sink = pending.accept().await?;
+
+        loop {
+            let notification = tokio::select! {
+                _ = sink.closed() => break,
+                recv = rx.recv() => match recv {
+                    Ok(summary) => BlockNotification::Block(summary),
+                    // The subscriber is too slow, the oldest blocks were already dropped from the
+                    // buffer so we let it know how many it missed.
+                    Err(RecvError::Lagged(skipped)) => BlockNotification::Lagged { skipped },
+                    Err(RecvError::Closed) => break,
+                },
+            };
+
+            if sink
+                .send(SubscriptionMessage::from_json(&notification)?)
+                .await
+                .is_err()
+            {
+                tracing::trace!("flk block subscription closed");
+                break;
+            }
+        }
+
+        Ok(())
+    }
 }
@@ -7,7 +7,10 @@
 use hmac::{Hmac, Mac};
 use hyper::body::HttpBody;
 use hyper::{Body, Request, Response};
+use lightning_interfaces::SyncProgress;
+use lightning_metrics::increment_counter;
 use sha2::Sha256;
+use tokio::sync::watch;
 use tower::Service as TowerService;
 
 use crate::{health, metrics, HMAC_SALT, VERSION};
@@ -25,6 +28,7 @@ pub struct RpcService<MainModule, AdminModule> {
     pub admin_server: AdminModule,
     secret: Arc<[u8; 32]>,
     nonce: Arc<AtomicU32>,
+    sync_progress: watch::Receiver<SyncProgress>,
 }
 
 impl<X, Y> Clone for RpcService<X, Y>
@@ -38,6 +42,7 @@ fn clone(&self) -> Self {
             admin_server: self.admin_server.clone(),
             secret: self.secret.clone(),
             nonce: self.nonce.clone(),
+            sync_progress: self.sync_progress.clone(),
         }
     }
 }
@@ -71,12 +76,18 @@ impl<X> ServiceMarker for X
 }
 
 impl<MainModule, AdminModule> RpcService<MainModule, AdminModule> {
-    pub fn new(main_server: MainModule, admin_server: AdminModule, secret: [u8; 32]) -> Self {
+    pub fn new(
+        main_server: MainModule,
+        admin_server: AdminModule,
+        secret: [u8; 32],
+        sync_progress: watch::Receiver<SyncProgress>,
+    ) -> Self {
         Self {
             main_server,
             admin_server,
             secret: Arc::new(secret),
             nonce: Arc::new(0.into()),
+            sync_progress,
         }
     }
 }
@@ -99,6 +110,17 @@ pub fn route(
         let path = req.uri().path().to_string().to_ascii_lowercase();
         let method = req.method();
 
+        // Everything that is not one of our own routes is handed to the main server.
+        let route = match path.as_str() {
+            route @ ("/health" | "/version" | "/metrics" | "/admin/nonce" | "/admin") => route,
+            _ => "/rpc",
+        };
+        increment_counter!(
+            "rpc_http_requests",
+            Some("Number of http requests received by the rpc, by route"),
+            "route" => route
+        );
+
         // todo(n)
         // in the future the struct could be change to suppport a "main" and a auxillary services
         // and you define new routes using a builder
@@ -114,9 +136,8 @@ pub fn route(
         // ```
         match path.as_str() {
             "/health" => {
+                let res = health(&self.sync_progress.borrow());
                 let fut = async {
-                    let res = health().await;
-
                     hyper::Response::builder()
                         .status(hyper::StatusCode::OK)
                         .body(hyper::Body::from(res))
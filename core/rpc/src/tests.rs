@@ -22,6 +22,10 @@
 use lightning_indexer::Indexer;
 use lightning_interfaces::prelude::*;
 use lightning_interfaces::types::{
+    Block,
+    BlockExecutionResponse,
+    BlockNotification,
+    BlockSummary,
     Event,
     Metadata,
     NodeInfo,
@@ -86,17 +90,28 @@ fn rpc(&self) -> fdi::Ref<Rpc<TestBinding>> {
     fn query_runner(&self) -> fdi::Ref<QueryRunner> {
         self.inner.provider.get()
     }
+    fn notifier(&self) -> fdi::Ref<Notifier<TestBinding>> {
+        self.inner.provider.get()
+    }
 }
 
 async fn init_rpc(temp_dir: &TempDir, genesis_path: ResolvedPathBuf, rpc_port: u16) -> TestNode {
-    let app_config = AppConfig::test(genesis_path);
-
     let rpc_config = RpcConfig {
         hmac_secret_dir: Some(temp_dir.path().to_path_buf()),
         firewall: FirewallConfig::none(format!("rpc-{}", rpc_port)),
         ..RpcConfig::default_with_port(rpc_port)
     };
 
+    init_rpc_with_config(temp_dir, genesis_path, rpc_config).await
+}
+
+async fn init_rpc_with_config(
+    temp_dir: &TempDir,
+    genesis_path: ResolvedPathBuf,
+    rpc_config: RpcConfig,
+) -> TestNode {
+    let app_config = AppConfig::test(genesis_path);
+
     let node = Node::<TestBinding>::init_with_provider(
         fdi::Provider::default().with(
             JsonConfigProvider::default()
@@ -104,6 +119,7 @@ async fn init_rpc(temp_dir: &TempDir, genesis_path: ResolvedPathBuf, rpc_port: u
                 .with::<Application<TestBinding>>(app_config)
                 .with::<Blockstore<TestBinding>>(BlockstoreConfig {
                     root: temp_dir.path().join("blockstore").try_into().unwrap(),
+                    max_total_bytes: None,
                 }),
         ),
     )
@@ -1143,3 +1159,218 @@ async fn test_rpc_events() -> Result<()> {
 
     Ok(())
 }
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_rpc_subscribe_blocks() -> Result<()> {
+    let temp_dir = tempdir()?;
+
+    let genesis_path = Genesis::default()
+        .write_to_dir(temp_dir.path().to_path_buf().try_into().unwrap())
+        .unwrap();
+
+    let port = 30024;
+    let node = init_rpc(&temp_dir, genesis_path, port).await;
+
+    wait_for_server_start(port).await?;
+
+    let client = jsonrpsee::ws_client::WsClientBuilder::default()
+        .build(&format!("ws://127.0.0.1:{port}/rpc/v0"))
+        .await?;
+
+    let mut sub = FleekApiClient::subscribe_blocks(&client).await?;
+
+    let response = BlockExecutionResponse {
+        block_number: 1,
+        block_hash: [1; 32],
+        parent_hash: [0; 32],
+        change_epoch: false,
+        node_registry_delta: vec![],
+        txn_receipts: vec![],
+    };
+    node.notifier().get_emitter().new_block(
+        Block {
+            digest: [1; 32],
+            sub_dag_index: 0,
+            transactions: vec![],
+        },
+        response.clone(),
+    );
+
+    assert_eq!(
+        sub.next().await.expect("A block from the sub")?,
+        BlockNotification::Block(BlockSummary::from(&response))
+    );
+
+    node.shutdown().await;
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_rpc_batch_request() -> Result<()> {
+    let temp_dir = tempdir()?;
+
+    let genesis_path = Genesis::default()
+        .write_to_dir(temp_dir.path().to_path_buf().try_into().unwrap())
+        .unwrap();
+
+    let port = 30025;
+    let node = init_rpc(&temp_dir, genesis_path, port).await;
+
+    wait_for_server_start(port).await?;
+
+    let batch = serde_json::json!([
+        { "jsonrpc": "2.0", "id": 1, "method": "flk_ping", "params": [] },
+        { "jsonrpc": "2.0", "id": 2, "method": "flk_not_a_method", "params": [] },
+        { "jsonrpc": "2.0", "id": 3, "method": "net_listening", "params": [] },
+    ]);
+
+    let responses: Vec<serde_json::Value> = Client::new()
+        .post(format!("http://127.0.0.1:{port}/rpc/v0"))
+        .json(&batch)
+        .send()
+        .await?
+        .json()
+        .await?;
+    assert_eq!(responses.len(), 3);
+
+    let response = |id: u64| {
+        responses
+            .iter()
+            .find(|response| response["id"] == id)
+            .expect("a response for every request")
+    };
+    assert_eq!(response(1)["result"], "pong");
+    assert!(response(2)["error"].is_object());
+    assert_eq!(response(3)["result"], true);
+
+    node.shutdown().await;
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_rpc_batch_request_too_large() -> Result<()> {
+    let temp_dir = tempdir()?;
+
+    let genesis_path = Genesis::default()
+        .write_to_dir(temp_dir.path().to_path_buf().try_into().unwrap())
+        .unwrap();
+
+    let port = 30026;
+    let rpc_config = RpcConfig {
+        hmac_secret_dir: Some(temp_dir.path().to_path_buf()),
+        firewall: FirewallConfig::none(format!("rpc-{}", port)),
+        max_batch_size: 2,
+        ..RpcConfig::default_with_port(port)
+    };
+    let node = init_rpc_with_config(&temp_dir, genesis_path, rpc_config).await;
+
+    wait_for_server_start(port).await?;
+
+    let batch: Vec<_> = (0..3)
+        .map(|id| serde_json::json!({ "jsonrpc": "2.0", "id": id, "method": "flk_ping" }))
+        .collect();
+
+    let response: serde_json::Value = Client::new()
+        .post(format!("http://127.0.0.1:{port}/rpc/v0"))
+        .json(&batch)
+        .send()
+        .await?
+        .json()
+        .await?;
+
+    // The whole batch is rejected with a single error.
+    assert!(response.is_object());
+    assert!(response["error"].is_object());
+
+    node.shutdown().await;
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_rpc_eth_get_balance() -> Result<()> {
+    let temp_dir = tempdir()?;
+
+    // Create keys
+    let owner_secret_key = AccountOwnerSecretKey::generate();
+    let owner_public_key = owner_secret_key.to_pk();
+    let eth_address: EthAddress = owner_public_key.into();
+
+    // Init application service
+    let mut genesis = Genesis::default();
+    genesis.account.push(GenesisAccount {
+        public_key: owner_public_key.into(),
+        flk_balance: 1000u64.into(),
+        stables_balance: 0,
+        bandwidth_balance: 0,
+    });
+
+    let genesis_path = genesis
+        .write_to_dir(temp_dir.path().to_path_buf().try_into().unwrap())
+        .unwrap();
+
+    let port = 30027;
+    let node = init_rpc(&temp_dir, genesis_path, port).await;
+
+    wait_for_server_start(port).await?;
+
+    let request = serde_json::json!({
+        "jsonrpc": "2.0",
+        "id": 1,
+        "method": "eth_getBalance",
+        "params": [eth_address, "latest"],
+    });
+
+    let response: RpcSuccessResponse<String> = Client::new()
+        .post(format!("http://127.0.0.1:{port}/rpc/v0"))
+        .json(&request)
+        .send()
+        .await?
+        .json()
+        .await?;
+
+    // 1000 FLK in the 18 decimals base unit.
+    assert_eq!(response.result, "0x3635c9adc5dea00000");
+
+    node.shutdown().await;
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_rpc_metrics_endpoint() -> Result<()> {
+    let temp_dir = tempdir()?;
+
+    let genesis = Genesis::default();
+    let genesis_path = genesis
+        .write_to_dir(temp_dir.path().to_path_buf().try_into().unwrap())
+        .unwrap();
+
+    let port = 30028;
+    let node = init_rpc(&temp_dir, genesis_path, port).await;
+
+    // Waiting for the server already hits the health route at least once.
+    wait_for_server_start(port).await?;
+
+    let response = Client::new()
+        .get(format!("http://127.0.0.1:{port}/metrics"))
+        .send()
+        .await?;
+    assert!(response.status().is_success());
+    let metrics = response.text().await?;
+
+    assert!(metrics.contains("# HELP rpc_http_requests "));
+    assert!(metrics.contains("# TYPE rpc_http_requests counter"));
+    let sample = metrics
+        .lines()
+        .find(|line| line.starts_with("rpc_http_requests{") && line.contains("route=\"/health\""))
+        .expect("the health requests should be counted");
+    let (_, count) = sample.rsplit_once(' ').unwrap();
+    assert!(count.parse::<f64>()? >= 1.0);
+
+    node.shutdown().await;
+
+    Ok(())
+}
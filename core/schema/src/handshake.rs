@@ -295,7 +295,7 @@ pub fn encode(&self) -> Bytes {
                 buf.put_slice(access_token.as_slice());
                 buf.into()
             },
-            Self::Termination { reason } => vec![*reason as u8].into(),
+            Self::Termination { reason } => vec![reason.code()].into(),
         }
     }
 
@@ -335,22 +335,39 @@ pub fn decode(bytes: &[u8]) -> Result<Self> {
     }
 }
 
-/// Termination signals
+/// Termination signals, sent as the single byte of a termination frame. The numeric code of every
+/// reason is stable, so that clients can tell the failures apart.
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 #[repr(u8)]
 #[non_exhaustive]
 pub enum TerminationReason {
+    /// The session timed out.
     Timeout = 0x80,
-    InvalidHandshake,
-    InvalidToken,
-    InvalidDeliveryAcknowledgment,
-    InvalidService,
-    ServiceTerminated,
-    ConnectionInUse,
-    WrongPermssion,
-    ResourcesUnavailable,
-    InternalError,
-    Shutdown,
+    /// The handshake request was malformed.
+    InvalidHandshake = 0x81,
+    /// The access token does not belong to the connection it names.
+    InvalidToken = 0x82,
+    /// The delivery acknowledgment was invalid.
+    InvalidDeliveryAcknowledgment = 0x83,
+    /// The requested service does not exist.
+    InvalidService = 0x84,
+    /// The service closed the connection or could not be reached.
+    ServiceTerminated = 0x85,
+    /// A newer connection took over the place of this one.
+    ConnectionInUse = 0x86,
+    /// The client is not permitted to perform the request.
+    WrongPermssion = 0x87,
+    /// The node does not have the resources to serve the client, for example because a quota is
+    /// exceeded.
+    ResourcesUnavailable = 0x88,
+    /// The node ran into an unexpected error.
+    InternalError = 0x89,
+    /// The node is shutting down.
+    Shutdown = 0x8A,
+    /// The access token is expired.
+    AccessTokenExpired = 0x8B,
+    /// The connection that a join or retry request names does not exist (anymore).
+    UnknownConnection = 0x8C,
     Unknown = 0xFF,
 }
 
@@ -366,9 +383,19 @@ pub fn from_u8(byte: u8) -> Self {
             0x85 => Self::ServiceTerminated,
             0x86 => Self::ConnectionInUse,
             0x87 => Self::WrongPermssion,
+            0x88 => Self::ResourcesUnavailable,
+            0x89 => Self::InternalError,
+            0x8A => Self::Shutdown,
+            0x8B => Self::AccessTokenExpired,
+            0x8C => Self::UnknownConnection,
             _ => Self::Unknown,
         }
     }
+
+    /// Returns the numeric code of the reason.
+    pub fn code(&self) -> u8 {
+        *self as u8
+    }
 }
 
 #[cfg(test)]
@@ -472,4 +499,33 @@ fn response_frames() {
             }
         );
     }
+
+    #[test]
+    fn termination_codes() {
+        let reasons = [
+            (TerminationReason::Timeout, 0x80),
+            (TerminationReason::InvalidHandshake, 0x81),
+            (TerminationReason::InvalidToken, 0x82),
+            (TerminationReason::InvalidDeliveryAcknowledgment, 0x83),
+            (TerminationReason::InvalidService, 0x84),
+            (TerminationReason::ServiceTerminated, 0x85),
+            (TerminationReason::ConnectionInUse, 0x86),
+            (TerminationReason::WrongPermssion, 0x87),
+            (TerminationReason::ResourcesUnavailable, 0x88),
+            (TerminationReason::InternalError, 0x89),
+            (TerminationReason::Shutdown, 0x8A),
+            (TerminationReason::AccessTokenExpired, 0x8B),
+            (TerminationReason::UnknownConnection, 0x8C),
+            (TerminationReason::Unknown, 0xFF),
+        ];
+        for (reason, code) in reasons {
+            assert_eq!(reason.code(), code);
+            assert_eq!(TerminationReason::from_u8(code), reason);
+            assert_eq!(
+                ResponseFrame::decode(&ResponseFrame::Termination { reason }.encode()).unwrap(),
+                ResponseFrame::Termination { reason }
+            );
+        }
+        assert_eq!(TerminationReason::from_u8(0x8D), TerminationReason::Unknown);
+    }
 }
@@ -1,3 +1,4 @@
+use std::collections::HashMap;
 use std::error::Error;
 use std::path::{Path, PathBuf};
 use std::process::Stdio;
@@ -6,6 +7,7 @@
 use dashmap::DashMap;
 use fleek_crypto::ClientPublicKey;
 use fn_sdk::ipc_types::{self, IpcMessage, IpcRequest, DELIMITER_SIZE};
+use fn_sdk::secrets::{Secrets, SECRETS_PATH_ENV};
 use lightning_interfaces::prelude::*;
 use tokio::io::{self, Interest};
 use tokio::net::{UnixListener, UnixStream};
@@ -22,6 +24,7 @@ pub struct Context<C: Collection> {
     pub ipc_path: PathBuf,
     pub fetcher_socket: FetcherSocket,
     pub query_runner: c!(C::ApplicationInterface::SyncExecutor),
+    pub secrets: HashMap<u32, Secrets>,
 }
 
 impl<C: Collection> Context<C> {
@@ -145,6 +148,16 @@ pub async fn spawn_service<C: Collection>(
         .env("BLOCKSTORE_PATH", &cx.blockstore_path)
         .env("IPC_PATH", &ipc_dir);
 
+    // Pass the secrets through a file, so that they do not end up in the environment of the
+    // process or in the command we report on panics.
+    if let Some(secrets) = cx.secrets.get(&id) {
+        let secrets_path = ipc_dir.join("secrets.json");
+        secrets
+            .write_to(&secrets_path)
+            .expect("Failed to write the secrets of the service.");
+        cmd.env(SECRETS_PATH_ENV, &secrets_path);
+    }
+
     panic_report::add_context(format!("service_{id}"), format!("{cmd:?}"));
 
     let cmd_permit = Arc::new(Notify::new());
@@ -1,6 +1,8 @@
+use std::collections::HashMap;
 use std::marker::PhantomData;
 use std::path::PathBuf;
 
+use fn_sdk::secrets::Secrets;
 use fxhash::FxHashSet;
 use lightning_interfaces::prelude::*;
 use lightning_interfaces::types::ServiceId;
@@ -8,6 +10,7 @@
 use lightning_utils::config::LIGHTNING_HOME_DIR;
 use resolved_pathbuf::ResolvedPathBuf;
 use serde::{Deserialize, Serialize};
+use serde_with::{serde_as, DisplayFromStr};
 use tokio::net::UnixStream;
 use tracing::{error, trace};
 use triomphe::Arc;
@@ -22,6 +25,7 @@ pub struct ServiceExecutor<C: Collection> {
     p: PhantomData<C>,
 }
 
+#[serde_as]
 #[derive(Debug, Serialize, Deserialize)]
 #[serde(default)]
 pub struct ServiceExecutorConfig {
@@ -29,6 +33,10 @@ pub struct ServiceExecutorConfig {
     /// The IPC directory is used to contain the Unix domain sockets that we use to communicate
     /// with the different services.
     pub ipc_path: ResolvedPathBuf,
+    /// Secrets handed to the services, by service id. A service only gets its own.
+    // The TOML crate requires string keys for maps.
+    #[serde_as(as = "HashMap<DisplayFromStr, _>")]
+    pub secrets: HashMap<ServiceId, Secrets>,
 }
 
 impl Default for ServiceExecutorConfig {
@@ -39,6 +47,7 @@ fn default() -> Self {
                 .join("ipc")
                 .try_into()
                 .expect("Failed to resolve path"),
+            secrets: Default::default(),
         }
     }
 }
@@ -51,6 +60,7 @@ pub fn test_default() -> Self {
                 .join("ipc")
                 .try_into()
                 .expect("Failed to resolve path"),
+            secrets: Default::default(),
         }
     }
 }
@@ -76,6 +86,7 @@ fn init(
             ipc_path: config.ipc_path.to_path_buf(),
             fetcher_socket: fetcher.get_socket(),
             query_runner,
+            secrets: config.secrets.clone(),
         });
 
         Ok(ServiceExecutor {
@@ -128,6 +139,10 @@ fn run_service(id: u32) {
             2 => {
                 fleek_service_ai::main();
             },
+            #[cfg(feature = "services")]
+            3 => {
+                fleek_service_wasm::main();
+            },
             1001 => {
                 crate::test_services::io_stress::main();
             },
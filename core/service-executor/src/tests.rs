@@ -53,11 +53,13 @@ async fn init_service_executor(
             JsonConfigProvider::default()
                 .with::<Blockstore<TestBinding>>(BlockstoreConfig {
                     root: temp_dir.path().join("dummy_blockstore").try_into().unwrap(),
+                    max_total_bytes: None,
                 })
                 .with::<Application<TestBinding>>(AppConfig::test(genesis_path))
                 .with::<ServiceExecutor<TestBinding>>(ServiceExecutorConfig {
                     services: [service_id].into_iter().collect(),
                     ipc_path: temp_dir.path().join("ipc").try_into().unwrap(),
+                    secrets: Default::default(),
                 }),
         ),
     )
@@ -113,6 +113,19 @@ pub async fn start(
             "SIGNER: new block task"
         );
     }
+
+    /// Returns the signed transaction the signer would submit next for the given method, without
+    /// submitting it or reserving its nonce, so that it can be inspected before broadcasting.
+    /// The next transaction sent through the socket is assigned the same nonce.
+    ///
+    /// Returns `None` if the signer has not been started yet, since the chain id is not known.
+    pub async fn build_signed(&self, method: UpdateMethod) -> Option<UpdateRequest> {
+        let state = self.worker.state.lock().await;
+        state
+            .chain_id
+            .is_some()
+            .then(|| state.sign(method, state.next_nonce))
+    }
 }
 
 impl<C: Collection> SignerInterface<C> for Signer<C> {
@@ -135,27 +148,31 @@ fn init_state(&mut self, chain_id: u32, base_nonce: u64) {
         self.chain_id = Some(chain_id);
     }
 
-    async fn sign_new_tx(&mut self, method: UpdateMethod) -> u64 {
-        let assigned_nonce = self.next_nonce;
+    fn sign(&self, method: UpdateMethod, nonce: u64) -> UpdateRequest {
         let update_payload = UpdatePayload {
             sender: TransactionSender::NodeMain(self.node_public_key),
             method,
-            nonce: assigned_nonce,
+            nonce,
             chain_id: self.chain_id.unwrap(),
         };
-
         let digest = update_payload.to_digest();
         let signature = self.node_secret_key.sign(&digest);
-        let update_request = UpdateRequest {
+        UpdateRequest {
             signature: signature.into(),
             payload: update_payload,
-        };
+        }
+    }
+
+    async fn sign_new_tx(&mut self, method: UpdateMethod) -> u64 {
+        let assigned_nonce = self.next_nonce;
+        let update_request = self.sign(method, assigned_nonce);
 
         if let Err(e) = self
             .mempool_socket
-            .enqueue(update_request.clone().into())
+            .run(update_request.clone().into())
             .await
             .map_err(|r| anyhow::anyhow!(format!("{r:?}")))
+            .and_then(|res| res.map_err(Into::into))
         {
             error!("Failed to send transaction to mempool: {e:?}");
         }
@@ -213,20 +230,8 @@ async fn sync_with_application<Q>(&mut self, application_nonce: u64, query_runne
                         // retry again.
                         // To prevent invalidating the nonces of the following pending transactions,
                         // we have to increment the nonce on the application state.
-                        let method = UpdateMethod::IncrementNonce {};
-                        let update_payload = UpdatePayload {
-                            sender: TransactionSender::NodeMain(self.node_public_key),
-                            method,
-                            nonce: self.next_nonce,
-                            chain_id: self.chain_id.unwrap(),
-                        };
-                        let digest = update_payload.to_digest();
-                        let signature = self.node_secret_key.sign(&digest);
-                        let update_request = UpdateRequest {
-                            signature: signature.into(),
-                            payload: update_payload,
-                        };
-                        tx.update_request = update_request;
+                        tx.update_request =
+                            self.sign(UpdateMethod::IncrementNonce {}, self.next_nonce);
                     } else {
                         // Since we just replace transactions that we don't resend with an
                         // increment nonce transaction, we don't have to update the nonce of the
@@ -248,6 +253,7 @@ async fn sync_with_application<Q>(&mut self, application_nonce: u64, query_runne
                         .run(pending_tx.update_request.clone().into())
                         .await
                         .map_err(|r| anyhow::anyhow!(format!("{r:?}")))
+                        .and_then(|res| res.map_err(Into::into))
                     {
                         error!("Failed to send transaction to mempool: {e:?}");
                     } else {
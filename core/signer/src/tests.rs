@@ -1,7 +1,13 @@
 use std::collections::BTreeMap;
 use std::time::Duration;
 
-use fleek_crypto::{AccountOwnerSecretKey, SecretKey};
+use fleek_crypto::{
+    AccountOwnerSecretKey,
+    PublicKey,
+    SecretKey,
+    TransactionSender,
+    TransactionSignature,
+};
 use lightning_application::app::Application;
 use lightning_application::config::Config as AppConfig;
 use lightning_application::genesis::{Genesis, GenesisNode};
@@ -70,6 +76,7 @@ fn build_node(temp_dir: &TempDir, transactions_to_lose: &[u32]) -> Node<TestBind
                     probability_txn_lost: 0.0,
                     transactions_to_lose: transactions_to_lose.iter().copied().collect(),
                     new_block_interval: Duration::from_secs(5),
+                    fault_injector: None,
                 }),
         ),
     )
@@ -110,6 +117,52 @@ async fn test_send_two_txs_in_a_row() {
     assert_eq!(new_nonce, 2);
 }
 
+#[tokio::test]
+async fn test_build_signed() {
+    let temp_dir = tempdir().unwrap();
+    let node = build_node(&temp_dir, &[]);
+
+    // The chain id is not known before the signer is started.
+    assert!(
+        node.provider
+            .get::<Signer<TestBinding>>()
+            .build_signed(UpdateMethod::OptIn {})
+            .await
+            .is_none()
+    );
+
+    node.start().await;
+    let signer = node.provider.get::<Signer<TestBinding>>();
+    let node_public_key = node
+        .provider
+        .get::<EphemeralKeystore<TestBinding>>()
+        .get_ed25519_pk();
+
+    let update_request = signer.build_signed(UpdateMethod::OptIn {}).await.unwrap();
+    assert_eq!(
+        update_request.payload.sender,
+        TransactionSender::NodeMain(node_public_key)
+    );
+    assert_eq!(update_request.payload.nonce, 1);
+    let TransactionSignature::NodeMain(signature) = update_request.signature else {
+        panic!("Expected a node signature");
+    };
+    assert!(node_public_key.verify(&signature, &update_request.payload.to_digest()));
+
+    // Building a transaction doesn't submit it or reserve its nonce.
+    assert_eq!(
+        signer
+            .build_signed(UpdateMethod::OptIn {})
+            .await
+            .unwrap()
+            .payload
+            .nonce,
+        1
+    );
+    tokio::time::sleep(Duration::from_secs(3)).await;
+    assert_eq!(get_our_nonce(&node), 0);
+}
+
 #[tokio::test]
 async fn test_retry_send() {
     let temp_dir = tempdir().unwrap();
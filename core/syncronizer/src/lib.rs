@@ -1,4 +1,5 @@
 pub mod config;
+mod progress;
 pub mod rpc;
 pub mod syncronizer;
 mod utils;
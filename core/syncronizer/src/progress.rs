@@ -0,0 +1,107 @@
+use std::future::Future;
+
+use lightning_interfaces::types::{Epoch, FetchProgress};
+use lightning_interfaces::SyncProgress;
+use tokio::sync::watch;
+
+/// Records the start of a new attempt to sync from `current_epoch` to `target_epoch`. Whatever an
+/// earlier attempt downloaded is not the checkpoint this attempt is after.
+pub(crate) fn start_sync(
+    progress: &watch::Sender<SyncProgress>,
+    current_epoch: Epoch,
+    target_epoch: Epoch,
+) {
+    progress.send_modify(|progress| {
+        progress.current_epoch = current_epoch;
+        progress.target_epoch = target_epoch;
+        progress.blocks_verified = 0;
+        progress.total_blocks = None;
+        progress.complete = false;
+    });
+}
+
+/// Runs the download of a checkpoint, copying the progress of the download into the sync
+/// progress while it is being made.
+pub(crate) async fn track_download<T>(
+    progress: &watch::Sender<SyncProgress>,
+    mut download: watch::Receiver<FetchProgress>,
+    fut: impl Future<Output = T>,
+) -> T {
+    tokio::pin!(fut);
+    loop {
+        tokio::select! {
+            res = &mut fut => return res,
+            Ok(()) = download.changed() => {
+                let FetchProgress {
+                    blocks_verified,
+                    total_blocks,
+                    ..
+                } = *download.borrow_and_update();
+                progress.send_modify(|progress| {
+                    progress.blocks_verified = blocks_verified;
+                    progress.total_blocks = total_blocks;
+                });
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_track_download() {
+        let (progress_tx, mut progress) = watch::channel(SyncProgress {
+            current_epoch: 1,
+            target_epoch: 3,
+            ..Default::default()
+        });
+        let (download_tx, download) = watch::channel(FetchProgress::default());
+
+        let observed = tokio::spawn(async move {
+            let mut observed = Vec::new();
+            while progress.changed().await.is_ok() {
+                observed.push(progress.borrow_and_update().percentage());
+            }
+            observed
+        });
+
+        // A source that serves the checkpoint in four blocks.
+        let source = async {
+            for blocks_verified in 1..=4 {
+                download_tx.send_modify(|download| {
+                    download.blocks_verified = blocks_verified;
+                    download.total_blocks = Some(4);
+                });
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        };
+        track_download(&progress_tx, download, source).await;
+        progress_tx.send_modify(|progress| progress.complete = true);
+        drop(progress_tx);
+
+        let observed = observed.await.unwrap();
+        assert!(observed.windows(2).all(|w| w[0] <= w[1]));
+        assert!(observed.iter().any(|p| *p > 0 && *p < 100));
+        assert_eq!(observed.last(), Some(&100));
+    }
+
+    #[test]
+    fn test_start_sync_resets_completion() {
+        let (progress_tx, progress) = watch::channel(SyncProgress {
+            current_epoch: 1,
+            target_epoch: 3,
+            complete: true,
+            ..Default::default()
+        });
+        assert!(!progress.borrow().is_syncing());
+
+        // The network moved on before we loaded the checkpoint we downloaded.
+        start_sync(&progress_tx, 1, 4);
+        assert!(progress.borrow().is_syncing());
+        assert_eq!(progress.borrow().percentage(), 0);
+    }
+}
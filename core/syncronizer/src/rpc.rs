@@ -1,10 +1,11 @@
+use std::collections::{HashMap, HashSet};
 use std::future::Future;
 
 use anyhow::{anyhow, Result};
 use fleek_crypto::NodePublicKey;
 use futures::stream::FuturesOrdered;
 use futures::StreamExt;
-use lightning_interfaces::types::{Epoch, EpochInfo, NodeIndex, NodeInfo};
+use lightning_interfaces::types::{Epoch, EpochHashAttestation, EpochInfo, NodeIndex, NodeInfo};
 use lightning_rpc::interface::Fleek;
 use lightning_rpc::RpcClient;
 use tokio::runtime::Handle;
@@ -125,23 +126,53 @@ pub async fn ask_is_valid_node(
         .await
 }
 
-/// Returns the hash of the last epoch ckpt, and the current epoch.
-pub async fn last_epoch_hash(nodes: &[(NodeIndex, NodeInfo)]) -> Result<[u8; 32]> {
-    let mut hash = ask_last_epoch_hash(nodes.to_vec()).await;
+/// Returns the hash of the last epoch ckpt, as long as a quorum of the committee signs it.
+pub async fn last_epoch_hash(committee: &[(NodeIndex, NodeInfo)]) -> Result<[u8; 32]> {
+    let attestations = ask_last_epoch_hash_attestations(committee.to_vec()).await;
 
-    if hash.is_empty() {
+    if attestations.is_empty() {
         return Err(anyhow!(
-            "Failed to get last epoch hash from bootstrap nodes"
+            "Failed to get last epoch hash from committee nodes"
         ));
     }
-    hash.sort_by(|(_, a), (_, b)| a.partial_cmp(b).unwrap());
-    Ok(hash.pop().unwrap().0)
+    let members = committee
+        .iter()
+        .map(|(_, node)| node.public_key)
+        .collect::<Vec<_>>();
+    attested_epoch_hash(&attestations, &members)
+        .ok_or_else(|| anyhow!("No last epoch hash is signed by a quorum of the committee"))
 }
 
-/// A list of the nodes reported last epoch hash
+/// Returns the hash of the latest epoch that a quorum of the committee signed. Attestations that
+/// don't verify or that come from outside the committee are ignored, and a member that answers
+/// more than once is only counted once.
+pub fn attested_epoch_hash(
+    attestations: &[EpochHashAttestation],
+    committee: &[NodePublicKey],
+) -> Option<[u8; 32]> {
+    let quorum = committee.len() * 2 / 3 + 1;
+    let mut signers = HashMap::<_, HashSet<NodePublicKey>>::new();
+    for attestation in attestations {
+        if committee.contains(&attestation.node) && attestation.verify() {
+            signers
+                .entry((attestation.epoch, attestation.hash))
+                .or_default()
+                .insert(attestation.node);
+        }
+    }
+    signers
+        .into_iter()
+        .filter(|(_, signers)| signers.len() >= quorum)
+        .max_by_key(|((epoch, _), _)| *epoch)
+        .map(|((_, hash), _)| hash)
+}
+
+/// A list of the nodes signed reports of the last epoch hash
 ///
 /// ### Empty if all the requests fail
-pub async fn ask_last_epoch_hash(nodes: Vec<(NodeIndex, NodeInfo)>) -> Vec<([u8; 32], Epoch)> {
+pub async fn ask_last_epoch_hash_attestations(
+    nodes: Vec<(NodeIndex, NodeInfo)>,
+) -> Vec<EpochHashAttestation> {
     nodes
         .into_iter()
         .map(|(_, node)| async move {
@@ -149,7 +180,7 @@ pub async fn ask_is_valid_node(
                 RpcClient::new_no_auth(&format!("http://{}:{}", node.domain, node.ports.rpc))
                     .ok()?;
 
-            client.get_last_epoch_hash().await.ok()
+            client.get_last_epoch_hash_attestation().await.ok()
         })
         .collect::<FuturesOrdered<_>>()
         .filter_map(std::future::ready)
@@ -181,3 +212,70 @@ pub async fn get_epoch(nodes: Vec<(NodeIndex, NodeInfo)>) -> Result<Vec<Epoch>>
         Ok(results)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use fleek_crypto::{NodeSecretKey, SecretKey};
+
+    use super::*;
+
+    #[test]
+    fn test_attested_epoch_hash() {
+        let keys = (0..4)
+            .map(|_| NodeSecretKey::generate())
+            .collect::<Vec<_>>();
+        let committee = keys.iter().map(|key| key.to_pk()).collect::<Vec<_>>();
+        let attested = [1; 32];
+        let forged = [2; 32];
+
+        // A hash that only one of four members signs is rejected, even for a newer epoch.
+        let attestations = [
+            EpochHashAttestation::sign(attested, 3, &keys[0]),
+            EpochHashAttestation::sign(attested, 3, &keys[1]),
+            EpochHashAttestation::sign(attested, 3, &keys[2]),
+            EpochHashAttestation::sign(forged, 4, &keys[3]),
+        ];
+        assert_eq!(
+            attested_epoch_hash(&attestations, &committee),
+            Some(attested)
+        );
+
+        // Without a quorum on any hash there is nothing to load.
+        let attestations = [
+            EpochHashAttestation::sign(attested, 3, &keys[0]),
+            EpochHashAttestation::sign(attested, 3, &keys[1]),
+            EpochHashAttestation::sign(forged, 3, &keys[2]),
+            EpochHashAttestation::sign(forged, 3, &keys[3]),
+        ];
+        assert_eq!(attested_epoch_hash(&attestations, &committee), None);
+    }
+
+    #[test]
+    fn test_attested_epoch_hash_ignores_unattributable_reports() {
+        let keys = (0..4)
+            .map(|_| NodeSecretKey::generate())
+            .collect::<Vec<_>>();
+        let committee = keys.iter().map(|key| key.to_pk()).collect::<Vec<_>>();
+        let outsider = NodeSecretKey::generate();
+        let forged = [2; 32];
+
+        let mut tampered = EpochHashAttestation::sign([1; 32], 3, &keys[2]);
+        tampered.hash = forged;
+        let attestations = [
+            EpochHashAttestation::sign(forged, 3, &keys[0]),
+            // The same member answering twice.
+            EpochHashAttestation::sign(forged, 3, &keys[1]),
+            EpochHashAttestation::sign(forged, 3, &keys[1]),
+            // A signature that doesn't match the reported hash.
+            tampered,
+            // A valid signature from a node outside of the committee.
+            EpochHashAttestation::sign(forged, 3, &outsider),
+        ];
+        assert_eq!(attested_epoch_hash(&attestations, &committee), None);
+
+        // The third member's real signature completes the quorum.
+        let mut attestations = attestations.to_vec();
+        attestations.push(EpochHashAttestation::sign(forged, 3, &keys[2]));
+        assert_eq!(attested_epoch_hash(&attestations, &committee), Some(forged));
+    }
+}
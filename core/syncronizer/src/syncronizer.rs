@@ -12,16 +12,19 @@
     Participation,
     ServerRequest,
 };
+use lightning_interfaces::{DownloadProgress, SyncProgress};
 use lightning_metrics::increment_counter;
 use lightning_utils::application::QueryRunnerExt;
 use rand::seq::SliceRandom;
+use tokio::sync::watch;
 use tracing::error;
 
 use crate::config::Config;
-use crate::{rpc, utils};
+use crate::{progress, rpc, utils};
 
 pub struct Syncronizer<C: Collection> {
     state: State<C>,
+    progress: watch::Receiver<SyncProgress>,
 }
 
 enum State<C: Collection> {
@@ -34,6 +37,8 @@ struct SyncronizerInner<C: Collection> {
     query_runner: c![C::ApplicationInterface::SyncExecutor],
     notifier: C::NotifierInterface,
     blockstore_server_socket: BlockstoreServerSocket,
+    download_progress: DownloadProgress,
+    progress: watch::Sender<SyncProgress>,
     genesis_committee: Vec<(NodeIndex, NodeInfo)>,
     epoch_change_delta: Duration,
 }
@@ -61,17 +66,24 @@ fn init(
             Syncronizer::<C>::prelude(our_public_key, &genesis_committee);
         }
 
+        let (progress_tx, progress) = watch::channel(SyncProgress {
+            current_epoch: query_runner.get_current_epoch(),
+            ..Default::default()
+        });
+
         let inner = SyncronizerInner::new(
             our_public_key,
             genesis_committee,
             query_runner.clone(),
             notifier.clone(),
             blockstore_server,
+            progress_tx,
             config.epoch_change_delta,
         )?;
 
         Ok(Self {
             state: State::Initialized(inner),
+            progress,
         })
     }
 
@@ -161,6 +173,10 @@ async fn next_checkpoint_hash(&self) -> Option<Blake3Hash> {
         };
         rx.recv().await.ok()
     }
+
+    fn sync_progress(&self) -> watch::Receiver<SyncProgress> {
+        self.progress.clone()
+    }
 }
 
 impl<C: Collection> SyncronizerInner<C> {
@@ -170,12 +186,15 @@ fn new(
         query_runner: c![C::ApplicationInterface::SyncExecutor],
         notifier: C::NotifierInterface,
         blockstore_server: &C::BlockstoreServerInterface,
+        progress: watch::Sender<SyncProgress>,
         epoch_change_delta: Duration,
     ) -> Result<Self> {
         Ok(Self {
             our_public_key,
             query_runner,
             blockstore_server_socket: blockstore_server.get_socket(),
+            download_progress: blockstore_server.get_download_progress(),
+            progress,
             notifier,
             genesis_committee,
             epoch_change_delta,
@@ -221,6 +240,10 @@ async fn run(&mut self, tx_update_ready: async_channel::Sender<Blake3Hash>) {
                 }
 
                 Some(_notification) = epoch_changed_sub.recv() => {
+                    let current_epoch = self.query_runner.get_current_epoch();
+                    self.progress
+                        .send_modify(|progress| progress.current_epoch = current_epoch);
+
                     if !cfg!(debug_assertions) {
                         // We only run the prelude in prod mode to avoid interfering with tests.
                         if !self.query_runner.is_valid_node(&self.our_public_key) {
@@ -268,6 +291,8 @@ async fn run(&mut self, tx_update_ready: async_channel::Sender<Blake3Hash>) {
         // Get the epoch the bootstrap nodes are at
         let bootstrap_epoch = self.get_current_epoch().await?;
 
+        progress::start_sync(&self.progress, current_epoch, bootstrap_epoch);
+
         if bootstrap_epoch <= current_epoch {
             bail!("Bootstrap nodes are on the same epoch");
         }
@@ -277,11 +302,17 @@ async fn run(&mut self, tx_update_ready: async_channel::Sender<Blake3Hash>) {
 
         // Attempt to download to our blockstore the latest checkpoint and if that is succesfully
         // alert the node that it is ready to load the checkpoint
-        if self
-            .download_checkpoint_from_bootstrap(latest_checkpoint_hash)
-            .await
-            .is_ok()
-        {
+        let download = self.download_progress.subscribe(latest_checkpoint_hash);
+        let res = progress::track_download(
+            &self.progress,
+            download,
+            self.download_checkpoint_from_bootstrap(latest_checkpoint_hash),
+        )
+        .await;
+
+        if res.is_ok() {
+            self.progress
+                .send_modify(|progress| progress.complete = true);
             Ok(latest_checkpoint_hash)
         } else {
             error!("Unable to download checkpoint");
@@ -309,8 +340,9 @@ async fn run(&mut self, tx_update_ready: async_channel::Sender<Blake3Hash>) {
         ))
     }
 
-    // This function will hit the bootstrap nodes(Genesis committee) to ask what epoch they are on
-    // who the current committee is
+    // This function will ask the bootstrap nodes(Genesis committee) for their signed last epoch
+    // hash, and only accept a hash that a quorum of them signed. Our own view of the current
+    // committee can not be trusted before we synced, so the genesis committee is the root of trust
     async fn get_latest_checkpoint_hash(&self) -> Result<[u8; 32]> {
         rpc::last_epoch_hash(&self.genesis_committee).await
     }
@@ -6,13 +6,14 @@
 
 use std::collections::HashSet;
 use std::marker::PhantomData;
+use std::sync::Arc;
 use std::time::Duration;
 
 use affair::AsyncWorkerUnordered;
 use fdi::Cloned;
 use lightning_interfaces::prelude::*;
-use lightning_interfaces::spawn_worker;
 use lightning_interfaces::types::{Block, TransactionRequest};
+use lightning_interfaces::{spawn_worker, ForwarderError};
 use rand::{thread_rng, Rng, SeedableRng};
 use rand_chacha::ChaCha12Rng;
 use rand_distr::{Bernoulli, Distribution};
@@ -77,9 +78,9 @@ fn new(sender: mpsc::Sender<TransactionRequest>, waiter: ShutdownWaiter) -> Self
         struct ProxyWorker(mpsc::Sender<TransactionRequest>);
         impl AsyncWorkerUnordered for ProxyWorker {
             type Request = TransactionRequest;
-            type Response = ();
-            async fn handle(&self, req: Self::Request) {
-                self.0.send(req).await.expect("Failed to send transaction.")
+            type Response = Result<(), ForwarderError>;
+            async fn handle(&self, req: Self::Request) -> Self::Response {
+                self.0.send(req).await.map_err(|_| ForwarderError::Closed)
             }
         }
         let worker = ProxyWorker(sender);
@@ -188,6 +189,28 @@ pub struct Config {
     /// This specifies the interval for new blocks being pretend submitted to the application.
     #[serde(with = "humantime_serde")]
     pub new_block_interval: Duration,
+    /// Decides for each transaction that gets through whether it is dropped, delayed or
+    /// duplicated. A closure can't be serialized, so this is only used when the
+    /// [MockConsensusGroup] is created from the config directly.
+    #[serde(skip)]
+    pub fault_injector: Option<FaultInjector>,
+}
+
+/// Called with the arrival number and the transaction for each transaction arriving at the
+/// consensus, see [Config::fault_injector].
+pub type FaultInjector = Arc<dyn Fn(u32, &TransactionRequest) -> Fault + Send + Sync>;
+
+/// The fault to inject for a transaction.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Fault {
+    /// Order the transaction as usual.
+    None,
+    /// Lose the transaction.
+    Drop,
+    /// Order the transaction after an additional delay.
+    Delay(Duration),
+    /// Order the transaction twice.
+    Duplicate,
 }
 
 impl Default for Config {
@@ -198,6 +221,7 @@ fn default() -> Self {
             probability_txn_lost: 0.0,
             transactions_to_lose: HashSet::new(),
             new_block_interval: Duration::from_secs(5),
+            fault_injector: None,
         }
     }
 }
@@ -249,17 +273,35 @@ async fn group_worker(
                     continue;
                 }
 
-                // Randomly wait before ordering the transaction to make it more realistic.
-                let range = config.min_ordering_time..config.max_ordering_time;
-                delayed_queue.spawn(async move {
-                    if !range.is_empty() && config.max_ordering_time > 0 {
-                        let ordering_delay = rand::thread_rng().gen_range(range);
-                        if ordering_delay > 0 {
-                            sleep(Duration::from_secs(ordering_delay)).await;
+                let fault = config
+                    .fault_injector
+                    .as_ref()
+                    .map_or(Fault::None, |injector| injector(tx_count, &req));
+                let (fault_delay, copies) = match fault {
+                    Fault::None => (Duration::ZERO, 1),
+                    Fault::Drop => continue,
+                    Fault::Delay(delay) => (delay, 1),
+                    Fault::Duplicate => (Duration::ZERO, 2),
+                };
+
+                for _ in 0..copies {
+                    // Randomly wait before ordering the transaction to make it more realistic.
+                    let range = config.min_ordering_time..config.max_ordering_time;
+                    let max_ordering_time = config.max_ordering_time;
+                    let req = req.clone();
+                    delayed_queue.spawn(async move {
+                        if !range.is_empty() && max_ordering_time > 0 {
+                            let ordering_delay = rand::thread_rng().gen_range(range);
+                            if ordering_delay > 0 {
+                                sleep(Duration::from_secs(ordering_delay)).await;
+                            }
+                        }
+                        if !fault_delay.is_zero() {
+                            sleep(fault_delay).await;
                         }
-                    }
-                    req
-                });
+                        req
+                    });
+                }
 
                 continue;
             },
@@ -292,3 +334,46 @@ async fn group_worker(
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transaction::get_update_transactions;
+
+    #[tokio::test]
+    async fn test_fault_injector_delays_transaction() {
+        let transactions = get_update_transactions(2)
+            .into_iter()
+            .map(TransactionRequest::from)
+            .collect::<Vec<_>>();
+
+        // Delay the first transaction so that it is ordered after the second one.
+        let delayed = transactions[0].hash();
+        let mut group = MockConsensusGroup::new(Config {
+            min_ordering_time: 0,
+            max_ordering_time: 0,
+            new_block_interval: Duration::ZERO,
+            fault_injector: Some(Arc::new(move |_, req: &TransactionRequest| {
+                if req.hash() == delayed {
+                    Fault::Delay(Duration::from_millis(200))
+                } else {
+                    Fault::None
+                }
+            })),
+            ..Default::default()
+        });
+        let req_tx = group.req_tx.take().unwrap();
+        let mut block_rx = group.block_producer_rx.take().unwrap();
+
+        for req in transactions.iter() {
+            req_tx.send(req.clone()).await.unwrap();
+        }
+
+        let first = block_rx.recv().await.unwrap();
+        let second = block_rx.recv().await.unwrap();
+        assert_eq!(first.transactions.len(), 1);
+        assert_eq!(first.transactions[0].hash(), transactions[1].hash());
+        assert_eq!(second.transactions.len(), 1);
+        assert_eq!(second.transactions[0].hash(), transactions[0].hash());
+    }
+}
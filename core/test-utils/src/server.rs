@@ -1,15 +1,32 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
 use axum::extract::Path;
 use axum::http::{HeaderMap, StatusCode};
 use axum::routing::get;
 use axum::Router;
 
 pub async fn spawn_server(port: u16) -> anyhow::Result<()> {
+    spawn_server_with_counter(port, Default::default()).await
+}
+
+/// Like [`spawn_server`], but increments the counter for every request for a cid.
+pub async fn spawn_server_with_counter(
+    port: u16,
+    cid_requests: Arc<AtomicUsize>,
+) -> anyhow::Result<()> {
     // Mostly taken from:
     // https://github.com/fleek-network/ursa/blob/main/crates/ursa-rpc-service/src/tests/mod.rs
     let ts_file: Vec<u8> = std::fs::read("../test-utils/files/index.ts")?;
 
     let router = Router::new()
-        .route("/ipfs/:cid", get(get_cid))
+        .route(
+            "/ipfs/:cid",
+            get(move |cid: Path<String>| async move {
+                cid_requests.fetch_add(1, Ordering::Relaxed);
+                get_cid(cid).await
+            }),
+        )
         .route("/bar/:filename", get(|| async move { ts_file.clone() }));
 
     axum::Server::bind(&format!("0.0.0.0:{port}").parse().unwrap())
@@ -1,9 +1,74 @@
 use ethers::types::{Transaction as EthersTransaction, U256};
 use ethers::utils::rlp;
 use fleek_crypto::{NodeSecretKey, SecretKey, TransactionSender, TransactionSignature};
-use lightning_interfaces::types::{UpdateMethod, UpdatePayload, UpdateRequest};
+use lightning_interfaces::types::{
+    Block,
+    TransactionRequest,
+    UpdateMethod,
+    UpdatePayload,
+    UpdateRequest,
+};
 use lightning_interfaces::ToDigest;
 
+/// Builds a [`Block`] out of multiple transactions, which are executed atomically by the
+/// application.
+///
+/// The digest of the block is computed from the digest of the parent block and the hashes of the
+/// included transactions, in the same way the mock consensus does it.
+#[derive(Default)]
+pub struct BlockBuilder {
+    parent_digest: [u8; 32],
+    sub_dag_index: u64,
+    transactions: Vec<TransactionRequest>,
+}
+
+impl BlockBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the digest of the block this block is built on top of.
+    pub fn with_parent_digest(mut self, parent_digest: [u8; 32]) -> Self {
+        self.parent_digest = parent_digest;
+        self
+    }
+
+    pub fn with_sub_dag_index(mut self, sub_dag_index: u64) -> Self {
+        self.sub_dag_index = sub_dag_index;
+        self
+    }
+
+    /// Append a transaction to the block.
+    pub fn with_transaction(mut self, transaction: impl Into<TransactionRequest>) -> Self {
+        self.transactions.push(transaction.into());
+        self
+    }
+
+    /// Append multiple transactions to the block, preserving their order.
+    pub fn with_transactions<T: Into<TransactionRequest>>(
+        mut self,
+        transactions: impl IntoIterator<Item = T>,
+    ) -> Self {
+        self.transactions
+            .extend(transactions.into_iter().map(Into::into));
+        self
+    }
+
+    pub fn build(self) -> Block {
+        let mut payload = Vec::with_capacity(32 * (self.transactions.len() + 1));
+        payload.extend(&self.parent_digest);
+        for tx in &self.transactions {
+            payload.extend(tx.hash());
+        }
+
+        Block {
+            digest: *fleek_blake3::hash(&payload).as_bytes(),
+            sub_dag_index: self.sub_dag_index,
+            transactions: self.transactions,
+        }
+    }
+}
+
 pub fn get_update_transactions(num_txns: usize) -> Vec<UpdateRequest> {
     (0..num_txns)
         .map(|i| {
@@ -11,7 +11,7 @@
 use lightning_application::config::Config as AppConfig;
 use lightning_application::genesis::{Genesis, GenesisLatency, GenesisNode};
 use lightning_interfaces::prelude::*;
-use lightning_interfaces::types::{NodePorts, Participation};
+use lightning_interfaces::types::{HandshakePorts, NodePorts, Participation};
 use lightning_notifier::Notifier;
 use lightning_test_utils::json_config::JsonConfigProvider;
 use lightning_test_utils::keys::EphemeralKeystore;
@@ -88,7 +88,11 @@ async fn test_build_latency_matrix() {
                     rpc: 38103,
                     pool: 38104,
                     pinger: 38106,
-                    handshake: Default::default(),
+                    handshake: HandshakePorts {
+                        http: 38220,
+                        webrtc: 38320,
+                        webtransport: 38321,
+                    },
                 },
                 None,
                 true,
@@ -101,13 +105,17 @@ async fn test_build_latency_matrix() {
                 "127.0.0.1".parse().unwrap(),
                 node_public_key2,
                 NodePorts {
-                    primary: 38000,
-                    worker: 38101,
-                    mempool: 38102,
-                    rpc: 38103,
-                    pool: 38104,
-                    pinger: 38106,
-                    handshake: Default::default(),
+                    primary: 28000,
+                    worker: 28101,
+                    mempool: 28102,
+                    rpc: 28103,
+                    pool: 28104,
+                    pinger: 28106,
+                    handshake: HandshakePorts {
+                        http: 28220,
+                        webrtc: 28320,
+                        webtransport: 28321,
+                    },
                 },
                 None,
                 true,
@@ -12,4 +12,5 @@ pub enum PeerRequestError {
     Timeout,
     Rejected(RejectReason),
     Incomplete,
+    InvalidContent,
 }
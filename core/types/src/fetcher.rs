@@ -13,3 +13,14 @@ pub enum FetcherResponse {
     Put(Result<Blake3Hash>),
     Fetch(Result<()>),
 }
+
+/// How far a fetch has got.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct FetchProgress {
+    /// The number of bytes of content received from peers.
+    pub bytes_fetched: u64,
+    /// The number of blocks received and verified against the root hash.
+    pub blocks_verified: u32,
+    /// The total number of blocks of the content, once known.
+    pub total_blocks: Option<u32>,
+}
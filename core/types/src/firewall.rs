@@ -7,6 +7,9 @@ pub struct FirewallConfig {
     pub name: String,
     pub connection_policy: ConnectionPolicyConfig,
     pub rate_limiting: RateLimitingConfig,
+    /// Trusted peers that are never rate limited.
+    #[serde(default)]
+    pub allowlist: Vec<IpAddr>,
 }
 
 impl FirewallConfig {
@@ -15,6 +18,7 @@ pub fn none(name: String) -> Self {
             name,
             connection_policy: ConnectionPolicyConfig::All,
             rate_limiting: RateLimitingConfig::None,
+            allowlist: Vec::new(),
         }
     }
 }
@@ -40,6 +44,9 @@ pub enum RateLimitingConfig {
 pub struct RateLimitingRule {
     pub period: Period,
     pub max_requests: u64,
+    /// The number of requests that can be made at once, defaults to `max_requests`.
+    #[serde(default)]
+    pub burst: Option<u64>,
 }
 
 #[derive(Clone, Debug, Copy, Serialize, Deserialize)]
@@ -1,8 +1,97 @@
-use fleek_crypto::{ConsensusPublicKey, NodePublicKey};
+use fleek_crypto::{
+    ConsensusPublicKey,
+    NodePublicKey,
+    NodeSecretKey,
+    NodeSignature,
+    PublicKey,
+    SecretKey,
+};
+use ink_quill::{ToDigest, TranscriptBuilder};
 use serde::{Deserialize, Serialize};
 
+use crate::{BlockExecutionResponse, Epoch};
+
+const FN_EPOCH_HASH_DOMAIN: &str = "fleek_network_epoch_hash";
+
 #[derive(Clone, PartialEq, Eq, Hash, Serialize, Deserialize, Debug, schemars::JsonSchema)]
 pub struct PublicKeys {
     pub node_public_key: NodePublicKey,
     pub consensus_public_key: ConsensusPublicKey,
 }
+
+/// A summary of an executed block, as streamed to the RPC block subscribers.
+#[derive(Clone, PartialEq, Eq, Hash, Serialize, Deserialize, Debug, schemars::JsonSchema)]
+pub struct BlockSummary {
+    pub block_number: u64,
+    pub block_hash: [u8; 32],
+    pub parent_hash: [u8; 32],
+    pub change_epoch: bool,
+    pub txn_count: usize,
+}
+
+impl From<&BlockExecutionResponse> for BlockSummary {
+    fn from(response: &BlockExecutionResponse) -> Self {
+        Self {
+            block_number: response.block_number,
+            block_hash: response.block_hash,
+            parent_hash: response.parent_hash,
+            change_epoch: response.change_epoch,
+            txn_count: response.txn_receipts.len(),
+        }
+    }
+}
+
+/// A notification sent to the RPC block subscribers.
+#[derive(Clone, PartialEq, Eq, Hash, Serialize, Deserialize, Debug, schemars::JsonSchema)]
+pub enum BlockNotification {
+    /// A new block was executed.
+    Block(BlockSummary),
+    /// The subscriber fell behind and this many of the oldest blocks were dropped.
+    Lagged { skipped: u64 },
+}
+
+/// The hash of the checkpoint of an epoch as reported by a node, signed with its node key so that
+/// the report can be attributed to it.
+#[derive(Clone, PartialEq, Eq, Hash, Serialize, Deserialize, Debug, schemars::JsonSchema)]
+pub struct EpochHashAttestation {
+    pub hash: [u8; 32],
+    pub epoch: Epoch,
+    pub node: NodePublicKey,
+    pub signature: NodeSignature,
+}
+
+impl EpochHashAttestation {
+    /// Signs the checkpoint hash of the given epoch with the node key.
+    pub fn sign(hash: [u8; 32], epoch: Epoch, secret_key: &NodeSecretKey) -> Self {
+        let digest = EpochHashPayload { hash: &hash, epoch }.to_digest();
+        Self {
+            hash,
+            epoch,
+            node: secret_key.to_pk(),
+            signature: secret_key.sign(&digest),
+        }
+    }
+
+    /// Returns true if the attestation was signed by the node it names.
+    pub fn verify(&self) -> bool {
+        let digest = EpochHashPayload {
+            hash: &self.hash,
+            epoch: self.epoch,
+        }
+        .to_digest();
+        self.node.verify(&self.signature, &digest)
+    }
+}
+
+struct EpochHashPayload<'a> {
+    hash: &'a [u8; 32],
+    epoch: Epoch,
+}
+
+impl ToDigest for EpochHashPayload<'_> {
+    fn transcript(&self) -> TranscriptBuilder {
+        TranscriptBuilder::empty(FN_EPOCH_HASH_DOMAIN)
+            .with("hash", self.hash)
+            .with("epoch", &self.epoch)
+    }
+}
@@ -183,6 +183,10 @@ pub enum ProtocolParams {
     /// Minimum number of reported measurements that have to be available for a node. If less
     /// measurements have been reported, no reputation score will be computed in that epoch.
     MinNumMeasurements = 12,
+    /// The number of median absolute deviations a reported measurement may be away from the
+    /// median before it is dropped as an outlier when computing reputation scores. Zero disables
+    /// the outlier filter.
+    ReputationOutlierThreshold = 13,
 }
 
 #[rustfmt::skip]
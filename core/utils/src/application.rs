@@ -41,6 +41,12 @@ fn get_committee_members_by_index(&self) -> Vec<NodeIndex> {
             .unwrap_or_default()
     }
 
+    /// Returns the committee members of the given epoch by NodeIndex, or `None` if there is no
+    /// committee for that epoch in the state.
+    fn get_committee_members_at_epoch(&self, epoch: Epoch) -> Option<Vec<NodeIndex>> {
+        self.get_committe_info(&epoch, |c| c.members)
+    }
+
     /// Get Current Epoch
     /// Returns just the current epoch
     fn get_current_epoch(&self) -> Epoch {
@@ -143,9 +149,15 @@ fn get_node_registry(&self, paging: Option<PagingParams>) -> Vec<NodeInfoWithInd
                     ignore_stake,
                     limit,
                     start,
+                    participation,
                 }) => {
                     let mut nodes = nodes
                         .filter(|node| ignore_stake || node.info.stake.staked >= staking_amount)
+                        .filter(|node| {
+                            participation.as_ref().map_or(true, |participation| {
+                                node.info.participation == *participation
+                            })
+                        })
                         .collect::<Vec<NodeInfoWithIndex>>();
 
                     nodes.sort_by_key(|info| info.index);
@@ -95,7 +95,7 @@ fn from(value: PacketFilterRule) -> Self {
 ///
 /// A profile specifies a list of files that a program
 /// can access and the operations the program may perform.
-#[derive(Clone, Default, Deserialize, Serialize)]
+#[derive(Clone, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
 pub struct Profile {
     /// Path to the executable file.
     ///
@@ -129,7 +129,7 @@ fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
 }
 
 /// Rule that defines how a file is accessed.
-#[derive(Clone, Debug, Deserialize, Serialize)]
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
 pub struct FileRule {
     /// Path of the file.
     pub file: ResolvedPathBuf,
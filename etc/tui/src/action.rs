@@ -1,3 +1,4 @@
+use lightning_guard::map::Profile;
 use serde::{Deserialize, Serialize};
 use strum::Display;
 
@@ -13,6 +14,7 @@ pub enum Action {
     Quit,
     Refresh,
     Error(String),
+    Saved(usize),
     Help,
     NavLeft,
     NavRight,
@@ -20,11 +22,14 @@ pub enum Action {
     Down,
     Add,
     Remove,
+    Undo,
     UpdateMode(Mode),
     Save,
     Cancel,
+    Confirm,
     Edit,
     Select,
+    ProfileLoaded(Profile),
     Back,
     PageUp,
     PageDown,
@@ -88,6 +88,7 @@ fn update_components(&mut self, action: Action) -> Result<Option<Action>> {
             Mode::FirewallForm => self.firewall.form().update(action.clone())?,
             Mode::Profiles => self.profiles.update(action.clone())?,
             Mode::ProfilesEdit => self.profiles.update(action.clone())?,
+            Mode::ConfirmRemove => self.profiles.update(action.clone())?,
             Mode::ProfileView => self.profiles.view().update(action.clone())?,
             Mode::ProfileViewEdit => self.profiles.view().update(action.clone())?,
             Mode::ProfileForm => self.profiles.form().update(action.clone())?,
@@ -113,6 +114,7 @@ fn handle_event(&mut self, event: tui::Event) -> Result<Option<Action>> {
             Mode::FirewallForm => self.firewall.form().handle_events(Some(event)),
             Mode::Profiles => self.profiles.handle_events(Some(event)),
             Mode::ProfilesEdit => self.profiles.handle_events(Some(event)),
+            Mode::ConfirmRemove => self.profiles.handle_events(Some(event)),
             Mode::ProfileView => self.profiles.view().handle_events(Some(event)),
             Mode::ProfileViewEdit => self.profiles.view().handle_events(Some(event)),
             Mode::ProfileForm => self.profiles.form().handle_events(Some(event)),
@@ -165,7 +167,7 @@ fn draw_components(&mut self, f: &mut Frame<'_>, _area: Rect) -> Result<()> {
             Mode::FirewallForm => {
                 self.firewall.form().draw(f, content[0])?;
             },
-            Mode::Profiles | Mode::ProfilesEdit => {
+            Mode::Profiles | Mode::ProfilesEdit | Mode::ConfirmRemove => {
                 self.profiles.draw(f, content[0])?;
             },
             Mode::ProfileView | Mode::ProfileViewEdit => {
@@ -338,6 +340,16 @@ pub async fn run(&mut self) -> Result<()> {
                             }
                         })?;
                     },
+                    Action::Saved(count) => {
+                        self.prompt.new_message(format!("Saved {count} profile(s)"));
+                        tui.draw(|f| {
+                            if let Err(e) = self.draw_components(f, f.size()) {
+                                action_tx
+                                    .send(Action::Error(format!("Failed to draw: {:?}", e)))
+                                    .unwrap();
+                            }
+                        })?;
+                    },
                     _ => {},
                 }
 
@@ -2,10 +2,14 @@
 mod view;
 
 use std::collections::HashSet;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
 
 use anyhow::Result;
 use lightning_guard::{map, ConfigSource};
 use ratatui::prelude::Rect;
+use ratatui::widgets::{Block, Borders, Clear, Paragraph};
 use tokio::sync::mpsc::UnboundedSender;
 
 use super::{Component, Frame};
@@ -15,11 +19,35 @@
 use crate::config::Config;
 use crate::mode::Mode;
 use crate::widgets::list::List;
+use crate::widgets::utils;
+
+/// Saves issued within this window of each other are written to storage together.
+const SAVE_DEBOUNCE: Duration = Duration::from_millis(250);
+const CONFIRM_REMOVE_X: u16 = 50;
+const CONFIRM_REMOVE_Y: u16 = 30;
+
+/// Changes that have been committed but not yet written to storage.
+#[derive(Default)]
+struct PendingSave {
+    remove: HashSet<Option<PathBuf>>,
+    update: Vec<map::Profile>,
+    scheduled: bool,
+}
+
+/// An edit to the list of profiles that can be undone.
+enum Edit {
+    Add,
+    Remove { index: usize },
+}
 
 /// Component that displaying and managing security profiles.
 pub struct Profile {
     command_tx: Option<UnboundedSender<Action>>,
     profiles_to_update: Option<Vec<map::Profile>>,
+    pending_save: Arc<Mutex<PendingSave>>,
+    undo_stack: Vec<Edit>,
+    /// Whether the selected profile is waiting for confirmation to be removed.
+    confirming_remove: bool,
     src: ConfigSource,
     list: List<map::Profile>,
     view: ProfileView,
@@ -32,6 +60,9 @@ pub fn new(src: ConfigSource) -> Self {
         Self {
             src: src.clone(),
             profiles_to_update: None,
+            pending_save: Arc::new(Mutex::new(PendingSave::default())),
+            undo_stack: Vec::new(),
+            confirming_remove: false,
             command_tx: None,
             list: List::new("Profiles"),
             view: ProfileView::new(src),
@@ -68,6 +99,26 @@ fn add_profile(&mut self, profile: map::Profile) {
             .as_mut()
             .expect("Already initialized");
         profiles_to_update.push(profile);
+        self.undo_stack.push(Edit::Add);
+    }
+
+    fn remove_profile(&mut self) {
+        if let Some(index) = self.list.remove_selected_record() {
+            self.undo_stack.push(Edit::Remove { index });
+        }
+    }
+
+    fn undo(&mut self) {
+        match self.undo_stack.pop() {
+            Some(Edit::Add) => {
+                self.list.undo_add_record();
+                if let Some(profiles_to_update) = self.profiles_to_update.as_mut() {
+                    profiles_to_update.pop();
+                }
+            },
+            Some(Edit::Remove { index }) => self.list.undo_remove_record(index),
+            None => {},
+        }
     }
 
     fn save(&mut self) {
@@ -78,38 +129,105 @@ fn save(&mut self) {
             .map(|profile| profile.name.take())
             .collect::<HashSet<_>>();
         self.list.commit_changes();
-        let update = self.profiles_to_update.take();
+        self.undo_stack.clear();
+        let update = self.profiles_to_update.take().unwrap_or_default();
+
+        // Merge the changes with the ones that have not been written yet,
+        // so that the latest change to a profile wins.
+        let mut pending = self.pending_save.lock().expect("Lock not to be poisoned");
+        pending
+            .update
+            .retain(|profile| !remove.contains(&profile.name));
+        pending
+            .remove
+            .retain(|name| !update.iter().any(|profile| &profile.name == name));
+        pending.remove.extend(remove);
+        pending.update.extend(update);
+
+        // A write is already scheduled and will pick up these changes.
+        if pending.scheduled {
+            return;
+        }
+        pending.scheduled = true;
+        drop(pending);
+
         let command_tx = self
             .command_tx
             .clone()
             .expect("Component always has a sender");
         let storage = self.src.clone();
+        let pending = self.pending_save.clone();
         tokio::spawn(async move {
+            tokio::time::sleep(SAVE_DEBOUNCE).await;
+
+            let (remove, update) = {
+                let mut pending = pending.lock().expect("Lock not to be poisoned");
+                pending.scheduled = false;
+                (
+                    std::mem::take(&mut pending.remove),
+                    std::mem::take(&mut pending.update),
+                )
+            };
+            let count = remove.len() + update.len();
+
             // Todo: do better.
+            let mut saved = true;
             if let Err(e) = storage.delete_profiles(remove).await {
+                saved = false;
                 let _ = command_tx.send(Action::Error(e.to_string()));
             }
-            if let Some(profiles) = update {
-                if let Err(e) = storage.write_profiles(profiles).await {
-                    let _ = command_tx.send(Action::Error(e.to_string()));
-                }
+            if let Err(e) = storage.write_profiles(update).await {
+                saved = false;
+                let _ = command_tx.send(Action::Error(e.to_string()));
+            }
+            if saved {
+                let _ = command_tx.send(Action::Saved(count));
             }
         });
     }
 
-    fn load_profile_into_view(&mut self) -> Result<()> {
+    /// Reads the selected profile from storage without blocking the UI.
+    ///
+    /// The result is sent back as an `Action::ProfileLoaded`.
+    fn load_profile_into_view(&mut self) {
         if let Some(selected) = self.list.get() {
-            let profile = self
-                .src
-                .blocking_read_profile(selected.name.as_ref().and_then(|name| name.file_stem()))?;
-            self.view.load_profile(profile);
+            let name = selected
+                .name
+                .as_ref()
+                .and_then(|name| name.file_stem())
+                .map(ToOwned::to_owned);
+            let command_tx = self
+                .command_tx
+                .clone()
+                .expect("Component always has a sender");
+            let storage = self.src.clone();
+            tokio::spawn(async move {
+                let action = match storage.read_profile(name.as_deref()).await {
+                    Ok(profile) => Action::ProfileLoaded(profile),
+                    Err(e) => Action::Error(e.to_string()),
+                };
+                let _ = command_tx.send(action);
+            });
         }
-        Ok(())
+    }
+
+    /// Draws a dialog over the list asking to confirm the removal of the selected profile.
+    fn draw_confirm_remove(&self, f: &mut Frame<'_>, area: Rect) {
+        let name = self.list.get().map(ToString::to_string).unwrap_or_default();
+        let area = utils::center_form(CONFIRM_REMOVE_X, CONFIRM_REMOVE_Y, area);
+        f.render_widget(Clear, area);
+        f.render_widget(
+            Paragraph::new(format!("Remove profile {name}?\n\n[y] Remove  [n] Keep"))
+                .centered()
+                .block(Block::default().borders(Borders::ALL).title("Confirm")),
+            area,
+        );
     }
 
     fn restore_state(&mut self) {
         self.list.restore_state();
         self.profiles_to_update.take();
+        self.undo_stack.clear();
     }
 }
 
@@ -131,13 +249,29 @@ fn update(&mut self, action: Action) -> Result<Option<Action>> {
                 self.save();
                 Ok(Some(Action::UpdateMode(Mode::Profiles)))
             },
+            Action::Cancel if self.confirming_remove => {
+                self.confirming_remove = false;
+                Ok(Some(Action::UpdateMode(Mode::ProfilesEdit)))
+            },
             Action::Cancel => {
                 self.restore_state();
                 Ok(Some(Action::UpdateMode(Mode::Profiles)))
             },
             Action::Add => Ok(Some(Action::UpdateMode(Mode::ProfileForm))),
             Action::Remove => {
-                self.list.remove_selected_record();
+                if self.list.get().is_none() {
+                    return Ok(None);
+                }
+                self.confirming_remove = true;
+                Ok(Some(Action::UpdateMode(Mode::ConfirmRemove)))
+            },
+            Action::Confirm if self.confirming_remove => {
+                self.confirming_remove = false;
+                self.remove_profile();
+                Ok(Some(Action::UpdateMode(Mode::ProfilesEdit)))
+            },
+            Action::Undo => {
+                self.undo();
                 Ok(Some(Action::Render))
             },
             Action::Up => {
@@ -149,9 +283,11 @@ fn update(&mut self, action: Action) -> Result<Option<Action>> {
                 Ok(Some(Action::Render))
             },
             Action::Select => {
-                if let Err(e) = self.load_profile_into_view() {
-                    return Ok(Some(Action::Error(e.to_string())));
-                }
+                self.load_profile_into_view();
+                Ok(None)
+            },
+            Action::ProfileLoaded(profile) => {
+                self.view.load_profile(profile);
                 Ok(Some(Action::UpdateMode(Mode::ProfileView)))
             },
             Action::UpdateMode(Mode::ProfilesEdit) => {
@@ -165,6 +301,192 @@ fn update(&mut self, action: Action) -> Result<Option<Action>> {
     }
 
     fn draw(&mut self, f: &mut Frame<'_>, area: Rect) -> Result<()> {
-        self.list.render(f, area)
+        self.list.render(f, area)?;
+        if self.confirming_remove {
+            self.draw_confirm_remove(f, area);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use lightning_guard::PathConfig;
+    use ratatui::backend::TestBackend;
+    use ratatui::Terminal;
+    use tokio::sync::mpsc;
+
+    use super::*;
+
+    fn config_source(root: &Path) -> ConfigSource {
+        let paths = PathConfig {
+            tmp_dir: root.join("tmp"),
+            packet_filter: root.join("filters.json"),
+            profiles_dir: root.join("profiles"),
+        };
+        std::fs::create_dir_all(&paths.tmp_dir).unwrap();
+        std::fs::create_dir_all(&paths.profiles_dir).unwrap();
+        ConfigSource::new(paths)
+    }
+
+    fn profile(name: &str) -> map::Profile {
+        map::Profile {
+            name: Some(PathBuf::from(name)),
+            ..Default::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_save_sends_saved_action() {
+        let root = std::env::temp_dir().join("lightning-tui-test-save-sends-saved-action");
+        let _ = std::fs::remove_dir_all(&root);
+        let src = config_source(&root);
+
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        let mut component = Profile::new(src.clone());
+        component.register_action_handler(tx).unwrap();
+
+        // Successive saves are written together.
+        component.add_profile(profile("first"));
+        component.update(Action::Save).unwrap();
+        component.add_profile(profile("second"));
+        component.update(Action::Save).unwrap();
+
+        let action = tokio::time::timeout(Duration::from_secs(5), rx.recv())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(action, Action::Saved(2));
+        assert_eq!(src.get_profiles().await.unwrap().len(), 2);
+
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_select_loads_profile_without_blocking() {
+        let root = std::env::temp_dir().join("lightning-tui-test-select-loads-profile");
+        let _ = std::fs::remove_dir_all(&root);
+        let src = config_source(&root);
+        src.write_profiles(vec![profile("first")]).await.unwrap();
+
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        let mut component = Profile::new(src);
+        component.register_action_handler(tx).unwrap();
+        component.get_profile_list_from_storage().await.unwrap();
+
+        // Selecting returns right away and the read happens in the background.
+        assert_eq!(component.update(Action::Select).unwrap(), None);
+
+        let action = tokio::time::timeout(Duration::from_secs(5), rx.recv())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(action, Action::ProfileLoaded(profile("first")));
+
+        // Handling the loaded profile opens the view.
+        assert_eq!(
+            component.update(action).unwrap(),
+            Some(Action::UpdateMode(Mode::ProfileView))
+        );
+
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_remove_requires_confirmation() {
+        let root = std::env::temp_dir().join("lightning-tui-test-remove-requires-confirmation");
+        let _ = std::fs::remove_dir_all(&root);
+        let mut component = Profile::new(config_source(&root));
+        component.add_profile(profile("first"));
+        let added = profile_names(&component);
+
+        // Removing asks for confirmation first.
+        assert_eq!(
+            component.update(Action::Remove).unwrap(),
+            Some(Action::UpdateMode(Mode::ConfirmRemove))
+        );
+        assert_eq!(profile_names(&component), added);
+
+        // The dialog is drawn over the list.
+        assert!(screen(&mut component).contains("Remove profile first?"));
+
+        // Cancelling keeps the profile and the pending edits.
+        assert_eq!(
+            component.update(Action::Cancel).unwrap(),
+            Some(Action::UpdateMode(Mode::ProfilesEdit))
+        );
+        assert_eq!(profile_names(&component), added);
+        assert_eq!(component.profiles_to_update.as_ref().unwrap().len(), 1);
+        assert!(!screen(&mut component).contains("Remove profile"));
+
+        // Confirming removes it.
+        component.update(Action::Remove).unwrap();
+        assert_eq!(
+            component.update(Action::Confirm).unwrap(),
+            Some(Action::UpdateMode(Mode::ProfilesEdit))
+        );
+        assert!(profile_names(&component).is_empty());
+        assert_eq!(component.list.records_to_remove_mut().count(), 1);
+
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+
+    fn screen(component: &mut Profile) -> String {
+        let mut terminal = Terminal::new(TestBackend::new(80, 20)).unwrap();
+        terminal
+            .draw(|f| component.draw(f, f.size()).unwrap())
+            .unwrap();
+        terminal
+            .backend()
+            .buffer()
+            .content()
+            .iter()
+            .map(|cell| cell.symbol())
+            .collect()
+    }
+
+    fn profile_names(component: &Profile) -> Vec<String> {
+        component
+            .list
+            .records()
+            .map(|profile| profile.to_string())
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_undo_add_and_remove() {
+        let root = std::env::temp_dir().join("lightning-tui-test-undo-add-and-remove");
+        let _ = std::fs::remove_dir_all(&root);
+        let mut component = Profile::new(config_source(&root));
+
+        component.add_profile(profile("first"));
+        component.add_profile(profile("second"));
+        let added = profile_names(&component);
+        assert_eq!(added.len(), 2);
+
+        // Remove the first profile, which is selected.
+        component.update(Action::Remove).unwrap();
+        component.update(Action::Confirm).unwrap();
+        assert_eq!(profile_names(&component), vec![added[1].clone()]);
+
+        // Undoing the remove puts the profile back where it was.
+        component.update(Action::Undo).unwrap();
+        assert_eq!(profile_names(&component), added);
+
+        // Undoing the adds removes the profiles and their pending writes.
+        component.update(Action::Undo).unwrap();
+        assert_eq!(profile_names(&component), vec![added[0].clone()]);
+        assert_eq!(component.profiles_to_update.as_ref().unwrap().len(), 1);
+        component.update(Action::Undo).unwrap();
+        assert!(profile_names(&component).is_empty());
+        assert!(component.profiles_to_update.as_ref().unwrap().is_empty());
+
+        // Nothing left to undo.
+        component.update(Action::Undo).unwrap();
+        assert!(profile_names(&component).is_empty());
+
+        std::fs::remove_dir_all(&root).unwrap();
     }
 }
@@ -13,6 +13,7 @@ pub enum Mode {
     Logger,
     Profiles,
     ProfilesEdit,
+    ConfirmRemove,
     ProfileForm,
     ProfileView,
     ProfileViewEdit,
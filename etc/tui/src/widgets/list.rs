@@ -34,6 +34,10 @@ pub fn get(&self) -> Option<&T> {
             .map(|(_, r)| r)
     }
 
+    pub fn records(&self) -> impl Iterator<Item = &T> {
+        self.records.iter().map(|(_, r)| r)
+    }
+
     pub fn records_to_remove_mut(&mut self) -> impl Iterator<Item = &mut T> {
         self.removing.iter_mut().map(|(_, r)| r)
     }
@@ -63,20 +67,43 @@ pub fn scroll_down(&mut self) {
         }
     }
 
-    pub fn remove_selected_record(&mut self) {
-        if let Some(cur) = self.list_state.selected() {
-            debug_assert!(cur < self.records.len());
-            let removing = self.records.remove(cur);
-            self.removing.push(removing);
-
-            if self.records.is_empty() {
-                self.list_state.select(None);
-            } else if cur == self.records.len() {
-                self.list_state.select(Some(cur - 1));
-            } else {
-                self.list_state.select(Some(cur));
-            }
+    /// Removes the selected record and returns its index.
+    pub fn remove_selected_record(&mut self) -> Option<usize> {
+        let cur = self.list_state.selected()?;
+        debug_assert!(cur < self.records.len());
+        let removing = self.records.remove(cur);
+        self.removing.push(removing);
+
+        if self.records.is_empty() {
+            self.list_state.select(None);
+        } else if cur == self.records.len() {
+            self.list_state.select(Some(cur - 1));
+        } else {
+            self.list_state.select(Some(cur));
+        }
+        Some(cur)
+    }
+
+    /// Reverts the last call to `remove_selected_record`, restoring the record at `index`.
+    pub fn undo_remove_record(&mut self, index: usize) {
+        if let Some(record) = self.removing.pop() {
+            let index = index.min(self.records.len());
+            self.records.insert(index, record);
+            self.list_state.select(Some(index));
+        }
+    }
+
+    /// Reverts the last call to `add_record`.
+    pub fn undo_add_record(&mut self) -> Option<T> {
+        let (_, record) = self.records.pop()?;
+        match self.list_state.selected() {
+            _ if self.records.is_empty() => self.list_state.select(None),
+            Some(cur) if cur >= self.records.len() => {
+                self.list_state.select(Some(self.records.len() - 1))
+            },
+            _ => {},
         }
+        Some(record)
     }
 
     pub fn restore_state(&mut self) {
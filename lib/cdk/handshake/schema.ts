@@ -290,15 +290,18 @@ export namespace Response {
 
   export enum TerminationReason {
     Timeout = 0x80,
-    InvalidHandshake,
-    InvalidToken,
-    InvalidDeliveryAcknowledgment,
-    InvalidService,
-    ServiceTerminated,
-    ConnectionInUse,
-    WrongPermssion,
-    InternalError,
-    Shutdown,
+    InvalidHandshake = 0x81,
+    InvalidToken = 0x82,
+    InvalidDeliveryAcknowledgment = 0x83,
+    InvalidService = 0x84,
+    ServiceTerminated = 0x85,
+    ConnectionInUse = 0x86,
+    WrongPermssion = 0x87,
+    ResourcesUnavailable = 0x88,
+    InternalError = 0x89,
+    Shutdown = 0x8a,
+    AccessTokenExpired = 0x8b,
+    UnknownConnection = 0x8c,
     Unknown = 0xff,
   }
 
@@ -1,6 +1,7 @@
 use std::pin::Pin;
 
 use bytes::BytesMut;
+use futures::Stream;
 use tokio::io::{AsyncRead, AsyncWrite, AsyncWriteExt};
 use tokio::net::{UnixListener, UnixStream};
 use tokio::sync::mpsc;
@@ -121,6 +122,36 @@ pub async fn read_payload(&mut self) -> Option<BytesMut> {
         read_length_delimited(&mut self.stream).await
     }
 
+    /// Read the next chunk of the body of an http request. The handshake streams the body as a
+    /// sequence of payloads that ends with an empty one, so that large bodies do not have to be
+    /// buffered. Returns `None` once the body is complete.
+    ///
+    /// # Cancel safety
+    ///
+    /// This method is not cancel safe, for the same reason as [`Connection::read_payload`].
+    pub async fn read_body_chunk(&mut self) -> std::io::Result<Option<BytesMut>> {
+        debug_assert!(self.is_http_request());
+        match self.read_payload().await {
+            Some(chunk) if chunk.is_empty() => Ok(None),
+            Some(chunk) => Ok(Some(chunk)),
+            None => Err(std::io::ErrorKind::UnexpectedEof.into()),
+        }
+    }
+
+    /// Returns the body of an http request as a stream of chunks. See
+    /// [`Connection::read_body_chunk`].
+    pub fn body_stream(&mut self) -> impl Stream<Item = std::io::Result<BytesMut>> + '_ {
+        futures::stream::unfold(Some(self), |connection| async move {
+            let connection = connection?;
+            match connection.read_body_chunk().await {
+                Ok(Some(chunk)) => Some((Ok(chunk), Some(connection))),
+                Ok(None) => None,
+                // The connection is broken, there is nothing left to read after the error.
+                Err(e) => Some((Err(e), None)),
+            }
+        })
+    }
+
     /// Returns true if this connection is an HTTP request.
     #[inline(always)]
     pub fn is_http_request(&self) -> bool {
@@ -179,3 +210,70 @@ fn poll_read(
         Pin::new(&mut self.get_mut().stream).poll_read(cx, buf)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use futures::StreamExt;
+    use url::Url;
+
+    use super::*;
+    use crate::header::HttpMethod;
+
+    fn http_connection(stream: UnixStream) -> Connection {
+        Connection {
+            stream,
+            header: ConnectionHeader {
+                transport_detail: TransportDetail::HttpRequest {
+                    method: HttpMethod::POST,
+                    url: Url::parse("http://localhost").unwrap(),
+                    header: Default::default(),
+                },
+                pk: None,
+            },
+        }
+    }
+
+    #[tokio::test]
+    async fn test_body_stream() {
+        let (mut handshake, service) = UnixStream::pair().unwrap();
+        let mut connection = http_connection(service);
+
+        tokio::spawn(async move {
+            for i in 0..16u8 {
+                let chunk = vec![i; 64 << 10];
+                handshake.write_u32(chunk.len() as u32).await.unwrap();
+                handshake.write_all(&chunk).await.unwrap();
+            }
+            // The end of the body.
+            handshake.write_u32(0).await.unwrap();
+            // A following payload is not part of the body.
+            handshake.write_u32(1).await.unwrap();
+            handshake.write_all(&[42]).await.unwrap();
+        });
+
+        let chunks: Vec<_> = connection.body_stream().collect().await;
+        assert_eq!(chunks.len(), 16);
+        for (i, chunk) in chunks.into_iter().enumerate() {
+            assert_eq!(chunk.unwrap(), vec![i as u8; 64 << 10]);
+        }
+        assert_eq!(connection.read_payload().await.unwrap(), vec![42]);
+    }
+
+    #[tokio::test]
+    async fn test_body_stream_truncated() {
+        let (mut handshake, service) = UnixStream::pair().unwrap();
+        let mut connection = http_connection(service);
+
+        handshake.write_u32(3).await.unwrap();
+        handshake.write_all(b"abc").await.unwrap();
+        drop(handshake);
+
+        let chunks: Vec<_> = connection.body_stream().collect().await;
+        assert_eq!(chunks.len(), 2);
+        assert_eq!(chunks[0].as_ref().unwrap(), &b"abc"[..]);
+        assert_eq!(
+            chunks[1].as_ref().unwrap_err().kind(),
+            std::io::ErrorKind::UnexpectedEof
+        );
+    }
+}
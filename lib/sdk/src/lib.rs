@@ -4,8 +4,10 @@
 pub mod http_util;
 pub mod ipc;
 pub mod ipc_types;
+pub mod secrets;
 
 pub mod connection;
 pub mod header;
 pub mod io_util;
 mod reqres;
+pub mod trace;
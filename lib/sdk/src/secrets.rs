@@ -0,0 +1,118 @@
+//! Secrets a service gets from the node config, such as api keys. The node writes them to a file
+//! only the service can read, and passes the path of it in the [`SECRETS_PATH_ENV`] environment
+//! variable.
+
+use std::collections::HashMap;
+use std::fmt::{self, Debug};
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::os::unix::fs::OpenOptionsExt;
+use std::path::Path;
+
+use anyhow::Context;
+use serde::{Deserialize, Serialize};
+
+pub const SECRETS_PATH_ENV: &str = "SECRETS_PATH";
+
+/// The secrets of a service, grouped by the scope they belong to. What a scope is depends on the
+/// service, the js service for example scopes secrets by the url of a script.
+///
+/// The values of the secrets never show up in the debug output.
+#[derive(Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Secrets(HashMap<String, HashMap<String, String>>);
+
+impl Secrets {
+    /// Loads the secrets the node passed to the service. A service without secrets gets none.
+    pub fn from_env() -> anyhow::Result<Self> {
+        match std::env::var_os(SECRETS_PATH_ENV) {
+            Some(path) => Self::read_from(Path::new(&path)),
+            None => Ok(Self::default()),
+        }
+    }
+
+    pub fn read_from(path: &Path) -> anyhow::Result<Self> {
+        let bytes = std::fs::read(path).context("Failed to read the secrets file")?;
+        serde_json::from_slice(&bytes).context("Failed to parse the secrets file")
+    }
+
+    /// Writes the secrets to a file that only the current user can read.
+    pub fn write_to(&self, path: &Path) -> anyhow::Result<()> {
+        let mut file = OpenOptions::new()
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .mode(0o600)
+            .open(path)
+            .context("Failed to create the secrets file")?;
+        file.write_all(&serde_json::to_vec(self)?)
+            .context("Failed to write the secrets file")
+    }
+
+    /// Returns the secrets of the given scope.
+    pub fn scope(&self, scope: &str) -> Option<&HashMap<String, String>> {
+        self.0.get(scope)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+impl From<HashMap<String, HashMap<String, String>>> for Secrets {
+    fn from(secrets: HashMap<String, HashMap<String, String>>) -> Self {
+        Self(secrets)
+    }
+}
+
+impl Debug for Secrets {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map()
+            .entries(
+                self.0
+                    .iter()
+                    .map(|(scope, secrets)| (scope, secrets.keys().collect::<Vec<_>>())),
+            )
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::os::unix::fs::PermissionsExt;
+
+    use super::*;
+
+    fn secrets() -> Secrets {
+        HashMap::from([(
+            "blake3://script".to_string(),
+            HashMap::from([("API_KEY".to_string(), "hunter2".to_string())]),
+        )])
+        .into()
+    }
+
+    #[test]
+    fn test_secrets_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("secrets.json");
+        secrets().write_to(&path).unwrap();
+
+        let permissions = std::fs::metadata(&path).unwrap().permissions();
+        assert_eq!(permissions.mode() & 0o777, 0o600);
+
+        let secrets = Secrets::read_from(&path).unwrap();
+        assert_eq!(secrets, self::secrets());
+        assert_eq!(
+            secrets.scope("blake3://script").unwrap().get("API_KEY"),
+            Some(&"hunter2".to_string())
+        );
+        assert!(secrets.scope("blake3://other").is_none());
+    }
+
+    #[test]
+    fn test_secrets_debug_hides_values() {
+        let debug = format!("{:?}", secrets());
+        assert!(debug.contains("API_KEY"));
+        assert!(!debug.contains("hunter2"));
+    }
+}
@@ -0,0 +1,174 @@
+use std::collections::HashMap;
+use std::fmt::{self, Display};
+
+use rand::Rng;
+
+/// The name of the header that carries the trace context of a request, as defined by the
+/// [W3C Trace Context](https://www.w3.org/TR/trace-context/) recommendation.
+pub const TRACEPARENT_HEADER: &str = "traceparent";
+
+/// The trace context of a request, which links the spans on the node executing the request to
+/// the span it is traced with at the edge.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TraceContext {
+    trace_id: [u8; 16],
+    span_id: [u8; 8],
+    flags: u8,
+}
+
+impl TraceContext {
+    /// Starts a new trace.
+    pub fn new_root() -> Self {
+        let mut rng = rand::thread_rng();
+        Self {
+            // An all zero id is invalid, so make sure that at least one bit is set.
+            trace_id: rng.gen_range(1..=u128::MAX).to_be_bytes(),
+            span_id: rng.gen_range(1..=u64::MAX).to_be_bytes(),
+            flags: 1,
+        }
+    }
+
+    /// Returns the context of a new span that is a child of this one. It belongs to the same
+    /// trace.
+    pub fn child(&self) -> Self {
+        Self {
+            trace_id: self.trace_id,
+            span_id: rand::thread_rng().gen_range(1..=u64::MAX).to_be_bytes(),
+            flags: self.flags,
+        }
+    }
+
+    /// Parses the value of a `traceparent` header. Returns `None` if it is malformed.
+    pub fn parse(value: &str) -> Option<Self> {
+        let mut parts = value.trim().split('-');
+        let version = parts.next()?;
+        let trace_id = parts.next()?;
+        let span_id = parts.next()?;
+        let flags = parts.next()?;
+
+        // Later versions may append fields, but the version 00 has exactly four.
+        if version.len() != 2 || version == "ff" || (version == "00" && parts.next().is_some()) {
+            return None;
+        }
+        u8::from_str_radix(version, 16).ok()?;
+
+        let trace_id = decode_id::<16>(trace_id)?;
+        let span_id = decode_id::<8>(span_id)?;
+        if flags.len() != 2 {
+            return None;
+        }
+        let flags = u8::from_str_radix(flags, 16).ok()?;
+
+        Some(Self {
+            trace_id,
+            span_id,
+            flags,
+        })
+    }
+
+    /// Returns the trace context carried by the given http headers, if there is a valid one.
+    pub fn from_headers(headers: &HashMap<String, String>) -> Option<Self> {
+        headers
+            .iter()
+            .find(|(name, _)| name.eq_ignore_ascii_case(TRACEPARENT_HEADER))
+            .and_then(|(_, value)| Self::parse(value))
+    }
+
+    /// Sets the `traceparent` header to this context, replacing the one that is there.
+    pub fn inject(&self, headers: &mut HashMap<String, String>) {
+        headers.retain(|name, _| !name.eq_ignore_ascii_case(TRACEPARENT_HEADER));
+        headers.insert(TRACEPARENT_HEADER.to_string(), self.to_string());
+    }
+
+    /// Returns the id of the trace in hex.
+    pub fn trace_id(&self) -> String {
+        hex(&self.trace_id)
+    }
+
+    /// Returns the id of the span in hex.
+    pub fn span_id(&self) -> String {
+        hex(&self.span_id)
+    }
+
+    /// Returns true if the caller recorded the trace.
+    pub fn is_sampled(&self) -> bool {
+        self.flags & 1 == 1
+    }
+}
+
+impl Display for TraceContext {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "00-{}-{}-{:02x}",
+            self.trace_id(),
+            self.span_id(),
+            self.flags
+        )
+    }
+}
+
+fn decode_id<const N: usize>(value: &str) -> Option<[u8; N]> {
+    if value.len() != N * 2 || !value.bytes().all(|b| b.is_ascii_hexdigit()) {
+        return None;
+    }
+    let mut id = [0; N];
+    for (i, byte) in id.iter_mut().enumerate() {
+        *byte = u8::from_str_radix(&value[i * 2..i * 2 + 2], 16).ok()?;
+    }
+    // An all zero id is invalid.
+    id.iter().any(|b| *b != 0).then_some(id)
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{b:02x}")).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_traceparent() {
+        let value = "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01";
+        let context = TraceContext::parse(value).unwrap();
+        assert_eq!(context.trace_id(), "4bf92f3577b34da6a3ce929d0e0e4736");
+        assert_eq!(context.span_id(), "00f067aa0ba902b7");
+        assert!(context.is_sampled());
+        assert_eq!(context.to_string(), value);
+
+        assert!(
+            TraceContext::parse("00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7").is_none()
+        );
+        assert!(
+            TraceContext::parse("00-00000000000000000000000000000000-00f067aa0ba902b7-01")
+                .is_none()
+        );
+        assert!(
+            TraceContext::parse("00-4bf92f3577b34da6a3ce929d0e0e4736-0000000000000000-01")
+                .is_none()
+        );
+        assert!(
+            TraceContext::parse("ff-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01")
+                .is_none()
+        );
+        assert!(
+            TraceContext::parse("00-4bf92f3577b34da6a3ce929d0e0e473g-00f067aa0ba902b7-01")
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn test_child_keeps_trace_id() {
+        let parent = TraceContext::new_root();
+        let child = parent.child();
+        assert_eq!(child.trace_id(), parent.trace_id());
+        assert_ne!(child.span_id(), parent.span_id());
+
+        let mut headers = HashMap::new();
+        headers.insert("TraceParent".to_string(), parent.to_string());
+        child.inject(&mut headers);
+        assert_eq!(headers.len(), 1);
+        assert_eq!(TraceContext::from_headers(&headers), Some(child));
+    }
+}
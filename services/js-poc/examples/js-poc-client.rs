@@ -34,6 +34,7 @@ async fn main() -> anyhow::Result<()> {
                 uri,
                 path: None,
                 param,
+                deterministic: None,
             })
             .expect("failed to encode request")
             .into(),
@@ -1,11 +1,32 @@
 use std::collections::HashMap;
+use std::pin::pin;
 
+use anyhow::bail;
+use deno_core::futures::StreamExt;
 use deno_core::url::Url;
+use fn_sdk::connection::Connection;
 use fn_sdk::header::HttpMethod;
+use fn_sdk::trace::TraceContext;
 use serde_json::json;
+use tracing::{info_span, Span};
 
 use crate::stream::{Origin, Request};
 
+/// Reads the body of an http request chunk by chunk as the handshake streams it in. Fails as soon
+/// as the body grows beyond `limit` bytes, without buffering the rest of it.
+pub async fn read_body(connection: &mut Connection, limit: usize) -> anyhow::Result<Vec<u8>> {
+    let mut body = Vec::new();
+    let mut stream = pin!(connection.body_stream());
+    while let Some(chunk) = stream.next().await {
+        let chunk = chunk?;
+        if body.len() + chunk.len() > limit {
+            bail!("Request body is larger than {limit} bytes");
+        }
+        body.extend_from_slice(&chunk);
+    }
+    Ok(body)
+}
+
 pub fn extract(
     url: &Url,
     headers: &HashMap<String, String>,
@@ -80,13 +101,138 @@ pub fn extract(
         uri,
         path: Some(path),
         param,
+        deterministic: None,
     })
 }
 
+/// Returns the span the request is executed in. When the request carries a `traceparent` header,
+/// the span is a child of the span it names, so that it is part of the same trace.
+pub fn span(headers: &HashMap<String, String>) -> Span {
+    match TraceContext::from_headers(headers) {
+        Some(parent) => {
+            let trace = parent.child();
+            info_span!(
+                "js_request",
+                trace_id = %trace.trace_id(),
+                span_id = %trace.span_id(),
+                parent_span_id = %parent.span_id(),
+            )
+        },
+        None => info_span!("js_request"),
+    }
+}
+
 #[cfg(test)]
 mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use fn_sdk::header::{ConnectionHeader, TransportDetail};
+    use tokio::io::AsyncWriteExt;
+    use tokio::net::UnixStream;
+    use tracing::field::{Field, Visit};
+    use tracing::span::{Attributes, Id};
+    use tracing::Subscriber;
+    use tracing_subscriber::layer::{Context, SubscriberExt};
+    use tracing_subscriber::Layer;
+
     use super::*;
 
+    /// Records the fields of every span that is created.
+    #[derive(Clone, Default)]
+    struct SpanFields(Arc<Mutex<HashMap<String, String>>>);
+
+    impl Visit for SpanFields {
+        fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+            self.0
+                .lock()
+                .unwrap()
+                .insert(field.name().to_string(), format!("{value:?}"));
+        }
+    }
+
+    impl<S: Subscriber> Layer<S> for SpanFields {
+        fn on_new_span(&self, attrs: &Attributes<'_>, _id: &Id, _ctx: Context<'_, S>) {
+            attrs.record(&mut self.clone());
+        }
+    }
+
+    fn http_connection(stream: UnixStream) -> Connection {
+        Connection {
+            stream,
+            header: ConnectionHeader {
+                transport_detail: TransportDetail::HttpRequest {
+                    method: HttpMethod::POST,
+                    url: Url::parse("http://fleek/blake3/content-hash/").unwrap(),
+                    header: Default::default(),
+                },
+                pk: None,
+            },
+        }
+    }
+
+    /// Streams the body the way the handshake does, in chunks followed by an empty payload.
+    fn stream_body(mut handshake: UnixStream, body: Vec<u8>) {
+        tokio::spawn(async move {
+            for chunk in body.chunks(16 << 10) {
+                handshake.write_u32(chunk.len() as u32).await.unwrap();
+                handshake.write_all(chunk).await.unwrap();
+            }
+            handshake.write_u32(0).await.unwrap();
+        });
+    }
+
+    #[tokio::test]
+    async fn test_read_large_body() {
+        let (handshake, service) = UnixStream::pair().unwrap();
+        let mut connection = http_connection(service);
+
+        let text = "fleek".repeat(1 << 20);
+        stream_body(
+            handshake,
+            serde_json::to_vec(&json!({ "text": text })).unwrap(),
+        );
+
+        let body = read_body(&mut connection, 8 << 20).await.unwrap();
+        let request = extract(
+            &Url::parse("http://fleek/blake3/content-hash/").unwrap(),
+            &HashMap::new(),
+            HttpMethod::POST,
+            body,
+        )
+        .unwrap();
+
+        // The script is handed the complete body.
+        assert_eq!(request.param.unwrap()["body"]["text"], json!(text));
+    }
+
+    #[tokio::test]
+    async fn test_read_body_over_limit() {
+        let (handshake, service) = UnixStream::pair().unwrap();
+        let mut connection = http_connection(service);
+
+        stream_body(handshake, vec![0; 1 << 20]);
+
+        assert!(read_body(&mut connection, 1 << 19).await.is_err());
+    }
+
+    #[test]
+    fn test_span_continues_trace() {
+        let parent = TraceContext::new_root();
+        let mut headers = HashMap::new();
+        parent.inject(&mut headers);
+
+        let fields = SpanFields::default();
+        let subscriber = tracing_subscriber::registry().with(fields.clone());
+        tracing::subscriber::with_default(subscriber, || {
+            let _span = span(&headers);
+        });
+
+        let fields = fields.0.lock().unwrap();
+        assert_eq!(fields.get("trace_id"), Some(&parent.trace_id()));
+        assert_eq!(fields.get("parent_span_id"), Some(&parent.span_id()));
+        assert_ne!(fields.get("span_id"), Some(&parent.span_id()));
+    }
+
     #[tokio::test]
     async fn test_extract_request() {
         // Simple request
@@ -108,6 +254,7 @@ async fn test_extract_request() {
                     "query": null,
                     "body": null,
                 })),
+                deterministic: None,
             })
         );
 
@@ -130,6 +277,7 @@ async fn test_extract_request() {
                     "query": null,
                     "body": "foobar",
                 })),
+                deterministic: None,
             })
         );
 
@@ -152,6 +300,7 @@ async fn test_extract_request() {
                     "query": null,
                     "body": { "foo": "bar" },
                 })),
+                deterministic: None,
             })
         );
 
@@ -174,6 +323,7 @@ async fn test_extract_request() {
                     "query": null,
                     "body": null,
                 })),
+                deterministic: None,
             })
         );
 
@@ -196,6 +346,7 @@ async fn test_extract_request() {
                     "query": null,
                     "body": null,
                 })),
+                deterministic: None,
             })
         );
 
@@ -218,6 +369,7 @@ async fn test_extract_request() {
                     "query": { "a": "4" },
                     "body": null,
                 })),
+                deterministic: None,
             })
         );
     }
@@ -1,6 +1,10 @@
+use std::io::Write;
+
 use anyhow::{anyhow, Context, Result};
 use fn_sdk::header::HttpResponse;
 
+use crate::params::{MAX_RESPONSE_BODY_SIZE, MAX_RESPONSE_HEADERS, MAX_RESPONSE_HEADER_SIZE};
+
 enum HeaderFormat {
     Undetermined,
     SingleValue,
@@ -9,7 +13,11 @@ enum HeaderFormat {
 }
 
 pub fn parse(value: &serde_json::Value) -> Result<HttpResponse> {
-    let body = value_to_string(value.get("body").context("Body is missing")?);
+    let body = value_to_string(
+        value.get("body").context("Body is missing")?,
+        MAX_RESPONSE_BODY_SIZE,
+    )
+    .context("Invalid body")?;
     let status = if let Some(status) = value.get("status") {
         Some(parse_status(status)?)
     } else {
@@ -36,14 +44,16 @@ fn parse_headers(headers: &serde_json::Value) -> Result<Vec<(String, Vec<String>
 
     if let Some(headers_j) = headers.as_array() {
         // the headers are wrapped into an array
-        let mut headers = Vec::new();
+        check_header_count(headers_j.len())?;
+        let mut headers = Headers::default();
         for header_j in headers_j {
             let (name, values) = parse_header(header_j)?;
-            headers.push((name, values));
+            headers.push(name, values)?;
         }
-        return Ok(headers);
+        return Ok(headers.headers);
     } else if let Some(headers_j) = headers.as_object() {
-        let mut headers = Vec::new();
+        check_header_count(headers_j.len())?;
+        let mut headers = Headers::default();
 
         let mut header_fmt = HeaderFormat::Undetermined;
         for (key, value) in headers_j {
@@ -60,7 +70,7 @@ fn parse_headers(headers: &serde_json::Value) -> Result<Vec<(String, Vec<String>
                     },
                 }
 
-                headers.push((
+                headers.push(
                     key.to_owned(),
                     vec![
                         value
@@ -68,12 +78,13 @@ fn parse_headers(headers: &serde_json::Value) -> Result<Vec<(String, Vec<String>
                             .context("Failed to convert value to string")?
                             .to_string(),
                     ],
-                ));
+                )?;
             } else if let Some(array) = value.as_array() {
                 // At this point the array either consists of header values corresponding to
                 // the header name stored in `key` or key value objects, where key and value
                 // are header name and header value, respectively.
 
+                check_header_count(array.len())?;
                 let mut header_values = Vec::new();
                 for elem in array {
                     if elem.is_string() {
@@ -113,13 +124,13 @@ fn parse_headers(headers: &serde_json::Value) -> Result<Vec<(String, Vec<String>
                         header_values.append(values);
                     }
                 }
-                headers.push((key.to_owned(), header_values));
+                headers.push(key.to_owned(), header_values)?;
             } else {
                 let (name, values) = parse_header(value)?;
-                headers.push((name, values));
+                headers.push(name, values)?;
             }
         }
-        return Ok(headers);
+        return Ok(headers.headers);
     }
 
     Err(anyhow!("Unsupported header format"))
@@ -132,7 +143,7 @@ fn parse_header(value: &serde_json::Value) -> Result<(String, Vec<String>)> {
                 .as_str()
                 .context("Header key must be a string")?
                 .to_string(),
-            vec![value_to_string(&value["value"])],
+            vec![value_to_string(&value["value"], MAX_RESPONSE_HEADER_SIZE)?],
         ))
     } else if let Some(arr) = value.as_array() {
         match arr.as_slice() {
@@ -141,9 +152,13 @@ fn parse_header(value: &serde_json::Value) -> Result<(String, Vec<String>)> {
             [
                 serde_json::Value::String(key),
                 serde_json::Value::Array(arr),
-            ] => Ok((key.clone(), arr.iter().map(value_to_string).collect())),
+            ] => {
+                check_header_count(arr.len())?;
+                Ok((key.clone(), header_values(arr)?))
+            },
             [serde_json::Value::String(key), _, ..] => {
-                Ok((key.clone(), arr[1..].iter().map(value_to_string).collect()))
+                check_header_count(arr.len() - 1)?;
+                Ok((key.clone(), header_values(&arr[1..])?))
             },
             [_, ..] => Err(anyhow!("Header key must be a string")),
             [] => Err(anyhow!("Empty header key value array pair")),
@@ -168,18 +183,112 @@ fn parse_status(status: &serde_json::Value) -> Result<u16> {
     }
 }
 
-/// Turn any value into a string, ignoring quotes if the value is a string already
-fn value_to_string(value: &serde_json::Value) -> String {
+/// The response override returned by a script exceeds one of the limits. Unlike the other parse
+/// errors this does not mean that the value is not an override, so it must not be sent back as
+/// plain json either.
+#[derive(Debug)]
+pub struct LimitExceeded(String);
+
+impl std::fmt::Display for LimitExceeded {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl std::error::Error for LimitExceeded {}
+
+/// The parsed headers, which are checked against the limits as they are added.
+#[derive(Default)]
+struct Headers {
+    headers: Vec<(String, Vec<String>)>,
+    count: usize,
+}
+
+impl Headers {
+    fn push(&mut self, name: String, values: Vec<String>) -> Result<()> {
+        check_header_size(&name)?;
+        for value in &values {
+            check_header_size(value)?;
+        }
+        // A header without any value still takes a line.
+        self.count += values.len().max(1);
+        check_header_count(self.count)?;
+        self.headers.push((name, values));
+        Ok(())
+    }
+}
+
+fn check_header_count(count: usize) -> Result<()> {
+    if count > MAX_RESPONSE_HEADERS {
+        return Err(LimitExceeded(format!(
+            "Too many headers, at most {MAX_RESPONSE_HEADERS} are allowed"
+        ))
+        .into());
+    }
+    Ok(())
+}
+
+fn check_header_size(value: &str) -> Result<()> {
+    if value.len() > MAX_RESPONSE_HEADER_SIZE {
+        return Err(LimitExceeded(format!(
+            "Header is larger than {MAX_RESPONSE_HEADER_SIZE} bytes"
+        ))
+        .into());
+    }
+    Ok(())
+}
+
+fn header_values(values: &[serde_json::Value]) -> Result<Vec<String>> {
+    values
+        .iter()
+        .map(|value| value_to_string(value, MAX_RESPONSE_HEADER_SIZE))
+        .collect()
+}
+
+/// Turn any value into a string, ignoring quotes if the value is a string already. Fails if the
+/// string would be longer than `limit` bytes, without building it.
+fn value_to_string(value: &serde_json::Value, limit: usize) -> Result<String> {
     if let Some(s) = value.as_str() {
-        s.into()
-    } else {
-        value.to_string()
+        if s.len() > limit {
+            return Err(LimitExceeded(format!("Value is larger than {limit} bytes")).into());
+        }
+        return Ok(s.into());
+    }
+
+    let mut writer = LimitedWriter {
+        buffer: Vec::new(),
+        limit,
+    };
+    if serde_json::to_writer(&mut writer, value).is_err() {
+        return Err(LimitExceeded(format!("Value is larger than {limit} bytes")).into());
+    }
+    Ok(String::from_utf8(writer.buffer)?)
+}
+
+/// A writer that fails once more than `limit` bytes are written to it.
+struct LimitedWriter {
+    buffer: Vec<u8>,
+    limit: usize,
+}
+
+impl Write for LimitedWriter {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        if self.buffer.len() + buf.len() > self.limit {
+            return Err(std::io::ErrorKind::OutOfMemory.into());
+        }
+        self.buffer.extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
     }
 }
 
 #[cfg(test)]
 mod tests {
     use fn_sdk::header::HttpResponse;
+    use serde_json::json;
 
     use super::*;
 
@@ -623,4 +732,54 @@ fn test_key_val_object_no_headers_no_status() {
 
         assert_eq!(http_res, target);
     }
+
+    #[test]
+    fn test_too_many_headers() {
+        let headers: serde_json::Map<_, _> = (0..MAX_RESPONSE_HEADERS + 1)
+            .map(|i| (format!("x-header-{i}"), json!("value")))
+            .collect();
+        let value = json!({ "headers": headers, "body": "hello" });
+        let err = parse(&value).unwrap_err();
+        assert!(err.is::<LimitExceeded>());
+        assert!(err.to_string().contains("Too many headers"), "{err}");
+
+        // The values of a single header count towards the limit as well.
+        let values = vec![json!("value"); MAX_RESPONSE_HEADERS + 1];
+        let value = json!({ "headers": { "x-header": values }, "body": "hello" });
+        assert!(parse(&value).is_err());
+
+        // Right at the limit is fine.
+        let headers: serde_json::Map<_, _> = (0..MAX_RESPONSE_HEADERS)
+            .map(|i| (format!("x-header-{i}"), json!("value")))
+            .collect();
+        let value = json!({ "headers": headers, "body": "hello" });
+        assert_eq!(
+            parse(&value).unwrap().headers.unwrap().len(),
+            MAX_RESPONSE_HEADERS
+        );
+    }
+
+    #[test]
+    fn test_header_too_large() {
+        let large = "a".repeat(MAX_RESPONSE_HEADER_SIZE + 1);
+        let value = json!({ "headers": { "x-header": large }, "body": "hello" });
+        assert!(parse(&value).is_err());
+
+        let value = json!({ "headers": [{ "key": large, "value": "value" }], "body": "hello" });
+        assert!(parse(&value).is_err());
+
+        // Values that are not strings are limited by the size of their json encoding.
+        let large = vec![0; MAX_RESPONSE_HEADER_SIZE];
+        let value = json!({ "headers": [{ "key": "x-header", "value": large }], "body": "hello" });
+        assert!(parse(&value).is_err());
+    }
+
+    #[test]
+    fn test_body_too_large() {
+        let value = json!({ "body": "a".repeat(MAX_RESPONSE_BODY_SIZE + 1) });
+        assert!(parse(&value).unwrap_err().is::<LimitExceeded>());
+
+        let value = json!({ "body": { "data": "a".repeat(MAX_RESPONSE_BODY_SIZE) } });
+        assert!(parse(&value).is_err());
+    }
 }
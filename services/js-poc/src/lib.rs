@@ -1,11 +1,14 @@
+use std::sync::Arc;
+
 use anyhow::{bail, Context};
 use deno_core::v8::{Global, IsolateHandle, Value};
 use deno_core::{serde_v8, v8, JsRuntime, ModuleSpecifier};
 use fn_sdk::connection::Connection;
 use fn_sdk::header::TransportDetail;
 use fn_sdk::http_util::{respond, respond_with_error, respond_with_http_response};
+use fn_sdk::secrets::Secrets;
 use tokio::sync::mpsc::UnboundedSender;
-use tracing::{debug, error, info};
+use tracing::{debug, error, info, Instrument};
 
 use crate::runtime::Runtime;
 use crate::stream::{Origin, Request};
@@ -20,6 +23,15 @@ pub(crate) mod params {
     pub const HEAP_INIT: usize = 1 << 10;
     pub const HEAP_LIMIT: usize = 50 << 20;
     pub const REQ_TIMEOUT: Duration = Duration::from_secs(15);
+    /// The request body ends up on the heap of the script, so it has to stay well below the
+    /// heap limit.
+    pub const MAX_BODY_SIZE: usize = HEAP_LIMIT / 2;
+    /// Limits on the http response override returned by a script.
+    pub const MAX_RESPONSE_HEADERS: usize = 100;
+    pub const MAX_RESPONSE_HEADER_SIZE: usize = 8 << 10;
+    pub const MAX_RESPONSE_BODY_SIZE: usize = HEAP_LIMIT / 2;
+    /// Total size of the module sources kept in memory across requests.
+    pub const MODULE_CACHE_SIZE: usize = 64 << 20;
     pub const FETCH_BLACKLIST: &[&str] = &["localhost", "127.0.0.1", "::1"];
 }
 
@@ -31,6 +43,14 @@ pub async fn main() {
 
     let mut listener = fn_sdk::ipc::conn_bind().await;
 
+    let secrets = match Secrets::from_env() {
+        Ok(secrets) => Arc::new(secrets),
+        Err(e) => {
+            error!("Failed to load secrets: {e:?}");
+            Default::default()
+        },
+    };
+
     // Explicitly initialize the v8 platform on the main thread
     JsRuntime::init_platform(None);
 
@@ -50,6 +70,7 @@ pub async fn main() {
 
     while let Ok(conn) = listener.accept().await {
         let tx = tx.clone();
+        let secrets = secrets.clone();
 
         // spawn a new thread and tokio runtime to handle the connection
         // TODO: This is very hacky and not very scalable
@@ -60,7 +81,7 @@ pub async fn main() {
                 .enable_all()
                 .build()
                 .expect("failed to create connection async runtime")
-                .block_on(handle_connection(tx, conn))
+                .block_on(handle_connection(tx, secrets, conn))
             {
                 error!("session failed: {e:?}");
             }
@@ -70,13 +91,17 @@ pub async fn main() {
 
 async fn handle_connection(
     tx: UnboundedSender<IsolateHandle>,
+    secrets: Arc<Secrets>,
     mut connection: Connection,
 ) -> anyhow::Result<()> {
     if connection.is_http_request() {
-        let body = connection
-            .read_payload()
-            .await
-            .context("Could not read body.")?;
+        let body = match http::request::read_body(&mut connection, params::MAX_BODY_SIZE).await {
+            Ok(body) => body,
+            Err(e) => {
+                respond_with_error(&mut connection, format!("{e:?}").as_bytes(), 400).await?;
+                return Err(e.context("Could not read body."));
+            },
+        };
 
         let TransportDetail::HttpRequest {
             method,
@@ -87,17 +112,21 @@ async fn handle_connection(
             unreachable!()
         };
 
-        let request = http::request::extract(url, header, method, body.to_vec())
-            .context("failed to parse request")?;
+        let request =
+            http::request::extract(url, header, method, body).context("failed to parse request")?;
 
-        if let Err(e) = handle_request(&mut connection, &tx, request).await {
+        let span = http::request::span(header);
+        if let Err(e) = handle_request(&mut connection, &tx, &secrets, request)
+            .instrument(span)
+            .await
+        {
             respond_with_error(&mut connection, format!("{e:?}").as_bytes(), 400).await?;
             return Err(e);
         }
     } else {
         while let Some(payload) = connection.read_payload().await {
             let request: Request = serde_json::from_slice(&payload)?;
-            if let Err(e) = handle_request(&mut connection, &tx, request).await {
+            if let Err(e) = handle_request(&mut connection, &tx, &secrets, request).await {
                 respond_with_error(&mut connection, e.to_string().as_bytes(), 400).await?;
                 return Err(e);
             };
@@ -110,6 +139,7 @@ async fn handle_connection(
 async fn handle_request(
     connection: &mut Connection,
     tx: &UnboundedSender<IsolateHandle>,
+    secrets: &Secrets,
     request: Request,
 ) -> anyhow::Result<()> {
     let Request {
@@ -117,6 +147,7 @@ async fn handle_request(
         uri,
         path,
         param,
+        deterministic,
     } = request;
     if uri.is_empty() {
         bail!("Empty origin uri");
@@ -136,8 +167,15 @@ async fn handle_request(
         location = location.join(&path).context("Invalid path string")?;
     }
 
+    // Scripts only get the secrets configured for their own url
+    let secrets = secrets
+        .scope(module_url.as_str())
+        .cloned()
+        .unwrap_or_default();
+
     // Create runtime and execute the source
-    let mut runtime = Runtime::new(location.clone()).context("Failed to initialize runtime")?;
+    let mut runtime = Runtime::new(location.clone(), deterministic, secrets)
+        .context("Failed to initialize runtime")?;
     tx.send(runtime.deno.v8_isolate().thread_safe_handle())
         .context("Failed to send the IsolateHandle to main thread.")?;
 
@@ -193,9 +231,13 @@ async fn parse_and_respond(
 
         // Attempt to parse and use the value as an http response override object
         if connection.is_http_request() {
-            if let Ok(http_response) = http::response::parse(&value) {
-                respond_with_http_response(connection, http_response).await?;
-                return Ok(());
+            match http::response::parse(&value) {
+                Ok(http_response) => {
+                    respond_with_http_response(connection, http_response).await?;
+                    return Ok(());
+                },
+                Err(e) if e.is::<http::response::LimitExceeded>() => return Err(e),
+                Err(_) => {},
             }
         }
 
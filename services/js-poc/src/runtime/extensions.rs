@@ -1,14 +1,17 @@
 //! Javascript runtime bindings for the SDK APIs
 
+use std::cell::RefCell;
+use std::rc::Rc;
+
 use anyhow::{anyhow, Result};
 use arrayref::array_ref;
 use blake3_tree::utils::{tree_index, HashVec};
-use deno_core::{extension, op2};
+use deno_core::{extension, op2, OpState};
 use fleek_crypto::ClientPublicKey;
 use fn_sdk::blockstore::get_internal_path;
 use tracing::info;
 
-use crate::runtime::Permissions;
+use crate::runtime::{Permissions, ScriptSecrets};
 
 extension!(
     fleek,
@@ -29,11 +32,16 @@
         load_content,
         read_block,
         query_client_flk_balance,
-        query_client_bandwidth_balance
+        query_client_bandwidth_balance,
+        get_secret
     ],
-    state = |state| {
+    options = { deterministic: bool },
+    state = |state, options| {
         // initialize permissions
-        state.put(Permissions {})
+        state.put(Permissions {
+            allow_net: !options.deterministic,
+            allow_state_queries: !options.deterministic,
+        })
     }
 );
 
@@ -79,9 +87,22 @@ pub async fn read_block(
     Ok(block)
 }
 
+fn check_state_queries(state: &Rc<RefCell<OpState>>) -> Result<()> {
+    if !state.borrow().borrow::<Permissions>().allow_state_queries {
+        return Err(anyhow!(
+            "balance queries are disabled in deterministic executions"
+        ));
+    }
+    Ok(())
+}
+
 #[op2(async)]
 #[string]
-pub async fn query_client_flk_balance(#[buffer(copy)] address: Vec<u8>) -> Result<String> {
+pub async fn query_client_flk_balance(
+    state: Rc<RefCell<OpState>>,
+    #[buffer(copy)] address: Vec<u8>,
+) -> Result<String> {
+    check_state_queries(&state)?;
     if address.len() != 96 {
         return Err(anyhow!("address must be 32 bytes"));
     }
@@ -95,7 +116,11 @@ pub async fn query_client_flk_balance(#[buffer(copy)] address: Vec<u8>) -> Resul
 
 #[op2(async)]
 #[string]
-pub async fn query_client_bandwidth_balance(#[buffer(copy)] address: Vec<u8>) -> Result<String> {
+pub async fn query_client_bandwidth_balance(
+    state: Rc<RefCell<OpState>>,
+    #[buffer(copy)] address: Vec<u8>,
+) -> Result<String> {
+    check_state_queries(&state)?;
     if address.len() != 96 {
         return Err(anyhow!("address must be 32 bytes"));
     }
@@ -106,3 +131,10 @@ pub async fn query_client_bandwidth_balance(#[buffer(copy)] address: Vec<u8>) ->
             .to_string(),
     )
 }
+
+/// Returns the secret with the given name, if the script has one.
+#[op2]
+#[string]
+pub fn get_secret(state: &mut OpState, #[string] name: String) -> Option<String> {
+    state.borrow::<ScriptSecrets>().0.get(&name).cloned()
+}
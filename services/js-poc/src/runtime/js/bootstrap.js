@@ -1,17 +1,97 @@
 import * as loc from "ext:deno_web/12_location.js";
 import { globalContext } from "ext:fleek/global.js";
 
+/** Returns a splitmix64 generator of numbers in [0, 1) for the given seed.
+ *  @param {BigInt} seed - 64 bit seed
+ *  @returns {() => number}
+ */
+const seededRandom = (seed) => {
+  let state = BigInt.asUintN(64, seed);
+  return () => {
+    state = BigInt.asUintN(64, state + 0x9e3779b97f4a7c15n);
+    let z = state;
+    z = BigInt.asUintN(64, (z ^ (z >> 30n)) * 0xbf58476d1ce4e5b9n);
+    z = BigInt.asUintN(64, (z ^ (z >> 27n)) * 0x94d049bb133111ebn);
+    z = z ^ (z >> 31n);
+    return Number(z >> 11n) / 2 ** 53;
+  };
+};
+
 /** Bootstrap function called at runtime before execution.
  *  Can only be called once.
  *  @param {number} time - Timestamp to hardcode to Date.now()
+ *  @param {string} url - Location of the runtime
+ *  @param {BigInt | undefined} seed - Seed for all randomness, for deterministic executions
  */
-globalThis.bootstrap = (time, url) => {
+globalThis.bootstrap = (time, url, seed) => {
   // Define webapis in the global scope
   Object.defineProperties(globalThis, globalContext);
 
   // Hardcode timestamp
   globalThis.Date.now = () => time;
 
+  // Derive all randomness from the seed
+  if (seed !== undefined) {
+    const random = seededRandom(seed);
+    const getRandomValues = (array) => {
+      const bytes = new Uint8Array(
+        array.buffer,
+        array.byteOffset,
+        array.byteLength,
+      );
+      for (let i = 0; i < bytes.length; i++) {
+        bytes[i] = Math.floor(random() * 256);
+      }
+      return array;
+    };
+    const randomUUID = () => {
+      const bytes = getRandomValues(new Uint8Array(16));
+      // Version 4, variant 1
+      bytes[6] = (bytes[6] & 0x0f) | 0x40;
+      bytes[8] = (bytes[8] & 0x3f) | 0x80;
+      const hex = Array.from(bytes, (b) => b.toString(16).padStart(2, "0"));
+      return [
+        hex.slice(0, 4),
+        hex.slice(4, 6),
+        hex.slice(6, 8),
+        hex.slice(8, 10),
+        hex.slice(10, 16),
+      ].map((part) => part.join("")).join("-");
+    };
+
+    globalThis.Math.random = random;
+    Object.defineProperties(globalThis.crypto, {
+      getRandomValues: { value: getRandomValues },
+      randomUUID: { value: randomUUID },
+    });
+
+    // Freeze every other clock to the timestamp
+    const OriginalDate = globalThis.Date;
+    const frozen = Number(time);
+    function Date(...args) {
+      // Called as a function, Date ignores its arguments and returns the current time
+      if (new.target === undefined) {
+        return new OriginalDate(frozen).toString();
+      }
+      return Reflect.construct(
+        OriginalDate,
+        args.length === 0 ? [frozen] : args,
+        new.target,
+      );
+    }
+    Object.setPrototypeOf(Date, OriginalDate);
+    Object.defineProperties(Date, {
+      prototype: { value: OriginalDate.prototype },
+      now: { value: OriginalDate.now, writable: true, configurable: true },
+    });
+    globalThis.Date = Date;
+
+    Object.defineProperties(globalThis.performance, {
+      now: { value: () => 0 },
+      timeOrigin: { value: frozen },
+    });
+  }
+
   // Set runtime location
   loc.setLocationHref(url);
 
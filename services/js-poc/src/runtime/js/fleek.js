@@ -34,6 +34,13 @@ const queryClientBandwidthBalance = async (account) => {
   return BigInt(balance, 10);
 };
 
+/** Get a secret of the script from the node config.
+ * Scripts can only read the secrets configured for their own url.
+ * @param {string} name - Name of the secret
+ * @returns {string | undefined} The secret, if there is one with the name
+ */
+const getSecret = (name) => ops.get_secret(name) ?? undefined;
+
 /** Handle to blockstore content.
  * Utility for traversing the proof and reading blocks from the blockstore.
  * @property {Uint8Array} proof - Blake3 proof of the content
@@ -73,4 +80,5 @@ export const Fleek = {
   loadContent,
   queryClientFlkBalance,
   queryClientBandwidthBalance,
+  getSecret,
 };
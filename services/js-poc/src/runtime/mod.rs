@@ -1,3 +1,4 @@
+use std::collections::HashMap;
 use std::rc::Rc;
 use std::sync::Arc;
 use std::time::{SystemTime, UNIX_EPOCH};
@@ -21,6 +22,7 @@
 use self::module_loader::FleekModuleLoader;
 use self::tape::{Punch, Tape};
 use crate::params::{FETCH_BLACKLIST, HEAP_INIT, HEAP_LIMIT};
+use crate::stream::Deterministic;
 
 pub mod extensions;
 pub mod module_loader;
@@ -34,7 +36,16 @@ pub struct Runtime {
     tape: Tape,
 }
 
-struct Permissions {}
+/// The secrets of the script a runtime executes.
+pub struct ScriptSecrets(pub HashMap<String, String>);
+
+struct Permissions {
+    /// Whether scripts can access the network, which deterministic executions can not.
+    allow_net: bool,
+    /// Whether scripts can query balances from the application state. The state differs between
+    /// the nodes executing the same request, so deterministic executions can not.
+    allow_state_queries: bool,
+}
 impl TimersPermission for Permissions {
     fn allow_hrtime(&mut self) -> bool {
         false
@@ -46,6 +57,9 @@ fn check_net_url(
         url: &Url,
         _api_name: &str,
     ) -> std::prelude::v1::Result<(), deno_core::error::AnyError> {
+        if !self.allow_net {
+            return Err(anyhow!("network access is disabled"));
+        }
         if let Some(host) = url.host_str() {
             if FETCH_BLACKLIST.contains(&host) {
                 return Err(anyhow!("{host} is blacklisted"));
@@ -68,7 +82,9 @@ fn check_net<T: AsRef<str>>(
         host: &(T, Option<u16>),
         _api_name: &str,
     ) -> std::prelude::v1::Result<(), deno_core::error::AnyError> {
-        if FETCH_BLACKLIST.contains(&host.0.as_ref()) {
+        if !self.allow_net {
+            Err(anyhow!("network access is disabled"))
+        } else if FETCH_BLACKLIST.contains(&host.0.as_ref()) {
             Err(anyhow!("{} is blacklisted", host.0.as_ref()))
         } else {
             Ok(())
@@ -93,8 +109,14 @@ fn check_write(
 }
 
 impl Runtime {
-    /// Create a new runtime
-    pub fn new(mut location: Url) -> Result<Self> {
+    /// Create a new runtime. A deterministic runtime gets seeded randomness, the clock frozen to
+    /// the given time, and no network access or balance queries. The secrets are the only ones the
+    /// script can read.
+    pub fn new(
+        mut location: Url,
+        deterministic: Option<Deterministic>,
+        secrets: HashMap<String, String>,
+    ) -> Result<Self> {
         let tape = Tape::new(location.clone());
         let mut deno = JsRuntime::new(RuntimeOptions {
             extensions: vec![
@@ -109,7 +131,7 @@ pub fn new(mut location: Url) -> Result<Self> {
                 deno_webgpu::init_ops(),
                 deno_canvas::init_ops(),
                 // Fleek runtime
-                fleek::init_ops(),
+                fleek::init_ops(deterministic.is_some()),
             ],
             startup_snapshot: Some(SNAPSHOT),
             op_metrics_factory_fn: Some(tape.op_metrics_factory_fn()),
@@ -118,6 +140,8 @@ pub fn new(mut location: Url) -> Result<Self> {
             ..Default::default()
         });
 
+        deno.op_state().borrow_mut().put(ScriptSecrets(secrets));
+
         {
             // Get global scope
             let context = deno.main_context();
@@ -134,15 +158,22 @@ pub fn new(mut location: Url) -> Result<Self> {
             let bootstrap_fn = v8::Local::<v8::Function>::try_from(bootstrap_fn).unwrap();
 
             // Construct parameters
-            let time = SystemTime::now().duration_since(UNIX_EPOCH)?.as_millis();
-            // TODO: parse directly from u128
-            let time: v8::Local<v8::Value> = v8::BigInt::new_from_u64(scope, time as u64).into();
+            let time = match deterministic {
+                Some(deterministic) => deterministic.time,
+                // TODO: parse directly from u128
+                None => SystemTime::now().duration_since(UNIX_EPOCH)?.as_millis() as u64,
+            };
+            let time: v8::Local<v8::Value> = v8::BigInt::new_from_u64(scope, time).into();
             let url = location.to_v8(scope).unwrap();
             let undefined = v8::undefined(scope);
+            let seed: v8::Local<v8::Value> = match deterministic {
+                Some(deterministic) => v8::BigInt::new_from_u64(scope, deterministic.seed).into(),
+                None => undefined.into(),
+            };
 
             // Bootstrap.
             bootstrap_fn
-                .call(scope, undefined.into(), &[time, url])
+                .call(scope, undefined.into(), &[time, url, seed])
                 .expect("Failed to execute bootstrap");
         }
 
@@ -202,3 +233,132 @@ pub fn end(self) -> Vec<Punch> {
         self.tape.end()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use deno_core::FastString;
+    use serde::de::DeserializeOwned;
+
+    use super::*;
+
+    // `Date.now` returns a bigint, which json can not encode
+    const SCRIPT: &str = r#"JSON.stringify({
+        random: [Math.random(), Math.random()],
+        bytes: Array.from(crypto.getRandomValues(new Uint8Array(16))),
+        uuid: crypto.randomUUID(),
+        now: String(Date.now()),
+        date: new Date().toISOString(),
+        dateString: Date(),
+        parsed: new Date(0).getTime(),
+        instance: new Date() instanceof Date,
+        performance: [performance.now(), performance.timeOrigin],
+    })"#;
+
+    fn eval<T: DeserializeOwned>(runtime: &mut Runtime, script: &'static str) -> T {
+        let res = runtime
+            .deno
+            .execute_script("<test>", FastString::from_static(script))
+            .unwrap();
+        let scope = &mut runtime.deno.handle_scope();
+        let local = v8::Local::new(scope, res);
+        serde_v8::from_v8::<T>(scope, local).unwrap()
+    }
+
+    fn run(deterministic: Deterministic) -> String {
+        let location = "blake3://test".parse().unwrap();
+        let mut runtime = Runtime::new(location, Some(deterministic), HashMap::new()).unwrap();
+        eval(&mut runtime, SCRIPT)
+    }
+
+    #[tokio::test]
+    async fn test_deterministic_runs_are_identical() {
+        let deterministic = Deterministic {
+            seed: 42,
+            time: 1_700_000_000_000,
+        };
+        let output = run(deterministic);
+        assert_eq!(output, run(deterministic));
+        assert!(output.contains(r#""now":"1700000000000""#));
+        assert!(output.contains(r#""date":"2023-11-14T22:13:20.000Z""#));
+        assert!(output.contains(r#""parsed":0"#));
+        assert!(output.contains(r#""instance":true"#));
+        assert!(output.contains(r#""performance":[0,1700000000000]"#));
+
+        assert_ne!(
+            output,
+            run(Deterministic {
+                seed: 43,
+                ..deterministic
+            })
+        );
+    }
+
+    #[test]
+    fn test_deterministic_disables_network() {
+        let url = "https://fleek.xyz".parse().unwrap();
+
+        let mut permissions = Permissions {
+            allow_net: false,
+            allow_state_queries: false,
+        };
+        assert!(permissions.check_net_url(&url, "fetch").is_err());
+        assert!(
+            permissions
+                .check_net(&("fleek.xyz", Some(443)), "connect")
+                .is_err()
+        );
+
+        let mut permissions = Permissions {
+            allow_net: true,
+            allow_state_queries: true,
+        };
+        assert!(permissions.check_net_url(&url, "fetch").is_ok());
+        assert!(
+            permissions
+                .check_net(&("fleek.xyz", Some(443)), "connect")
+                .is_ok()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_deterministic_disables_balance_queries() {
+        let location = "blake3://test".parse().unwrap();
+        let deterministic = Deterministic { seed: 42, time: 0 };
+        let mut runtime = Runtime::new(location, Some(deterministic), HashMap::new()).unwrap();
+
+        for script in [
+            "Fleek.queryClientFlkBalance(new Uint8Array(96))",
+            "Fleek.queryClientBandwidthBalance(new Uint8Array(96))",
+        ] {
+            let res = runtime
+                .deno
+                .execute_script("<test>", FastString::from_static(script))
+                .unwrap();
+            #[allow(deprecated)]
+            let err = runtime.deno.resolve_value(res).await.unwrap_err();
+            assert!(err.to_string().contains("balance queries are disabled"));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_script_reads_only_its_own_secrets() {
+        let secrets = fn_sdk::secrets::Secrets::from(HashMap::from([(
+            "blake3://script".to_string(),
+            HashMap::from([("API_KEY".to_string(), "hunter2".to_string())]),
+        )]));
+        let runtime = |url: &str| {
+            let secrets = secrets.scope(url).cloned().unwrap_or_default();
+            Runtime::new(url.parse().unwrap(), None, secrets).unwrap()
+        };
+        const GET_SECRET: &str = r#"Fleek.getSecret("API_KEY") ?? null"#;
+
+        let mut own = runtime("blake3://script");
+        assert_eq!(
+            eval::<Option<String>>(&mut own, GET_SECRET),
+            Some("hunter2".to_string())
+        );
+
+        let mut other = runtime("blake3://other");
+        assert_eq!(eval::<Option<String>>(&mut other, GET_SECRET), None);
+    }
+}
@@ -1,5 +1,6 @@
 use std::collections::HashMap;
-use std::sync::OnceLock;
+use std::future::Future;
+use std::sync::{Arc, Mutex, OnceLock};
 
 use anyhow::{anyhow, bail, Context};
 use arrayref::array_ref;
@@ -18,10 +19,14 @@
 };
 use fn_sdk::api::fetch_from_origin;
 use fn_sdk::blockstore::ContentHandle;
+use lru::LruCache;
 use tokio::sync::Semaphore;
 use tracing::{debug, trace, warn};
 
+use crate::params::MODULE_CACHE_SIZE;
+
 static IMPORTS: OnceLock<HashMap<ModuleSpecifier, ModuleSpecifier>> = OnceLock::new();
+static MODULE_CACHE: OnceLock<Mutex<ModuleCache>> = OnceLock::new();
 
 // Initialize the module loader
 pub fn get_or_init_imports<'a>() -> &'a HashMap<ModuleSpecifier, ModuleSpecifier> {
@@ -58,6 +63,119 @@ pub fn get_or_init_imports<'a>() -> &'a HashMap<ModuleSpecifier, ModuleSpecifier
     })
 }
 
+fn module_cache() -> &'static Mutex<ModuleCache> {
+    MODULE_CACHE.get_or_init(|| Mutex::new(ModuleCache::new(MODULE_CACHE_SIZE)))
+}
+
+/// Cache of module sources keyed by their blake3 hash, holding at most `capacity` bytes.
+///
+/// Every runtime has its own isolate, so compiled modules can not be shared between requests,
+/// but a cached module skips the fetch and the read from the blockstore.
+pub struct ModuleCache {
+    capacity: usize,
+    size: usize,
+    sources: LruCache<[u8; 32], Arc<[u8]>>,
+    /// Hashes of the modules loaded from ipfs or http by their specifier. A cid and a url with
+    /// an integrity fragment always resolve to the same content.
+    hashes: HashMap<ModuleSpecifier, [u8; 32]>,
+}
+
+impl ModuleCache {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            size: 0,
+            sources: LruCache::unbounded(),
+            hashes: HashMap::new(),
+        }
+    }
+
+    pub fn get(&mut self, hash: &[u8; 32]) -> Option<Arc<[u8]>> {
+        self.sources.get(hash).cloned()
+    }
+
+    pub fn get_by_specifier(&mut self, specifier: &ModuleSpecifier) -> Option<Arc<[u8]>> {
+        let hash = *self.hashes.get(specifier)?;
+        self.get(&hash)
+    }
+
+    /// Caches a source, evicting the least recently used ones until it fits.
+    pub fn insert(
+        &mut self,
+        hash: [u8; 32],
+        specifier: Option<ModuleSpecifier>,
+        source: Arc<[u8]>,
+    ) {
+        if source.len() > self.capacity {
+            return;
+        }
+
+        self.size += source.len();
+        if let Some(old) = self.sources.put(hash, source) {
+            self.size -= old.len();
+        }
+        while self.size > self.capacity {
+            let Some((evicted, source)) = self.sources.pop_lru() else {
+                break;
+            };
+            self.size -= source.len();
+            self.hashes.retain(|_, hash| *hash != evicted);
+        }
+
+        if let Some(specifier) = specifier {
+            self.hashes.insert(specifier, hash);
+        }
+    }
+}
+
+#[derive(Debug)]
+enum CacheKey {
+    Hash([u8; 32]),
+    Specifier(ModuleSpecifier),
+}
+
+/// Returns the source of a module from the cache, or fetches it with `fetch`, which returns the
+/// blake3 hash of the source along with it.
+async fn load_source<F, Fut>(
+    cache: &Mutex<ModuleCache>,
+    key: CacheKey,
+    fetch: F,
+) -> anyhow::Result<Arc<[u8]>>
+where
+    F: FnOnce() -> Fut,
+    Fut: Future<Output = anyhow::Result<([u8; 32], Vec<u8>)>>,
+{
+    let cached = {
+        let mut cache = cache.lock().unwrap();
+        match &key {
+            CacheKey::Hash(hash) => cache.get(hash),
+            CacheKey::Specifier(specifier) => cache.get_by_specifier(specifier),
+        }
+    };
+    if let Some(source) = cached {
+        trace!("Loaded {key:?} from the module cache");
+        return Ok(source);
+    }
+
+    let (hash, source) = fetch().await?;
+    let source: Arc<[u8]> = source.into();
+    let specifier = match key {
+        CacheKey::Hash(_) => None,
+        CacheKey::Specifier(specifier) => Some(specifier),
+    };
+    cache
+        .lock()
+        .unwrap()
+        .insert(hash, specifier, source.clone());
+
+    Ok(source)
+}
+
+async fn read_content(hash: [u8; 32]) -> anyhow::Result<([u8; 32], Vec<u8>)> {
+    let handle = ContentHandle::load(&hash).await?;
+    Ok((hash, handle.read_to_end().await?))
+}
+
 pub struct FleekModuleLoader {}
 
 impl FleekModuleLoader {
@@ -143,16 +261,17 @@ fn load(
 
                 let hash = *array_ref![bytes, 0, 32];
                 ModuleLoadResponse::Async(Box::pin(async move {
-                    if !fn_sdk::api::fetch_blake3(hash).await {
-                        bail!("Failed to fetch {module_specifier}")
-                    }
-
-                    let handle = ContentHandle::load(&hash).await?;
-                    let source = handle.read_to_end().await?.into_boxed_slice();
+                    let source = load_source(module_cache(), CacheKey::Hash(hash), || async {
+                        if !fn_sdk::api::fetch_blake3(hash).await {
+                            bail!("Failed to fetch {module_specifier}")
+                        }
+                        read_content(hash).await
+                    })
+                    .await?;
 
                     Ok(ModuleSource::new(
                         module_type,
-                        deno_core::ModuleSourceCode::Bytes(source.into()),
+                        deno_core::ModuleSourceCode::Bytes(Box::<[u8]>::from(&*source).into()),
                         &module_specifier,
                         None,
                     ))
@@ -167,18 +286,20 @@ fn load(
                 };
 
                 ModuleLoadResponse::Async(Box::pin(async move {
-                    let hash = fetch_from_origin(fn_sdk::api::Origin::IPFS, cid.to_bytes())
-                        .await
-                        .with_context(|| {
-                            format!("Failed to fetch {module_specifier} from origin")
-                        })?;
-
-                    let handle = ContentHandle::load(&hash).await?;
-                    let bytes = handle.read_to_end().await?;
+                    let key = CacheKey::Specifier(module_specifier.clone());
+                    let source = load_source(module_cache(), key, || async {
+                        let hash = fetch_from_origin(fn_sdk::api::Origin::IPFS, cid.to_bytes())
+                            .await
+                            .with_context(|| {
+                                format!("Failed to fetch {module_specifier} from origin")
+                            })?;
+                        read_content(hash).await
+                    })
+                    .await?;
 
                     let module = ModuleSource::new(
                         module_type,
-                        ModuleSourceCode::Bytes(bytes.into_boxed_slice().into()),
+                        ModuleSourceCode::Bytes(Box::<[u8]>::from(&*source).into()),
                         &module_specifier,
                         None,
                     );
@@ -197,19 +318,23 @@ fn load(
                 }
 
                 ModuleLoadResponse::Async(Box::pin(async move {
-                    let hash = fn_sdk::api::fetch_from_origin(
-                        fn_sdk::api::Origin::HTTP,
-                        module_specifier.as_str(),
-                    )
-                    .await
-                    .with_context(|| format!("Failed to fetch {module_specifier} from origin"))?;
-
-                    let handle = ContentHandle::load(&hash).await?;
-                    let bytes = handle.read_to_end().await?;
+                    let key = CacheKey::Specifier(module_specifier.clone());
+                    let source = load_source(module_cache(), key, || async {
+                        let hash = fn_sdk::api::fetch_from_origin(
+                            fn_sdk::api::Origin::HTTP,
+                            module_specifier.as_str(),
+                        )
+                        .await
+                        .with_context(|| {
+                            format!("Failed to fetch {module_specifier} from origin")
+                        })?;
+                        read_content(hash).await
+                    })
+                    .await?;
 
                     let module = ModuleSource::new(
                         module_type,
-                        ModuleSourceCode::Bytes(bytes.into_boxed_slice().into()),
+                        ModuleSourceCode::Bytes(Box::<[u8]>::from(&*source).into()),
                         &module_specifier,
                         None,
                     );
@@ -220,3 +345,54 @@ fn load(
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::cell::Cell;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_cached_module_is_not_refetched() {
+        let cache = Mutex::new(ModuleCache::new(1024));
+        let fetches = Cell::new(0);
+        let fetch = || async {
+            fetches.set(fetches.get() + 1);
+            Ok(([1; 32], b"export const main = () => 42;".to_vec()))
+        };
+
+        let specifier: ModuleSpecifier =
+            "ipfs://bafkreihdwdcefgh4dqkjv67uzcmw7ojee6xedzdetojuzjevtenxquvyku"
+                .parse()
+                .unwrap();
+        for _ in 0..2 {
+            let key = CacheKey::Specifier(specifier.clone());
+            let source = load_source(&cache, key, fetch).await.unwrap();
+            assert_eq!(&*source, b"export const main = () => 42;");
+        }
+        assert_eq!(fetches.get(), 1);
+
+        // The same content imported by its hash is in the cache as well.
+        load_source(&cache, CacheKey::Hash([1; 32]), fetch)
+            .await
+            .unwrap();
+        assert_eq!(fetches.get(), 1);
+    }
+
+    #[test]
+    fn test_module_cache_evicts_least_recently_used() {
+        let mut cache = ModuleCache::new(8);
+        let specifier: ModuleSpecifier = "ipfs://first".parse().unwrap();
+        cache.insert([1; 32], Some(specifier.clone()), b"first".as_slice().into());
+        cache.insert([2; 32], None, b"again".as_slice().into());
+
+        assert!(cache.get(&[1; 32]).is_none());
+        assert!(cache.get_by_specifier(&specifier).is_none());
+        assert!(cache.get(&[2; 32]).is_some());
+
+        // A source larger than the cache is not cached at all.
+        cache.insert([3; 32], None, b"too large".as_slice().into());
+        assert!(cache.get(&[3; 32]).is_none());
+        assert!(cache.get(&[2; 32]).is_some());
+    }
+}
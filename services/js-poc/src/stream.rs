@@ -20,6 +20,20 @@ pub struct Request {
     #[serde(skip_serializing_if = "Option::is_none")]
     #[serde(alias = "params", alias = "parameter", alias = "parameters")]
     pub param: Option<serde_json::Value>,
+    /// Runs the script deterministically, so that every node executing the request produces
+    /// the same output.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub deterministic: Option<Deterministic>,
+}
+
+/// Configuration of a deterministic execution. Randomness is derived from the seed, the clock is
+/// frozen to the given time, and network access is disabled.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Eq, PartialEq)]
+pub struct Deterministic {
+    /// Seed for `Math.random` and `crypto.getRandomValues`.
+    pub seed: u64,
+    /// Unix timestamp in milliseconds that `Date.now` returns.
+    pub time: u64,
 }
 
 #[derive(Debug, Clone, Copy, Serialize, Deserialize, Eq, PartialEq)]
@@ -0,0 +1,133 @@
+use anyhow::{bail, Context};
+use cid::Cid;
+use fn_sdk::blockstore::ContentHandle;
+use fn_sdk::connection::Connection;
+use fn_sdk::http_util::{respond, respond_with_error};
+use tracing::{error, info};
+
+use crate::stream::{Origin, Request};
+
+mod runtime;
+pub mod stream;
+
+pub(crate) mod params {
+    use std::time::Duration;
+
+    pub const REQ_TIMEOUT: Duration = Duration::from_secs(15);
+    /// A low estimate of the fuel the interpreter burns through per second. Roughly one unit is
+    /// consumed per executed instruction.
+    const FUEL_PER_SECOND: u64 = 100_000_000;
+    /// The amount of fuel a module gets for a single request. A running module can not be stopped
+    /// from the outside, so this is sized for the module to run out of fuel well before the request
+    /// times out.
+    pub const FUEL_LIMIT: u64 = FUEL_PER_SECOND * (REQ_TIMEOUT.as_secs() * 2 / 3);
+    /// The most memory in bytes a module can have, including what it declares up front.
+    pub const MEMORY_LIMIT: usize = 64 << 20;
+    /// The most elements a module can have in its table.
+    pub const TABLE_ELEMENTS_LIMIT: u32 = 10_000;
+    /// The largest module in bytes we load from the blockstore.
+    pub const MAX_MODULE_SIZE: usize = 16 << 20;
+}
+
+#[tokio::main(flavor = "current_thread")]
+pub async fn main() {
+    fn_sdk::ipc::init_from_env();
+
+    info!("Initialized WASM service!");
+
+    let mut listener = fn_sdk::ipc::conn_bind().await;
+    while let Ok(conn) = listener.accept().await {
+        tokio::spawn(async move {
+            if let Err(e) = handle_connection(conn).await {
+                error!("session failed: {e:?}");
+            }
+        });
+    }
+}
+
+async fn handle_connection(mut connection: Connection) -> anyhow::Result<()> {
+    if connection.is_http_request() {
+        respond_with_error(&mut connection, b"http requests are not supported", 400).await?;
+        bail!("Unsupported http request");
+    }
+
+    while let Some(payload) = connection.read_payload().await {
+        let request: Request = serde_json::from_slice(&payload)?;
+        if let Err(e) = handle_request(&mut connection, request).await {
+            respond_with_error(&mut connection, e.to_string().as_bytes(), 400).await?;
+            return Err(e);
+        };
+    }
+
+    Ok(())
+}
+
+async fn handle_request(connection: &mut Connection, request: Request) -> anyhow::Result<()> {
+    let Request { origin, uri, param } = request;
+    if uri.is_empty() {
+        bail!("Empty origin uri");
+    }
+
+    let wasm = load_module(origin, &uri).await?;
+    let input = match param {
+        Some(param) => serde_json::to_vec(&param).context("failed to encode param")?,
+        None => Vec::new(),
+    };
+
+    // The fuel limit bounds the execution, the timeout only keeps the client from waiting on a
+    // module that is slow to get there.
+    let output = tokio::time::timeout(
+        params::REQ_TIMEOUT,
+        tokio::task::spawn_blocking(move || {
+            runtime::execute(&wasm, &input, params::FUEL_LIMIT, params::MEMORY_LIMIT)
+        }),
+    )
+    .await
+    .context("Execution timeout")?
+    .context("Execution panicked")??;
+
+    respond(connection, &output).await?;
+
+    Ok(())
+}
+
+/// Fetches the module from the origin and loads it from the blockstore.
+async fn load_module(origin: Origin, uri: &str) -> anyhow::Result<Vec<u8>> {
+    let hash = match origin {
+        Origin::Blake3 => {
+            let hash = hex::decode(uri).context("Invalid blake3 hash")?;
+            let hash: [u8; 32] = hash
+                .try_into()
+                .map_err(|_| anyhow::anyhow!("Invalid blake3 hash length"))?;
+            if !fn_sdk::api::fetch_blake3(hash).await {
+                bail!("Failed to fetch blake3://{uri}");
+            }
+            hash
+        },
+        Origin::Ipfs => {
+            let cid = uri.parse::<Cid>().context("Invalid ipfs cid")?;
+            fn_sdk::api::fetch_from_origin(fn_sdk::api::Origin::IPFS, cid.to_bytes())
+                .await
+                .with_context(|| format!("Failed to fetch ipfs://{uri} from origin"))?
+        },
+        Origin::Unknown => bail!("Unknown origin"),
+    };
+
+    let handle = ContentHandle::load(&hash)
+        .await
+        .context("Failed to load the module from the blockstore")?;
+
+    // Every block but the last one is 256KiB, so the number of blocks tells us if the module is
+    // too large before we read any of it.
+    if handle.len() > params::MAX_MODULE_SIZE.div_ceil(256 << 10) {
+        bail!("Module is larger than {} bytes", params::MAX_MODULE_SIZE);
+    }
+    let mut wasm = Vec::new();
+    for block in 0..handle.len() {
+        wasm.append(&mut handle.read(block).await?);
+        if wasm.len() > params::MAX_MODULE_SIZE {
+            bail!("Module is larger than {} bytes", params::MAX_MODULE_SIZE);
+        }
+    }
+    Ok(wasm)
+}
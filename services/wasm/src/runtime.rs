@@ -0,0 +1,161 @@
+use anyhow::{anyhow, Context};
+use wasmi::{Config, Engine, Linker, Module, Store, StoreLimits, StoreLimitsBuilder};
+
+use crate::params::TABLE_ELEMENTS_LIMIT;
+
+struct State {
+    limits: StoreLimits,
+}
+
+/// Runs a wasm module on the given input and returns its output.
+///
+/// The module has to export its `memory`, an `alloc(len: i32) -> i32` function that returns a
+/// pointer to `len` free bytes, and a `main(ptr: i32, len: i32) -> i64` function. The input is
+/// written to a buffer returned by `alloc` and passed to `main`, which returns the pointer to its
+/// output in the upper 32 bits and the length of it in the lower 32 bits.
+///
+/// No host functions are linked, so a module can only compute on its input. Every instruction
+/// consumes fuel, and the execution traps once the given amount of fuel is used up. The module
+/// can not have more than `memory_limit` bytes of memory, a module that declares more fails to
+/// instantiate and growing past it fails. The same goes for a single table of at most
+/// [`TABLE_ELEMENTS_LIMIT`] elements.
+pub fn execute(
+    wasm: &[u8],
+    input: &[u8],
+    fuel: u64,
+    memory_limit: usize,
+) -> anyhow::Result<Vec<u8>> {
+    let mut config = Config::default();
+    config.consume_fuel(true);
+    let engine = Engine::new(&config);
+    let module = Module::new(&engine, wasm).context("Invalid wasm module")?;
+
+    let limits = StoreLimitsBuilder::new()
+        .memory_size(memory_limit)
+        .instances(1)
+        .memories(1)
+        .table_elements(TABLE_ELEMENTS_LIMIT)
+        .tables(1)
+        .build();
+    let mut store = Store::new(&engine, State { limits });
+    store.limiter(|state| &mut state.limits);
+    store
+        .add_fuel(fuel)
+        .map_err(|e| anyhow!("Failed to add fuel: {e}"))?;
+
+    let instance = Linker::<State>::new(&engine)
+        .instantiate(&mut store, &module)
+        .context("Failed to instantiate module")?
+        .start(&mut store)
+        .context("Failed to start module")?;
+
+    let memory = instance
+        .get_memory(&store, "memory")
+        .context("Module does not export its memory")?;
+    let alloc = instance
+        .get_typed_func::<i32, i32>(&store, "alloc")
+        .context("Module does not export an alloc function")?;
+    let main = instance
+        .get_typed_func::<(i32, i32), i64>(&store, "main")
+        .context("Module does not export a main function")?;
+
+    let len = i32::try_from(input.len()).context("Input is too large")?;
+    let ptr = alloc
+        .call(&mut store, len)
+        .context("Failed to allocate the input")?;
+    memory
+        .write(&mut store, ptr as u32 as usize, input)
+        .map_err(|e| anyhow!("Failed to write the input: {e}"))?;
+
+    let res = main
+        .call(&mut store, (ptr, len))
+        .context("Execution failed")?;
+
+    let out_ptr = (res as u64 >> 32) as usize;
+    let out_len = (res as u64 & u32::MAX as u64) as usize;
+    let output = memory
+        .data(&store)
+        .get(out_ptr..out_ptr + out_len)
+        .context("Output is out of bounds of the module's memory")?;
+
+    Ok(output.to_vec())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Hands out the memory after the first page and returns the input as the output.
+    const ECHO: &str = r#"
+        (module
+            (memory (export "memory") 2)
+            (func (export "alloc") (param $len i32) (result i32)
+                i32.const 65536)
+            (func (export "main") (param $ptr i32) (param $len i32) (result i64)
+                local.get $ptr
+                i64.extend_i32_u
+                i64.const 32
+                i64.shl
+                local.get $len
+                i64.extend_i32_u
+                i64.or))
+    "#;
+
+    const LOOP: &str = r#"
+        (module
+            (memory (export "memory") 1)
+            (func (export "alloc") (param $len i32) (result i32)
+                i32.const 0)
+            (func (export "main") (param $ptr i32) (param $len i32) (result i64)
+                (loop $forever
+                    br $forever)
+                i64.const 0))
+    "#;
+
+    #[test]
+    fn test_echo() {
+        let wasm = wat::parse_str(ECHO).unwrap();
+        let output = execute(&wasm, b"hello wasm", 1_000_000, 1 << 20).unwrap();
+        assert_eq!(output, b"hello wasm");
+
+        let output = execute(&wasm, b"", 1_000_000, 1 << 20).unwrap();
+        assert!(output.is_empty());
+    }
+
+    #[test]
+    fn test_out_of_fuel() {
+        let wasm = wat::parse_str(LOOP).unwrap();
+        assert!(execute(&wasm, b"hello wasm", 1_000_000, 1 << 20).is_err());
+    }
+
+    #[test]
+    fn test_memory_limit() {
+        let wasm = wat::parse_str(ECHO).unwrap();
+        // The module declares two pages of memory.
+        assert!(execute(&wasm, b"hello wasm", 1_000_000, 2 << 16).is_ok());
+        assert!(execute(&wasm, b"hello wasm", 1_000_000, 1 << 16).is_err());
+    }
+
+    #[test]
+    fn test_table_limit() {
+        let with_table = |elements: u32| {
+            ECHO.replacen(
+                "(memory",
+                &format!("(table {elements} funcref)\n            (memory"),
+                1,
+            )
+        };
+
+        let wasm = wat::parse_str(with_table(TABLE_ELEMENTS_LIMIT)).unwrap();
+        assert!(execute(&wasm, b"hello wasm", 1_000_000, 1 << 20).is_ok());
+        let wasm = wat::parse_str(with_table(TABLE_ELEMENTS_LIMIT + 1)).unwrap();
+        assert!(execute(&wasm, b"hello wasm", 1_000_000, 1 << 20).is_err());
+    }
+
+    #[test]
+    fn test_missing_exports() {
+        let wasm = wat::parse_str("(module)").unwrap();
+        assert!(execute(&wasm, b"hello wasm", 1_000_000, 1 << 20).is_err());
+        assert!(execute(b"not wasm", b"hello wasm", 1_000_000, 1 << 20).is_err());
+    }
+}
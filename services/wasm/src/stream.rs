@@ -0,0 +1,24 @@
+use serde::{Deserialize, Serialize};
+
+/// Request to execute a wasm module from an origin
+#[derive(Serialize, Deserialize, Debug, Eq, PartialEq)]
+pub struct Request {
+    /// Origin to use
+    pub origin: Origin,
+    /// URI For the origin
+    /// - for blake3 should be hex encoded bytes
+    /// - for ipfs should be cid string
+    pub uri: String,
+    /// Parameter to pass to the module's main function, encoded as json.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(alias = "params", alias = "parameter", alias = "parameters")]
+    pub param: Option<serde_json::Value>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Eq, PartialEq)]
+#[repr(u8)]
+pub enum Origin {
+    Blake3,
+    Ipfs,
+    Unknown,
+}